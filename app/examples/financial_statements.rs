@@ -0,0 +1,69 @@
+//! Example printing the balance sheet, income statement, cash flow and
+//! trial balance of a GnuCash file.
+//!
+//! Foreign-currency and security balances are converted to the report
+//! currency with the prices stored in the file.
+//!
+//! Usage: financial_statements <gnucash_file> <currency> <YYYY-MM-DD>
+
+use std::env;
+
+use gnucash_ext::reports::{self, CashFlowOptions, Period};
+use gnucash_ext::{BookExt, Date, Session, SessionOpenMode, init_engine};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 4 {
+        eprintln!("Usage: {} <gnucash_file> <currency> <YYYY-MM-DD>", args[0]);
+        eprintln!();
+        eprintln!("Example:");
+        eprintln!("  {} myfile.gnucash USD 2024-12-31", args[0]);
+        std::process::exit(1);
+    }
+    let Some(as_of) = Date::parse_iso(&args[3]) else {
+        eprintln!("Invalid date: {}", args[3]);
+        std::process::exit(1);
+    };
+
+    init_engine();
+
+    let session = match Session::open(&args[1], SessionOpenMode::SESSION_READ_ONLY) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("Failed to open file: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(book) = session.book() {
+        let currency = book
+            .commodity_table()
+            .and_then(|table| table.lookup("CURRENCY", &args[2]));
+        match currency {
+            Some(currency) => {
+                let year_start = Date::new(as_of.year(), 1, 1).unwrap();
+                let period = Period::new(year_start, as_of);
+
+                match reports::balance_sheet(&book, as_of, &currency) {
+                    Ok(sheet) => println!("{}", sheet.hide_zero().to_text()),
+                    Err(e) => eprintln!("Balance sheet failed: {}", e),
+                }
+                match reports::income_statement(&book, period, &currency) {
+                    Ok(statement) => println!("{}", statement.hide_zero().to_text()),
+                    Err(e) => eprintln!("Income statement failed: {}", e),
+                }
+                match reports::cash_flow(&book, period, &currency, &CashFlowOptions::new()) {
+                    Ok(flow) => println!("{}", flow.to_text()),
+                    Err(e) => eprintln!("Cash flow failed: {}", e),
+                }
+                match reports::trial_balance(&book, as_of, &currency) {
+                    Ok(trial) => println!("{}", trial.to_text()),
+                    Err(e) => eprintln!("Trial balance failed: {}", e),
+                }
+            }
+            None => eprintln!("Unknown currency: {}", args[2]),
+        }
+    }
+
+    session.end();
+}
@@ -0,0 +1,482 @@
+//! Book integrity checks.
+//!
+//! [`audit`] walks the whole book and reports problems the engine's
+//! Check & Repair does not look for, each with a [`Severity`] and the
+//! GUID of the entity concerned. Nothing is changed:
+//!
+//! ```ignore
+//! use gnucash_ext::audit::{audit, AuditOptions, Severity};
+//!
+//! let report = audit(&book, &AuditOptions::new().future_days(7));
+//! print!("{}", report.to_text());
+//! if report.count(Severity::Error) > 0 {
+//!     std::process::exit(1);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use gnucash_sys::{Account, Book, GNCAccountType, Guid, Transaction, ffi};
+
+use crate::business::{Entry, Invoice, Job};
+use crate::date::Date;
+use crate::engine_ext::{AccountExt, TransactionExt, collection};
+use crate::price::PriceDB;
+use crate::reports::render::{csv_record, grid};
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth a look, but often intended.
+    Info,
+    /// Probably a mistake.
+    Warning,
+    /// The books are wrong.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// The check that produced a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Check {
+    /// A transaction whose split values do not sum to zero.
+    UnbalancedTransaction,
+    /// A split posted to a placeholder account.
+    PlaceholderSplit,
+    /// An account without a commodity, or a split whose amount is not in
+    /// its account's commodity.
+    CommodityMismatch,
+    /// A transaction posted after the audit date.
+    FuturePosted,
+    /// A check number used by more than one transaction in an account.
+    DuplicateCheckNumber,
+    /// An invoice, entry or job that lost its owner, invoice or posting.
+    OrphanedBusinessObject,
+    /// A price with a value of zero.
+    ZeroPrice,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Check::UnbalancedTransaction => "unbalanced-transaction",
+            Check::PlaceholderSplit => "placeholder-split",
+            Check::CommodityMismatch => "commodity-mismatch",
+            Check::FuturePosted => "future-posted",
+            Check::DuplicateCheckNumber => "duplicate-check-number",
+            Check::OrphanedBusinessObject => "orphaned-business-object",
+            Check::ZeroPrice => "zero-price",
+        })
+    }
+}
+
+/// A problem found by [`audit`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub check: Check,
+    /// The transaction, split, account, business object or price concerned.
+    pub guid: Guid,
+    pub message: String,
+}
+
+/// Settings for [`audit`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditOptions {
+    as_of: i64,
+    future_days: i64,
+}
+
+impl AuditOptions {
+    /// Creates options auditing as of now, flagging any transaction
+    /// posted after today.
+    pub fn new() -> Self {
+        Self {
+            as_of: unsafe { ffi::gnc_time(std::ptr::null_mut()) },
+            future_days: 0,
+        }
+    }
+
+    /// Sets the time the audit treats as now.
+    pub fn as_of(mut self, time: i64) -> Self {
+        self.as_of = time;
+        self
+    }
+
+    /// Allows transactions posted up to `days` after the audit date, such
+    /// as scheduled payments already entered.
+    pub fn future_days(mut self, days: i64) -> Self {
+        self.future_days = days;
+        self
+    }
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The findings of an [`audit`], in the order the checks ran.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditReport {
+    pub findings: Vec<Finding>,
+}
+
+impl AuditReport {
+    /// Returns true if nothing was found.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns the number of findings of `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    /// Returns the most serious severity found.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+
+    /// Returns the findings of `severity` or worse.
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |f| f.severity >= severity)
+    }
+
+    /// Renders the findings as a plain-text table.
+    pub fn to_text(&self) -> String {
+        let rows: Vec<Vec<String>> = self
+            .findings
+            .iter()
+            .map(|f| {
+                vec![
+                    f.severity.to_string(),
+                    f.check.to_string(),
+                    f.guid.to_string(),
+                    f.message.clone(),
+                ]
+            })
+            .collect();
+        grid(&["Severity", "Check", "GUID", "Message"], &rows, &[])
+    }
+
+    /// Renders the findings as CSV.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(&mut out, &["severity", "check", "guid", "message"]);
+        for f in &self.findings {
+            csv_record(
+                &mut out,
+                &[
+                    &f.severity.to_string(),
+                    &f.check.to_string(),
+                    &f.guid.to_string(),
+                    &f.message,
+                ],
+            );
+        }
+        out
+    }
+}
+
+/// Runs every check over `book`.
+pub fn audit(book: &Book, options: &AuditOptions) -> AuditReport {
+    let transactions: Vec<Transaction> = collection(book, c"Trans")
+        .into_iter()
+        .filter_map(|p| unsafe { Transaction::from_raw(p as *mut ffi::Transaction, false) })
+        .collect();
+    let accounts: Vec<Account> = book
+        .root_account()
+        .map(|root| root.descendants().collect())
+        .unwrap_or_default();
+
+    let mut findings = Vec::new();
+    check_transactions(&transactions, options, &mut findings);
+    check_accounts(&accounts, &mut findings);
+    check_business(book, &mut findings);
+    check_prices(book, &mut findings);
+    AuditReport { findings }
+}
+
+fn finding(severity: Severity, check: Check, guid: Guid, message: String) -> Finding {
+    Finding {
+        severity,
+        check,
+        guid,
+        message,
+    }
+}
+
+/// Returns "description (date)" to name a transaction in a message.
+fn describe(trans: &Transaction) -> String {
+    format!(
+        "'{}' ({})",
+        trans.description().unwrap_or_default(),
+        Date::from_time64(trans.date_posted())
+    )
+}
+
+fn check_transactions(
+    transactions: &[Transaction],
+    options: &AuditOptions,
+    findings: &mut Vec<Finding>,
+) {
+    let horizon = Date::from_time64(options.as_of)
+        .add_days(options.future_days)
+        .end_time64();
+
+    for trans in transactions {
+        if !trans.is_balanced() {
+            findings.push(finding(
+                Severity::Error,
+                Check::UnbalancedTransaction,
+                trans.guid(),
+                format!("{} is off by {}", describe(trans), trans.imbalance_value()),
+            ));
+        }
+
+        if trans.date_posted() > horizon {
+            findings.push(finding(
+                Severity::Info,
+                Check::FuturePosted,
+                trans.guid(),
+                format!("{} is posted in the future", describe(trans)),
+            ));
+        }
+
+        let currency = trans.currency();
+        for split in trans.splits() {
+            let Some(account) = split.account() else {
+                continue;
+            };
+            let name = account.full_name().unwrap_or_default();
+            if account.is_placeholder() {
+                findings.push(finding(
+                    Severity::Warning,
+                    Check::PlaceholderSplit,
+                    split.guid(),
+                    format!(
+                        "split of {} is in placeholder account {}",
+                        describe(trans),
+                        name
+                    ),
+                ));
+            }
+            // An account in the transaction currency must have amount
+            // equal to value; anything else means the amount was entered
+            // in some other commodity.
+            if let (Some(commodity), Some(currency)) = (account.commodity(), &currency)
+                && commodity.equiv(currency)
+                && !split.amount().eq_value(&split.value())
+            {
+                findings.push(finding(
+                    Severity::Error,
+                    Check::CommodityMismatch,
+                    split.guid(),
+                    format!(
+                        "split of {} in {} has amount {} but value {}",
+                        describe(trans),
+                        name,
+                        split.amount(),
+                        split.value()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn check_accounts(accounts: &[Account], findings: &mut Vec<Finding>) {
+    for account in accounts {
+        let name = account.full_name().unwrap_or_default();
+        if account.commodity().is_none() {
+            findings.push(finding(
+                Severity::Error,
+                Check::CommodityMismatch,
+                account.guid(),
+                format!("account {} has no commodity", name),
+            ));
+        }
+
+        if !matches!(
+            account.account_type(),
+            GNCAccountType::ACCT_TYPE_BANK
+                | GNCAccountType::ACCT_TYPE_CASH
+                | GNCAccountType::ACCT_TYPE_CREDIT
+        ) {
+            continue;
+        }
+        let numbered: Vec<(Guid, String)> = account
+            .splits()
+            .filter_map(|split| {
+                let trans = split.transaction()?;
+                Some((trans.guid(), trans.num()?))
+            })
+            .collect();
+        for (guid, first, num) in duplicate_numbers(&numbered) {
+            findings.push(finding(
+                Severity::Warning,
+                Check::DuplicateCheckNumber,
+                guid,
+                format!("number {} in {} is also used by {}", num, name, first),
+            ));
+        }
+    }
+}
+
+/// Returns `(transaction, first user, number)` for every transaction
+/// reusing a non-blank number already taken by an earlier one.
+fn duplicate_numbers(numbered: &[(Guid, String)]) -> Vec<(Guid, Guid, String)> {
+    let mut first: HashMap<&str, Guid> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for (guid, num) in numbered {
+        let num = num.trim();
+        if num.is_empty() {
+            continue;
+        }
+        match first.get(num) {
+            Some(owner) if owner != guid => duplicates.push((*guid, *owner, num.to_string())),
+            Some(_) => {}
+            None => {
+                first.insert(num, *guid);
+            }
+        }
+    }
+    duplicates
+}
+
+fn check_business(book: &Book, findings: &mut Vec<Finding>) {
+    for ptr in collection(book, c"gncInvoice") {
+        let Some(invoice) = (unsafe { Invoice::from_raw(ptr as *mut ffi::GncInvoice, false) })
+        else {
+            continue;
+        };
+        let id = invoice.id().unwrap_or_default();
+        if invoice.owner().is_undefined() || invoice.owner().guid().is_none() {
+            findings.push(finding(
+                Severity::Warning,
+                Check::OrphanedBusinessObject,
+                invoice.guid(),
+                format!("invoice {} has no owner", id),
+            ));
+        }
+        if invoice.is_posted() && (invoice.posted_txn().is_none() || invoice.posted_acc().is_none())
+        {
+            findings.push(finding(
+                Severity::Error,
+                Check::OrphanedBusinessObject,
+                invoice.guid(),
+                format!(
+                    "invoice {} is posted but its transaction or account is gone",
+                    id
+                ),
+            ));
+        }
+    }
+
+    for ptr in collection(book, c"gncEntry") {
+        if let Some(entry) = unsafe { Entry::from_raw(ptr as *mut ffi::GncEntry, false) }
+            && entry.invoice().is_none()
+            && entry.bill().is_none()
+        {
+            findings.push(finding(
+                Severity::Warning,
+                Check::OrphanedBusinessObject,
+                entry.guid(),
+                "entry belongs to no invoice or bill".to_string(),
+            ));
+        }
+    }
+
+    for ptr in collection(book, c"gncJob") {
+        if let Some(job) = unsafe { Job::from_raw(ptr as *mut ffi::GncJob, false) }
+            && job.owner().is_undefined()
+        {
+            findings.push(finding(
+                Severity::Warning,
+                Check::OrphanedBusinessObject,
+                job.guid(),
+                format!("job {} has no owner", job.name().unwrap_or_default()),
+            ));
+        }
+    }
+}
+
+fn check_prices(book: &Book, findings: &mut Vec<Finding>) {
+    let Some(db) = PriceDB::get(book) else {
+        return;
+    };
+    for price in db.prices() {
+        if price.value().is_zero() {
+            findings.push(finding(
+                Severity::Warning,
+                Check::ZeroPrice,
+                price.guid(),
+                format!("price on {} is zero", Date::from_time64(price.time())),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_numbers() {
+        let a = Guid::from_bytes([1; 16]);
+        let b = Guid::from_bytes([2; 16]);
+        let c = Guid::from_bytes([3; 16]);
+        let numbered = [
+            (a, "1001".to_string()),
+            // The same transaction seen through a second split.
+            (a, "1001".to_string()),
+            (b, " 1001 ".to_string()),
+            (c, String::new()),
+            (b, String::new()),
+        ];
+        assert_eq!(
+            duplicate_numbers(&numbered),
+            vec![(b, a, "1001".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_report_severity() {
+        let report = AuditReport {
+            findings: vec![
+                finding(
+                    Severity::Info,
+                    Check::FuturePosted,
+                    Guid::from_bytes([1; 16]),
+                    "a".into(),
+                ),
+                finding(
+                    Severity::Error,
+                    Check::ZeroPrice,
+                    Guid::from_bytes([2; 16]),
+                    "b, c".into(),
+                ),
+            ],
+        };
+        assert_eq!(report.max_severity(), Some(Severity::Error));
+        assert_eq!(report.count(Severity::Warning), 0);
+        assert_eq!(report.at_least(Severity::Warning).count(), 1);
+        assert!(report.to_csv().ends_with(",\"b, c\"\n"));
+        assert!(report.to_text().contains("future-posted"));
+    }
+}
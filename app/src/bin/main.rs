@@ -5,19 +5,19 @@
 
 // Use gnucash_ext which re-exports gnucash_sys types plus business entities
 use gnucash_ext::{
-    gnucash_sys::ffi::GncAmountType,
-    init_engine, Account, Book, Customer, Employee, Entry, GNCAccountType, Invoice, Job, Numeric,
-    OwnerType, Session, TaxTable, TaxTableEntry, Vendor,
+    Account, Book, Customer, Editable, Employee, Entry, GNCAccountType, Invoice, Job, Numeric,
+    OwnerType, Session, TaxTable, TaxTableEntry, Vendor, gnucash_sys::ffi::GncAmountType,
+    init_engine,
 };
 
 /// Creates an account with the given properties.
 /// Returns a mutable account so it can be marked as unowned after attaching to hierarchy.
 fn create_account(book: &Book, name: &str, account_type: GNCAccountType) -> Account {
     let mut account = Account::new(book);
-    account.begin_edit();
-    account.set_name(name);
-    account.set_type(account_type);
-    account.commit_edit();
+    let edit = account.edit();
+    edit.set_name(name);
+    edit.set_type(account_type);
+    edit.commit();
     // Mark as unowned since we'll attach to hierarchy (book takes ownership)
     account.mark_unowned();
     account
@@ -62,27 +62,27 @@ fn main() {
 
     // Assets
     let assets = create_account(&book, "Assets", GNCAccountType::ACCT_TYPE_ASSET);
-    root.append_child(&assets);
+    root.edit().append_child(&assets);
 
     // Assets:Receivables
     let receivables = create_account(&book, "Receivables", GNCAccountType::ACCT_TYPE_RECEIVABLE);
-    assets.append_child(&receivables);
+    assets.edit().append_child(&receivables);
 
     // Assets:Bank
     let bank = create_account(&book, "Bank", GNCAccountType::ACCT_TYPE_ASSET);
-    assets.append_child(&bank);
+    assets.edit().append_child(&bank);
 
     // Income
     let income = create_account(&book, "Income", GNCAccountType::ACCT_TYPE_INCOME);
-    root.append_child(&income);
+    root.edit().append_child(&income);
 
     // Liabilities
     let liabilities = create_account(&book, "Liabilities", GNCAccountType::ACCT_TYPE_LIABILITY);
-    root.append_child(&liabilities);
+    root.edit().append_child(&liabilities);
 
     // Liabilities:Tax payable
     let tax_payable = create_account(&book, "Tax payable", GNCAccountType::ACCT_TYPE_LIABILITY);
-    liabilities.append_child(&tax_payable);
+    liabilities.edit().append_child(&tax_payable);
 
     println!("Created account hierarchy:");
     println!("  Root");
@@ -95,62 +95,62 @@ fn main() {
 
     // Create a Customer
     let customer = Customer::new(&book);
-    customer.begin_edit();
-    customer.set_id("1");
-    customer.set_name("Bill & Bob Industries");
-    if let Some(addr) = customer.addr() {
+    let edit = customer.edit();
+    edit.set_id("1");
+    edit.set_name("Bill & Bob Industries");
+    if let Some(addr) = edit.addr() {
         addr.set_name("Bill & Bob");
         addr.set_addr1("201 Nowhere street");
     }
-    customer.commit_edit();
+    edit.commit();
     println!("Created customer: {:?}", customer);
 
     // Create an Employee
     let employee = Employee::new(&book);
-    employee.begin_edit();
-    employee.set_id("2");
-    employee.set_username("Reliable employee");
-    employee.commit_edit();
+    let edit = employee.edit();
+    edit.set_id("2");
+    edit.set_username("Reliable employee");
+    edit.commit();
     println!("Created employee: {:?}", employee);
 
     // Create a Vendor
     let vendor = Vendor::new(&book);
-    vendor.begin_edit();
-    vendor.set_id("3");
-    vendor.set_name("Dependable vendor");
-    vendor.commit_edit();
+    let edit = vendor.edit();
+    edit.set_id("3");
+    edit.set_name("Dependable vendor");
+    edit.commit();
     println!("Created vendor: {:?}", vendor);
 
     // Create a Job linked to the vendor
     let job = Job::new(&book);
-    job.begin_edit();
-    job.set_id("4");
-    job.set_name("Good clean, fun");
-    job.set_owner(&vendor.to_owner());
-    job.commit_edit();
+    let edit = job.edit();
+    edit.set_id("4");
+    edit.set_name("Good clean, fun");
+    edit.set_owner(&vendor.to_owner());
+    edit.commit();
     println!("Created job: {:?}", job);
 
     // Create a TaxTable with 7% tax rate
     let tax_table = TaxTable::new(&book);
-    tax_table.begin_edit();
-    tax_table.set_name("good tax");
+    let edit = tax_table.edit();
+    edit.set_name("good tax");
 
     // Create a tax table entry (7% = 700000/100000)
     let tax_entry = TaxTableEntry::new();
     tax_entry.set_account(&tax_payable);
     tax_entry.set_type(GncAmountType::GNC_AMT_TYPE_PERCENT);
     tax_entry.set_amount(Numeric::new(700000, 100000)); // 7%
-    tax_table.add_entry(&tax_entry);
+    edit.add_entry(&tax_entry);
 
-    tax_table.commit_edit();
+    edit.commit();
     println!("Created tax table: {:?}", tax_table);
 
     // Create Invoice for Customer
     let invoice_customer = Invoice::new(&book);
-    invoice_customer.begin_edit();
-    invoice_customer.set_id("5");
-    invoice_customer.set_owner(&customer.to_owner());
-    invoice_customer.commit_edit();
+    let edit = invoice_customer.edit();
+    edit.set_id("5");
+    edit.set_owner(&customer.to_owner());
+    edit.commit();
 
     // Verify owner extraction
     let customer_extract = invoice_customer.owner();
@@ -162,10 +162,10 @@ fn main() {
 
     // Create Invoice for Employee
     let invoice_employee = Invoice::new(&book);
-    invoice_employee.begin_edit();
-    invoice_employee.set_id("6");
-    invoice_employee.set_owner(&employee.to_owner());
-    invoice_employee.commit_edit();
+    let edit = invoice_employee.edit();
+    edit.set_id("6");
+    edit.set_owner(&employee.to_owner());
+    edit.commit();
 
     let employee_extract = invoice_employee.owner();
     assert!(
@@ -176,10 +176,10 @@ fn main() {
 
     // Create Invoice for Vendor (this is a bill)
     let invoice_vendor = Invoice::new(&book);
-    invoice_vendor.begin_edit();
-    invoice_vendor.set_id("7");
-    invoice_vendor.set_owner(&vendor.to_owner());
-    invoice_vendor.commit_edit();
+    let edit = invoice_vendor.edit();
+    edit.set_id("7");
+    edit.set_owner(&vendor.to_owner());
+    edit.commit();
 
     let vendor_extract = invoice_vendor.owner();
     assert!(
@@ -190,10 +190,10 @@ fn main() {
 
     // Create Invoice for Job
     let invoice_job = Invoice::new(&book);
-    invoice_job.begin_edit();
-    invoice_job.set_id("8");
-    invoice_job.set_owner(&job.to_owner());
-    invoice_job.commit_edit();
+    let edit = invoice_job.edit();
+    edit.set_id("8");
+    edit.set_owner(&job.to_owner());
+    edit.commit();
 
     let job_extract = invoice_job.owner();
     assert!(
@@ -204,25 +204,25 @@ fn main() {
 
     // Create an Entry for the customer invoice
     let invoice_entry = Entry::new(&book);
-    invoice_entry.begin_edit();
-    invoice_entry.set_description("excellent product");
-    invoice_entry.set_quantity(Numeric::new(1, 1));
-    invoice_entry.set_inv_account(&income);
-    invoice_entry.set_inv_price(Numeric::new(1, 1));
-    invoice_entry.set_inv_tax_table(&tax_table);
-    invoice_entry.set_inv_tax_included(false);
-    invoice_entry.set_inv_taxable(true);
+    let edit = invoice_entry.edit();
+    edit.set_description("excellent product");
+    edit.set_quantity(Numeric::new(1, 1));
+    edit.set_inv_account(&income);
+    edit.set_inv_price(Numeric::new(1, 1));
+    edit.set_inv_tax_table(&tax_table);
+    edit.set_inv_tax_included(false);
+    edit.set_inv_taxable(true);
 
     // Get current timestamp for date_entered
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_secs() as i64;
-    invoice_entry.set_date_entered(now);
-    invoice_entry.commit_edit();
+    edit.set_date_entered(now);
+    edit.commit();
 
     // Add entry to invoice
-    invoice_customer.add_entry(&invoice_entry);
+    invoice_customer.edit().add_entry(&invoice_entry);
     println!("Created and added entry to customer invoice: {:?}", invoice_entry);
 
     // Summary
@@ -2,7 +2,9 @@
 //!
 //! This module provides fluent builders for complex entity creation.
 
-use gnucash_sys::{Account, Book, Numeric, Split, Transaction};
+use gnucash_sys::{Account, Book, Editable, Numeric, Split, Transaction};
+
+use crate::engine_ext::{AccountExt, BookExt, TransactionEditorExt};
 
 /// Builder for creating transactions with splits.
 ///
@@ -30,6 +32,8 @@ pub struct TransactionBuilder<'a> {
 struct SplitSpec<'a> {
     account: &'a Account,
     amount: Numeric,
+    value: Numeric,
+    share_price: Option<Numeric>,
     memo: Option<String>,
 }
 
@@ -71,7 +75,9 @@ impl<'a> TransactionBuilder<'a> {
         self
     }
 
-    /// Sets the currency mnemonic (e.g., "USD").
+    /// Sets the currency mnemonic (e.g., "USD"). The currency must be
+    /// registered in the book's commodity table. Defaults to the
+    /// commodity of the first split account denominated in a currency.
     pub fn currency(mut self, mnemonic: &str) -> Self {
         self.currency_mnemonic = Some(mnemonic.to_string());
         self
@@ -82,6 +88,28 @@ impl<'a> TransactionBuilder<'a> {
         self.splits.push(SplitSpec {
             account,
             amount,
+            value: amount,
+            share_price: None,
+            memo: memo.map(|s| s.to_string()),
+        });
+        self
+    }
+
+    /// Adds a split to a security account: `shares` of the account's
+    /// commodity worth `value` in the transaction currency. The share
+    /// price is derived as `value / shares`.
+    pub fn stock_split(
+        mut self,
+        account: &'a Account,
+        shares: Numeric,
+        value: Numeric,
+        memo: Option<&str>,
+    ) -> Self {
+        self.splits.push(SplitSpec {
+            account,
+            amount: shares,
+            value,
+            share_price: value.checked_div(&shares),
             memo: memo.map(|s| s.to_string()),
         });
         self
@@ -99,88 +127,111 @@ impl<'a> TransactionBuilder<'a> {
             .split(to, amount, memo)
     }
 
-    /// Builds and returns the transaction.
+    /// Checks that the splits added so far balance, without touching
+    /// the book.
     ///
-    /// Returns an error if the transaction is imbalanced or invalid.
-    pub fn build(self) -> gnucash_sys::Result<Transaction> {
+    /// Returns an error if there are no splits or the split values do
+    /// not sum to zero.
+    pub fn check_balance(&self) -> gnucash_sys::Result<()> {
         if self.splits.is_empty() {
             return Err(gnucash_sys::Error::InvalidOperation(
                 "Transaction must have at least one split".to_string(),
             ));
         }
 
-        // Check balance
-        let mut total_num: i64 = 0;
-        let mut common_denom: i64 = 1;
-        for split in &self.splits {
-            // Simple balance check (assumes same currency)
-            let amount = split.amount;
-            // Find common denominator
-            common_denom = lcm(common_denom, amount.denom());
-        }
-        for split in &self.splits {
-            let amount = split.amount;
-            let scaled = amount.num() * (common_denom / amount.denom());
-            total_num += scaled;
-        }
-        if total_num != 0 {
+        // Check balance on split values (in the transaction currency)
+        let total = self
+            .splits
+            .iter()
+            .try_fold(Numeric::zero(), |total, split| {
+                total.checked_add(&split.value)
+            })
+            .ok_or_else(|| {
+                gnucash_sys::Error::InvalidOperation(
+                    "Transaction split values overflow".to_string(),
+                )
+            })?;
+        if !total.is_zero() {
             return Err(gnucash_sys::Error::InvalidOperation(format!(
-                "Transaction is imbalanced by {}/{}",
-                total_num, common_denom
+                "Transaction is imbalanced by {}",
+                total
             )));
         }
+        Ok(())
+    }
+
+    /// Builds and returns the transaction.
+    ///
+    /// Returns an error if the transaction is imbalanced or invalid.
+    pub fn build(self) -> gnucash_sys::Result<Transaction> {
+        self.check_balance()?;
+
+        // Resolve the currency: an explicit mnemonic, else the first
+        // split account commodity that is a currency.
+        let currency = match &self.currency_mnemonic {
+            Some(mnemonic) => Some(
+                self.book
+                    .commodity_table()
+                    .and_then(|table| table.lookup("CURRENCY", mnemonic))
+                    .ok_or_else(|| {
+                        gnucash_sys::Error::InvalidOperation(format!(
+                            "Unknown currency {}",
+                            mnemonic
+                        ))
+                    })?,
+            ),
+            None => self
+                .splits
+                .iter()
+                .filter_map(|spec| spec.account.commodity())
+                .find(|commodity| commodity.is_currency()),
+        };
 
         // Create transaction
         let txn = Transaction::new(self.book);
-        txn.begin_edit();
+        let edit = txn.edit();
+
+        if let Some(currency) = &currency {
+            edit.set_currency(currency);
+        }
 
         if let Some(desc) = &self.description {
-            txn.set_description(desc);
+            edit.set_description(desc);
         }
         if let Some(num) = &self.num {
-            txn.set_num(num);
+            edit.set_num(num);
         }
         if let Some(notes) = &self.notes {
-            txn.set_notes(notes);
+            edit.set_notes(notes);
         }
         if let Some((day, month, year)) = self.date_posted {
-            txn.set_date(day, month, year);
+            edit.set_date(day, month, year);
         }
 
         // Create splits
         for split_spec in self.splits {
-            let split = Split::new(self.book);
-            split.set_account(split_spec.account);
-            split.set_transaction(&txn);
-            split.set_amount(split_spec.amount);
-            split.set_value(split_spec.amount);
+            let mut split = Split::new(self.book);
+            let s = edit.add_split(&split);
+            s.set_account(split_spec.account);
+            match split_spec.share_price {
+                Some(price) => s.set_share_price_and_amount(price, split_spec.amount),
+                None => {
+                    s.set_amount(split_spec.amount);
+                    s.set_value(split_spec.value);
+                }
+            }
             if let Some(memo) = &split_spec.memo {
-                split.set_memo(memo);
+                s.set_memo(memo);
             }
+            // The transaction owns the split now.
+            split.mark_unowned();
         }
 
-        txn.commit_edit();
+        edit.commit();
         Ok(txn)
     }
 }
 
-/// Calculates the least common multiple.
-fn lcm(a: i64, b: i64) -> i64 {
-    (a * b).abs() / gcd(a, b)
-}
-
-/// Calculates the greatest common divisor.
-fn gcd(mut a: i64, mut b: i64) -> i64 {
-    a = a.abs();
-    b = b.abs();
-    while b != 0 {
-        let t = b;
-        b = a % b;
-        a = t;
-    }
-    a
-}
-
 use crate::business::{Entry, Invoice, Owner};
 
 /// Builder for creating invoices with entries.
@@ -298,28 +349,28 @@ impl<'a> InvoiceBuilder<'a> {
     /// Builds and returns the invoice.
     pub fn build(self) -> gnucash_sys::Result<Invoice> {
         let invoice = Invoice::new(self.book);
-        invoice.begin_edit();
+        let edit = invoice.edit();
 
         if let Some(id) = &self.id {
-            invoice.set_id(id);
+            edit.set_id(id);
         }
         if let Some(notes) = &self.notes {
-            invoice.set_notes(notes);
+            edit.set_notes(notes);
         }
         if let Some(billing_id) = &self.billing_id {
-            invoice.set_billing_id(billing_id);
+            edit.set_billing_id(billing_id);
         }
         if let Some(owner) = self.owner {
-            invoice.set_owner(owner);
+            edit.set_owner(owner);
         }
         if let Some(date) = self.date_opened {
-            invoice.set_date_opened(date);
+            edit.set_date_opened(date);
         }
 
         // Create entries
         for entry_spec in self.entries {
             let entry = Entry::new(self.book);
-            entry.begin_edit();
+            let entry = entry.edit();
             entry.set_description(&entry_spec.description);
             entry.set_inv_price(entry_spec.price);
             entry.set_quantity(entry_spec.quantity);
@@ -327,12 +378,11 @@ impl<'a> InvoiceBuilder<'a> {
             if let Some(action) = &entry_spec.action {
                 entry.set_action(action);
             }
-            entry.commit_edit();
             // Note: Entry needs to be added to invoice via gncInvoiceAddEntry
             // which we'd need to expose
         }
 
-        invoice.commit_edit();
+        edit.commit();
         Ok(invoice)
     }
 }
@@ -342,15 +392,30 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_gcd() {
-        assert_eq!(gcd(12, 8), 4);
-        assert_eq!(gcd(100, 25), 25);
-        assert_eq!(gcd(17, 13), 1);
-    }
-
-    #[test]
-    fn test_lcm() {
-        assert_eq!(lcm(4, 6), 12);
-        assert_eq!(lcm(100, 100), 100);
+    fn test_check_balance() {
+        let book = Book::new();
+        let account = Account::new(&book);
+        let builder = |a: Numeric, b: Numeric| {
+            TransactionBuilder::new(&book)
+                .split(&account, a, None)
+                .split(&account, b, None)
+        };
+
+        assert!(
+            builder(Numeric::new(-1, 2), Numeric::new(50, 100))
+                .check_balance()
+                .is_ok()
+        );
+        assert!(
+            builder(Numeric::new(-1, 2), Numeric::new(1, 3))
+                .check_balance()
+                .is_err()
+        );
+        // The common denominator does not fit an i64.
+        assert!(
+            builder(Numeric::new(1, i64::MAX), Numeric::new(1, i64::MAX - 1))
+                .check_balance()
+                .is_err()
+        );
     }
 }
@@ -1,12 +1,14 @@
 //! Safe wrapper for GncAddress.
 
-use std::ffi::{CStr, CString};
+use std::ffi::{CStr, CString, c_char};
 use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::Book;
+use gnucash_sys::edit::Undo;
 
-/// A mailing address.
+/// A mailing address. Read-only; an owner's address is changed through
+/// the [`AddressEditor`] of the owner's edit session.
 pub struct Address {
     ptr: NonNull<ffi::GncAddress>,
     #[allow(dead_code)]
@@ -139,56 +141,136 @@ impl Address {
     // ==================== Setters ====================
 
     /// Sets the name.
-    pub fn set_name(&self, name: &str) {
+    fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe { ffi::gncAddressSetName(self.ptr.as_ptr(), c_name.as_ptr()) }
     }
 
     /// Sets address line 1.
-    pub fn set_addr1(&self, addr: &str) {
+    fn set_addr1(&self, addr: &str) {
         let c_addr = CString::new(addr).unwrap();
         unsafe { ffi::gncAddressSetAddr1(self.ptr.as_ptr(), c_addr.as_ptr()) }
     }
 
     /// Sets address line 2.
-    pub fn set_addr2(&self, addr: &str) {
+    fn set_addr2(&self, addr: &str) {
         let c_addr = CString::new(addr).unwrap();
         unsafe { ffi::gncAddressSetAddr2(self.ptr.as_ptr(), c_addr.as_ptr()) }
     }
 
     /// Sets address line 3.
-    pub fn set_addr3(&self, addr: &str) {
+    fn set_addr3(&self, addr: &str) {
         let c_addr = CString::new(addr).unwrap();
         unsafe { ffi::gncAddressSetAddr3(self.ptr.as_ptr(), c_addr.as_ptr()) }
     }
 
     /// Sets address line 4.
-    pub fn set_addr4(&self, addr: &str) {
+    fn set_addr4(&self, addr: &str) {
         let c_addr = CString::new(addr).unwrap();
         unsafe { ffi::gncAddressSetAddr4(self.ptr.as_ptr(), c_addr.as_ptr()) }
     }
 
     /// Sets the phone number.
-    pub fn set_phone(&self, phone: &str) {
+    fn set_phone(&self, phone: &str) {
         let c_phone = CString::new(phone).unwrap();
         unsafe { ffi::gncAddressSetPhone(self.ptr.as_ptr(), c_phone.as_ptr()) }
     }
 
     /// Sets the fax number.
-    pub fn set_fax(&self, fax: &str) {
+    fn set_fax(&self, fax: &str) {
         let c_fax = CString::new(fax).unwrap();
         unsafe { ffi::gncAddressSetFax(self.ptr.as_ptr(), c_fax.as_ptr()) }
     }
 
     /// Sets the email address.
-    pub fn set_email(&self, email: &str) {
+    fn set_email(&self, email: &str) {
         let c_email = CString::new(email).unwrap();
         unsafe { ffi::gncAddressSetEmail(self.ptr.as_ptr(), c_email.as_ptr()) }
     }
+}
+
+/// An address setter from the engine.
+type AddressSetter = unsafe extern "C" fn(*mut ffi::GncAddress, *const c_char);
+
+/// The setters of the [`Address`] of a customer, vendor or employee in
+/// an open edit session of its owner, reached through the owner's editor.
+/// Records each value it replaces so a rolled-back edit can put it back.
+pub struct AddressEditor<'a, T> {
+    address: Address,
+    undo: &'a Undo<T>,
+}
+
+impl<'a, T> AddressEditor<'a, T> {
+    pub(crate) fn new(address: Address, undo: &'a Undo<T>) -> Self {
+        Self { address, undo }
+    }
+
+    /// Records how to restore a field as it is now.
+    fn keep(&self, old: Option<String>, set: AddressSetter) {
+        let ptr = self.address.as_ptr();
+        let old = CString::new(old.unwrap_or_default()).unwrap_or_default();
+        self.undo.record(move |_| unsafe { set(ptr, old.as_ptr()) });
+    }
+
+    /// Sets the name.
+    pub fn set_name(&self, name: &str) {
+        self.keep(self.address.name(), ffi::gncAddressSetName);
+        self.address.set_name(name);
+    }
+
+    /// Sets address line 1.
+    pub fn set_addr1(&self, addr: &str) {
+        self.keep(self.address.addr1(), ffi::gncAddressSetAddr1);
+        self.address.set_addr1(addr);
+    }
+
+    /// Sets address line 2.
+    pub fn set_addr2(&self, addr: &str) {
+        self.keep(self.address.addr2(), ffi::gncAddressSetAddr2);
+        self.address.set_addr2(addr);
+    }
+
+    /// Sets address line 3.
+    pub fn set_addr3(&self, addr: &str) {
+        self.keep(self.address.addr3(), ffi::gncAddressSetAddr3);
+        self.address.set_addr3(addr);
+    }
+
+    /// Sets address line 4.
+    pub fn set_addr4(&self, addr: &str) {
+        self.keep(self.address.addr4(), ffi::gncAddressSetAddr4);
+        self.address.set_addr4(addr);
+    }
+
+    /// Sets the phone number.
+    pub fn set_phone(&self, phone: &str) {
+        self.keep(self.address.phone(), ffi::gncAddressSetPhone);
+        self.address.set_phone(phone);
+    }
+
+    /// Sets the fax number.
+    pub fn set_fax(&self, fax: &str) {
+        self.keep(self.address.fax(), ffi::gncAddressSetFax);
+        self.address.set_fax(fax);
+    }
+
+    /// Sets the email address.
+    pub fn set_email(&self, email: &str) {
+        self.keep(self.address.email(), ffi::gncAddressSetEmail);
+        self.address.set_email(email);
+    }
 
     /// Clears the address (sets all fields to empty).
     pub fn clear(&self) {
-        unsafe { ffi::gncAddressClearDirty(self.ptr.as_ptr()) }
+        unsafe { ffi::gncAddressClearDirty(self.address.as_ptr()) }
+    }
+}
+
+impl<T> std::ops::Deref for AddressEditor<'_, T> {
+    type Target = Address;
+
+    fn deref(&self) -> &Address {
+        &self.address
     }
 }
 
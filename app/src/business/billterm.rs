@@ -5,6 +5,8 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Book, Guid, Numeric};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
 pub use ffi::GncBillTermType as BillTermType;
 
@@ -41,12 +43,12 @@ impl BillTerm {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncBillTermBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncBillTermCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -63,6 +65,12 @@ impl BillTerm {
         }
     }
 
+    /// Returns the key-value slots attached to this bill term.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     // ==================== Getters ====================
 
     /// Returns the name.
@@ -122,39 +130,39 @@ impl BillTerm {
     // ==================== Setters ====================
 
     /// Sets the name.
-    pub fn set_name(&self, name: &str) {
+    fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe { ffi::gncBillTermSetName(self.ptr.as_ptr(), c_name.as_ptr()) }
     }
 
     /// Sets the description.
-    pub fn set_description(&self, desc: &str) {
+    fn set_description(&self, desc: &str) {
         let c_desc = CString::new(desc).unwrap();
         unsafe { ffi::gncBillTermSetDescription(self.ptr.as_ptr(), c_desc.as_ptr()) }
     }
 
     /// Sets the bill term type.
-    pub fn set_type(&self, term_type: BillTermType) {
+    fn set_type(&self, term_type: BillTermType) {
         unsafe { ffi::gncBillTermSetType(self.ptr.as_ptr(), term_type) }
     }
 
     /// Sets the due days.
-    pub fn set_due_days(&self, days: i32) {
+    fn set_due_days(&self, days: i32) {
         unsafe { ffi::gncBillTermSetDueDays(self.ptr.as_ptr(), days) }
     }
 
     /// Sets the discount days.
-    pub fn set_discount_days(&self, days: i32) {
+    fn set_discount_days(&self, days: i32) {
         unsafe { ffi::gncBillTermSetDiscountDays(self.ptr.as_ptr(), days) }
     }
 
     /// Sets the discount.
-    pub fn set_discount(&self, discount: Numeric) {
+    fn set_discount(&self, discount: Numeric) {
         unsafe { ffi::gncBillTermSetDiscount(self.ptr.as_ptr(), discount.into()) }
     }
 
     /// Sets the cutoff day.
-    pub fn set_cutoff(&self, cutoff: i32) {
+    fn set_cutoff(&self, cutoff: i32) {
         unsafe { ffi::gncBillTermSetCutoff(self.ptr.as_ptr(), cutoff) }
     }
 
@@ -170,6 +178,95 @@ impl BillTerm {
     }
 }
 
+/// The setters of a [`BillTerm`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct BillTermEditor<'a> {
+    term: &'a BillTerm,
+    undo: Undo<BillTerm>,
+}
+
+impl BillTermEditor<'_> {
+    /// Returns the bill term's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, BillTerm> {
+        SlotEditor::new(self.term.slots(), &self.undo)
+    }
+
+    /// Sets the name.
+    pub fn set_name(&self, name: &str) {
+        let old = self.term.name().unwrap_or_default();
+        self.undo.record(move |e| e.set_name(&old));
+        self.term.set_name(name);
+    }
+
+    /// Sets the description.
+    pub fn set_description(&self, desc: &str) {
+        let old = self.term.description().unwrap_or_default();
+        self.undo.record(move |e| e.set_description(&old));
+        self.term.set_description(desc);
+    }
+
+    /// Sets the bill term type.
+    pub fn set_type(&self, term_type: BillTermType) {
+        let old = self.term.term_type();
+        self.undo.record(move |e| e.set_type(old));
+        self.term.set_type(term_type);
+    }
+
+    /// Sets the due days.
+    pub fn set_due_days(&self, days: i32) {
+        let old = self.term.due_days();
+        self.undo.record(move |e| e.set_due_days(old));
+        self.term.set_due_days(days);
+    }
+
+    /// Sets the discount days.
+    pub fn set_discount_days(&self, days: i32) {
+        let old = self.term.discount_days();
+        self.undo.record(move |e| e.set_discount_days(old));
+        self.term.set_discount_days(days);
+    }
+
+    /// Sets the discount.
+    pub fn set_discount(&self, discount: Numeric) {
+        let old = self.term.discount();
+        self.undo.record(move |e| e.set_discount(old));
+        self.term.set_discount(discount);
+    }
+
+    /// Sets the cutoff day.
+    pub fn set_cutoff(&self, cutoff: i32) {
+        let old = self.term.cutoff();
+        self.undo.record(move |e| e.set_cutoff(old));
+        self.term.set_cutoff(cutoff);
+    }
+}
+
+impl std::ops::Deref for BillTermEditor<'_> {
+    type Target = BillTerm;
+
+    fn deref(&self) -> &BillTerm {
+        self.term
+    }
+}
+
+impl Editable for BillTerm {
+    type Editor<'e> = BillTermEditor<'e>;
+
+    fn begin_edit(&self) {
+        BillTerm::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        BillTerm::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> BillTermEditor<'_> {
+        BillTermEditor { term: self, undo }
+    }
+}
+
 impl std::fmt::Debug for BillTerm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BillTerm")
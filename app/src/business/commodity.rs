@@ -138,6 +138,16 @@ impl Commodity {
     pub fn fraction(&self) -> i32 {
         unsafe { ffi::gnc_commodity_get_fraction(self.ptr.as_ptr()) as i32 }
     }
+
+    /// Returns true if the commodity is an ISO 4217 currency.
+    pub fn is_currency(&self) -> bool {
+        unsafe { ffi::gnc_commodity_is_currency(self.ptr.as_ptr()) != 0 }
+    }
+
+    /// Returns true if both refer to the same namespace and mnemonic.
+    pub fn equiv(&self, other: &Commodity) -> bool {
+        unsafe { ffi::gnc_commodity_equiv(self.ptr.as_ptr(), other.as_ptr()) != 0 }
+    }
 }
 
 impl std::fmt::Debug for Commodity {
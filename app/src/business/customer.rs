@@ -5,8 +5,10 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Book, Guid, Numeric};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
-use super::{Address, BillTerm, Commodity, Owner, TaxTable};
+use super::{Address, AddressEditor, BillTerm, Commodity, Owner, TaxTable};
 
 /// A customer entity.
 pub struct Customer {
@@ -41,12 +43,12 @@ impl Customer {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncCustomerBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncCustomerCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -63,6 +65,12 @@ impl Customer {
         }
     }
 
+    /// Returns the key-value slots attached to this customer.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     /// Creates an Owner from this customer.
     pub fn to_owner(&self) -> Owner {
         let mut owner = Owner::new();
@@ -142,52 +150,158 @@ impl Customer {
     // ==================== Setters ====================
 
     /// Sets the customer ID.
-    pub fn set_id(&self, id: &str) {
+    fn set_id(&self, id: &str) {
         let c_id = CString::new(id).unwrap();
         unsafe { ffi::gncCustomerSetID(self.ptr.as_ptr(), c_id.as_ptr()) }
     }
 
     /// Sets the customer name.
-    pub fn set_name(&self, name: &str) {
+    fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe { ffi::gncCustomerSetName(self.ptr.as_ptr(), c_name.as_ptr()) }
     }
 
     /// Sets the notes.
-    pub fn set_notes(&self, notes: &str) {
+    fn set_notes(&self, notes: &str) {
         let c_notes = CString::new(notes).unwrap();
         unsafe { ffi::gncCustomerSetNotes(self.ptr.as_ptr(), c_notes.as_ptr()) }
     }
 
     /// Sets the discount.
-    pub fn set_discount(&self, discount: Numeric) {
+    fn set_discount(&self, discount: Numeric) {
         unsafe { ffi::gncCustomerSetDiscount(self.ptr.as_ptr(), discount.into()) }
     }
 
     /// Sets the credit limit.
-    pub fn set_credit(&self, credit: Numeric) {
+    fn set_credit(&self, credit: Numeric) {
         unsafe { ffi::gncCustomerSetCredit(self.ptr.as_ptr(), credit.into()) }
     }
 
     /// Sets the active flag.
-    pub fn set_active(&self, active: bool) {
+    fn set_active(&self, active: bool) {
         unsafe { ffi::gncCustomerSetActive(self.ptr.as_ptr(), active as i32) }
     }
 
+    /// Sets the customer's default currency.
+    fn set_currency(&self, currency: &Commodity) {
+        unsafe { ffi::gncCustomerSetCurrency(self.ptr.as_ptr(), currency.as_ptr()) }
+    }
+
+    /// Sets the customer's default bill terms.
+    fn set_terms(&self, terms: &BillTerm) {
+        unsafe { ffi::gncCustomerSetTerms(self.ptr.as_ptr(), terms.as_ptr()) }
+    }
+
+    /// Sets the customer's default tax table.
+    fn set_tax_table(&self, table: &TaxTable) {
+        unsafe { ffi::gncCustomerSetTaxTable(self.ptr.as_ptr(), table.as_ptr()) }
+    }
+
+    /// Sets the tax table override flag.
+    fn set_tax_table_override(&self, override_default: bool) {
+        unsafe {
+            ffi::gncCustomerSetTaxTableOverride(
+                self.ptr.as_ptr(),
+                if override_default { 1 } else { 0 },
+            )
+        }
+    }
+
+}
+
+/// The setters of a [`Customer`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct CustomerEditor<'a> {
+    customer: &'a Customer,
+    undo: Undo<Customer>,
+}
+
+impl CustomerEditor<'_> {
+    /// Returns the customer's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Customer> {
+        SlotEditor::new(self.customer.slots(), &self.undo)
+    }
+
+    /// Returns the customer's billing address for writing. Its changes roll back
+    /// with the customer's edit.
+    pub fn addr(&self) -> Option<AddressEditor<'_, Customer>> {
+        self.customer
+            .addr()
+            .map(|addr| AddressEditor::new(addr, &self.undo))
+    }
+
+    /// Returns the customer's shipping address for writing. Its changes
+    /// roll back with the customer's edit.
+    pub fn ship_addr(&self) -> Option<AddressEditor<'_, Customer>> {
+        self.customer
+            .ship_addr()
+            .map(|addr| AddressEditor::new(addr, &self.undo))
+    }
+
+    /// Sets the customer ID.
+    pub fn set_id(&self, id: &str) {
+        let old = self.customer.id().unwrap_or_default();
+        self.undo.record(move |c| c.set_id(&old));
+        self.customer.set_id(id);
+    }
+
+    /// Sets the customer name.
+    pub fn set_name(&self, name: &str) {
+        let old = self.customer.name().unwrap_or_default();
+        self.undo.record(move |c| c.set_name(&old));
+        self.customer.set_name(name);
+    }
+
+    /// Sets the notes.
+    pub fn set_notes(&self, notes: &str) {
+        let old = self.customer.notes().unwrap_or_default();
+        self.undo.record(move |c| c.set_notes(&old));
+        self.customer.set_notes(notes);
+    }
+
+    /// Sets the discount.
+    pub fn set_discount(&self, discount: Numeric) {
+        let old = self.customer.discount();
+        self.undo.record(move |c| c.set_discount(old));
+        self.customer.set_discount(discount);
+    }
+
+    /// Sets the credit limit.
+    pub fn set_credit(&self, credit: Numeric) {
+        let old = self.customer.credit();
+        self.undo.record(move |c| c.set_credit(old));
+        self.customer.set_credit(credit);
+    }
+
+    /// Sets the active flag.
+    pub fn set_active(&self, active: bool) {
+        let old = self.customer.is_active();
+        self.undo.record(move |c| c.set_active(old));
+        self.customer.set_active(active);
+    }
+
     /// Sets the customer's default currency. Invoices opened against
     /// this customer inherit this currency, which is required for
     /// `Invoice::post_to_account` to succeed.
     pub fn set_currency(&self, currency: &Commodity) {
-        unsafe { ffi::gncCustomerSetCurrency(self.ptr.as_ptr(), currency.as_ptr()) }
+        let old = unsafe { ffi::gncCustomerGetCurrency(self.customer.as_ptr()) };
+        self.undo
+            .record(move |c| unsafe { ffi::gncCustomerSetCurrency(c.as_ptr(), old) });
+        self.customer.set_currency(currency);
     }
 
     /// Sets the customer's default bill terms. Invoices opened
     /// against this customer inherit these unless explicitly
-    /// overridden via [`Invoice::set_terms`].
+    /// overridden via [`InvoiceEditor::set_terms`].
     ///
-    /// [`Invoice::set_terms`]: super::Invoice::set_terms
+    /// [`InvoiceEditor::set_terms`]: super::invoice::InvoiceEditor::set_terms
     pub fn set_terms(&self, terms: &BillTerm) {
-        unsafe { ffi::gncCustomerSetTerms(self.ptr.as_ptr(), terms.as_ptr()) }
+        let old = unsafe { ffi::gncCustomerGetTerms(self.customer.as_ptr()) };
+        self.undo
+            .record(move |c| unsafe { ffi::gncCustomerSetTerms(c.as_ptr(), old) });
+        self.customer.set_terms(terms);
     }
 
     /// Sets the customer's default tax table. Has no effect unless
@@ -197,7 +311,10 @@ impl Customer {
     ///
     /// [`set_tax_table_override`]: Self::set_tax_table_override
     pub fn set_tax_table(&self, table: &TaxTable) {
-        unsafe { ffi::gncCustomerSetTaxTable(self.ptr.as_ptr(), table.as_ptr()) }
+        let old = unsafe { ffi::gncCustomerGetTaxTable(self.customer.as_ptr()) };
+        self.undo
+            .record(move |c| unsafe { ffi::gncCustomerSetTaxTable(c.as_ptr(), old) });
+        self.customer.set_tax_table(table);
     }
 
     /// Toggles whether [`set_tax_table`] takes precedence over the
@@ -205,14 +322,38 @@ impl Customer {
     ///
     /// [`set_tax_table`]: Self::set_tax_table
     pub fn set_tax_table_override(&self, override_default: bool) {
-        unsafe {
-            ffi::gncCustomerSetTaxTableOverride(
-                self.ptr.as_ptr(),
-                if override_default { 1 } else { 0 },
-            )
-        }
+        let old = unsafe { ffi::gncCustomerGetTaxTableOverride(self.customer.as_ptr()) };
+        self.undo
+            .record(move |c| unsafe { ffi::gncCustomerSetTaxTableOverride(c.as_ptr(), old) });
+        self.customer.set_tax_table_override(override_default);
+    }
+}
+
+impl std::ops::Deref for CustomerEditor<'_> {
+    type Target = Customer;
+
+    fn deref(&self) -> &Customer {
+        self.customer
+    }
+}
+
+impl Editable for Customer {
+    type Editor<'e> = CustomerEditor<'e>;
+
+    fn begin_edit(&self) {
+        Customer::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Customer::commit_edit(self)
     }
 
+    fn editor(&self, undo: Undo<Self>) -> CustomerEditor<'_> {
+        CustomerEditor {
+            customer: self,
+            undo,
+        }
+    }
 }
 
 impl std::fmt::Debug for Customer {
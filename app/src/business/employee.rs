@@ -5,8 +5,10 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Book, Guid, Numeric};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
-use super::{Address, Owner};
+use super::{Address, AddressEditor, Owner};
 
 /// An employee entity.
 pub struct Employee {
@@ -41,12 +43,12 @@ impl Employee {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncEmployeeBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncEmployeeCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -63,6 +65,12 @@ impl Employee {
         }
     }
 
+    /// Returns the key-value slots attached to this employee.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     /// Creates an Owner from this employee.
     pub fn to_owner(&self) -> Owner {
         let mut owner = Owner::new();
@@ -134,40 +142,133 @@ impl Employee {
     // ==================== Setters ====================
 
     /// Sets the employee ID.
-    pub fn set_id(&self, id: &str) {
+    fn set_id(&self, id: &str) {
         let c_id = CString::new(id).unwrap();
         unsafe { ffi::gncEmployeeSetID(self.ptr.as_ptr(), c_id.as_ptr()) }
     }
 
     /// Sets the employee username.
-    pub fn set_username(&self, username: &str) {
+    fn set_username(&self, username: &str) {
         let c_username = CString::new(username).unwrap();
         unsafe { ffi::gncEmployeeSetUsername(self.ptr.as_ptr(), c_username.as_ptr()) }
     }
 
     /// Sets the language.
-    pub fn set_language(&self, language: &str) {
+    fn set_language(&self, language: &str) {
         let c_language = CString::new(language).unwrap();
         unsafe { ffi::gncEmployeeSetLanguage(self.ptr.as_ptr(), c_language.as_ptr()) }
     }
 
     /// Sets the workday (hours per day).
-    pub fn set_workday(&self, workday: Numeric) {
+    fn set_workday(&self, workday: Numeric) {
         unsafe { ffi::gncEmployeeSetWorkday(self.ptr.as_ptr(), workday.into()) }
     }
 
     /// Sets the rate (hourly rate).
-    pub fn set_rate(&self, rate: Numeric) {
+    fn set_rate(&self, rate: Numeric) {
         unsafe { ffi::gncEmployeeSetRate(self.ptr.as_ptr(), rate.into()) }
     }
 
     /// Sets the active flag.
-    pub fn set_active(&self, active: bool) {
+    fn set_active(&self, active: bool) {
         unsafe { ffi::gncEmployeeSetActive(self.ptr.as_ptr(), active as i32) }
     }
 
 }
 
+/// The setters of an [`Employee`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct EmployeeEditor<'a> {
+    employee: &'a Employee,
+    undo: Undo<Employee>,
+}
+
+impl EmployeeEditor<'_> {
+    /// Returns the employee's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Employee> {
+        SlotEditor::new(self.employee.slots(), &self.undo)
+    }
+
+    /// Returns the employee's address for writing. Its changes roll back
+    /// with the employee's edit.
+    pub fn addr(&self) -> Option<AddressEditor<'_, Employee>> {
+        self.employee
+            .addr()
+            .map(|addr| AddressEditor::new(addr, &self.undo))
+    }
+
+    /// Sets the employee ID.
+    pub fn set_id(&self, id: &str) {
+        let old = self.employee.id().unwrap_or_default();
+        self.undo.record(move |e| e.set_id(&old));
+        self.employee.set_id(id);
+    }
+
+    /// Sets the employee username.
+    pub fn set_username(&self, username: &str) {
+        let old = self.employee.username().unwrap_or_default();
+        self.undo.record(move |e| e.set_username(&old));
+        self.employee.set_username(username);
+    }
+
+    /// Sets the language.
+    pub fn set_language(&self, language: &str) {
+        let old = self.employee.language().unwrap_or_default();
+        self.undo.record(move |e| e.set_language(&old));
+        self.employee.set_language(language);
+    }
+
+    /// Sets the workday (hours per day).
+    pub fn set_workday(&self, workday: Numeric) {
+        let old = self.employee.workday();
+        self.undo.record(move |e| e.set_workday(old));
+        self.employee.set_workday(workday);
+    }
+
+    /// Sets the rate (hourly rate).
+    pub fn set_rate(&self, rate: Numeric) {
+        let old = self.employee.rate();
+        self.undo.record(move |e| e.set_rate(old));
+        self.employee.set_rate(rate);
+    }
+
+    /// Sets the active flag.
+    pub fn set_active(&self, active: bool) {
+        let old = self.employee.is_active();
+        self.undo.record(move |e| e.set_active(old));
+        self.employee.set_active(active);
+    }
+}
+
+impl std::ops::Deref for EmployeeEditor<'_> {
+    type Target = Employee;
+
+    fn deref(&self) -> &Employee {
+        self.employee
+    }
+}
+
+impl Editable for Employee {
+    type Editor<'e> = EmployeeEditor<'e>;
+
+    fn begin_edit(&self) {
+        Employee::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Employee::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> EmployeeEditor<'_> {
+        EmployeeEditor {
+            employee: self,
+            undo,
+        }
+    }
+}
+
 impl std::fmt::Debug for Employee {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Employee")
@@ -5,6 +5,8 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Account, Book, Guid, Numeric};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
 use super::tax::{AmountType, DiscountHow};
 use super::{Invoice, TaxTable};
@@ -42,12 +44,12 @@ impl Entry {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncEntryBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncEntryCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -64,6 +66,12 @@ impl Entry {
         }
     }
 
+    /// Returns the key-value slots attached to this entry.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     // ==================== Getters ====================
 
     /// Returns the entry date.
@@ -167,81 +175,70 @@ impl Entry {
     // ==================== Setters ====================
 
     /// Sets the entry date.
-    pub fn set_date(&self, date: i64) {
+    fn set_date(&self, date: i64) {
         unsafe { ffi::gncEntrySetDate(self.ptr.as_ptr(), date) }
     }
 
     /// Sets the date entered.
-    pub fn set_date_entered(&self, date: i64) {
+    fn set_date_entered(&self, date: i64) {
         unsafe { ffi::gncEntrySetDateEntered(self.ptr.as_ptr(), date) }
     }
 
     /// Sets the description.
-    pub fn set_description(&self, desc: &str) {
+    fn set_description(&self, desc: &str) {
         let c_desc = CString::new(desc).unwrap();
         unsafe { ffi::gncEntrySetDescription(self.ptr.as_ptr(), c_desc.as_ptr()) }
     }
 
     /// Sets the action.
-    pub fn set_action(&self, action: &str) {
+    fn set_action(&self, action: &str) {
         let c_action = CString::new(action).unwrap();
         unsafe { ffi::gncEntrySetAction(self.ptr.as_ptr(), c_action.as_ptr()) }
     }
 
     /// Sets the notes.
-    pub fn set_notes(&self, notes: &str) {
+    fn set_notes(&self, notes: &str) {
         let c_notes = CString::new(notes).unwrap();
         unsafe { ffi::gncEntrySetNotes(self.ptr.as_ptr(), c_notes.as_ptr()) }
     }
 
     /// Sets the quantity.
-    pub fn set_quantity(&self, quantity: Numeric) {
+    fn set_quantity(&self, quantity: Numeric) {
         unsafe { ffi::gncEntrySetQuantity(self.ptr.as_ptr(), quantity.into()) }
     }
 
     /// Sets the invoice price.
-    pub fn set_inv_price(&self, price: Numeric) {
+    fn set_inv_price(&self, price: Numeric) {
         unsafe { ffi::gncEntrySetInvPrice(self.ptr.as_ptr(), price.into()) }
     }
 
     /// Sets the invoice discount.
-    pub fn set_inv_discount(&self, discount: Numeric) {
+    fn set_inv_discount(&self, discount: Numeric) {
         unsafe { ffi::gncEntrySetInvDiscount(self.ptr.as_ptr(), discount.into()) }
     }
 
-    /// Sets how the invoice discount value is interpreted —
-    /// `GNC_AMT_TYPE_VALUE` for an absolute currency amount,
-    /// `GNC_AMT_TYPE_PERCENT` for a percentage of the line subtotal.
-    /// Without this, libgnucash falls back to whatever default the
-    /// owning entry was last edited with, which can flip discount
-    /// semantics silently when an invoice is round-tripped through
-    /// the API.
-    pub fn set_inv_discount_type(&self, kind: AmountType) {
+    /// Sets how the invoice discount value is interpreted.
+    fn set_inv_discount_type(&self, kind: AmountType) {
         unsafe { ffi::gncEntrySetInvDiscountType(self.ptr.as_ptr(), kind) }
     }
 
-    /// Sets when the invoice discount is applied relative to tax —
-    /// `PRETAX`, `SAMETIME`, or `POSTTAX`. Pairs with
-    /// [`set_inv_discount_type`] to fully describe the discount
-    /// behavior of a programmatically-built line.
-    ///
-    /// [`set_inv_discount_type`]: Self::set_inv_discount_type
-    pub fn set_inv_discount_how(&self, how: DiscountHow) {
+    /// Sets when the invoice discount is applied relative to tax.
+    fn set_inv_discount_how(&self, how: DiscountHow) {
         unsafe { ffi::gncEntrySetInvDiscountHow(self.ptr.as_ptr(), how) }
     }
 
     /// Sets the invoice account.
-    pub fn set_inv_account(&self, account: &Account) {
+    fn set_inv_account(&self, account: &Account) {
         unsafe { ffi::gncEntrySetInvAccount(self.ptr.as_ptr(), account.as_ptr()) }
     }
 
     /// Sets the bill price.
-    pub fn set_bill_price(&self, price: Numeric) {
+    fn set_bill_price(&self, price: Numeric) {
         unsafe { ffi::gncEntrySetBillPrice(self.ptr.as_ptr(), price.into()) }
     }
 
     /// Sets the bill account.
-    pub fn set_bill_account(&self, account: &Account) {
+    fn set_bill_account(&self, account: &Account) {
         unsafe { ffi::gncEntrySetBillAccount(self.ptr.as_ptr(), account.as_ptr()) }
     }
 
@@ -256,7 +253,7 @@ impl Entry {
     }
 
     /// Sets the invoice tax table.
-    pub fn set_inv_tax_table(&self, table: &TaxTable) {
+    fn set_inv_tax_table(&self, table: &TaxTable) {
         unsafe { ffi::gncEntrySetInvTaxTable(self.ptr.as_ptr(), table.as_ptr()) }
     }
 
@@ -266,7 +263,7 @@ impl Entry {
     }
 
     /// Sets whether tax is included in the invoice price.
-    pub fn set_inv_tax_included(&self, included: bool) {
+    fn set_inv_tax_included(&self, included: bool) {
         unsafe { ffi::gncEntrySetInvTaxIncluded(self.ptr.as_ptr(), included as i32) }
     }
 
@@ -276,12 +273,180 @@ impl Entry {
     }
 
     /// Sets whether the entry is taxable (invoice).
-    pub fn set_inv_taxable(&self, taxable: bool) {
+    fn set_inv_taxable(&self, taxable: bool) {
         unsafe { ffi::gncEntrySetInvTaxable(self.ptr.as_ptr(), taxable as i32) }
     }
 
 }
 
+/// The setters of an [`Entry`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct EntryEditor<'a> {
+    entry: &'a Entry,
+    undo: Undo<Entry>,
+}
+
+impl EntryEditor<'_> {
+    /// Returns the entry's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Entry> {
+        SlotEditor::new(self.entry.slots(), &self.undo)
+    }
+
+    /// Sets the entry date.
+    pub fn set_date(&self, date: i64) {
+        let old = self.entry.date();
+        self.undo.record(move |e| e.set_date(old));
+        self.entry.set_date(date);
+    }
+
+    /// Sets the date entered.
+    pub fn set_date_entered(&self, date: i64) {
+        let old = self.entry.date_entered();
+        self.undo.record(move |e| e.set_date_entered(old));
+        self.entry.set_date_entered(date);
+    }
+
+    /// Sets the description.
+    pub fn set_description(&self, desc: &str) {
+        let old = self.entry.description().unwrap_or_default();
+        self.undo.record(move |e| e.set_description(&old));
+        self.entry.set_description(desc);
+    }
+
+    /// Sets the action.
+    pub fn set_action(&self, action: &str) {
+        let old = self.entry.action().unwrap_or_default();
+        self.undo.record(move |e| e.set_action(&old));
+        self.entry.set_action(action);
+    }
+
+    /// Sets the notes.
+    pub fn set_notes(&self, notes: &str) {
+        let old = self.entry.notes().unwrap_or_default();
+        self.undo.record(move |e| e.set_notes(&old));
+        self.entry.set_notes(notes);
+    }
+
+    /// Sets the quantity.
+    pub fn set_quantity(&self, quantity: Numeric) {
+        let old = self.entry.quantity();
+        self.undo.record(move |e| e.set_quantity(old));
+        self.entry.set_quantity(quantity);
+    }
+
+    /// Sets the invoice price.
+    pub fn set_inv_price(&self, price: Numeric) {
+        let old = self.entry.inv_price();
+        self.undo.record(move |e| e.set_inv_price(old));
+        self.entry.set_inv_price(price);
+    }
+
+    /// Sets the invoice discount.
+    pub fn set_inv_discount(&self, discount: Numeric) {
+        let old = self.entry.inv_discount();
+        self.undo.record(move |e| e.set_inv_discount(old));
+        self.entry.set_inv_discount(discount);
+    }
+
+    /// Sets how the invoice discount value is interpreted —
+    /// `GNC_AMT_TYPE_VALUE` for an absolute currency amount,
+    /// `GNC_AMT_TYPE_PERCENT` for a percentage of the line subtotal.
+    /// Without this, libgnucash falls back to whatever default the
+    /// owning entry was last edited with, which can flip discount
+    /// semantics silently when an invoice is round-tripped through
+    /// the API.
+    pub fn set_inv_discount_type(&self, kind: AmountType) {
+        let old = unsafe { ffi::gncEntryGetInvDiscountType(self.entry.as_ptr()) };
+        self.undo
+            .record(move |e| unsafe { ffi::gncEntrySetInvDiscountType(e.as_ptr(), old) });
+        self.entry.set_inv_discount_type(kind);
+    }
+
+    /// Sets when the invoice discount is applied relative to tax —
+    /// `PRETAX`, `SAMETIME`, or `POSTTAX`. Pairs with
+    /// [`set_inv_discount_type`] to fully describe the discount
+    /// behavior of a programmatically-built line.
+    ///
+    /// [`set_inv_discount_type`]: Self::set_inv_discount_type
+    pub fn set_inv_discount_how(&self, how: DiscountHow) {
+        let old = unsafe { ffi::gncEntryGetInvDiscountHow(self.entry.as_ptr()) };
+        self.undo
+            .record(move |e| unsafe { ffi::gncEntrySetInvDiscountHow(e.as_ptr(), old) });
+        self.entry.set_inv_discount_how(how);
+    }
+
+    /// Sets the invoice account.
+    pub fn set_inv_account(&self, account: &Account) {
+        let old = unsafe { ffi::gncEntryGetInvAccount(self.entry.as_ptr()) };
+        self.undo
+            .record(move |e| unsafe { ffi::gncEntrySetInvAccount(e.as_ptr(), old) });
+        self.entry.set_inv_account(account);
+    }
+
+    /// Sets the bill price.
+    pub fn set_bill_price(&self, price: Numeric) {
+        let old = self.entry.bill_price();
+        self.undo.record(move |e| e.set_bill_price(old));
+        self.entry.set_bill_price(price);
+    }
+
+    /// Sets the bill account.
+    pub fn set_bill_account(&self, account: &Account) {
+        let old = unsafe { ffi::gncEntryGetBillAccount(self.entry.as_ptr()) };
+        self.undo
+            .record(move |e| unsafe { ffi::gncEntrySetBillAccount(e.as_ptr(), old) });
+        self.entry.set_bill_account(account);
+    }
+
+    /// Sets the invoice tax table.
+    pub fn set_inv_tax_table(&self, table: &TaxTable) {
+        let old = unsafe { ffi::gncEntryGetInvTaxTable(self.entry.as_ptr()) };
+        self.undo
+            .record(move |e| unsafe { ffi::gncEntrySetInvTaxTable(e.as_ptr(), old) });
+        self.entry.set_inv_tax_table(table);
+    }
+
+    /// Sets whether tax is included in the invoice price.
+    pub fn set_inv_tax_included(&self, included: bool) {
+        let old = self.entry.inv_tax_included();
+        self.undo.record(move |e| e.set_inv_tax_included(old));
+        self.entry.set_inv_tax_included(included);
+    }
+
+    /// Sets whether the entry is taxable (invoice).
+    pub fn set_inv_taxable(&self, taxable: bool) {
+        let old = self.entry.inv_taxable();
+        self.undo.record(move |e| e.set_inv_taxable(old));
+        self.entry.set_inv_taxable(taxable);
+    }
+}
+
+impl std::ops::Deref for EntryEditor<'_> {
+    type Target = Entry;
+
+    fn deref(&self) -> &Entry {
+        self.entry
+    }
+}
+
+impl Editable for Entry {
+    type Editor<'e> = EntryEditor<'e>;
+
+    fn begin_edit(&self) {
+        Entry::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Entry::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> EntryEditor<'_> {
+        EntryEditor { entry: self, undo }
+    }
+}
+
 impl std::fmt::Debug for Entry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Entry")
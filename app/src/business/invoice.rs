@@ -5,6 +5,8 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Account, Book, Guid, Numeric, Transaction};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
 use super::{BillTerm, Commodity, Entry, Owner};
 
@@ -43,12 +45,12 @@ impl Invoice {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncInvoiceBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncInvoiceCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -65,6 +67,12 @@ impl Invoice {
         }
     }
 
+    /// Returns the key-value slots attached to this invoice.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     // ==================== Getters ====================
 
     /// Returns the invoice ID.
@@ -179,60 +187,50 @@ impl Invoice {
     // ==================== Setters ====================
 
     /// Sets the invoice ID.
-    pub fn set_id(&self, id: &str) {
+    fn set_id(&self, id: &str) {
         let c_id = CString::new(id).unwrap();
         unsafe { ffi::gncInvoiceSetID(self.ptr.as_ptr(), c_id.as_ptr()) }
     }
 
     /// Sets the invoice notes.
-    pub fn set_notes(&self, notes: &str) {
+    fn set_notes(&self, notes: &str) {
         let c_notes = CString::new(notes).unwrap();
         unsafe { ffi::gncInvoiceSetNotes(self.ptr.as_ptr(), c_notes.as_ptr()) }
     }
 
     /// Sets the billing ID.
-    pub fn set_billing_id(&self, billing_id: &str) {
+    fn set_billing_id(&self, billing_id: &str) {
         let c_billing_id = CString::new(billing_id).unwrap();
         unsafe { ffi::gncInvoiceSetBillingID(self.ptr.as_ptr(), c_billing_id.as_ptr()) }
     }
 
     /// Sets the owner of this invoice.
-    pub fn set_owner(&self, owner: &Owner) {
+    fn set_owner(&self, owner: &Owner) {
         unsafe { ffi::gncInvoiceSetOwner(self.ptr.as_ptr(), owner.as_ptr()) }
     }
 
     /// Sets the date opened.
-    pub fn set_date_opened(&self, date: i64) {
+    fn set_date_opened(&self, date: i64) {
         unsafe { ffi::gncInvoiceSetDateOpened(self.ptr.as_ptr(), date) }
     }
 
-    /// Sets the currency for this invoice. Required before posting;
-    /// `Invoice::post_to_account` will otherwise refuse to create the
-    /// posted transaction.
-    pub fn set_currency(&self, currency: &Commodity) {
+    /// Sets the currency for this invoice.
+    fn set_currency(&self, currency: &Commodity) {
         unsafe { ffi::gncInvoiceSetCurrency(self.ptr.as_ptr(), currency.as_ptr()) }
     }
 
-    /// Sets the bill terms (net 30, due-on-receipt, etc.). Pulls
-    /// from the book's bill-term registry — use
-    /// [`BillTerm::lookup_by_name`] to resolve a name to a handle
-    /// first.
-    pub fn set_terms(&self, terms: &BillTerm) {
+    /// Sets the bill terms.
+    fn set_terms(&self, terms: &BillTerm) {
         unsafe { ffi::gncInvoiceSetTerms(self.ptr.as_ptr(), terms.as_ptr()) }
     }
 
-    /// Sets the active flag. Inactive invoices are hidden from the
-    /// default GUI invoice list.
-    pub fn set_active(&self, active: bool) {
+    /// Sets the active flag.
+    fn set_active(&self, active: bool) {
         unsafe { ffi::gncInvoiceSetActive(self.ptr.as_ptr(), if active { 1 } else { 0 }) }
     }
 
-    /// Marks this invoice as a credit note rather than a regular
-    /// invoice. Credit-note posting flips the sign of the resulting
-    /// transaction. Set this before [`post_to_account`].
-    ///
-    /// [`post_to_account`]: Self::post_to_account
-    pub fn set_is_credit_note(&self, credit_note: bool) {
+    /// Sets the credit note flag.
+    fn set_is_credit_note(&self, credit_note: bool) {
         unsafe {
             ffi::gncInvoiceSetIsCreditNote(
                 self.ptr.as_ptr(),
@@ -241,16 +239,13 @@ impl Invoice {
         }
     }
 
-    /// Sets the bill-to owner — the third-party who actually pays
-    /// when the receiving customer is themselves billing through.
-    /// Most invoices leave this unset.
-    pub fn set_bill_to(&self, owner: &Owner) {
+    /// Sets the bill-to owner.
+    fn set_bill_to(&self, owner: &Owner) {
         unsafe { ffi::gncInvoiceSetBillTo(self.ptr.as_ptr(), owner.as_ptr()) }
     }
 
-    /// Sets the document link — a URL or filesystem path pointing
-    /// at supplemental paperwork (PO scan, contract, etc.).
-    pub fn set_doc_link(&self, link: &str) {
+    /// Sets the document link.
+    fn set_doc_link(&self, link: &str) {
         let c_link = CString::new(link).unwrap();
         unsafe { ffi::gncInvoiceSetDocLink(self.ptr.as_ptr(), c_link.as_ptr()) }
     }
@@ -258,12 +253,12 @@ impl Invoice {
     // ==================== Entry Management ====================
 
     /// Adds an entry to this invoice.
-    pub fn add_entry(&self, entry: &super::Entry) {
+    fn add_entry(&self, entry: &super::Entry) {
         unsafe { ffi::gncInvoiceAddEntry(self.ptr.as_ptr(), entry.as_ptr()) }
     }
 
     /// Removes an entry from this invoice.
-    pub fn remove_entry(&self, entry: &super::Entry) {
+    fn remove_entry(&self, entry: &super::Entry) {
         unsafe { ffi::gncInvoiceRemoveEntry(self.ptr.as_ptr(), entry.as_ptr()) }
     }
 
@@ -353,6 +348,173 @@ impl Invoice {
 
 }
 
+/// The setters of an [`Invoice`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct InvoiceEditor<'a> {
+    invoice: &'a Invoice,
+    undo: Undo<Invoice>,
+}
+
+impl InvoiceEditor<'_> {
+    /// Returns the invoice's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Invoice> {
+        SlotEditor::new(self.invoice.slots(), &self.undo)
+    }
+
+    /// Sets the invoice ID.
+    pub fn set_id(&self, id: &str) {
+        let old = self.invoice.id().unwrap_or_default();
+        self.undo.record(move |e| e.set_id(&old));
+        self.invoice.set_id(id);
+    }
+
+    /// Sets the invoice notes.
+    pub fn set_notes(&self, notes: &str) {
+        let old = self.invoice.notes().unwrap_or_default();
+        self.undo.record(move |e| e.set_notes(&old));
+        self.invoice.set_notes(notes);
+    }
+
+    /// Sets the billing ID.
+    pub fn set_billing_id(&self, billing_id: &str) {
+        let old = self.invoice.billing_id().unwrap_or_default();
+        self.undo.record(move |e| e.set_billing_id(&old));
+        self.invoice.set_billing_id(billing_id);
+    }
+
+    /// Sets the owner of this invoice.
+    pub fn set_owner(&self, owner: &Owner) {
+        let old = self.invoice.owner();
+        self.undo.record(move |e| e.set_owner(&old));
+        self.invoice.set_owner(owner);
+    }
+
+    /// Sets the date opened.
+    pub fn set_date_opened(&self, date: i64) {
+        let old = self.invoice.date_opened();
+        self.undo.record(move |e| e.set_date_opened(old));
+        self.invoice.set_date_opened(date);
+    }
+
+    /// Sets the currency for this invoice. Required before posting;
+    /// `Invoice::post_to_account` will otherwise refuse to create the
+    /// posted transaction.
+    pub fn set_currency(&self, currency: &Commodity) {
+        let old = unsafe { ffi::gncInvoiceGetCurrency(self.invoice.as_ptr()) };
+        self.undo
+            .record(move |i| unsafe { ffi::gncInvoiceSetCurrency(i.as_ptr(), old) });
+        self.invoice.set_currency(currency);
+    }
+
+    /// Sets the bill terms (net 30, due-on-receipt, etc.). Pulls
+    /// from the book's bill-term registry — use
+    /// [`BillTerm::lookup_by_name`] to resolve a name to a handle
+    /// first.
+    pub fn set_terms(&self, terms: &BillTerm) {
+        let old = unsafe { ffi::gncInvoiceGetTerms(self.invoice.as_ptr()) };
+        self.undo
+            .record(move |i| unsafe { ffi::gncInvoiceSetTerms(i.as_ptr(), old) });
+        self.invoice.set_terms(terms);
+    }
+
+    /// Sets the active flag. Inactive invoices are hidden from the
+    /// default GUI invoice list.
+    pub fn set_active(&self, active: bool) {
+        let old = unsafe { ffi::gncInvoiceGetActive(self.invoice.as_ptr()) };
+        self.undo
+            .record(move |i| unsafe { ffi::gncInvoiceSetActive(i.as_ptr(), old) });
+        self.invoice.set_active(active);
+    }
+
+    /// Marks this invoice as a credit note rather than a regular
+    /// invoice. Credit-note posting flips the sign of the resulting
+    /// transaction. Set this before [`post_to_account`].
+    ///
+    /// [`post_to_account`]: Invoice::post_to_account
+    pub fn set_is_credit_note(&self, credit_note: bool) {
+        let old = unsafe { ffi::gncInvoiceGetIsCreditNote(self.invoice.as_ptr()) };
+        self.undo
+            .record(move |i| unsafe { ffi::gncInvoiceSetIsCreditNote(i.as_ptr(), old) });
+        self.invoice.set_is_credit_note(credit_note);
+    }
+
+    /// Sets the bill-to owner — the third-party who actually pays
+    /// when the receiving customer is themselves billing through.
+    /// Most invoices leave this unset.
+    pub fn set_bill_to(&self, owner: &Owner) {
+        let old = unsafe {
+            let ptr = ffi::gncInvoiceGetBillTo(self.invoice.as_ptr());
+            if ptr.is_null() {
+                Owner::new()
+            } else {
+                Owner::from_raw(*ptr)
+            }
+        };
+        self.undo.record(move |i| i.set_bill_to(&old));
+        self.invoice.set_bill_to(owner);
+    }
+
+    /// Sets the document link — a URL or filesystem path pointing
+    /// at supplemental paperwork (PO scan, contract, etc.).
+    pub fn set_doc_link(&self, link: &str) {
+        let old = unsafe {
+            let ptr = ffi::gncInvoiceGetDocLink(self.invoice.as_ptr());
+            if ptr.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(ptr).to_string_lossy().into_owned()
+            }
+        };
+        self.undo.record(move |i| i.set_doc_link(&old));
+        self.invoice.set_doc_link(link);
+    }
+
+    /// Adds an entry to this invoice.
+    pub fn add_entry(&self, entry: &Entry) {
+        let added = entry.as_ptr();
+        self.undo
+            .record(move |i| unsafe { ffi::gncInvoiceRemoveEntry(i.as_ptr(), added) });
+        self.invoice.add_entry(entry);
+    }
+
+    /// Removes an entry from this invoice.
+    pub fn remove_entry(&self, entry: &Entry) {
+        let removed = entry.as_ptr();
+        self.undo
+            .record(move |i| unsafe { ffi::gncInvoiceAddEntry(i.as_ptr(), removed) });
+        self.invoice.remove_entry(entry);
+    }
+}
+
+impl std::ops::Deref for InvoiceEditor<'_> {
+    type Target = Invoice;
+
+    fn deref(&self) -> &Invoice {
+        self.invoice
+    }
+}
+
+impl Editable for Invoice {
+    type Editor<'e> = InvoiceEditor<'e>;
+
+    fn begin_edit(&self) {
+        Invoice::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Invoice::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> InvoiceEditor<'_> {
+        InvoiceEditor {
+            invoice: self,
+            undo,
+        }
+    }
+}
+
 impl std::fmt::Debug for Invoice {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Invoice")
@@ -5,6 +5,8 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Book, Guid};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
 use super::Owner;
 
@@ -41,12 +43,12 @@ impl Job {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncJobBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncJobCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -63,6 +65,12 @@ impl Job {
         }
     }
 
+    /// Returns the key-value slots attached to this job.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     /// Creates an Owner from this job.
     pub fn to_owner(&self) -> Owner {
         let mut owner = Owner::new();
@@ -128,35 +136,110 @@ impl Job {
     // ==================== Setters ====================
 
     /// Sets the job ID.
-    pub fn set_id(&self, id: &str) {
+    fn set_id(&self, id: &str) {
         let c_id = CString::new(id).unwrap();
         unsafe { ffi::gncJobSetID(self.ptr.as_ptr(), c_id.as_ptr()) }
     }
 
     /// Sets the job name.
-    pub fn set_name(&self, name: &str) {
+    fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe { ffi::gncJobSetName(self.ptr.as_ptr(), c_name.as_ptr()) }
     }
 
     /// Sets the job reference.
-    pub fn set_reference(&self, reference: &str) {
+    fn set_reference(&self, reference: &str) {
         let c_ref = CString::new(reference).unwrap();
         unsafe { ffi::gncJobSetReference(self.ptr.as_ptr(), c_ref.as_ptr()) }
     }
 
     /// Sets the owner of this job.
-    pub fn set_owner(&self, owner: &Owner) {
+    fn set_owner(&self, owner: &Owner) {
         unsafe { ffi::gncJobSetOwner(self.ptr.as_ptr(), owner.as_ptr()) }
     }
 
     /// Sets the active flag.
-    pub fn set_active(&self, active: bool) {
+    fn set_active(&self, active: bool) {
         unsafe { ffi::gncJobSetActive(self.ptr.as_ptr(), active as i32) }
     }
 
 }
 
+/// The setters of a [`Job`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct JobEditor<'a> {
+    job: &'a Job,
+    undo: Undo<Job>,
+}
+
+impl JobEditor<'_> {
+    /// Returns the job's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Job> {
+        SlotEditor::new(self.job.slots(), &self.undo)
+    }
+
+    /// Sets the job ID.
+    pub fn set_id(&self, id: &str) {
+        let old = self.job.id().unwrap_or_default();
+        self.undo.record(move |e| e.set_id(&old));
+        self.job.set_id(id);
+    }
+
+    /// Sets the job name.
+    pub fn set_name(&self, name: &str) {
+        let old = self.job.name().unwrap_or_default();
+        self.undo.record(move |e| e.set_name(&old));
+        self.job.set_name(name);
+    }
+
+    /// Sets the job reference.
+    pub fn set_reference(&self, reference: &str) {
+        let old = self.job.reference().unwrap_or_default();
+        self.undo.record(move |e| e.set_reference(&old));
+        self.job.set_reference(reference);
+    }
+
+    /// Sets the owner of this job.
+    pub fn set_owner(&self, owner: &Owner) {
+        let old = self.job.owner();
+        self.undo.record(move |e| e.set_owner(&old));
+        self.job.set_owner(owner);
+    }
+
+    /// Sets the active flag.
+    pub fn set_active(&self, active: bool) {
+        let old = self.job.is_active();
+        self.undo.record(move |e| e.set_active(old));
+        self.job.set_active(active);
+    }
+}
+
+impl std::ops::Deref for JobEditor<'_> {
+    type Target = Job;
+
+    fn deref(&self) -> &Job {
+        self.job
+    }
+}
+
+impl Editable for Job {
+    type Editor<'e> = JobEditor<'e>;
+
+    fn begin_edit(&self) {
+        Job::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Job::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> JobEditor<'_> {
+        JobEditor { job: self, undo }
+    }
+}
+
 impl std::fmt::Debug for Job {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Job")
@@ -25,14 +25,14 @@ pub mod owner;
 pub mod tax;
 pub mod vendor;
 
-pub use address::Address;
-pub use billterm::BillTerm;
+pub use address::{Address, AddressEditor};
+pub use billterm::{BillTerm, BillTermEditor};
 pub use commodity::{Commodity, CommodityTable};
-pub use customer::Customer;
-pub use employee::Employee;
-pub use entry::Entry;
-pub use invoice::Invoice;
-pub use job::Job;
+pub use customer::{Customer, CustomerEditor};
+pub use employee::{Employee, EmployeeEditor};
+pub use entry::{Entry, EntryEditor};
+pub use invoice::{Invoice, InvoiceEditor};
+pub use job::{Job, JobEditor};
 pub use owner::{Owner, OwnerType, TypedOwner};
-pub use tax::{TaxTable, TaxTableEntry};
-pub use vendor::Vendor;
+pub use tax::{TaxTable, TaxTableEditor, TaxTableEntry};
+pub use vendor::{Vendor, VendorEditor};
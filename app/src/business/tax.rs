@@ -5,6 +5,8 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Account, Book, Guid, Numeric};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
 pub use ffi::GncAmountType as AmountType;
 pub use ffi::GncDiscountHow as DiscountHow;
@@ -42,12 +44,12 @@ impl TaxTable {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncTaxTableBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncTaxTableCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -64,6 +66,12 @@ impl TaxTable {
         }
     }
 
+    /// Returns the key-value slots attached to this tax table.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     // ==================== Getters ====================
 
     /// Returns the name.
@@ -111,18 +119,18 @@ impl TaxTable {
     // ==================== Setters ====================
 
     /// Sets the name.
-    pub fn set_name(&self, name: &str) {
+    fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe { ffi::gncTaxTableSetName(self.ptr.as_ptr(), c_name.as_ptr()) }
     }
 
     /// Adds an entry to this tax table.
-    pub fn add_entry(&self, entry: &TaxTableEntry) {
+    fn add_entry(&self, entry: &TaxTableEntry) {
         unsafe { ffi::gncTaxTableAddEntry(self.ptr.as_ptr(), entry.as_ptr()) }
     }
 
     /// Removes an entry from this tax table.
-    pub fn remove_entry(&self, entry: &TaxTableEntry) {
+    fn remove_entry(&self, entry: &TaxTableEntry) {
         unsafe { ffi::gncTaxTableRemoveEntry(self.ptr.as_ptr(), entry.as_ptr()) }
     }
 
@@ -138,6 +146,69 @@ impl TaxTable {
     }
 }
 
+/// The setters of a [`TaxTable`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct TaxTableEditor<'a> {
+    table: &'a TaxTable,
+    undo: Undo<TaxTable>,
+}
+
+impl TaxTableEditor<'_> {
+    /// Returns the tax table's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, TaxTable> {
+        SlotEditor::new(self.table.slots(), &self.undo)
+    }
+
+    /// Sets the name.
+    pub fn set_name(&self, name: &str) {
+        let old = self.table.name().unwrap_or_default();
+        self.undo.record(move |e| e.set_name(&old));
+        self.table.set_name(name);
+    }
+
+    /// Adds an entry to this tax table.
+    pub fn add_entry(&self, entry: &TaxTableEntry) {
+        let added = entry.as_ptr();
+        self.undo
+            .record(move |t| unsafe { ffi::gncTaxTableRemoveEntry(t.as_ptr(), added) });
+        self.table.add_entry(entry);
+    }
+
+    /// Removes an entry from this tax table.
+    pub fn remove_entry(&self, entry: &TaxTableEntry) {
+        let removed = entry.as_ptr();
+        self.undo
+            .record(move |t| unsafe { ffi::gncTaxTableAddEntry(t.as_ptr(), removed) });
+        self.table.remove_entry(entry);
+    }
+}
+
+impl std::ops::Deref for TaxTableEditor<'_> {
+    type Target = TaxTable;
+
+    fn deref(&self) -> &TaxTable {
+        self.table
+    }
+}
+
+impl Editable for TaxTable {
+    type Editor<'e> = TaxTableEditor<'e>;
+
+    fn begin_edit(&self) {
+        TaxTable::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        TaxTable::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> TaxTableEditor<'_> {
+        TaxTableEditor { table: self, undo }
+    }
+}
+
 impl std::fmt::Debug for TaxTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TaxTable")
@@ -5,8 +5,10 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Book, Guid};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
 
-use super::{Address, Owner};
+use super::{Address, AddressEditor, Owner};
 
 /// A vendor/supplier entity.
 pub struct Vendor {
@@ -41,12 +43,12 @@ impl Vendor {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gncVendorBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gncVendorCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -63,6 +65,12 @@ impl Vendor {
         }
     }
 
+    /// Returns the key-value slots attached to this vendor.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     /// Creates an Owner from this vendor.
     pub fn to_owner(&self) -> Owner {
         let mut owner = Owner::new();
@@ -124,30 +132,106 @@ impl Vendor {
     // ==================== Setters ====================
 
     /// Sets the vendor ID.
-    pub fn set_id(&self, id: &str) {
+    fn set_id(&self, id: &str) {
         let c_id = CString::new(id).unwrap();
         unsafe { ffi::gncVendorSetID(self.ptr.as_ptr(), c_id.as_ptr()) }
     }
 
     /// Sets the vendor name.
-    pub fn set_name(&self, name: &str) {
+    fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe { ffi::gncVendorSetName(self.ptr.as_ptr(), c_name.as_ptr()) }
     }
 
     /// Sets the notes.
-    pub fn set_notes(&self, notes: &str) {
+    fn set_notes(&self, notes: &str) {
         let c_notes = CString::new(notes).unwrap();
         unsafe { ffi::gncVendorSetNotes(self.ptr.as_ptr(), c_notes.as_ptr()) }
     }
 
     /// Sets the active flag.
-    pub fn set_active(&self, active: bool) {
+    fn set_active(&self, active: bool) {
         unsafe { ffi::gncVendorSetActive(self.ptr.as_ptr(), active as i32) }
     }
 
 }
 
+/// The setters of a [`Vendor`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct VendorEditor<'a> {
+    vendor: &'a Vendor,
+    undo: Undo<Vendor>,
+}
+
+impl VendorEditor<'_> {
+    /// Returns the vendor's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Vendor> {
+        SlotEditor::new(self.vendor.slots(), &self.undo)
+    }
+
+    /// Returns the vendor's address for writing. Its changes roll back
+    /// with the vendor's edit.
+    pub fn addr(&self) -> Option<AddressEditor<'_, Vendor>> {
+        self.vendor
+            .addr()
+            .map(|addr| AddressEditor::new(addr, &self.undo))
+    }
+
+    /// Sets the vendor ID.
+    pub fn set_id(&self, id: &str) {
+        let old = self.vendor.id().unwrap_or_default();
+        self.undo.record(move |e| e.set_id(&old));
+        self.vendor.set_id(id);
+    }
+
+    /// Sets the vendor name.
+    pub fn set_name(&self, name: &str) {
+        let old = self.vendor.name().unwrap_or_default();
+        self.undo.record(move |e| e.set_name(&old));
+        self.vendor.set_name(name);
+    }
+
+    /// Sets the notes.
+    pub fn set_notes(&self, notes: &str) {
+        let old = self.vendor.notes().unwrap_or_default();
+        self.undo.record(move |e| e.set_notes(&old));
+        self.vendor.set_notes(notes);
+    }
+
+    /// Sets the active flag.
+    pub fn set_active(&self, active: bool) {
+        let old = self.vendor.is_active();
+        self.undo.record(move |e| e.set_active(old));
+        self.vendor.set_active(active);
+    }
+}
+
+impl std::ops::Deref for VendorEditor<'_> {
+    type Target = Vendor;
+
+    fn deref(&self) -> &Vendor {
+        self.vendor
+    }
+}
+
+impl Editable for Vendor {
+    type Editor<'e> = VendorEditor<'e>;
+
+    fn begin_edit(&self) {
+        Vendor::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Vendor::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> VendorEditor<'_> {
+        VendorEditor { vendor: self, undo }
+    }
+}
+
 impl std::fmt::Debug for Vendor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Vendor")
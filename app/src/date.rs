@@ -0,0 +1,168 @@
+//! Calendar-date helpers for GnuCash `time64` values.
+//!
+//! GnuCash normalizes posted dates to 10:59 UTC ("neutral time") so the
+//! calendar day is the same in nearly every time zone. [`Date`] converts
+//! between that representation and a plain year/month/day triple in pure
+//! Rust, which keeps importers and reports independent of the process
+//! time zone.
+
+use std::fmt;
+
+/// Seconds per day.
+pub const SECS_PER_DAY: i64 = 86_400;
+
+/// Offset of GnuCash's neutral time (10:59:00 UTC) from midnight.
+pub const NEUTRAL_TIME_OFFSET: i64 = 10 * 3600 + 59 * 60;
+
+/// A proleptic Gregorian calendar date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Creates a date, returning `None` if the day does not exist.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    /// Returns the calendar day (in UTC) containing a `time64` value.
+    pub fn from_time64(time: i64) -> Self {
+        Self::from_days(time.div_euclid(SECS_PER_DAY))
+    }
+
+    /// Returns the date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        // Howard Hinnant's civil_from_days.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Self { year, month, day }
+    }
+
+    /// Returns the number of days since 1970-01-01.
+    pub fn days_since_epoch(&self) -> i64 {
+        let y = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = y.div_euclid(400);
+        let yoe = y.rem_euclid(400);
+        let m = self.month as i64;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Returns the neutral `time64` (10:59 UTC) GnuCash uses for posted dates.
+    pub fn to_time64(&self) -> i64 {
+        self.start_time64() + NEUTRAL_TIME_OFFSET
+    }
+
+    /// Returns 00:00:00 UTC on this date.
+    pub fn start_time64(&self) -> i64 {
+        self.days_since_epoch() * SECS_PER_DAY
+    }
+
+    /// Returns 23:59:59 UTC on this date.
+    pub fn end_time64(&self) -> i64 {
+        self.start_time64() + SECS_PER_DAY - 1
+    }
+
+    /// Returns the year.
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Returns the month (1-12).
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// Returns the day of the month (1-31).
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// Returns the date `n` days later (or earlier for negative `n`).
+    pub fn add_days(&self, n: i64) -> Self {
+        Self::from_days(self.days_since_epoch() + n)
+    }
+
+    /// Returns the number of days from `other` to `self`.
+    pub fn days_between(&self, other: &Date) -> i64 {
+        self.days_since_epoch() - other.days_since_epoch()
+    }
+
+    /// Parses an ISO 8601 date (`YYYY-MM-DD`), ignoring anything after
+    /// the day such as a time component.
+    pub fn parse_iso(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let year = s.get(0..4)?.parse().ok()?;
+        if s.get(4..5)? != "-" || s.get(7..8)? != "-" {
+            return None;
+        }
+        let month = s.get(5..7)?.parse().ok()?;
+        let day = s.get(8..10)?.parse().ok()?;
+        Self::new(year, month, day)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Returns true for Gregorian leap years.
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Returns the number of days in a month, or 0 for an invalid month.
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for days in [-800_000, -1, 0, 1, 59, 365, 11_016, 19_723, 2_932_896] {
+            assert_eq!(Date::from_days(days).days_since_epoch(), days);
+        }
+        let d = Date::new(2024, 2, 29).unwrap();
+        assert_eq!(Date::from_time64(d.to_time64()), d);
+        assert_eq!(d.to_string(), "2024-02-29");
+    }
+
+    #[test]
+    fn test_validation_and_parse() {
+        assert!(Date::new(2023, 2, 29).is_none());
+        assert!(Date::new(2024, 13, 1).is_none());
+        assert_eq!(
+            Date::parse_iso("2024-01-15T10:00:00"),
+            Date::new(2024, 1, 15)
+        );
+        assert_eq!(
+            Date::new(2024, 1, 31).unwrap().add_days(1),
+            Date::new(2024, 2, 1).unwrap()
+        );
+    }
+}
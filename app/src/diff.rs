@@ -0,0 +1,1672 @@
+//! Book comparison and three-way merge.
+//!
+//! [`diff`] matches the entities of two books by GUID and lists what was
+//! added, removed or modified, down to the changed fields. [`merge`]
+//! replays such a diff onto a third book, typically the shared copy both
+//! were taken from, and reports a conflict wherever that book no longer
+//! holds the value the diff started from:
+//!
+//! ```ignore
+//! use gnucash_ext::diff::{diff, merge};
+//!
+//! // What changed in a working copy since it was taken from the golden book?
+//! let changes = diff(&snapshot, &working);
+//! print!("{}", changes.to_text());
+//! let report = merge(&changes, &golden);
+//! for conflict in &report.conflicts {
+//!     eprintln!("{conflict}");
+//! }
+//! ```
+//!
+//! Accounts, transactions, splits, prices, customers, vendors,
+//! employees, jobs, invoices and entries are compared and merged. A
+//! transaction is merged together with its splits or not at all.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::fmt;
+
+use gnucash_sys::{
+    Account, AccountEditor, Book, Editable, Guid, Numeric, ReconcileState, Split, SplitEditor,
+    Transaction, TransactionEditor, ffi,
+};
+
+use crate::business::{Commodity, Customer, Employee, Entry, Invoice, Job, Owner, Vendor};
+use crate::date::Date;
+use crate::engine_ext::{
+    AccountEditorExt, AccountExt, BookExt, TransactionEditorExt, TransactionExt, collection,
+};
+use crate::price::{Price, PriceDB, PriceEditor};
+
+// ==================== Diff ====================
+
+/// The kinds of entity compared, in the order changes are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EntityKind {
+    Account,
+    Transaction,
+    Split,
+    Price,
+    Customer,
+    Vendor,
+    Employee,
+    Job,
+    Invoice,
+    Entry,
+}
+
+impl EntityKind {
+    /// Returns true for customers, vendors, employees, jobs, invoices and
+    /// entries.
+    pub fn is_business(self) -> bool {
+        self >= EntityKind::Customer
+    }
+}
+
+impl fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EntityKind::Account => "account",
+            EntityKind::Transaction => "transaction",
+            EntityKind::Split => "split",
+            EntityKind::Price => "price",
+            EntityKind::Customer => "customer",
+            EntityKind::Vendor => "vendor",
+            EntityKind::Employee => "employee",
+            EntityKind::Job => "job",
+            EntityKind::Invoice => "invoice",
+            EntityKind::Entry => "entry",
+        })
+    }
+}
+
+/// The value of one compared field.
+#[derive(Debug, Clone)]
+pub enum FieldValue {
+    Text(String),
+    Bool(bool),
+    Number(Numeric),
+    /// A `time64`.
+    Time(i64),
+    /// A reference to another entity; the null GUID for none.
+    Guid(Guid),
+    Char(char),
+}
+
+/// Numbers compare by value, so `1/2` equals `50/100`.
+impl PartialEq for FieldValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FieldValue::Text(a), FieldValue::Text(b)) => a == b,
+            (FieldValue::Bool(a), FieldValue::Bool(b)) => a == b,
+            (FieldValue::Number(a), FieldValue::Number(b)) => a.eq_value(b),
+            (FieldValue::Time(a), FieldValue::Time(b)) => a == b,
+            (FieldValue::Guid(a), FieldValue::Guid(b)) => a == b,
+            (FieldValue::Char(a), FieldValue::Char(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(s) => write!(f, "'{s}'"),
+            FieldValue::Bool(b) => write!(f, "{b}"),
+            FieldValue::Number(n) => write!(f, "{n}"),
+            FieldValue::Time(t) => write!(f, "{}", Date::from_time64(*t)),
+            FieldValue::Guid(g) if g.is_null() => f.write_str("none"),
+            FieldValue::Guid(g) => write!(f, "{g}"),
+            FieldValue::Char(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// A field whose value differs between the two books. `old` is `None`
+/// for an added entity and `new` is `None` for a removed one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: Option<FieldValue>,
+    pub new: Option<FieldValue>,
+}
+
+/// How an entity differs between the two books.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// An entity that differs between the two books.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityChange {
+    pub kind: EntityKind,
+    pub guid: Guid,
+    /// A readable name, such as an account's full name.
+    pub label: String,
+    pub change: ChangeKind,
+    /// Every field of an added or removed entity; only the changed ones
+    /// of a modified entity.
+    pub fields: Vec<FieldChange>,
+}
+
+impl EntityChange {
+    /// Returns the value `field` had before the change.
+    pub fn old_value(&self, field: &str) -> Option<&FieldValue> {
+        self.fields.iter().find(|f| f.field == field)?.old.as_ref()
+    }
+
+    /// Returns the value `field` has after the change.
+    pub fn new_value(&self, field: &str) -> Option<&FieldValue> {
+        self.fields.iter().find(|f| f.field == field)?.new.as_ref()
+    }
+}
+
+/// The differences between two books, accounts first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookDiff {
+    pub changes: Vec<EntityChange>,
+}
+
+impl BookDiff {
+    /// Returns true if the books hold the same entities with the same
+    /// values.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the number of entities with the given kind of change.
+    pub fn count(&self, change: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.change == change).count()
+    }
+
+    /// Returns the changes to entities of `kind`.
+    pub fn of_kind(&self, kind: EntityKind) -> impl Iterator<Item = &EntityChange> {
+        self.changes.iter().filter(move |c| c.kind == kind)
+    }
+
+    /// Renders the diff one entity per line, `+` added, `-` removed and
+    /// `~` modified, with the changed fields of modified entities below.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for change in &self.changes {
+            let sign = match change.change {
+                ChangeKind::Added => '+',
+                ChangeKind::Removed => '-',
+                ChangeKind::Modified => '~',
+            };
+            out.push_str(&format!(
+                "{sign} {} {} [{}]\n",
+                change.kind, change.label, change.guid
+            ));
+            if change.change == ChangeKind::Modified {
+                for field in &change.fields {
+                    let show = |v: &Option<FieldValue>| {
+                        v.as_ref().map_or(String::new(), |v| v.to_string())
+                    };
+                    out.push_str(&format!(
+                        "    {}: {} -> {}\n",
+                        field.field,
+                        show(&field.old),
+                        show(&field.new)
+                    ));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// The compared state of one entity.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Snapshot {
+    pub(crate) kind: EntityKind,
+    pub(crate) guid: Guid,
+    pub(crate) label: String,
+    pub(crate) fields: Vec<(&'static str, FieldValue)>,
+}
+
+impl Snapshot {
+    fn get(&self, field: &str) -> Option<&FieldValue> {
+        self.fields
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map(|(_, v)| v)
+    }
+}
+
+/// Compares `book_a` with `book_b`: entities only in `book_b` are added,
+/// those only in `book_a` removed.
+pub fn diff(book_a: &Book, book_b: &Book) -> BookDiff {
+    compare(&snapshot_book(book_a), &snapshot_book(book_b))
+}
+
+fn compare(before: &[Snapshot], after: &[Snapshot]) -> BookDiff {
+    let old: HashMap<Guid, &Snapshot> = before.iter().map(|s| (s.guid, s)).collect();
+    let present: HashSet<Guid> = after.iter().map(|s| s.guid).collect();
+
+    let mut changes: Vec<EntityChange> = after
+        .iter()
+        .filter_map(|new| change_between(old.get(&new.guid).copied(), Some(new)))
+        .collect();
+    changes.extend(
+        before
+            .iter()
+            .filter(|s| !present.contains(&s.guid))
+            .filter_map(|gone| change_between(Some(gone), None)),
+    );
+    changes.sort_by_key(|c| c.kind);
+    BookDiff { changes }
+}
+
+/// Returns how an entity changed between two states, `None` standing
+/// for not existing; `None` if nothing changed.
+pub(crate) fn change_between(
+    before: Option<&Snapshot>,
+    after: Option<&Snapshot>,
+) -> Option<EntityChange> {
+    let (change, entity, fields) = match (before, after) {
+        (None, None) => return None,
+        (None, Some(new)) => (
+            ChangeKind::Added,
+            new,
+            new.fields
+                .iter()
+                .map(|(field, value)| FieldChange {
+                    field,
+                    old: None,
+                    new: Some(value.clone()),
+                })
+                .collect(),
+        ),
+        (Some(old), None) => (
+            ChangeKind::Removed,
+            old,
+            old.fields
+                .iter()
+                .map(|(field, value)| FieldChange {
+                    field,
+                    old: Some(value.clone()),
+                    new: None,
+                })
+                .collect(),
+        ),
+        (Some(old), Some(new)) => {
+            let fields: Vec<FieldChange> = new
+                .fields
+                .iter()
+                .filter_map(|(field, value)| {
+                    let before = old.get(field);
+                    (before != Some(value)).then(|| FieldChange {
+                        field,
+                        old: before.cloned(),
+                        new: Some(value.clone()),
+                    })
+                })
+                .collect();
+            if fields.is_empty() {
+                return None;
+            }
+            (ChangeKind::Modified, new, fields)
+        }
+    };
+    Some(EntityChange {
+        kind: entity.kind,
+        guid: entity.guid,
+        label: entity.label.clone(),
+        change,
+        fields,
+    })
+}
+
+// ==================== Snapshots ====================
+
+fn text(value: Option<String>) -> FieldValue {
+    FieldValue::Text(value.unwrap_or_default())
+}
+
+fn commodity_name(commodity: Option<Commodity>) -> FieldValue {
+    text(commodity.and_then(|c| c.unique_name()))
+}
+
+fn reference(guid: Option<Guid>) -> FieldValue {
+    FieldValue::Guid(guid.unwrap_or(*Guid::null()))
+}
+
+/// Returns the snapshots of every compared entity in `book`, accounts
+/// parents first, then transactions, each followed by its splits.
+pub(crate) fn snapshot_book(book: &Book) -> Vec<Snapshot> {
+    let mut snapshots = Vec::new();
+    if let Some(root) = book.root_account() {
+        snapshots.extend(root.descendants().map(|a| snapshot_account(&a)));
+    }
+
+    let mut transactions: Vec<Transaction> = collection(book, c"Trans")
+        .into_iter()
+        .filter_map(|p| unsafe { Transaction::from_raw(p as *mut ffi::Transaction, false) })
+        .collect();
+    transactions.sort_by_key(|t| (t.date_posted(), *t.guid().as_bytes()));
+    for trans in &transactions {
+        snapshots.push(snapshot_transaction(trans));
+        snapshots.extend(trans.splits().map(|s| snapshot_split(&s)));
+    }
+
+    if let Some(db) = PriceDB::get(book) {
+        snapshots.extend(db.prices().iter().map(snapshot_price));
+    }
+
+    for (kind, id_type) in BUSINESS {
+        snapshots.extend(
+            collection(book, id_type)
+                .into_iter()
+                .filter_map(|ptr| unsafe { Business::from_raw(kind, ptr) })
+                .map(|entity| snapshot_business(&entity)),
+        );
+    }
+    snapshots
+}
+
+/// The business object kinds and their QOF type names.
+const BUSINESS: [(EntityKind, &CStr); 6] = [
+    (EntityKind::Customer, c"gncCustomer"),
+    (EntityKind::Vendor, c"gncVendor"),
+    (EntityKind::Employee, c"gncEmployee"),
+    (EntityKind::Job, c"gncJob"),
+    (EntityKind::Invoice, c"gncInvoice"),
+    (EntityKind::Entry, c"gncEntry"),
+];
+
+/// A business object of any compared kind.
+enum Business {
+    Customer(Customer),
+    Vendor(Vendor),
+    Employee(Employee),
+    Job(Job),
+    Invoice(Invoice),
+    Entry(Entry),
+}
+
+impl Business {
+    /// Wraps the entity `ptr` of kind `kind`.
+    ///
+    /// # Safety
+    /// The pointer must be null or point to an entity of that kind.
+    unsafe fn from_raw(kind: EntityKind, ptr: *mut ffi::QofInstance) -> Option<Self> {
+        unsafe {
+            Some(match kind {
+                EntityKind::Customer => {
+                    Business::Customer(Customer::from_raw(ptr as *mut ffi::GncCustomer, false)?)
+                }
+                EntityKind::Vendor => {
+                    Business::Vendor(Vendor::from_raw(ptr as *mut ffi::GncVendor, false)?)
+                }
+                EntityKind::Employee => {
+                    Business::Employee(Employee::from_raw(ptr as *mut ffi::GncEmployee, false)?)
+                }
+                EntityKind::Job => Business::Job(Job::from_raw(ptr as *mut ffi::GncJob, false)?),
+                EntityKind::Invoice => {
+                    Business::Invoice(Invoice::from_raw(ptr as *mut ffi::GncInvoice, false)?)
+                }
+                EntityKind::Entry => {
+                    Business::Entry(Entry::from_raw(ptr as *mut ffi::GncEntry, false)?)
+                }
+                _ => return None,
+            })
+        }
+    }
+
+    /// Creates an empty entity of `kind` in `book`.
+    fn create(kind: EntityKind, book: &Book) -> Option<Self> {
+        Some(match kind {
+            EntityKind::Customer => Business::Customer(Customer::new(book)),
+            EntityKind::Vendor => Business::Vendor(Vendor::new(book)),
+            EntityKind::Employee => Business::Employee(Employee::new(book)),
+            EntityKind::Job => Business::Job(Job::new(book)),
+            EntityKind::Invoice => Business::Invoice(Invoice::new(book)),
+            EntityKind::Entry => Business::Entry(Entry::new(book)),
+            _ => return None,
+        })
+    }
+
+    fn as_instance(&self) -> ffi::gpointer {
+        match self {
+            Business::Customer(c) => c.as_ptr() as ffi::gpointer,
+            Business::Vendor(v) => v.as_ptr() as ffi::gpointer,
+            Business::Employee(e) => e.as_ptr() as ffi::gpointer,
+            Business::Job(j) => j.as_ptr() as ffi::gpointer,
+            Business::Invoice(i) => i.as_ptr() as ffi::gpointer,
+            Business::Entry(e) => e.as_ptr() as ffi::gpointer,
+        }
+    }
+
+    fn begin_edit(&self) {
+        match self {
+            Business::Customer(c) => c.begin_edit(),
+            Business::Vendor(v) => v.begin_edit(),
+            Business::Employee(e) => e.begin_edit(),
+            Business::Job(j) => j.begin_edit(),
+            Business::Invoice(i) => i.begin_edit(),
+            Business::Entry(e) => e.begin_edit(),
+        }
+    }
+
+    fn commit_edit(&self) {
+        match self {
+            Business::Customer(c) => c.commit_edit(),
+            Business::Vendor(v) => v.commit_edit(),
+            Business::Employee(e) => e.commit_edit(),
+            Business::Job(j) => j.commit_edit(),
+            Business::Invoice(i) => i.commit_edit(),
+            Business::Entry(e) => e.commit_edit(),
+        }
+    }
+
+    /// Destroys the entity, which must be open for editing.
+    fn destroy(&self) {
+        unsafe {
+            match self {
+                Business::Customer(c) => ffi::gncCustomerDestroy(c.as_ptr()),
+                Business::Vendor(v) => ffi::gncVendorDestroy(v.as_ptr()),
+                Business::Employee(e) => ffi::gncEmployeeDestroy(e.as_ptr()),
+                Business::Job(j) => ffi::gncJobDestroy(j.as_ptr()),
+                Business::Invoice(i) => ffi::gncInvoiceDestroy(i.as_ptr()),
+                Business::Entry(e) => ffi::gncEntryDestroy(e.as_ptr()),
+            }
+        }
+    }
+}
+
+/// Returns the entity of `kind` with `guid` in `book`, or null.
+fn lookup_business(book: &Book, kind: EntityKind, guid: &Guid) -> *mut ffi::QofInstance {
+    let Some((_, id_type)) = BUSINESS.iter().find(|(k, _)| *k == kind) else {
+        return std::ptr::null_mut();
+    };
+    unsafe {
+        let col = ffi::qof_book_get_collection(book.as_ptr(), id_type.as_ptr());
+        if col.is_null() {
+            return std::ptr::null_mut();
+        }
+        ffi::qof_collection_lookup_entity(col, guid.as_ffi())
+    }
+}
+
+fn snapshot_business(entity: &Business) -> Snapshot {
+    let (kind, guid, label, fields) = match entity {
+        Business::Customer(c) => (
+            EntityKind::Customer,
+            c.guid(),
+            c.name(),
+            vec![
+                ("id", text(c.id())),
+                ("name", text(c.name())),
+                ("notes", text(c.notes())),
+                ("active", FieldValue::Bool(c.is_active())),
+            ],
+        ),
+        Business::Vendor(v) => (
+            EntityKind::Vendor,
+            v.guid(),
+            v.name(),
+            vec![
+                ("id", text(v.id())),
+                ("name", text(v.name())),
+                ("notes", text(v.notes())),
+                ("active", FieldValue::Bool(v.is_active())),
+            ],
+        ),
+        Business::Employee(e) => (
+            EntityKind::Employee,
+            e.guid(),
+            e.username(),
+            vec![
+                ("id", text(e.id())),
+                ("username", text(e.username())),
+                ("rate", FieldValue::Number(e.rate())),
+                ("active", FieldValue::Bool(e.is_active())),
+            ],
+        ),
+        Business::Job(j) => (
+            EntityKind::Job,
+            j.guid(),
+            j.name(),
+            vec![
+                ("id", text(j.id())),
+                ("name", text(j.name())),
+                ("reference", text(j.reference())),
+                ("owner", reference(j.owner().guid())),
+                ("active", FieldValue::Bool(j.is_active())),
+            ],
+        ),
+        Business::Invoice(i) => (
+            EntityKind::Invoice,
+            i.guid(),
+            i.id(),
+            vec![
+                ("id", text(i.id())),
+                ("owner", reference(i.owner().guid())),
+                ("billing_id", text(i.billing_id())),
+                ("notes", text(i.notes())),
+                ("opened", FieldValue::Time(i.date_opened())),
+                ("posted", FieldValue::Bool(i.is_posted())),
+            ],
+        ),
+        Business::Entry(e) => {
+            let invoice = e.invoice().or_else(|| e.bill()).map(|i| i.guid());
+            (
+                EntityKind::Entry,
+                e.guid(),
+                e.description(),
+                vec![
+                    ("invoice", reference(invoice)),
+                    ("date", FieldValue::Time(e.date())),
+                    ("description", text(e.description())),
+                    ("action", text(e.action())),
+                    ("quantity", FieldValue::Number(e.quantity())),
+                    ("price", FieldValue::Number(e.inv_price())),
+                ],
+            )
+        }
+    };
+    Snapshot {
+        kind,
+        guid,
+        label: label.unwrap_or_default(),
+        fields,
+    }
+}
+
+fn snapshot_account(account: &Account) -> Snapshot {
+    let type_name = unsafe {
+        let ptr = ffi::xaccAccountTypeEnumAsString(account.account_type());
+        (!ptr.is_null()).then(|| std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    };
+    // Top-level accounts hang off each book's own root.
+    let parent = account.parent().filter(|p| !p.is_root()).map(|p| p.guid());
+    Snapshot {
+        kind: EntityKind::Account,
+        guid: account.guid(),
+        label: account.full_name().unwrap_or_default(),
+        fields: vec![
+            ("name", text(account.name())),
+            ("code", text(account.code())),
+            ("description", text(account.description())),
+            ("notes", text(account.notes())),
+            ("type", text(type_name)),
+            ("commodity", commodity_name(account.commodity())),
+            ("placeholder", FieldValue::Bool(account.is_placeholder())),
+            ("hidden", FieldValue::Bool(account.is_hidden())),
+            ("parent", reference(parent)),
+        ],
+    }
+}
+
+fn snapshot_transaction(trans: &Transaction) -> Snapshot {
+    Snapshot {
+        kind: EntityKind::Transaction,
+        guid: trans.guid(),
+        label: format!(
+            "'{}' ({})",
+            trans.description().unwrap_or_default(),
+            Date::from_time64(trans.date_posted())
+        ),
+        fields: vec![
+            ("description", text(trans.description())),
+            ("num", text(trans.num())),
+            ("notes", text(trans.notes())),
+            ("posted", FieldValue::Time(trans.date_posted())),
+            ("currency", commodity_name(trans.currency())),
+        ],
+    }
+}
+
+/// The account comes before the amounts, which the engine rounds to the
+/// account's commodity when they are set.
+fn snapshot_split(split: &Split) -> Snapshot {
+    let account = split.account();
+    let trans = split.transaction();
+    Snapshot {
+        kind: EntityKind::Split,
+        guid: split.guid(),
+        label: format!(
+            "{} in '{}'",
+            account
+                .as_ref()
+                .and_then(|a| a.full_name())
+                .unwrap_or_default(),
+            trans
+                .as_ref()
+                .and_then(|t| t.description())
+                .unwrap_or_default()
+        ),
+        fields: vec![
+            ("transaction", reference(trans.map(|t| t.guid()))),
+            ("account", reference(account.map(|a| a.guid()))),
+            ("memo", text(split.memo())),
+            ("action", text(split.action())),
+            ("amount", FieldValue::Number(split.amount())),
+            ("value", FieldValue::Number(split.value())),
+            (
+                "reconcile",
+                FieldValue::Char(split.reconcile_state().as_char()),
+            ),
+        ],
+    }
+}
+
+fn snapshot_price(price: &Price) -> Snapshot {
+    let (commodity, currency) = (price.commodity(), price.currency());
+    Snapshot {
+        kind: EntityKind::Price,
+        guid: price.guid(),
+        label: format!(
+            "{} in {} ({})",
+            commodity
+                .as_ref()
+                .and_then(|c| c.mnemonic())
+                .unwrap_or_default(),
+            currency
+                .as_ref()
+                .and_then(|c| c.mnemonic())
+                .unwrap_or_default(),
+            Date::from_time64(price.time())
+        ),
+        fields: vec![
+            ("commodity", commodity_name(commodity)),
+            ("currency", commodity_name(currency)),
+            ("time", FieldValue::Time(price.time())),
+            ("value", FieldValue::Number(price.value())),
+            ("source", text(price.source_string())),
+            ("type", text(price.type_string())),
+        ],
+    }
+}
+
+// ==================== Merge ====================
+
+/// A change [`merge`] could not apply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub kind: EntityKind,
+    pub guid: Guid,
+    pub label: String,
+    /// The field concerned, for a field-level conflict.
+    pub field: Option<&'static str>,
+    pub reason: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} [{}]", self.kind, self.label, self.guid)?;
+        if let Some(field) = self.field {
+            write!(f, " {field}")?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// Outcome of a [`merge`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeReport {
+    /// Entities created, changed or removed in the target.
+    pub applied: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl MergeReport {
+    /// Returns true if every change was applied.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// What to do to the target for one change.
+#[derive(Debug, PartialEq)]
+enum Action {
+    /// The target already matches.
+    Nothing,
+    Create,
+    /// Set these fields.
+    Update(Vec<(&'static str, FieldValue)>),
+    Remove,
+}
+
+/// Applies `diff` to `target` and returns what was applied and what
+/// conflicted.
+///
+/// A change applies only where `target` still holds the diff's old
+/// value; a field that already has the new value is left alone, and
+/// any other value is a conflict, as is modifying or removing an
+/// entity the target changed or lost. The rest of the entity's fields
+/// are still applied, except for transactions: a transaction and its
+/// splits are merged whole or not at all, so if any of them conflicts
+/// none of their changes are applied. Accounts that still have splits
+/// or children, owners that still have jobs or invoices, and posted
+/// invoices or invoices with entries are not removed.
+pub fn merge(diff: &BookDiff, target: &Book) -> MergeReport {
+    let mut report = MergeReport::default();
+
+    // Decide everything against the untouched target first.
+    let mut actions = Vec::new();
+    let mut owners = HashMap::new();
+    for change in &diff.changes {
+        let current = snapshot_one(target, change.kind, change.guid);
+        if let Some(trans) = transaction_of(change, current.as_ref()) {
+            owners.insert(change.guid, trans);
+        }
+        let action = resolve(change, current.as_ref(), &mut report.conflicts);
+        if action != Action::Nothing {
+            actions.push((change, action));
+        }
+    }
+    hold_back_transactions(&owners, &mut actions, &mut report.conflicts);
+
+    let mut merger = Merger {
+        book: target,
+        report: &mut report,
+    };
+    let of_kind = |kind: EntityKind| {
+        actions
+            .iter()
+            .filter(move |(c, _)| c.kind == kind)
+            .map(|(c, a)| (*c, a))
+    };
+
+    for (change, action) in of_kind(EntityKind::Account) {
+        if *action != Action::Remove {
+            merger.apply_account(change, action);
+        }
+    }
+    merger.apply_transactions(
+        of_kind(EntityKind::Transaction)
+            .chain(of_kind(EntityKind::Split))
+            .collect(),
+        &owners,
+    );
+    for (change, action) in of_kind(EntityKind::Price) {
+        merger.apply_price(change, action);
+    }
+    // Owners come before the jobs and invoices that refer to them, and
+    // invoices before their entries.
+    for (kind, _) in BUSINESS {
+        for (change, action) in of_kind(kind) {
+            if *action != Action::Remove {
+                merger.apply_business(change, action);
+            }
+        }
+    }
+    for (kind, _) in BUSINESS.into_iter().rev() {
+        for (change, action) in of_kind(kind) {
+            if *action == Action::Remove {
+                merger.remove_business(change);
+            }
+        }
+    }
+    // Children were listed after their parents.
+    let removals: Vec<_> = of_kind(EntityKind::Account)
+        .filter(|(_, a)| **a == Action::Remove)
+        .collect();
+    for (change, _) in removals.into_iter().rev() {
+        merger.remove_account(change);
+    }
+    report
+}
+
+/// Drops the actions on every transaction with a conflicting change to
+/// it or one of its splits, reporting the changes left out. `owners`
+/// maps each transaction and split to its transaction.
+fn hold_back_transactions(
+    owners: &HashMap<Guid, Guid>,
+    actions: &mut Vec<(&EntityChange, Action)>,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let conflicted: HashSet<Guid> = conflicts.iter().map(|c| c.guid).collect();
+    let blocked: HashSet<Guid> = conflicted
+        .iter()
+        .filter_map(|guid| owners.get(guid).copied())
+        .collect();
+    actions.retain(|(change, _)| {
+        let Some(trans) = owners.get(&change.guid).filter(|t| blocked.contains(t)) else {
+            return true;
+        };
+        if !conflicted.contains(&change.guid) {
+            conflicts.push(conflict(
+                change,
+                None,
+                format!("not applied, as another change to transaction {trans} conflicts"),
+            ));
+        }
+        false
+    });
+}
+
+/// Returns the transaction a transaction or split change belongs to.
+/// A modified split only lists its transaction if that changed, so the
+/// target's `current` state is used otherwise.
+fn transaction_of(change: &EntityChange, current: Option<&Snapshot>) -> Option<Guid> {
+    match change.kind {
+        EntityKind::Transaction => Some(change.guid),
+        EntityKind::Split => match change
+            .new_value("transaction")
+            .or(change.old_value("transaction"))
+            .or(current.and_then(|c| c.get("transaction")))
+        {
+            Some(FieldValue::Guid(g)) => Some(*g),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn conflict(change: &EntityChange, field: Option<&'static str>, reason: String) -> Conflict {
+    Conflict {
+        kind: change.kind,
+        guid: change.guid,
+        label: change.label.clone(),
+        field,
+        reason,
+    }
+}
+
+/// Decides how to apply `change` given the target's `current` state,
+/// recording any conflicts.
+fn resolve(
+    change: &EntityChange,
+    current: Option<&Snapshot>,
+    conflicts: &mut Vec<Conflict>,
+) -> Action {
+    let matches = |current: &Snapshot, pick: fn(&FieldChange) -> Option<&FieldValue>| {
+        change
+            .fields
+            .iter()
+            .all(|f| current.get(f.field) == pick(f))
+    };
+    match (change.change, current) {
+        (ChangeKind::Added, None) => Action::Create,
+        (ChangeKind::Added, Some(current)) if matches(current, |f| f.new.as_ref()) => {
+            Action::Nothing
+        }
+        (ChangeKind::Added, Some(_)) => {
+            conflicts.push(conflict(
+                change,
+                None,
+                "added, but the target already has it with other values".to_string(),
+            ));
+            Action::Nothing
+        }
+        (ChangeKind::Removed, None) => Action::Nothing,
+        (ChangeKind::Removed, Some(current)) if matches(current, |f| f.old.as_ref()) => {
+            Action::Remove
+        }
+        (ChangeKind::Removed, Some(_)) => {
+            conflicts.push(conflict(
+                change,
+                None,
+                "removed, but the target changed it".to_string(),
+            ));
+            Action::Nothing
+        }
+        (ChangeKind::Modified, None) => {
+            conflicts.push(conflict(
+                change,
+                None,
+                "modified, but the target no longer has it".to_string(),
+            ));
+            Action::Nothing
+        }
+        (ChangeKind::Modified, Some(current)) => {
+            let mut updates = Vec::new();
+            for field in &change.fields {
+                let (Some(new), now) = (&field.new, current.get(field.field)) else {
+                    continue;
+                };
+                if now == Some(new) {
+                    continue;
+                }
+                if now == field.old.as_ref() {
+                    updates.push((field.field, new.clone()));
+                } else {
+                    let now = now.map_or(String::new(), |v| v.to_string());
+                    conflicts.push(conflict(
+                        change,
+                        Some(field.field),
+                        format!("changed to {new}, but the target has {now}"),
+                    ));
+                }
+            }
+            if updates.is_empty() {
+                Action::Nothing
+            } else {
+                Action::Update(updates)
+            }
+        }
+    }
+}
+
+/// Returns the current state of one entity of `book`.
+pub(crate) fn snapshot_one(book: &Book, kind: EntityKind, guid: Guid) -> Option<Snapshot> {
+    let book_ptr = book.as_ptr();
+    unsafe {
+        match kind {
+            EntityKind::Account => {
+                Account::from_raw(ffi::xaccAccountLookup(guid.as_ffi(), book_ptr), false)
+                    .map(|a| snapshot_account(&a))
+            }
+            EntityKind::Transaction => {
+                Transaction::from_raw(ffi::xaccTransLookup(guid.as_ffi(), book_ptr), false)
+                    .map(|t| snapshot_transaction(&t))
+            }
+            EntityKind::Split => {
+                Split::from_raw(ffi::xaccSplitLookup(guid.as_ffi(), book_ptr), false)
+                    .map(|s| snapshot_split(&s))
+            }
+            EntityKind::Price => {
+                Price::from_raw(ffi::gnc_price_lookup(guid.as_ffi(), book_ptr), false)
+                    .map(|p| snapshot_price(&p))
+            }
+            kind => Business::from_raw(kind, lookup_business(book, kind, &guid))
+                .map(|entity| snapshot_business(&entity)),
+        }
+    }
+}
+
+/// Applies resolved actions to the target book.
+struct Merger<'a> {
+    book: &'a Book,
+    report: &'a mut MergeReport,
+}
+
+impl Merger<'_> {
+    fn fail(&mut self, change: &EntityChange, field: Option<&'static str>, reason: String) {
+        self.report.conflicts.push(conflict(change, field, reason));
+    }
+
+    fn commodity(&self, value: &FieldValue) -> Result<Commodity, String> {
+        let FieldValue::Text(name) = value else {
+            return Err(format!("{value} is not a commodity"));
+        };
+        name.split_once("::")
+            .and_then(|(namespace, mnemonic)| {
+                self.book.commodity_table()?.lookup(namespace, mnemonic)
+            })
+            .ok_or_else(|| format!("commodity {name} is not in the target"))
+    }
+
+    fn account(&self, guid: &Guid) -> Result<Account, String> {
+        unsafe {
+            Account::from_raw(
+                ffi::xaccAccountLookup(guid.as_ffi(), self.book.as_ptr()),
+                false,
+            )
+        }
+        .ok_or_else(|| format!("account {guid} is not in the target"))
+    }
+
+    fn transaction(&self, guid: &Guid) -> Option<Transaction> {
+        unsafe {
+            Transaction::from_raw(
+                ffi::xaccTransLookup(guid.as_ffi(), self.book.as_ptr()),
+                false,
+            )
+        }
+    }
+
+    /// Returns the fields to set for a create or update.
+    fn fields(change: &EntityChange, action: &Action) -> Vec<(&'static str, FieldValue)> {
+        match action {
+            Action::Create => change
+                .fields
+                .iter()
+                .filter_map(|f| Some((f.field, f.new.clone()?)))
+                .collect(),
+            Action::Update(fields) => fields.clone(),
+            Action::Nothing | Action::Remove => Vec::new(),
+        }
+    }
+
+    fn apply_account(&mut self, change: &EntityChange, action: &Action) {
+        let mut account = match action {
+            Action::Create => {
+                let account = Account::new(self.book);
+                set_guid(account.as_ptr() as ffi::gpointer, &change.guid);
+                account
+            }
+            _ => match self.account(&change.guid) {
+                Ok(account) => account,
+                Err(reason) => return self.fail(change, None, reason),
+            },
+        };
+        let edit = account.edit();
+        for (field, value) in Self::fields(change, action) {
+            if let Err(reason) = self.set_account_field(&edit, field, &value) {
+                self.fail(change, Some(field), reason);
+            }
+        }
+        edit.commit();
+        if *action == Action::Create {
+            if account.parent().is_none()
+                && let Some(root) = self.book.root_account()
+            {
+                root.edit().append_child(&account);
+            }
+            account.mark_unowned();
+        }
+        self.report.applied += 1;
+    }
+
+    fn set_account_field(
+        &self,
+        account: &AccountEditor<'_>,
+        field: &str,
+        value: &FieldValue,
+    ) -> Result<(), String> {
+        match (field, value) {
+            ("name", FieldValue::Text(s)) => account.set_name(s),
+            ("code", FieldValue::Text(s)) => account.set_code(s),
+            ("description", FieldValue::Text(s)) => account.set_description(s),
+            ("notes", FieldValue::Text(s)) => account.set_notes(s),
+            ("type", FieldValue::Text(s)) => {
+                let name = CString::new(s.as_str()).map_err(|e| e.to_string())?;
+                account.set_type(unsafe { ffi::xaccAccountStringToEnum(name.as_ptr()) });
+            }
+            ("commodity", value) => account.set_commodity(&self.commodity(value)?),
+            ("placeholder", FieldValue::Bool(b)) => account.set_placeholder(*b),
+            ("hidden", FieldValue::Bool(b)) => account.set_hidden(*b),
+            ("parent", FieldValue::Guid(g)) => {
+                let parent = if g.is_null() {
+                    self.book
+                        .root_account()
+                        .ok_or_else(|| "the target has no root account".to_string())?
+                } else {
+                    self.account(g)?
+                };
+                parent.edit().append_child(account);
+            }
+            _ => return Err(format!("cannot set {field} to {value}")),
+        }
+        Ok(())
+    }
+
+    fn remove_account(&mut self, change: &EntityChange) {
+        let account = match self.account(&change.guid) {
+            Ok(account) => account,
+            Err(_) => return,
+        };
+        if account.splits_size() > 0 || account.n_children() > 0 {
+            return self.fail(
+                change,
+                None,
+                "removed, but it still has splits or children in the target".to_string(),
+            );
+        }
+        unsafe {
+            ffi::xaccAccountBeginEdit(account.as_ptr());
+            ffi::xaccAccountDestroy(account.as_ptr());
+        }
+        self.report.applied += 1;
+    }
+
+    /// Applies transaction and split changes one transaction at a time,
+    /// so each transaction is committed balanced. A transaction with a
+    /// change that cannot be applied is rolled back whole.
+    fn apply_transactions(
+        &mut self,
+        actions: Vec<(&EntityChange, &Action)>,
+        owners: &HashMap<Guid, Guid>,
+    ) {
+        let mut order: Vec<Guid> = Vec::new();
+        let mut groups: HashMap<Guid, Vec<(&EntityChange, &Action)>> = HashMap::new();
+        for (change, action) in actions {
+            let guid = owners.get(&change.guid).copied().unwrap_or(*Guid::null());
+            if !groups.contains_key(&guid) {
+                order.push(guid);
+            }
+            groups.entry(guid).or_default().push((change, action));
+        }
+
+        for guid in order {
+            let group = &groups[&guid];
+            let own = group
+                .iter()
+                .find(|(c, _)| c.kind == EntityKind::Transaction);
+            if let Some((change, Action::Remove)) = own {
+                // Its splits go with it.
+                if let Some(trans) = self.transaction(&guid) {
+                    trans.edit().destroy();
+                    self.report.applied += 1;
+                } else {
+                    self.fail(change, None, "no longer in the target".to_string());
+                }
+                continue;
+            }
+
+            let mut trans = match own {
+                Some((change, Action::Create)) => {
+                    let trans = Transaction::new(self.book);
+                    set_guid(trans.as_ptr() as ffi::gpointer, &change.guid);
+                    trans
+                }
+                _ => match self.transaction(&guid) {
+                    Some(trans) => trans,
+                    None => {
+                        for (change, _) in group {
+                            self.fail(
+                                change,
+                                None,
+                                "its transaction is not in the target".to_string(),
+                            );
+                        }
+                        continue;
+                    }
+                },
+            };
+            let edit = trans.edit();
+            let mut failures = Vec::new();
+            for (change, action) in group {
+                match change.kind {
+                    EntityKind::Transaction => {
+                        for (field, value) in Self::fields(change, action) {
+                            if let Err(reason) = self.set_transaction_field(&edit, field, &value) {
+                                failures.push(conflict(change, Some(field), reason));
+                            }
+                        }
+                    }
+                    _ => self.apply_split(&edit, change, action, &mut failures),
+                }
+            }
+            if failures.is_empty() {
+                edit.commit();
+                trans.mark_unowned();
+                self.report.applied += group.len();
+                continue;
+            }
+            // Undoes the split changes too; a created transaction is
+            // destroyed when `trans` is dropped.
+            edit.rollback();
+            for (change, _) in group {
+                if !failures.iter().any(|f| f.guid == change.guid) {
+                    failures.push(conflict(
+                        change,
+                        None,
+                        format!("not applied, as another change to transaction {guid} failed"),
+                    ));
+                }
+            }
+            self.report.conflicts.extend(failures);
+        }
+    }
+
+    fn set_transaction_field(
+        &self,
+        trans: &TransactionEditor<'_>,
+        field: &str,
+        value: &FieldValue,
+    ) -> Result<(), String> {
+        match (field, value) {
+            ("description", FieldValue::Text(s)) => trans.set_description(s),
+            ("num", FieldValue::Text(s)) => trans.set_num(s),
+            ("notes", FieldValue::Text(s)) => trans.set_notes(s),
+            ("posted", FieldValue::Time(t)) => trans.set_date_posted(*t),
+            ("currency", value) => trans.set_currency(&self.commodity(value)?),
+            _ => return Err(format!("cannot set {field} to {value}")),
+        }
+        Ok(())
+    }
+
+    fn apply_split(
+        &self,
+        trans: &TransactionEditor<'_>,
+        change: &EntityChange,
+        action: &Action,
+        failures: &mut Vec<Conflict>,
+    ) {
+        let lookup = unsafe {
+            Split::from_raw(
+                ffi::xaccSplitLookup(change.guid.as_ffi(), self.book.as_ptr()),
+                false,
+            )
+        };
+        let mut split = match (action, lookup) {
+            (Action::Create, _) => {
+                let split = Split::new(self.book);
+                set_guid(split.as_ptr() as ffi::gpointer, &change.guid);
+                split
+            }
+            (_, Some(split)) => split,
+            (_, None) => {
+                return failures.push(conflict(
+                    change,
+                    None,
+                    "no longer in the target".to_string(),
+                ));
+            }
+        };
+        if *action == Action::Remove {
+            unsafe { ffi::xaccSplitDestroy(split.as_ptr()) };
+            return;
+        }
+        // Changes are grouped by the split's transaction, which a new or
+        // moved split is put in here.
+        let in_trans = split.transaction().map(|t| t.as_ptr()) == Some(trans.as_ptr());
+        {
+            let editor = if in_trans {
+                trans.split(&split)
+            } else {
+                trans.add_split(&split)
+            };
+            for (field, value) in Self::fields(change, action) {
+                if let Err(reason) = self.set_split_field(&editor, field, &value) {
+                    failures.push(conflict(change, Some(field), reason));
+                }
+            }
+        }
+        // Once in the transaction, rolling it back destroys a new split.
+        split.mark_unowned();
+    }
+
+    fn set_split_field(
+        &self,
+        split: &SplitEditor<'_>,
+        field: &str,
+        value: &FieldValue,
+    ) -> Result<(), String> {
+        match (field, value) {
+            ("transaction", _) => {}
+            ("account", FieldValue::Guid(g)) => split.set_account(&self.account(g)?),
+            ("memo", FieldValue::Text(s)) => split.set_memo(s),
+            ("action", FieldValue::Text(s)) => split.set_action(s),
+            ("amount", FieldValue::Number(n)) => split.set_amount(*n),
+            ("value", FieldValue::Number(n)) => split.set_value(*n),
+            ("reconcile", FieldValue::Char(c)) => split.set_reconcile_state(
+                ReconcileState::from_char(*c)
+                    .ok_or_else(|| format!("unknown reconcile state {c}"))?,
+            ),
+            _ => return Err(format!("cannot set {field} to {value}")),
+        }
+        Ok(())
+    }
+
+    fn apply_price(&mut self, change: &EntityChange, action: &Action) {
+        let Some(db) = PriceDB::get(self.book) else {
+            return self.fail(change, None, "the target has no price database".to_string());
+        };
+        let lookup = unsafe {
+            Price::from_raw(
+                ffi::gnc_price_lookup(change.guid.as_ffi(), self.book.as_ptr()),
+                false,
+            )
+        };
+        let price = match (action, lookup) {
+            (Action::Create, _) => {
+                let price = Price::new(self.book);
+                set_guid(price.as_ptr() as ffi::gpointer, &change.guid);
+                price
+            }
+            (_, Some(price)) => price,
+            (_, None) => return self.fail(change, None, "no longer in the target".to_string()),
+        };
+        if *action == Action::Remove {
+            db.edit().remove_price(&price);
+            self.report.applied += 1;
+            return;
+        }
+        let edit = price.edit();
+        for (field, value) in Self::fields(change, action) {
+            if let Err(reason) = self.set_price_field(&edit, field, &value) {
+                self.fail(change, Some(field), reason);
+            }
+        }
+        edit.commit();
+        if *action == Action::Create {
+            db.edit().add_price(&price);
+        }
+        self.report.applied += 1;
+    }
+
+    fn set_price_field(
+        &self,
+        price: &PriceEditor<'_>,
+        field: &str,
+        value: &FieldValue,
+    ) -> Result<(), String> {
+        match (field, value) {
+            ("commodity", value) => price.set_commodity(&self.commodity(value)?),
+            ("currency", value) => price.set_currency(&self.commodity(value)?),
+            ("time", FieldValue::Time(t)) => price.set_time(*t),
+            ("value", FieldValue::Number(n)) => price.set_value(*n),
+            ("source", FieldValue::Text(s)) => price.set_source_string(s),
+            ("type", FieldValue::Text(s)) => price.set_type_string(s),
+            _ => return Err(format!("cannot set {field} to {value}")),
+        }
+        Ok(())
+    }
+    fn business(&self, kind: EntityKind, guid: &Guid) -> Option<Business> {
+        unsafe { Business::from_raw(kind, lookup_business(self.book, kind, guid)) }
+    }
+
+    /// Returns the customer, vendor, employee or job `guid` as an owner.
+    fn owner(&self, guid: &Guid) -> Result<Owner, String> {
+        let kinds = [
+            EntityKind::Customer,
+            EntityKind::Vendor,
+            EntityKind::Employee,
+            EntityKind::Job,
+        ];
+        kinds
+            .into_iter()
+            .find_map(|kind| match self.business(kind, guid)? {
+                Business::Customer(c) => Some(c.to_owner()),
+                Business::Vendor(v) => Some(v.to_owner()),
+                Business::Employee(e) => Some(e.to_owner()),
+                Business::Job(j) => Some(j.to_owner()),
+                Business::Invoice(_) | Business::Entry(_) => None,
+            })
+            .ok_or_else(|| format!("owner {guid} is not in the target"))
+    }
+
+    fn apply_business(&mut self, change: &EntityChange, action: &Action) {
+        let entity = match action {
+            Action::Create => {
+                let entity = Business::create(change.kind, self.book)
+                    .expect("only business changes are applied here");
+                set_guid(entity.as_instance(), &change.guid);
+                entity
+            }
+            _ => match self.business(change.kind, &change.guid) {
+                Some(entity) => entity,
+                None => return self.fail(change, None, "no longer in the target".to_string()),
+            },
+        };
+        entity.begin_edit();
+        for (field, value) in Self::fields(change, action) {
+            if let Err(reason) = self.set_business_field(&entity, field, &value) {
+                self.fail(change, Some(field), reason);
+            }
+        }
+        // A new invoice takes its owner's currency.
+        if let (Action::Create, Business::Invoice(invoice)) = (action, &entity)
+            && invoice.currency().is_none()
+        {
+            unsafe {
+                let currency = ffi::gncOwnerGetCurrency(invoice.owner().as_ptr());
+                if !currency.is_null() {
+                    ffi::gncInvoiceSetCurrency(invoice.as_ptr(), currency);
+                }
+            }
+        }
+        entity.commit_edit();
+        self.report.applied += 1;
+    }
+
+    fn set_business_field(
+        &self,
+        entity: &Business,
+        field: &str,
+        value: &FieldValue,
+    ) -> Result<(), String> {
+        // Each guard nests inside the session `apply_business` holds
+        // open, so the fields are still committed together.
+        match (entity, field, value) {
+            (Business::Customer(c), "id", FieldValue::Text(s)) => c.edit().set_id(s),
+            (Business::Customer(c), "name", FieldValue::Text(s)) => c.edit().set_name(s),
+            (Business::Customer(c), "notes", FieldValue::Text(s)) => c.edit().set_notes(s),
+            (Business::Customer(c), "active", FieldValue::Bool(b)) => c.edit().set_active(*b),
+            (Business::Vendor(v), "id", FieldValue::Text(s)) => v.edit().set_id(s),
+            (Business::Vendor(v), "name", FieldValue::Text(s)) => v.edit().set_name(s),
+            (Business::Vendor(v), "notes", FieldValue::Text(s)) => v.edit().set_notes(s),
+            (Business::Vendor(v), "active", FieldValue::Bool(b)) => v.edit().set_active(*b),
+            (Business::Employee(e), "id", FieldValue::Text(s)) => e.edit().set_id(s),
+            (Business::Employee(e), "username", FieldValue::Text(s)) => e.edit().set_username(s),
+            (Business::Employee(e), "rate", FieldValue::Number(n)) => e.edit().set_rate(*n),
+            (Business::Employee(e), "active", FieldValue::Bool(b)) => e.edit().set_active(*b),
+            (Business::Job(j), "id", FieldValue::Text(s)) => j.edit().set_id(s),
+            (Business::Job(j), "name", FieldValue::Text(s)) => j.edit().set_name(s),
+            (Business::Job(j), "reference", FieldValue::Text(s)) => j.edit().set_reference(s),
+            (Business::Job(j), "owner", FieldValue::Guid(g)) => j.edit().set_owner(&self.owner(g)?),
+            (Business::Job(j), "active", FieldValue::Bool(b)) => j.edit().set_active(*b),
+            (Business::Invoice(i), "id", FieldValue::Text(s)) => i.edit().set_id(s),
+            (Business::Invoice(i), "owner", FieldValue::Guid(g)) => {
+                i.edit().set_owner(&self.owner(g)?)
+            }
+            (Business::Invoice(i), "billing_id", FieldValue::Text(s)) => i.edit().set_billing_id(s),
+            (Business::Invoice(i), "notes", FieldValue::Text(s)) => i.edit().set_notes(s),
+            (Business::Invoice(i), "opened", FieldValue::Time(t)) => i.edit().set_date_opened(*t),
+            (Business::Invoice(_), "posted", _) => {
+                return Err("posting is not merged; post or unpost it in the target".to_string());
+            }
+            (Business::Entry(e), "invoice", FieldValue::Guid(g)) => self.move_entry(e, g)?,
+            (Business::Entry(e), "date", FieldValue::Time(t)) => e.edit().set_date(*t),
+            (Business::Entry(e), "description", FieldValue::Text(s)) => e.edit().set_description(s),
+            (Business::Entry(e), "action", FieldValue::Text(s)) => e.edit().set_action(s),
+            (Business::Entry(e), "quantity", FieldValue::Number(n)) => e.edit().set_quantity(*n),
+            (Business::Entry(e), "price", FieldValue::Number(n)) => e.edit().set_inv_price(*n),
+            _ => return Err(format!("cannot set {field} to {value}")),
+        }
+        Ok(())
+    }
+
+    /// Moves `entry` to the customer invoice `guid`, or out of its
+    /// invoice for the null GUID.
+    fn move_entry(&self, entry: &Entry, guid: &Guid) -> Result<(), String> {
+        if guid.is_null() {
+            if let Some(current) = entry.invoice().or_else(|| entry.bill()) {
+                current.edit().remove_entry(entry);
+            }
+            return Ok(());
+        }
+        let Some(Business::Invoice(invoice)) = self.business(EntityKind::Invoice, guid) else {
+            return Err(format!("invoice {guid} is not in the target"));
+        };
+        let owner_type = unsafe { ffi::gncInvoiceGetOwnerType(invoice.as_ptr()) };
+        if owner_type != ffi::GncOwnerType::GNC_OWNER_CUSTOMER {
+            return Err("entries are only moved to customer invoices".to_string());
+        }
+        invoice.edit().add_entry(entry);
+        Ok(())
+    }
+
+    fn remove_business(&mut self, change: &EntityChange) {
+        let Some(entity) = self.business(change.kind, &change.guid) else {
+            return;
+        };
+        if let Err(reason) = self.check_removable(&entity) {
+            return self.fail(change, None, format!("removed, but {reason}"));
+        }
+        if let Business::Entry(entry) = &entity
+            && let Some(invoice) = entry.invoice().or_else(|| entry.bill())
+        {
+            invoice.edit().remove_entry(entry);
+        }
+        entity.begin_edit();
+        entity.destroy();
+        self.report.applied += 1;
+    }
+
+    /// Returns why `entity` cannot be removed from the target, if it
+    /// cannot.
+    fn check_removable(&self, entity: &Business) -> Result<(), String> {
+        let guid = match entity {
+            Business::Invoice(invoice) if invoice.is_posted() => {
+                return Err("it is posted in the target".to_string());
+            }
+            Business::Invoice(invoice) if !invoice.entries().is_empty() => {
+                return Err("it still has entries in the target".to_string());
+            }
+            Business::Invoice(_) | Business::Entry(_) => return Ok(()),
+            Business::Customer(c) => c.guid(),
+            Business::Vendor(v) => v.guid(),
+            Business::Employee(e) => e.guid(),
+            Business::Job(j) => j.guid(),
+        };
+        let owned = [EntityKind::Job, EntityKind::Invoice]
+            .into_iter()
+            .flat_map(|kind| {
+                let (_, id_type) = BUSINESS.iter().find(|(k, _)| *k == kind)?;
+                Some(
+                    collection(self.book, id_type)
+                        .into_iter()
+                        .filter_map(move |ptr| unsafe { Business::from_raw(kind, ptr) }),
+                )
+            })
+            .flatten()
+            .any(|other| match other {
+                Business::Job(j) => j.owner().guid() == Some(guid),
+                Business::Invoice(i) => i.owner().guid() == Some(guid),
+                _ => false,
+            });
+        if owned {
+            return Err("it still has jobs or invoices in the target".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Gives a freshly created entity the GUID it has in the other book.
+fn set_guid(instance: ffi::gpointer, guid: &Guid) {
+    unsafe { ffi::qof_instance_set_guid(instance, guid.as_ffi()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: u8, name: &str, placeholder: bool) -> Snapshot {
+        Snapshot {
+            kind: EntityKind::Account,
+            guid: Guid::from_bytes([id; 16]),
+            label: name.to_string(),
+            fields: vec![
+                ("name", FieldValue::Text(name.to_string())),
+                ("placeholder", FieldValue::Bool(placeholder)),
+            ],
+        }
+    }
+
+    fn price(id: u8, cents: i64) -> Snapshot {
+        Snapshot {
+            kind: EntityKind::Price,
+            guid: Guid::from_bytes([id; 16]),
+            label: String::new(),
+            fields: vec![("value", FieldValue::Number(Numeric::new(cents, 100)))],
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let before = [
+            account(1, "Bank", false),
+            account(2, "Cash", false),
+            price(3, 150),
+        ];
+        let mut repriced = price(3, 0);
+        repriced.fields[0].1 = FieldValue::Number(Numeric::new(3, 2));
+        let after = [
+            repriced,
+            account(1, "Checking", false),
+            account(4, "Savings", true),
+        ];
+        let diff = compare(&before, &after);
+        assert_eq!(diff.changes.len(), 3);
+        assert_eq!(diff.count(ChangeKind::Added), 1);
+        assert_eq!(diff.count(ChangeKind::Removed), 1);
+        // The price only changed its representation.
+        assert_eq!(diff.of_kind(EntityKind::Price).count(), 0);
+
+        let renamed = &diff.changes[0];
+        assert_eq!(renamed.change, ChangeKind::Modified);
+        assert_eq!(renamed.fields.len(), 1);
+        assert_eq!(
+            renamed.new_value("name"),
+            Some(&FieldValue::Text("Checking".to_string()))
+        );
+        assert!(diff.to_text().contains("    name: 'Bank' -> 'Checking'\n"));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let diff = compare(
+            &[account(1, "Bank", false)],
+            &[account(1, "Checking", true)],
+        );
+        let change = &diff.changes[0];
+        let mut conflicts = Vec::new();
+
+        // Untouched target: both fields apply.
+        let action = resolve(change, Some(&account(1, "Bank", false)), &mut conflicts);
+        assert!(matches!(action, Action::Update(ref f) if f.len() == 2));
+
+        // The target renamed it too: the name conflicts, the flag applies.
+        let action = resolve(change, Some(&account(1, "Current", false)), &mut conflicts);
+        assert_eq!(
+            action,
+            Action::Update(vec![("placeholder", FieldValue::Bool(true))])
+        );
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, Some("name"));
+
+        // Already applied.
+        let action = resolve(change, Some(&account(1, "Checking", true)), &mut conflicts);
+        assert_eq!(action, Action::Nothing);
+        assert_eq!(conflicts.len(), 1);
+
+        let removed = compare(&[account(2, "Cash", false)], &[]);
+        let action = resolve(
+            &removed.changes[0],
+            Some(&account(2, "Petty cash", false)),
+            &mut conflicts,
+        );
+        assert_eq!(action, Action::Nothing);
+        assert_eq!(conflicts.len(), 2);
+    }
+
+    fn split(id: u8, trans: u8, memo: &str) -> Snapshot {
+        Snapshot {
+            kind: EntityKind::Split,
+            guid: Guid::from_bytes([id; 16]),
+            label: String::new(),
+            fields: vec![
+                (
+                    "transaction",
+                    FieldValue::Guid(Guid::from_bytes([trans; 16])),
+                ),
+                ("memo", FieldValue::Text(memo.to_string())),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_hold_back_transactions() {
+        let diff = compare(
+            &[split(1, 9, "a"), split(2, 9, "b"), split(3, 8, "c")],
+            &[split(1, 9, "A"), split(2, 9, "B"), split(3, 8, "C")],
+        );
+        let mut conflicts = Vec::new();
+        let targets = [split(1, 9, "a"), split(2, 9, "x"), split(3, 8, "c")];
+        let owners: HashMap<Guid, Guid> = diff
+            .changes
+            .iter()
+            .zip(&targets)
+            .filter_map(|(change, target)| {
+                Some((change.guid, transaction_of(change, Some(target))?))
+            })
+            .collect();
+        let mut actions: Vec<_> = diff
+            .changes
+            .iter()
+            .zip(&targets)
+            .map(|(change, target)| (change, resolve(change, Some(target), &mut conflicts)))
+            .filter(|(_, action)| *action != Action::Nothing)
+            .collect();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(conflicts.len(), 1);
+
+        // Split 2 conflicts, so split 1 of the same transaction is held
+        // back too; split 3 belongs to another transaction.
+        hold_back_transactions(&owners, &mut actions, &mut conflicts);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].0.guid, Guid::from_bytes([3; 16]));
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[1].guid, Guid::from_bytes([1; 16]));
+    }
+}
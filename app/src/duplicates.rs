@@ -0,0 +1,372 @@
+//! Duplicate transaction detection.
+//!
+//! [`find_duplicates`] groups transactions that touch the same accounts
+//! with the same amounts, were posted within a few days of each other and
+//! have similar descriptions, which is what importing overlapping bank
+//! statements twice leaves behind. [`merge`] keeps one transaction of a
+//! group and deletes the others:
+//!
+//! ```ignore
+//! use gnucash_ext::duplicates::{find_duplicates, merge, DuplicateOptions};
+//!
+//! let options = DuplicateOptions::new().date_window_days(5).min_similarity(0.7);
+//! for group in find_duplicates(&book, &options) {
+//!     println!("{:.2} {:?}", group.score, group.transactions);
+//!     if group.score > 0.9 {
+//!         merge(&book, &group)?;
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use gnucash_sys::{
+    Book, Editable, Error, Guid, Numeric, ReconcileState, Result, Split, Transaction, ffi,
+};
+
+use crate::engine_ext::collection;
+
+/// Thresholds for [`find_duplicates`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateOptions {
+    date_window_days: i64,
+    min_similarity: f64,
+}
+
+impl DuplicateOptions {
+    /// Creates options with a 3 day window and a description similarity
+    /// of at least 0.6.
+    pub fn new() -> Self {
+        Self {
+            date_window_days: 3,
+            min_similarity: 0.6,
+        }
+    }
+
+    /// Sets how many days apart two duplicates may be posted.
+    pub fn date_window_days(mut self, days: i64) -> Self {
+        self.date_window_days = days;
+        self
+    }
+
+    /// Sets the lowest description similarity, from 0 (anything) to 1
+    /// (identical ignoring case and surrounding whitespace).
+    pub fn min_similarity(mut self, similarity: f64) -> Self {
+        self.min_similarity = similarity;
+        self
+    }
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Transactions that look like copies of each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// The transactions, the one [`merge`] keeps first: the one with the
+    /// most reconciled or cleared splits, then the earliest entered.
+    pub transactions: Vec<Guid>,
+    /// How alike the least alike linked pair is, from 0 to 1: the mean of
+    /// their description similarity and date closeness, where closeness
+    /// is 1 on the same day and falls to 0 just outside the window.
+    pub score: f64,
+}
+
+impl DuplicateGroup {
+    /// Returns the transaction [`merge`] keeps.
+    pub fn keep(&self) -> Guid {
+        self.transactions[0]
+    }
+
+    /// Returns the transactions [`merge`] deletes.
+    pub fn redundant(&self) -> &[Guid] {
+        &self.transactions[1..]
+    }
+}
+
+/// The facts about a transaction the detector compares.
+struct Candidate {
+    guid: Guid,
+    /// Posted date in whole days.
+    day: i64,
+    entered: i64,
+    description: String,
+    /// Reconciled and cleared splits, weighted to prefer reconciled.
+    progress: usize,
+}
+
+/// Returns every group of likely duplicates in `book`, best score first.
+/// Voided transactions are ignored.
+pub fn find_duplicates(book: &Book, options: &DuplicateOptions) -> Vec<DuplicateGroup> {
+    let mut buckets: HashMap<Vec<(Guid, Numeric)>, Vec<Candidate>> = HashMap::new();
+    for ptr in collection(book, c"Trans") {
+        let Some(trans) = (unsafe { Transaction::from_raw(ptr as *mut ffi::Transaction, false) })
+        else {
+            continue;
+        };
+        if trans.is_void() || trans.split_count() == 0 {
+            continue;
+        }
+        let progress = trans
+            .splits()
+            .map(|s| match s.reconcile_state() {
+                state if state.is_reconciled() => 2,
+                ReconcileState::Cleared => 1,
+                _ => 0,
+            })
+            .sum();
+        buckets
+            .entry(signature(&trans))
+            .or_default()
+            .push(Candidate {
+                guid: trans.guid(),
+                day: trans.date_posted().div_euclid(86_400),
+                entered: trans.date_entered(),
+                description: trans.description().unwrap_or_default(),
+                progress,
+            });
+    }
+
+    let mut groups: Vec<DuplicateGroup> = buckets
+        .into_values()
+        .filter(|bucket| bucket.len() > 1)
+        .flat_map(|bucket| group_candidates(bucket, options))
+        .collect();
+    groups.sort_by(|a, b| b.score.total_cmp(&a.score));
+    groups
+}
+
+/// Returns the accounts and amounts of a transaction's splits, sorted,
+/// so equal signatures mean the same money moved the same way.
+fn signature(trans: &Transaction) -> Vec<(Guid, Numeric)> {
+    let mut signature: Vec<(Guid, Numeric)> = trans
+        .splits()
+        .map(|s| {
+            let account = s.account().map_or(*Guid::null(), |a| a.guid());
+            (account, s.amount().reduce())
+        })
+        .collect();
+    signature.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()).then(a.1.cmp_value(&b.1)));
+    signature
+}
+
+/// Links the candidates of one signature bucket that are close enough
+/// in date and description, and returns the connected groups.
+fn group_candidates(mut bucket: Vec<Candidate>, options: &DuplicateOptions) -> Vec<DuplicateGroup> {
+    bucket.sort_by_key(|c| c.day);
+    let mut parent: Vec<usize> = (0..bucket.len()).collect();
+    let mut weakest: HashMap<usize, f64> = HashMap::new();
+    let mut links = Vec::new();
+
+    for i in 0..bucket.len() {
+        for j in i + 1..bucket.len() {
+            let days = bucket[j].day - bucket[i].day;
+            if days > options.date_window_days {
+                break;
+            }
+            let similarity = similarity(&bucket[i].description, &bucket[j].description);
+            if similarity < options.min_similarity {
+                continue;
+            }
+            let closeness = 1.0 - days as f64 / (options.date_window_days + 1) as f64;
+            links.push((i, j, (similarity + closeness) / 2.0));
+            let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+            if a != b {
+                parent[b] = a;
+            }
+        }
+    }
+    for (i, _, score) in links {
+        let root = find(&mut parent, i);
+        let entry = weakest.entry(root).or_insert(score);
+        *entry = entry.min(score);
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..bucket.len() {
+        let root = find(&mut parent, i);
+        members.entry(root).or_default().push(i);
+    }
+    members
+        .into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .map(|(root, mut indices)| {
+            indices.sort_by_key(|&i| (std::cmp::Reverse(bucket[i].progress), bucket[i].entered));
+            DuplicateGroup {
+                transactions: indices.into_iter().map(|i| bucket[i].guid).collect(),
+                score: weakest[&root],
+            }
+        })
+        .collect()
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Returns how alike two descriptions are, from 0 to 1, as one minus
+/// their edit distance over the longer length. Case and surrounding
+/// whitespace are ignored; two empty descriptions are identical.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.trim().to_lowercase().chars().collect();
+    let b: Vec<char> = b.trim().to_lowercase().chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// Levenshtein distance.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Keeps the first transaction of `group` and deletes the rest,
+/// returning how many were deleted.
+///
+/// Before deleting, each kept split takes the most advanced reconcile
+/// state, and its reconcile date, of the matching splits (same account
+/// and amount) in the deleted transactions, so a statement reconciled
+/// against a copy stays reconciled.
+///
+/// # Errors
+///
+/// Nothing is changed if a transaction of the group no longer exists or
+/// no longer matches the kept one ([`Error::InvalidOperation`]), or if
+/// any of them is read-only ([`Error::ReadOnly`]).
+pub fn merge(book: &Book, group: &DuplicateGroup) -> Result<usize> {
+    let lookup = |guid: &Guid| {
+        unsafe { Transaction::from_raw(ffi::xaccTransLookup(guid.as_ffi(), book.as_ptr()), false) }
+            .ok_or_else(|| Error::InvalidOperation(format!("transaction {guid} not found")))
+    };
+    let Some((keep, redundant)) = group.transactions.split_first() else {
+        return Ok(0);
+    };
+    let keep = lookup(keep)?;
+    let redundant = redundant.iter().map(lookup).collect::<Result<Vec<_>>>()?;
+
+    let expected = signature(&keep);
+    for trans in std::iter::once(&keep).chain(&redundant) {
+        if trans.read_only_reason().is_some() || trans.is_readonly_by_posted_date() {
+            return Err(Error::ReadOnly);
+        }
+        if signature(trans) != expected {
+            return Err(Error::InvalidOperation(format!(
+                "transaction {} no longer matches {}",
+                trans.guid(),
+                keep.guid()
+            )));
+        }
+    }
+
+    let edit = keep.edit();
+    let kept: Vec<Split> = keep.splits().collect();
+    for trans in &redundant {
+        let mut unclaimed: Vec<Split> = kept.iter().map(rewrap).collect();
+        for split in trans.splits() {
+            let Some(pos) = unclaimed.iter().position(|k| same_leg(k, &split)) else {
+                continue;
+            };
+            let target = unclaimed.swap_remove(pos);
+            if rank(split.reconcile_state()) > rank(target.reconcile_state()) {
+                let target = edit.split(&target);
+                target.set_reconcile_state(split.reconcile_state());
+                target.set_date_reconciled(split.date_reconciled());
+            }
+        }
+    }
+    edit.commit();
+
+    for trans in &redundant {
+        trans.edit().destroy();
+    }
+    Ok(redundant.len())
+}
+
+fn rewrap(split: &Split) -> Split {
+    unsafe { Split::from_raw(split.as_ptr(), false) }.expect("split pointer is non-null")
+}
+
+fn same_leg(a: &Split, b: &Split) -> bool {
+    a.account().map(|a| a.guid()) == b.account().map(|a| a.guid())
+        && a.amount().eq_value(&b.amount())
+}
+
+/// Orders reconcile states by how far along they are.
+fn rank(state: ReconcileState) -> u8 {
+    match state {
+        ReconcileState::New | ReconcileState::Void => 0,
+        ReconcileState::Cleared => 1,
+        ReconcileState::Reconciled => 2,
+        ReconcileState::Frozen => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: u8, day: i64, description: &str, progress: usize) -> Candidate {
+        Candidate {
+            guid: Guid::from_bytes([id; 16]),
+            day,
+            entered: id as i64,
+            description: description.to_string(),
+            progress,
+        }
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("", "  "), 1.0);
+        assert_eq!(similarity("ACME Corp", "acme corp "), 1.0);
+        assert_eq!(edit_distance(&['k', 'i', 't'], &['s', 'i', 't', 's']), 2);
+        assert!(similarity("POS 1234 GROCER", "POS 1243 GROCER") > 0.8);
+        assert!(similarity("Rent", "Salary") < 0.5);
+    }
+
+    #[test]
+    fn test_group_candidates() {
+        let bucket = vec![
+            candidate(1, 100, "Coffee Shop", 0),
+            candidate(2, 102, "COFFEE SHOP #12", 2),
+            candidate(3, 101, "Landlord", 0),
+            candidate(4, 110, "Coffee Shop", 0),
+        ];
+        let groups = group_candidates(bucket, &DuplicateOptions::new());
+        assert_eq!(groups.len(), 1);
+        // The reconciled copy is kept even though it was entered later.
+        assert_eq!(groups[0].keep(), Guid::from_bytes([2; 16]));
+        assert_eq!(groups[0].redundant(), &[Guid::from_bytes([1; 16])]);
+        assert!(groups[0].score > 0.5 && groups[0].score < 1.0);
+
+        let bucket = vec![
+            candidate(1, 100, "Coffee Shop", 0),
+            candidate(4, 110, "Coffee Shop", 0),
+        ];
+        let options = DuplicateOptions::new().date_window_days(10);
+        assert_eq!(group_candidates(bucket, &options).len(), 1);
+    }
+}
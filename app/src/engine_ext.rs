@@ -4,14 +4,19 @@
 //! - [`BookExt`]: `Book::commodity_table()` returning the
 //!   [`CommodityTable`](crate::business::CommodityTable) attached to
 //!   the book.
-//! - [`AccountExt`]: `Account::commodity()` /
-//!   `Account::set_commodity()` — every transaction on an account
-//!   requires the account's commodity to be set.
+//! - [`AccountExt`]: `Account::commodity()`, with
+//!   [`AccountEditorExt`] adding `set_commodity()` to the account's
+//!   editor — every transaction on an account requires the account's
+//!   commodity to be set.
+//! - [`TransactionExt`]: `Transaction::currency()`, with
+//!   [`TransactionEditorExt`] adding `set_currency()` to the
+//!   transaction's editor — the commodity split values are expressed
+//!   in.
 //!
 //! Usage:
 //!
 //! ```ignore
-//! use gnucash_ext::{BookExt, AccountExt, init_engine};
+//! use gnucash_ext::{AccountEditorExt, BookExt, Editable};
 //! use gnucash_ext::business::{Commodity, CommodityTable};
 //!
 //! let book = gnucash_ext::Book::new();
@@ -21,12 +26,12 @@
 //! ).unwrap();
 //!
 //! let account = gnucash_ext::Account::new(&book);
-//! account.begin_edit();
-//! account.set_commodity(&crc);
-//! account.commit_edit();
+//! account.edit().set_commodity(&crc);
 //! ```
 
-use gnucash_sys::{ffi, Account, Book};
+use std::ffi::CStr;
+
+use gnucash_sys::{Account, AccountEditor, Book, Transaction, TransactionEditor, ffi};
 
 use crate::business::{Commodity, CommodityTable};
 
@@ -51,10 +56,6 @@ pub trait AccountExt {
     /// Returns the commodity this account is denominated in.
     /// `None` for an account that has not had a commodity set yet.
     fn commodity(&self) -> Option<Commodity>;
-
-    /// Sets the commodity. Must be called between `begin_edit` and
-    /// `commit_edit` like all other account mutators.
-    fn set_commodity(&self, commodity: &Commodity);
 }
 
 impl AccountExt for Account {
@@ -64,10 +65,71 @@ impl AccountExt for Account {
             Commodity::from_raw(ptr, false)
         }
     }
+}
+
+pub trait AccountEditorExt {
+    /// Sets the commodity. Like the account's other setters, this is
+    /// only reachable inside an edit session.
+    fn set_commodity(&self, commodity: &Commodity);
+}
 
+impl AccountEditorExt for AccountEditor<'_> {
     fn set_commodity(&self, commodity: &Commodity) {
+        let old = unsafe { ffi::xaccAccountGetCommodity(self.as_ptr()) };
+        self.on_rollback(move |a| unsafe { ffi::xaccAccountSetCommodity(a.as_ptr(), old) });
         unsafe {
             ffi::xaccAccountSetCommodity(self.as_ptr(), commodity.as_ptr());
         }
     }
 }
+
+pub trait TransactionExt {
+    /// Returns the currency the transaction's split values are in.
+    fn currency(&self) -> Option<Commodity>;
+}
+
+impl TransactionExt for Transaction {
+    fn currency(&self) -> Option<Commodity> {
+        unsafe {
+            let ptr = ffi::xaccTransGetCurrency(self.as_ptr());
+            Commodity::from_raw(ptr, false)
+        }
+    }
+}
+
+pub trait TransactionEditorExt {
+    /// Sets the transaction currency. Like the transaction's other
+    /// setters, this is only reachable inside an edit session.
+    fn set_currency(&self, currency: &Commodity);
+}
+
+impl TransactionEditorExt for TransactionEditor<'_> {
+    fn set_currency(&self, currency: &Commodity) {
+        let old = unsafe { ffi::xaccTransGetCurrency(self.as_ptr()) };
+        self.on_rollback(move |t| unsafe { ffi::xaccTransSetCurrency(t.as_ptr(), old) });
+        unsafe {
+            ffi::xaccTransSetCurrency(self.as_ptr(), currency.as_ptr());
+        }
+    }
+}
+
+/// Returns every instance in `book`'s collection of `id_type`, such as
+/// `c"Trans"` or `c"gncInvoice"`.
+pub(crate) fn collection(book: &Book, id_type: &CStr) -> Vec<*mut ffi::QofInstance> {
+    unsafe extern "C" fn collect(inst: *mut ffi::QofInstance, data: ffi::gpointer) {
+        unsafe { (*(data as *mut Vec<*mut ffi::QofInstance>)).push(inst) }
+    }
+
+    let mut found: Vec<*mut ffi::QofInstance> = Vec::new();
+    unsafe {
+        let col = ffi::qof_book_get_collection(book.as_ptr(), id_type.as_ptr());
+        if !col.is_null() {
+            ffi::qof_collection_foreach(
+                col,
+                Some(collect),
+                &mut found as *mut Vec<*mut ffi::QofInstance> as ffi::gpointer,
+            );
+        }
+    }
+    found
+}
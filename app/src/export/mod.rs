@@ -0,0 +1,7 @@
+//! Export to interchange formats.
+//!
+//! - [`qif`] - QIF register of a single account
+//! - [`txf`] - TXF tax-related totals for a tax year
+
+pub mod qif;
+pub mod txf;
@@ -0,0 +1,234 @@
+//! QIF export of a single account register.
+//!
+//! Writes an `!Account` record followed by the register under the QIF
+//! type matching the account type. Income and expense counter accounts
+//! are written as categories (without their top-level account), all
+//! others as `[Full:Name]` transfers, so the output reads back with
+//! [`import::qif`](crate::import::qif). Stock and mutual fund accounts
+//! are written as `!Type:Invst` with `BuyX`/`SellX` actions whose cash
+//! moves through the `L` account.
+//!
+//! ```ignore
+//! let mut file = std::fs::File::create("checking.qif")?;
+//! let records = gnucash_ext::export::qif::write_account(&checking, &mut file)?;
+//! ```
+
+use std::io::{self, Write};
+
+use gnucash_sys::{Account, GNCAccountType, Numeric, ReconcileState, Split, Transaction};
+
+use crate::date::Date;
+use crate::engine_ext::{AccountExt, TransactionExt};
+use crate::import::qif::QifAccountType;
+use crate::reports::decimal_places;
+
+/// Writes the account's register as QIF and returns the number of
+/// records written.
+pub fn write_account<W: Write>(account: &Account, out: &mut W) -> io::Result<usize> {
+    let qif_type = QifAccountType::from_account_type(account.account_type());
+    writeln!(out, "!Account")?;
+    writeln!(out, "N{}", account.full_name().unwrap_or_default())?;
+    writeln!(out, "T{}", qif_type.name())?;
+    if let Some(description) = account.description()
+        && !description.is_empty()
+    {
+        writeln!(out, "D{}", description)?;
+    }
+    writeln!(out, "^")?;
+    writeln!(out, "!Type:{}", qif_type.name())?;
+
+    let places = decimal_places(account.commodity_scu());
+    let mut count = 0;
+    for split in account.splits() {
+        let Some(txn) = split.transaction() else {
+            continue;
+        };
+        let written = if qif_type == QifAccountType::Invst {
+            write_investment(out, account, &split, &txn, places)?
+        } else {
+            write_transaction(out, &split, &txn, places)?
+        };
+        if written {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn write_transaction<W: Write>(
+    out: &mut W,
+    split: &Split,
+    txn: &Transaction,
+    places: u32,
+) -> io::Result<bool> {
+    write_header(out, split, txn)?;
+    writeln!(out, "T{}", split.amount().to_decimal_string(places))?;
+    if let Some(num) = non_empty(txn.num()) {
+        writeln!(out, "N{}", num)?;
+    }
+    write_text(out, split, txn)?;
+
+    let others = other_splits(split, txn);
+    match others.as_slice() {
+        [] => {}
+        [other] => {
+            if let Some(label) = other.account().map(|a| target_label(&a)) {
+                writeln!(out, "L{}", label)?;
+            }
+        }
+        others => {
+            for other in others {
+                if let Some(label) = other.account().map(|a| target_label(&a)) {
+                    writeln!(out, "S{}", label)?;
+                }
+                if let Some(memo) = non_empty(other.memo()) {
+                    writeln!(out, "E{}", memo)?;
+                }
+                writeln!(out, "${}", other.value().neg().to_decimal_string(places))?;
+            }
+        }
+    }
+    writeln!(out, "^")?;
+    Ok(true)
+}
+
+fn write_investment<W: Write>(
+    out: &mut W,
+    account: &Account,
+    split: &Split,
+    txn: &Transaction,
+    places: u32,
+) -> io::Result<bool> {
+    let shares = split.amount();
+    if shares.is_zero() {
+        return Ok(false);
+    }
+    let value_places = txn
+        .currency()
+        .map(|c| decimal_places(c.fraction()))
+        .unwrap_or(2);
+
+    // The cash side is the first split outside income and expense
+    // accounts; expense splits are the commission.
+    let others = other_splits(split, txn);
+    let cash = others.iter().find(|s| !is_category(s.account().as_ref()));
+    let commission: Numeric = others
+        .iter()
+        .filter(|s| {
+            s.account()
+                .is_some_and(|a| a.account_type() == GNCAccountType::ACCT_TYPE_EXPENSE)
+        })
+        .map(|s| s.value())
+        .sum();
+    let total = match cash {
+        Some(cash) => cash.value().abs(),
+        None => split.value().abs() + commission,
+    };
+
+    write_header(out, split, txn)?;
+    writeln!(
+        out,
+        "N{}",
+        if shares.is_positive() {
+            "BuyX"
+        } else {
+            "SellX"
+        }
+    )?;
+    writeln!(out, "Y{}", security_name(account))?;
+    writeln!(
+        out,
+        "I{}",
+        trim_decimal(split.share_price().to_decimal_string(6))
+    )?;
+    writeln!(out, "Q{}", shares.abs().to_decimal_string(places))?;
+    writeln!(out, "T{}", total.to_decimal_string(value_places))?;
+    if !commission.is_zero() {
+        writeln!(out, "O{}", commission.to_decimal_string(value_places))?;
+    }
+    write_text(out, split, txn)?;
+    if let Some(account) = cash.and_then(|s| s.account()) {
+        writeln!(out, "L{}", target_label(&account))?;
+        writeln!(out, "${}", total.to_decimal_string(value_places))?;
+    }
+    writeln!(out, "^")?;
+    Ok(true)
+}
+
+/// Writes the date and reconcile flag shared by both record kinds.
+fn write_header<W: Write>(out: &mut W, split: &Split, txn: &Transaction) -> io::Result<()> {
+    let date = Date::from_time64(txn.date_posted());
+    writeln!(
+        out,
+        "D{:02}/{:02}/{:04}",
+        date.month(),
+        date.day(),
+        date.year()
+    )?;
+    match split.reconcile_state() {
+        ReconcileState::Cleared => writeln!(out, "C*")?,
+        ReconcileState::Reconciled | ReconcileState::Frozen => writeln!(out, "CX")?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Writes payee and memo.
+fn write_text<W: Write>(out: &mut W, split: &Split, txn: &Transaction) -> io::Result<()> {
+    if let Some(description) = non_empty(txn.description()) {
+        writeln!(out, "P{}", description)?;
+    }
+    if let Some(memo) = non_empty(split.memo()) {
+        writeln!(out, "M{}", memo)?;
+    }
+    Ok(())
+}
+
+fn other_splits(split: &Split, txn: &Transaction) -> Vec<Split> {
+    let guid = split.guid();
+    txn.splits().filter(|s| s.guid() != guid).collect()
+}
+
+fn is_category(account: Option<&Account>) -> bool {
+    account.is_some_and(|a| {
+        matches!(
+            a.account_type(),
+            GNCAccountType::ACCT_TYPE_INCOME | GNCAccountType::ACCT_TYPE_EXPENSE
+        )
+    })
+}
+
+/// Returns the QIF `L`/`S` label for a counter account.
+fn target_label(account: &Account) -> String {
+    let full_name = account.full_name().unwrap_or_default();
+    if is_category(Some(account)) {
+        match full_name.split_once(':') {
+            Some((_, category)) => category.to_string(),
+            None => full_name,
+        }
+    } else {
+        format!("[{}]", full_name)
+    }
+}
+
+/// Returns the security name for an investment account: its commodity
+/// mnemonic, falling back to the account name.
+fn security_name(account: &Account) -> String {
+    account
+        .commodity()
+        .and_then(|c| c.mnemonic())
+        .or_else(|| account.name())
+        .unwrap_or_default()
+}
+
+fn non_empty(s: Option<String>) -> Option<String> {
+    s.filter(|s| !s.is_empty())
+}
+
+fn trim_decimal(s: String) -> String {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        s
+    }
+}
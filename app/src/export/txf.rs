@@ -0,0 +1,216 @@
+//! TXF export of tax-related account totals.
+//!
+//! Accounts flagged tax-related with a TXF code (see
+//! [`Account::tax_us_code`]) are totalled for a tax year, grouped by
+//! code, payer and form copy, the same totals the GnuCash "Tax Schedule
+//! Report" exports. [`TaxReport::write_txf`] writes them in TXF V042
+//! for tax software; [`TaxReport::to_csv`] writes the same lines as CSV.
+//!
+//! ```ignore
+//! let report = gnucash_ext::export::txf::tax_report(&book, 2024, &usd)?;
+//! let mut file = std::fs::File::create("taxes-2024.txf")?;
+//! report.write_txf(&mut file, Date::new(2025, 2, 1).unwrap())?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use gnucash_sys::{Account, Book, Error, Numeric, Result};
+
+use crate::business::Commodity;
+use crate::date::Date;
+use crate::engine_ext::TransactionExt;
+use crate::reports::render::csv_record;
+use crate::reports::{AccountClass, CurrencyConverter, Period};
+
+/// The total for one TXF code, payer and form copy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxLine {
+    /// TXF reference code, e.g. "N521".
+    pub code: String,
+    /// Payer name, for codes reported per payer.
+    pub payer: Option<String>,
+    /// Copy number of the form.
+    pub copy: i64,
+    /// Full names of the accounts contributing to the line.
+    pub accounts: Vec<String>,
+    /// Total for the year; income is positive.
+    pub amount: Numeric,
+}
+
+/// Tax-related totals for a tax year.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxReport {
+    /// The tax year.
+    pub year: i32,
+    /// Report currency mnemonic.
+    pub currency: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// Lines ordered by code, payer and copy.
+    pub lines: Vec<TaxLine>,
+    /// Commodities that could not be converted for lack of a price.
+    pub missing_prices: Vec<String>,
+}
+
+/// Totals the tax-related accounts of `book` with a TXF code over the
+/// calendar year `year`. Closing entries are left out.
+pub fn tax_report(book: &Book, year: i32, currency: &Commodity) -> Result<TaxReport> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let period = Period::year(year);
+
+    let mut lines: BTreeMap<(String, Option<String>, i64), TaxLine> = BTreeMap::new();
+    for account in root.descendants() {
+        if !account.is_tax_related() {
+            continue;
+        }
+        let Some(code) = account.tax_us_code().filter(|c| !c.is_empty()) else {
+            continue;
+        };
+        let credit = AccountClass::of(account.account_type()).is_some_and(|c| c.is_credit());
+        let mut amount = Numeric::zero();
+        for split in account.splits() {
+            let Some(txn) = split.transaction() else {
+                continue;
+            };
+            let time = txn.date_posted();
+            if period.contains(time) && !txn.is_closing() {
+                amount += converter.convert(split.value(), txn.currency().as_ref(), time);
+            }
+        }
+        let payer = payer_name(&account);
+        let copy = account.tax_us_copy_number().max(1);
+        let line = lines
+            .entry((code.clone(), payer.clone(), copy))
+            .or_insert_with(|| TaxLine {
+                code,
+                payer,
+                copy,
+                accounts: Vec::new(),
+                amount: Numeric::zero(),
+            });
+        line.accounts.push(account.full_name().unwrap_or_default());
+        line.amount += if credit { -amount } else { amount };
+    }
+
+    Ok(TaxReport {
+        year,
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        lines: lines.into_values().collect(),
+        missing_prices: converter.missing_prices(),
+    })
+}
+
+/// Returns the payer named by the account's payer name source.
+fn payer_name(account: &Account) -> Option<String> {
+    match account.tax_us_payer_name_source().as_deref() {
+        Some("current") => account.name(),
+        Some("parent") => account.parent().and_then(|p| p.name()),
+        _ => None,
+    }
+}
+
+impl TaxReport {
+    /// Writes the report as TXF V042 with `exported` as the export date
+    /// and returns the number of records written. Lines without a payer
+    /// are written as summary records (format 1), the others with a `P`
+    /// payer line (format 3). Zero lines are skipped.
+    pub fn write_txf<W: Write>(&self, out: &mut W, exported: Date) -> io::Result<usize> {
+        write!(out, "V042\r\nAgnucash-ext\r\n")?;
+        write!(
+            out,
+            "D{:02}/{:02}/{:04}\r\n^\r\n",
+            exported.month(),
+            exported.day(),
+            exported.year()
+        )?;
+        let mut count = 0;
+        for line in self.lines.iter().filter(|l| !l.amount.is_zero()) {
+            let code = if line.code.starts_with('N') {
+                line.code.clone()
+            } else {
+                format!("N{}", line.code)
+            };
+            write!(out, "TD\r\n{}\r\nC{}\r\nL1\r\n", code, line.copy)?;
+            write!(out, "${}\r\n", line.amount.to_decimal_string(self.places))?;
+            if let Some(payer) = &line.payer {
+                write!(out, "P{}\r\n", payer)?;
+            }
+            write!(out, "^\r\n")?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Renders the report as CSV with one record per line.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(
+            &mut out,
+            &["year", "code", "payer", "copy", "amount", "accounts"],
+        );
+        for line in &self.lines {
+            csv_record(
+                &mut out,
+                &[
+                    &self.year.to_string(),
+                    &line.code,
+                    line.payer.as_deref().unwrap_or(""),
+                    &line.copy.to_string(),
+                    &line.amount.to_decimal_string(self.places),
+                    &line.accounts.join("; "),
+                ],
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_txf() {
+        let line = |code: &str, payer: Option<&str>, cents: i64| TaxLine {
+            code: code.to_string(),
+            payer: payer.map(str::to_string),
+            copy: 1,
+            accounts: vec!["Income:Interest".to_string()],
+            amount: Numeric::new(cents, 100),
+        };
+        let report = TaxReport {
+            year: 2024,
+            currency: "USD".to_string(),
+            places: 2,
+            lines: vec![
+                line("N287", Some("First Bank"), 12_345),
+                line("521", None, 5_000),
+                line("N522", None, 0),
+            ],
+            missing_prices: Vec::new(),
+        };
+        let mut out = Vec::new();
+        let count = report
+            .write_txf(&mut out, Date::new(2025, 2, 1).unwrap())
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "V042\r\nAgnucash-ext\r\nD02/01/2025\r\n^\r\n",
+                "TD\r\nN287\r\nC1\r\nL1\r\n$123.45\r\nPFirst Bank\r\n^\r\n",
+                "TD\r\nN521\r\nC1\r\nL1\r\n$50.00\r\n^\r\n",
+            )
+        );
+        assert!(
+            report.to_csv().starts_with(
+                "year,code,payer,copy,amount,accounts\n2024,N287,First Bank,1,123.45,"
+            )
+        );
+    }
+}
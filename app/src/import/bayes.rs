@@ -0,0 +1,287 @@
+//! Bayesian payee-to-account matching.
+//!
+//! GnuCash's import matcher learns which transfer account a description
+//! belongs to. It splits the description, notes and memos into tokens
+//! and stores a count per (token, account) pair in the importing
+//! account's `import-map-bayes` KVP frame. [`BayesMatcher`] reads and
+//! writes that same map, so mappings learned in the GUI are used here
+//! and vice versa.
+//!
+//! Suggestions follow the GnuCash algorithm: for each token, an
+//! account's probability is its share of the token's count; per account
+//! the probabilities are combined as `Πp / (Πp + Π(1 - p))`. GnuCash
+//! auto-selects an account above 0.9.
+//!
+//! ```ignore
+//! use gnucash_ext::import::bayes::BayesMatcher;
+//!
+//! let mut matcher = BayesMatcher::new(&checking);
+//! if matcher.is_empty() {
+//!     matcher.train();
+//! }
+//! if let Some(s) = matcher.suggest("ACME SUPERMARKET 1234", None) {
+//!     println!("{:?} ({:.0}%)", s.account.full_name(), s.confidence * 100.0);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+
+use gnucash_sys::{Account, Guid, ffi};
+
+/// Confidence above which GnuCash selects a matched account itself.
+pub const GNUCASH_THRESHOLD: f64 = 0.9;
+
+/// A suggested counter account.
+#[derive(Debug)]
+pub struct Suggestion {
+    /// The suggested account.
+    pub account: Account,
+    /// Combined probability between 0 and 1.
+    pub confidence: f64,
+}
+
+/// Token counts per account, keyed by token.
+type Model = HashMap<String, HashMap<Guid, i64>>;
+
+/// Suggests counter accounts for imported descriptions from the Bayes
+/// map stored on the importing account.
+pub struct BayesMatcher<'a> {
+    account: &'a Account,
+    model: Model,
+    accounts: HashMap<Guid, Account>,
+}
+
+impl<'a> BayesMatcher<'a> {
+    /// Loads the Bayes map stored on `account`, the account statements
+    /// are imported into.
+    pub fn new(account: &'a Account) -> Self {
+        let mut matcher = Self {
+            account,
+            model: HashMap::new(),
+            accounts: HashMap::new(),
+        };
+        unsafe {
+            let list = ffi::gnc_account_imap_get_info_bayes(account.as_ptr());
+            let mut node = list;
+            while !node.is_null() {
+                let info = (*node).data as *mut ffi::GncImapInfo;
+                if !info.is_null() {
+                    matcher.load_entry(&*info);
+                    ffi::gnc_account_imap_info_destroy(info);
+                }
+                node = (*node).next;
+            }
+            ffi::g_list_free(list);
+        }
+        matcher
+    }
+
+    unsafe fn load_entry(&mut self, info: &ffi::GncImapInfo) {
+        let Some(target) = (unsafe { Account::from_raw(info.map_account, false) }) else {
+            return;
+        };
+        if info.match_string.is_null() || info.count.is_null() {
+            return;
+        }
+        let token = unsafe { CStr::from_ptr(info.match_string) }
+            .to_string_lossy()
+            .into_owned();
+        let count: i64 = unsafe { CStr::from_ptr(info.count) }
+            .to_string_lossy()
+            .trim()
+            .parse()
+            .unwrap_or(0);
+        let guid = target.guid();
+        *self
+            .model
+            .entry(token)
+            .or_default()
+            .entry(guid)
+            .or_insert(0) += count;
+        self.accounts.entry(guid).or_insert(target);
+    }
+
+    /// Returns true if nothing has been learned yet.
+    pub fn is_empty(&self) -> bool {
+        self.model.is_empty()
+    }
+
+    /// Returns the number of distinct tokens in the map.
+    pub fn token_count(&self) -> usize {
+        self.model.len()
+    }
+
+    /// Returns how often `token` was mapped to `account`.
+    pub fn count(&self, token: &str, account: &Account) -> i64 {
+        self.model
+            .get(token)
+            .and_then(|counts| counts.get(&account.guid()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Suggests the counter account for a description and optional memo,
+    /// or `None` if no token has been seen before.
+    pub fn suggest(&self, description: &str, memo: Option<&str>) -> Option<Suggestion> {
+        let tokens = tokenize(&[description, memo.unwrap_or("")]);
+        let (guid, confidence) = score(&self.model, &tokens)?;
+        let account = self.accounts.get(&guid)?;
+        Some(Suggestion {
+            account: unsafe { Account::from_raw(account.as_ptr(), false)? },
+            confidence,
+        })
+    }
+
+    /// Records that a description and memo belong to `counter`, updating
+    /// the map stored on the importing account.
+    pub fn learn(&mut self, description: &str, memo: Option<&str>, counter: &Account) {
+        let tokens = tokenize(&[description, memo.unwrap_or("")]);
+        if tokens.is_empty() {
+            return;
+        }
+        let c_tokens: Vec<CString> = tokens
+            .iter()
+            .filter_map(|t| CString::new(t.as_str()).ok())
+            .collect();
+        unsafe {
+            let mut list: *mut ffi::GList = std::ptr::null_mut();
+            for token in &c_tokens {
+                list = ffi::g_list_prepend(list, token.as_ptr() as ffi::gpointer);
+            }
+            ffi::gnc_account_imap_add_account_bayes(self.account.as_ptr(), list, counter.as_ptr());
+            ffi::g_list_free(list);
+        }
+
+        let guid = counter.guid();
+        for token in tokens {
+            *self
+                .model
+                .entry(token)
+                .or_default()
+                .entry(guid)
+                .or_insert(0) += 1;
+        }
+        if let Some(account) = unsafe { Account::from_raw(counter.as_ptr(), false) } {
+            self.accounts.entry(guid).or_insert(account);
+        }
+    }
+
+    /// Learns from the importing account's existing transactions. Each
+    /// transaction whose other splits all post to a single account
+    /// teaches its description, notes and memos. Counts accumulate, so
+    /// train a given history only once. Returns the number of
+    /// transactions learned from.
+    pub fn train(&mut self) -> usize {
+        let own = self.account.guid();
+        let mut learned = 0;
+        for split in self.account.splits() {
+            let Some(txn) = split.transaction() else {
+                continue;
+            };
+            let mut counter: Option<Account> = None;
+            let mut texts = vec![
+                txn.description().unwrap_or_default(),
+                txn.notes().unwrap_or_default(),
+            ];
+            let mut ambiguous = false;
+            for other in txn.splits() {
+                texts.push(other.memo().unwrap_or_default());
+                let Some(account) = other.account() else {
+                    continue;
+                };
+                if account.guid() == own {
+                    continue;
+                }
+                match &counter {
+                    Some(existing) if existing.guid() != account.guid() => ambiguous = true,
+                    Some(_) => {}
+                    None => counter = Some(account),
+                }
+            }
+            if let (Some(counter), false) = (counter, ambiguous) {
+                let text = texts.join(" ");
+                self.learn(&text, None, &counter);
+                learned += 1;
+            }
+        }
+        learned
+    }
+}
+
+/// Splits texts into unique tokens at spaces, the way GnuCash does.
+pub fn tokenize(texts: &[&str]) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for text in texts {
+        for token in text.split(' ').filter(|t| !t.is_empty()) {
+            if !tokens.iter().any(|t| t == token) {
+                tokens.push(token.to_string());
+            }
+        }
+    }
+    tokens
+}
+
+/// Returns the most probable account and its combined probability.
+fn score(model: &Model, tokens: &[String]) -> Option<(Guid, f64)> {
+    // Per account: (product of p, product of 1 - p).
+    let mut running: HashMap<Guid, (f64, f64)> = HashMap::new();
+    for token in tokens {
+        let Some(counts) = model.get(token) else {
+            continue;
+        };
+        let total: i64 = counts.values().sum();
+        if total <= 0 {
+            continue;
+        }
+        for (guid, count) in counts {
+            let p = *count as f64 / total as f64;
+            let entry = running.entry(*guid).or_insert((1.0, 1.0));
+            entry.0 *= p;
+            entry.1 *= 1.0 - p;
+        }
+    }
+    running
+        .into_iter()
+        .map(|(guid, (product, difference))| (guid, product / (product + difference)))
+        .filter(|(_, p)| p.is_finite())
+        .max_by(|a, b| {
+            a.1.total_cmp(&b.1)
+                .then_with(|| b.0.as_bytes().cmp(a.0.as_bytes()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guid(n: u8) -> Guid {
+        Guid::from_bytes([n; 16])
+    }
+
+    #[test]
+    fn test_tokenize_dedups() {
+        assert_eq!(
+            tokenize(&["ACME  STORE 12", "STORE", ""]),
+            vec!["ACME", "STORE", "12"]
+        );
+    }
+
+    #[test]
+    fn test_score() {
+        let mut model = Model::new();
+        model.insert("ACME".into(), HashMap::from([(guid(1), 9), (guid(2), 1)]));
+        model.insert("STORE".into(), HashMap::from([(guid(1), 3), (guid(2), 3)]));
+        model.insert("FUEL".into(), HashMap::from([(guid(2), 4)]));
+
+        let (best, p) = score(&model, &tokenize(&["ACME STORE"])).unwrap();
+        assert_eq!(best, guid(1));
+        assert!((p - 0.9).abs() < 1e-9);
+
+        let (best, p) = score(&model, &tokenize(&["FUEL"])).unwrap();
+        assert_eq!(best, guid(2));
+        assert_eq!(p, 1.0);
+
+        assert!(score(&model, &tokenize(&["UNKNOWN"])).is_none());
+    }
+}
@@ -0,0 +1,496 @@
+//! ISO 20022 camt.053 (account statement) and camt.054 (debit/credit
+//! notification) parser.
+//!
+//! Each `Stmt` / `Ntfctn` becomes a [`CamtStatement`]. Entries (`Ntry`)
+//! keep their transaction details (`TxDtls`); a batched entry that books
+//! several payments at once carries one detail per payment, and
+//! [`CamtStatement::lines`] turns each of them into its own
+//! [`StatementLine`]. Only booked entries are converted; pending ones
+//! are kept in the parsed statement for inspection.
+//!
+//! ```ignore
+//! use gnucash_ext::import::{camt, Importer};
+//!
+//! for stmt in camt::parse(&std::fs::read_to_string("statement.xml")?)? {
+//!     Importer::new(&book, &checking, &imbalance).import(&stmt.lines())?;
+//!     stmt.record_reconcile_target(&checking);
+//! }
+//! ```
+
+use gnucash_sys::{Account, Editable, Error, Numeric, Result};
+
+use super::StatementLine;
+use super::markup::{self, Element};
+use crate::date::Date;
+
+/// One camt.053 statement or camt.054 notification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CamtStatement {
+    /// Statement or notification ID (`Id`).
+    pub id: Option<String>,
+    /// Account IBAN (`Acct/Id/IBAN`), or other account ID.
+    pub account_id: Option<String>,
+    /// Account currency (`Acct/Ccy`).
+    pub currency: Option<String>,
+    /// Opening booked balance (`OPBD`) and its date.
+    pub opening_balance: Option<(Numeric, Date)>,
+    /// Closing booked balance (`CLBD`) and its date.
+    pub closing_balance: Option<(Numeric, Date)>,
+    /// Entries in file order.
+    pub entries: Vec<CamtEntry>,
+}
+
+/// A statement entry (`Ntry`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CamtEntry {
+    /// Bank reference (`AcctSvcrRef`, falling back to `NtryRef`).
+    pub reference: Option<String>,
+    /// Signed amount; negative for debits.
+    pub amount: Numeric,
+    /// Currency of the amount (`Amt/@Ccy`).
+    pub currency: Option<String>,
+    /// True if the entry is booked (`Sts` = `BOOK`).
+    pub booked: bool,
+    /// Booking date (`BookgDt`).
+    pub booking_date: Option<Date>,
+    /// Value date (`ValDt`).
+    pub value_date: Option<Date>,
+    /// Free-text entry information (`AddtlNtryInf`).
+    pub additional_info: Option<String>,
+    /// Transaction details; several for a batched entry.
+    pub details: Vec<CamtTransaction>,
+}
+
+/// Transaction details (`TxDtls`) of an entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CamtTransaction {
+    /// Signed amount, if given per transaction.
+    pub amount: Option<Numeric>,
+    /// Bank reference of the transaction (`Refs/AcctSvcrRef`).
+    pub reference: Option<String>,
+    /// End-to-end ID assigned by the initiating party.
+    pub end_to_end_id: Option<String>,
+    /// Name of the other party: the debtor of a credit, the creditor of
+    /// a debit.
+    pub counterparty_name: Option<String>,
+    /// IBAN of the other party.
+    pub counterparty_iban: Option<String>,
+    /// Remittance information (`RmtInf`), unstructured lines joined with
+    /// spaces, or the structured creditor reference.
+    pub remittance_info: Option<String>,
+    /// Free-text transaction information (`AddtlTxInf`).
+    pub additional_info: Option<String>,
+}
+
+impl CamtStatement {
+    /// Converts booked entries to importer lines, one per transaction
+    /// detail. The counterparty name becomes the description; the
+    /// remittance information and counterparty IBAN go into the memo.
+    /// A batched entry whose details do not all carry an amount becomes a
+    /// single line for the entry amount, with the counterparty names in
+    /// the memo.
+    pub fn lines(&self) -> Vec<StatementLine> {
+        let mut lines = Vec::new();
+        for entry in self.entries.iter().filter(|e| e.booked) {
+            let Some(date) = entry.booking_date.or(entry.value_date) else {
+                continue;
+            };
+            let batched = entry.details.len() > 1;
+            // Without an amount on every detail a batch cannot be split
+            // into lines that add up to the entry, so it stays one line.
+            if entry.details.is_empty()
+                || (batched && entry.details.iter().any(|d| d.amount.is_none()))
+            {
+                let names: Vec<&str> = entry
+                    .details
+                    .iter()
+                    .filter_map(|d| d.counterparty_name.as_deref())
+                    .collect();
+                lines.push(StatementLine {
+                    online_id: entry.reference.clone(),
+                    date,
+                    amount: entry.amount,
+                    description: entry.additional_info.clone().unwrap_or_default(),
+                    memo: (!names.is_empty()).then(|| names.join(", ")),
+                    num: None,
+                });
+                continue;
+            }
+            for (index, detail) in entry.details.iter().enumerate() {
+                let online_id = detail.reference.clone().or_else(|| match &entry.reference {
+                    Some(reference) if batched => Some(format!("{}/{}", reference, index + 1)),
+                    reference => reference.clone(),
+                });
+                let memo = match (&detail.remittance_info, &detail.counterparty_iban) {
+                    (Some(info), Some(iban)) => Some(format!("{} ({})", info, iban)),
+                    (Some(info), None) => Some(info.clone()),
+                    (None, iban) => iban.clone(),
+                };
+                lines.push(StatementLine {
+                    online_id,
+                    date,
+                    amount: detail.amount.unwrap_or(entry.amount),
+                    description: detail
+                        .counterparty_name
+                        .clone()
+                        .or_else(|| detail.additional_info.clone())
+                        .or_else(|| entry.additional_info.clone())
+                        .or_else(|| detail.remittance_info.clone())
+                        .unwrap_or_default(),
+                    memo,
+                    num: None,
+                });
+            }
+        }
+        lines
+    }
+
+    /// Records the closing balance as the account's pending reconciliation
+    /// (statement date and ending balance), which the GnuCash reconcile
+    /// dialog picks up. Returns false if the statement has no closing
+    /// balance.
+    pub fn record_reconcile_target(&self, account: &Account) -> bool {
+        let Some((balance, date)) = self.closing_balance else {
+            return false;
+        };
+        let account = account.edit();
+        account.set_reconcile_postpone_date(date.end_time64());
+        account.set_reconcile_postpone_balance(balance);
+        account.commit();
+        true
+    }
+}
+
+/// Parses every statement (camt.053) or notification (camt.054) in a
+/// document.
+pub fn parse(data: &str) -> Result<Vec<CamtStatement>> {
+    let doc = markup::parse(data)?;
+    let document = doc
+        .child("Document")
+        .ok_or_else(|| Error::Parse("missing <Document> root element".to_string()))?;
+    let reports: Vec<&Element> = ["Stmt", "Ntfctn", "Rpt"]
+        .iter()
+        .flat_map(|name| document.find_all(name))
+        .collect();
+    if reports.is_empty() {
+        return Err(Error::Parse(
+            "no <Stmt>, <Ntfctn> or <Rpt> element found".to_string(),
+        ));
+    }
+    reports.into_iter().map(parse_statement).collect()
+}
+
+fn parse_statement(stmt: &Element) -> Result<CamtStatement> {
+    let mut opening_balance = None;
+    let mut closing_balance = None;
+    for bal in stmt.children_named("Bal") {
+        let code = bal
+            .path_value(&["Tp", "CdOrPrtry", "Cd"])
+            .or_else(|| bal.path_value(&["Tp", "CdOrPrtry", "Prtry"]));
+        let slot = match code {
+            Some("OPBD") | Some("PRCD") if opening_balance.is_none() => &mut opening_balance,
+            Some("CLBD") => &mut closing_balance,
+            _ => continue,
+        };
+        let amount = signed_amount(bal)?.ok_or_else(|| missing(bal, "Amt"))?;
+        let date = date_of(bal.child("Dt")).ok_or_else(|| missing(bal, "Dt"))?;
+        *slot = Some((amount, date));
+    }
+
+    let acct = stmt.child("Acct");
+    Ok(CamtStatement {
+        id: stmt.path_value(&["Id"]).map(str::to_string),
+        account_id: acct
+            .and_then(|a| {
+                a.path_value(&["Id", "IBAN"])
+                    .or_else(|| a.path_value(&["Id", "Othr", "Id"]))
+            })
+            .map(str::to_string),
+        currency: acct
+            .and_then(|a| a.path_value(&["Ccy"]))
+            .map(str::to_string),
+        opening_balance,
+        closing_balance,
+        entries: stmt
+            .children_named("Ntry")
+            .map(parse_entry)
+            .collect::<Result<Vec<_>>>()?,
+    })
+}
+
+fn parse_entry(ntry: &Element) -> Result<CamtEntry> {
+    let amount = signed_amount(ntry)?.ok_or_else(|| missing(ntry, "Amt"))?;
+    let status = ntry
+        .path_value(&["Sts", "Cd"])
+        .or_else(|| ntry.path_value(&["Sts"]));
+    let details = ntry
+        .children_named("NtryDtls")
+        .flat_map(|d| d.children_named("TxDtls"))
+        .map(|tx| parse_details(tx, ntry))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(CamtEntry {
+        reference: ntry
+            .path_value(&["AcctSvcrRef"])
+            .or_else(|| ntry.path_value(&["NtryRef"]))
+            .map(str::to_string),
+        amount,
+        currency: ntry
+            .child("Amt")
+            .and_then(|a| a.attr("Ccy"))
+            .map(str::to_string),
+        booked: status == Some("BOOK"),
+        booking_date: date_of(ntry.child("BookgDt")),
+        value_date: date_of(ntry.child("ValDt")),
+        additional_info: ntry.path_value(&["AddtlNtryInf"]).map(str::to_string),
+        details,
+    })
+}
+
+fn parse_details(tx: &Element, ntry: &Element) -> Result<CamtTransaction> {
+    // Newer versions put Amt directly in TxDtls, older ones under
+    // AmtDtls/TxAmt. Either may leave the indicator to the entry.
+    let amt = tx
+        .child("Amt")
+        .or_else(|| tx.path(&["AmtDtls", "TxAmt", "Amt"]));
+    let indicator = tx
+        .path_value(&["CdtDbtInd"])
+        .or_else(|| ntry.path_value(&["CdtDbtInd"]));
+    let amount = parse_amount(amt)?.map(|v| apply_indicator(v, indicator));
+    let credit = match amount {
+        Some(amount) => !amount.is_negative(),
+        None => ntry.path_value(&["CdtDbtInd"]) != Some("DBIT"),
+    };
+    let (party, account) = if credit {
+        ("Dbtr", "DbtrAcct")
+    } else {
+        ("Cdtr", "CdtrAcct")
+    };
+    let parties = tx.child("RltdPties");
+    let counterparty_name = parties
+        .and_then(|p| {
+            p.path_value(&[party, "Nm"])
+                .or_else(|| p.path_value(&[party, "Pty", "Nm"]))
+        })
+        .map(str::to_string);
+    let counterparty_iban = parties
+        .and_then(|p| p.path_value(&[account, "Id", "IBAN"]))
+        .map(str::to_string);
+
+    let remittance_info = tx.child("RmtInf").and_then(|rmt| {
+        let unstructured: Vec<&str> = rmt
+            .children_named("Ustrd")
+            .filter_map(Element::value)
+            .collect();
+        if unstructured.is_empty() {
+            rmt.find_all("Ref")
+                .into_iter()
+                .find_map(Element::value)
+                .map(str::to_string)
+        } else {
+            Some(unstructured.join(" "))
+        }
+    });
+
+    Ok(CamtTransaction {
+        amount,
+        reference: tx.path_value(&["Refs", "AcctSvcrRef"]).map(str::to_string),
+        end_to_end_id: tx
+            .path_value(&["Refs", "EndToEndId"])
+            .filter(|id| *id != "NOTPROVIDED")
+            .map(str::to_string),
+        counterparty_name,
+        counterparty_iban,
+        remittance_info,
+        additional_info: tx.path_value(&["AddtlTxInf"]).map(str::to_string),
+    })
+}
+
+fn missing(el: &Element, name: &str) -> Error {
+    Error::Parse(format!("<{}> is missing <{}>", el.name, name))
+}
+
+/// Reads `Amt` and `CdtDbtInd` children into a signed amount.
+fn signed_amount(el: &Element) -> Result<Option<Numeric>> {
+    let value = parse_amount(el.child("Amt"))?;
+    Ok(value.map(|v| apply_indicator(v, el.path_value(&["CdtDbtInd"]))))
+}
+
+fn apply_indicator(value: Numeric, indicator: Option<&str>) -> Numeric {
+    if indicator == Some("DBIT") {
+        value.abs().neg()
+    } else {
+        value.abs()
+    }
+}
+
+fn parse_amount(amt: Option<&Element>) -> Result<Option<Numeric>> {
+    match amt.and_then(Element::value) {
+        Some(text) => Numeric::parse_decimal(text)
+            .map(Some)
+            .ok_or_else(|| Error::Parse(format!("invalid camt amount '{}'", text))),
+        None => Ok(None),
+    }
+}
+
+/// Reads a `Dt` or `DtTm` child of a date element.
+fn date_of(el: Option<&Element>) -> Option<Date> {
+    let el = el?;
+    el.path_value(&["Dt"])
+        .or_else(|| el.path_value(&["DtTm"]))
+        .and_then(Date::parse_iso)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAMT053: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08">
+ <BkToCstmrStmt>
+  <GrpHdr><MsgId>MSG1</MsgId></GrpHdr>
+  <Stmt>
+   <Id>STMT-2024-01</Id>
+   <Acct><Id><IBAN>DE89370400440532013000</IBAN></Id><Ccy>EUR</Ccy></Acct>
+   <Bal>
+    <Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp>
+    <Amt Ccy="EUR">1000.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+    <Dt><Dt>2024-01-01</Dt></Dt>
+   </Bal>
+   <Bal>
+    <Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp>
+    <Amt Ccy="EUR">1150.50</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+    <Dt><Dt>2024-01-31</Dt></Dt>
+   </Bal>
+   <Ntry>
+    <Amt Ccy="EUR">49.50</Amt><CdtDbtInd>DBIT</CdtDbtInd>
+    <Sts><Cd>BOOK</Cd></Sts>
+    <BookgDt><Dt>2024-01-10</Dt></BookgDt>
+    <ValDt><Dt>2024-01-10</Dt></ValDt>
+    <AcctSvcrRef>REF-1</AcctSvcrRef>
+    <NtryDtls><TxDtls>
+     <Refs><EndToEndId>NOTPROVIDED</EndToEndId></Refs>
+     <RltdPties>
+      <Cdtr><Pty><Nm>Power &amp; Light AG</Nm></Pty></Cdtr>
+      <CdtrAcct><Id><IBAN>DE02120300000000202051</IBAN></Id></CdtrAcct>
+     </RltdPties>
+     <RmtInf><Ustrd>Invoice 4711</Ustrd><Ustrd>January</Ustrd></RmtInf>
+    </TxDtls></NtryDtls>
+   </Ntry>
+   <Ntry>
+    <Amt Ccy="EUR">200.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+    <Sts><Cd>BOOK</Cd></Sts>
+    <BookgDt><Dt>2024-01-15</Dt></BookgDt>
+    <AcctSvcrRef>BATCH-7</AcctSvcrRef>
+    <NtryDtls>
+     <Btch><NbOfTxs>2</NbOfTxs></Btch>
+     <TxDtls>
+      <Amt Ccy="EUR">120.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+      <RltdPties><Dbtr><Nm>Alice</Nm></Dbtr></RltdPties>
+      <RmtInf><Strd><CdtrRefInf><Ref>RF18539007547034</Ref></CdtrRefInf></Strd></RmtInf>
+     </TxDtls>
+     <TxDtls>
+      <Amt Ccy="EUR">80.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+      <RltdPties><Dbtr><Nm>Bob</Nm></Dbtr></RltdPties>
+     </TxDtls>
+    </NtryDtls>
+   </Ntry>
+   <Ntry>
+    <Amt Ccy="EUR">5.00</Amt><CdtDbtInd>DBIT</CdtDbtInd>
+    <Sts><Cd>PDNG</Cd></Sts>
+    <BookgDt><Dt>2024-01-31</Dt></BookgDt>
+   </Ntry>
+  </Stmt>
+ </BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn test_parse_statement() {
+        let stmts = parse(CAMT053).unwrap();
+        assert_eq!(stmts.len(), 1);
+        let stmt = &stmts[0];
+        assert_eq!(stmt.account_id.as_deref(), Some("DE89370400440532013000"));
+        assert_eq!(stmt.currency.as_deref(), Some("EUR"));
+        assert_eq!(
+            stmt.closing_balance,
+            Some((Numeric::new(115050, 100), Date::new(2024, 1, 31).unwrap()))
+        );
+        assert_eq!(stmt.entries.len(), 3);
+        assert_eq!(stmt.entries[0].amount, Numeric::new(-4950, 100));
+        assert!(!stmt.entries[2].booked);
+
+        let tx = &stmt.entries[0].details[0];
+        assert_eq!(tx.counterparty_name.as_deref(), Some("Power & Light AG"));
+        assert_eq!(
+            tx.counterparty_iban.as_deref(),
+            Some("DE02120300000000202051")
+        );
+        assert_eq!(tx.remittance_info.as_deref(), Some("Invoice 4711 January"));
+        assert_eq!(tx.end_to_end_id, None);
+    }
+
+    #[test]
+    fn test_batched_entry_lines() {
+        let stmts = parse(CAMT053).unwrap();
+        let lines = stmts[0].lines();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].online_id.as_deref(), Some("REF-1"));
+        assert_eq!(
+            lines[0].memo.as_deref(),
+            Some("Invoice 4711 January (DE02120300000000202051)")
+        );
+        assert_eq!(lines[1].online_id.as_deref(), Some("BATCH-7/1"));
+        assert_eq!(lines[1].description, "Alice");
+        assert_eq!(lines[1].amount, Numeric::new(12000, 100));
+        assert_eq!(lines[1].memo.as_deref(), Some("RF18539007547034"));
+        assert_eq!(lines[2].online_id.as_deref(), Some("BATCH-7/2"));
+        assert_eq!(lines[2].amount, Numeric::new(8000, 100));
+    }
+
+    #[test]
+    fn test_batch_without_detail_amounts() {
+        let doc = r#"<Document><BkToCstmrStmt><Stmt>
+            <Ntry><Amt Ccy="EUR">300.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+              <Sts><Cd>BOOK</Cd></Sts><BookgDt><Dt>2024-03-01</Dt></BookgDt>
+              <AcctSvcrRef>BATCH-9</AcctSvcrRef>
+              <NtryDtls>
+               <TxDtls><RltdPties><Dbtr><Nm>Alice</Nm></Dbtr></RltdPties></TxDtls>
+               <TxDtls><RltdPties><Dbtr><Nm>Bob</Nm></Dbtr></RltdPties></TxDtls>
+              </NtryDtls></Ntry>
+            </Stmt></BkToCstmrStmt></Document>"#;
+        let lines = parse(doc).unwrap()[0].lines();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].amount, Numeric::new(30000, 100));
+        assert_eq!(lines[0].online_id.as_deref(), Some("BATCH-9"));
+        assert_eq!(lines[0].memo.as_deref(), Some("Alice, Bob"));
+    }
+
+    #[test]
+    fn test_legacy_status_and_amount_details() {
+        let doc = r#"<Document><BkToCstmrDbtCdtNtfctn><Ntfctn>
+            <Ntry><Amt Ccy="CHF">10.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts>
+              <BookgDt><DtTm>2024-02-01T08:00:00</DtTm></BookgDt>
+              <NtryDtls><TxDtls><AmtDtls><TxAmt><Amt Ccy="CHF">10.00</Amt></TxAmt></AmtDtls>
+              </TxDtls></NtryDtls></Ntry>
+            </Ntfctn></BkToCstmrDbtCdtNtfctn></Document>"#;
+        let stmts = parse(doc).unwrap();
+        let entry = &stmts[0].entries[0];
+        assert!(entry.booked);
+        assert_eq!(entry.booking_date, Date::new(2024, 2, 1));
+        assert_eq!(entry.details[0].amount, Some(Numeric::new(-1000, 100)));
+    }
+
+    #[test]
+    fn test_detail_amount_inherits_entry_indicator() {
+        let doc = r#"<Document><BkToCstmrStmt><Stmt>
+            <Ntry><Amt Ccy="EUR">25.00</Amt><CdtDbtInd>DBIT</CdtDbtInd>
+              <Sts><Cd>BOOK</Cd></Sts><BookgDt><Dt>2024-04-02</Dt></BookgDt>
+              <NtryDtls><TxDtls><Amt Ccy="EUR">25.00</Amt>
+               <RltdPties><Cdtr><Nm>Landlord</Nm></Cdtr></RltdPties>
+              </TxDtls></NtryDtls></Ntry>
+            </Stmt></BkToCstmrStmt></Document>"#;
+        let stmts = parse(doc).unwrap();
+        let tx = &stmts[0].entries[0].details[0];
+        assert_eq!(tx.amount, Some(Numeric::new(-2500, 100)));
+        assert_eq!(tx.counterparty_name.as_deref(), Some("Landlord"));
+    }
+}
@@ -0,0 +1,620 @@
+//! CSV transaction import driven by column-mapping profiles.
+//!
+//! Every bank lays out its CSV export differently, so a [`CsvProfile`]
+//! describes which column holds what: the date and its format, the
+//! description, a single signed amount or a debit/credit pair, memo,
+//! number and transfer account.
+//!
+//! Without an ID column each row is one transaction between the
+//! importer's account and the row's transfer account (or the default
+//! counter account). With an ID column, rows sharing an ID form one
+//! multi-split transaction: each row is a split in its account column
+//! (defaulting to the importer's account), plus a balancing split when
+//! the row names a transfer account. The date, description and number
+//! come from the first row of each group.
+//!
+//! Every transaction is checked with [`TransactionBuilder::check_balance`]
+//! before anything is written, and a dry run stops there, returning the
+//! proposed transactions for review.
+//!
+//! ```ignore
+//! use gnucash_ext::import::csv::{AmountColumns, CsvImporter, CsvProfile};
+//!
+//! let profile = CsvProfile::new(0, "%d.%m.%Y", AmountColumns::DebitCredit { debit: 3, credit: 4 })
+//!     .description(1)
+//!     .memo(2)
+//!     .delimiter(';')
+//!     .decimal_comma()
+//!     .skip_rows(1);
+//! let report = CsvImporter::new(&book, &root, &checking, profile)
+//!     .counter_account(&imbalance)
+//!     .dry_run(true)
+//!     .import(&std::fs::read_to_string("bank.csv")?)?;
+//! for txn in &report.proposed {
+//!     println!("{} {} {:?}", txn.date, txn.description, txn.splits);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use gnucash_sys::{Account, Book, Error, Guid, Numeric, Result};
+
+use crate::builder::TransactionBuilder;
+use crate::date::Date;
+
+/// Where the amount of a row comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountColumns {
+    /// One signed column; positive for money into the account.
+    Single(usize),
+    /// Separate columns for money out (`debit`) and money in (`credit`).
+    DebitCredit { debit: usize, credit: usize },
+}
+
+/// Column layout and formatting of a bank's CSV export. Columns are
+/// zero-based.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvProfile {
+    delimiter: char,
+    skip_rows: usize,
+    date: usize,
+    date_format: String,
+    amount: AmountColumns,
+    decimal_comma: bool,
+    negate: bool,
+    description: Option<usize>,
+    memo: Option<usize>,
+    num: Option<usize>,
+    account: Option<usize>,
+    transfer_account: Option<usize>,
+    id: Option<usize>,
+}
+
+impl CsvProfile {
+    /// Creates a profile with the required date and amount columns.
+    ///
+    /// `date_format` uses `%Y` (four-digit year), `%y` (two-digit year),
+    /// `%m` (month number), `%b` (English month abbreviation), `%d`
+    /// (day) and `%%`; any other character must match literally.
+    pub fn new(date: usize, date_format: &str, amount: AmountColumns) -> Self {
+        Self {
+            delimiter: ',',
+            skip_rows: 0,
+            date,
+            date_format: date_format.to_string(),
+            amount,
+            decimal_comma: false,
+            negate: false,
+            description: None,
+            memo: None,
+            num: None,
+            account: None,
+            transfer_account: None,
+            id: None,
+        }
+    }
+
+    /// Sets the field delimiter. Defaults to `,`.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Skips leading rows such as a header line.
+    pub fn skip_rows(mut self, rows: usize) -> Self {
+        self.skip_rows = rows;
+        self
+    }
+
+    /// Reads amounts with a decimal comma and `.` thousands separators.
+    pub fn decimal_comma(mut self) -> Self {
+        self.decimal_comma = true;
+        self
+    }
+
+    /// Negates every amount, for exports that list spending as positive.
+    pub fn negate_amounts(mut self) -> Self {
+        self.negate = true;
+        self
+    }
+
+    /// Sets the description column.
+    pub fn description(mut self, column: usize) -> Self {
+        self.description = Some(column);
+        self
+    }
+
+    /// Sets the memo column.
+    pub fn memo(mut self, column: usize) -> Self {
+        self.memo = Some(column);
+        self
+    }
+
+    /// Sets the check/reference number column.
+    pub fn num(mut self, column: usize) -> Self {
+        self.num = Some(column);
+        self
+    }
+
+    /// Sets the column holding the full name of the split's own account
+    /// in grouped (multi-split) files.
+    pub fn account(mut self, column: usize) -> Self {
+        self.account = Some(column);
+        self
+    }
+
+    /// Sets the column holding the full name of the transfer account.
+    pub fn transfer_account(mut self, column: usize) -> Self {
+        self.transfer_account = Some(column);
+        self
+    }
+
+    /// Sets the transaction ID column; rows sharing an ID are grouped
+    /// into one multi-split transaction.
+    pub fn id(mut self, column: usize) -> Self {
+        self.id = Some(column);
+        self
+    }
+}
+
+/// A transaction the importer would create.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedTransaction {
+    /// Line number of the first row of the transaction (1-based).
+    pub line: usize,
+    /// Posting date.
+    pub date: Date,
+    /// Description.
+    pub description: String,
+    /// Check or reference number.
+    pub num: Option<String>,
+    /// Splits, in row order.
+    pub splits: Vec<ProposedSplit>,
+}
+
+/// A split of a [`ProposedTransaction`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedSplit {
+    /// Full name of the account.
+    pub account: String,
+    /// Amount; positive for money into the account.
+    pub amount: Numeric,
+    /// Memo.
+    pub memo: Option<String>,
+}
+
+/// Outcome of a [`CsvImporter::import`] run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvImportReport {
+    /// Every transaction in the file, as validated.
+    pub proposed: Vec<ProposedTransaction>,
+    /// Transactions created; empty for a dry run.
+    pub created: Vec<Guid>,
+}
+
+/// Imports CSV rows into a target account according to a profile.
+pub struct CsvImporter<'a> {
+    book: &'a Book,
+    root: &'a Account,
+    account: &'a Account,
+    counter_account: Option<&'a Account>,
+    profile: CsvProfile,
+    dry_run: bool,
+}
+
+impl<'a> CsvImporter<'a> {
+    /// Creates an importer posting to `account`. Account names in the
+    /// file are resolved as full names below `root`.
+    pub fn new(
+        book: &'a Book,
+        root: &'a Account,
+        account: &'a Account,
+        profile: CsvProfile,
+    ) -> Self {
+        Self {
+            book,
+            root,
+            account,
+            counter_account: None,
+            profile,
+            dry_run: false,
+        }
+    }
+
+    /// Sets the account that balances rows without a transfer account.
+    pub fn counter_account(mut self, account: &'a Account) -> Self {
+        self.counter_account = Some(account);
+        self
+    }
+
+    /// When true, [`import`](Self::import) validates and returns the
+    /// proposed transactions without creating anything.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Parses `data` into proposed transactions without resolving
+    /// accounts or checking balances.
+    pub fn propose(&self, data: &str) -> Result<Vec<ProposedTransaction>> {
+        let profile = &self.profile;
+        let default_account = self.account.full_name().unwrap_or_default();
+        let counter_account = self.counter_account.and_then(|a| a.full_name());
+
+        let mut proposed: Vec<ProposedTransaction> = Vec::new();
+        let mut groups: HashMap<String, usize> = HashMap::new();
+        for (line, row) in read_records(data, profile.delimiter)?
+            .into_iter()
+            .skip(profile.skip_rows)
+        {
+            if row.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            let field = |column: Option<usize>| {
+                column
+                    .and_then(|c| row.get(c))
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+            };
+            let amount = self.row_amount(&row, line)?;
+            let memo = field(profile.memo);
+            let transfer = field(profile.transfer_account);
+
+            let group = field(profile.id).and_then(|id| groups.get(&id).copied());
+            let index = match group {
+                Some(index) => index,
+                None => {
+                    let text =
+                        field(Some(profile.date)).ok_or_else(|| row_error(line, "missing date"))?;
+                    let date = parse_date(&text, &profile.date_format)
+                        .ok_or_else(|| row_error(line, &format!("invalid date '{}'", text)))?;
+                    if let Some(id) = field(profile.id) {
+                        groups.insert(id, proposed.len());
+                    }
+                    proposed.push(ProposedTransaction {
+                        line,
+                        date,
+                        description: field(profile.description).unwrap_or_default(),
+                        num: field(profile.num),
+                        splits: Vec::new(),
+                    });
+                    proposed.len() - 1
+                }
+            };
+            let txn = &mut proposed[index];
+
+            if profile.id.is_some() {
+                let account = field(profile.account).unwrap_or_else(|| default_account.clone());
+                txn.splits.push(ProposedSplit {
+                    account,
+                    amount,
+                    memo: memo.clone(),
+                });
+                if let Some(transfer) = transfer {
+                    txn.splits.push(ProposedSplit {
+                        account: transfer,
+                        amount: amount.neg(),
+                        memo,
+                    });
+                }
+            } else {
+                let counter = transfer
+                    .or_else(|| counter_account.clone())
+                    .ok_or_else(|| {
+                        row_error(line, "no transfer account and no default counter account")
+                    })?;
+                txn.splits.push(ProposedSplit {
+                    account: default_account.clone(),
+                    amount,
+                    memo: memo.clone(),
+                });
+                txn.splits.push(ProposedSplit {
+                    account: counter,
+                    amount: amount.neg(),
+                    memo,
+                });
+            }
+        }
+        Ok(proposed)
+    }
+
+    /// Validates every transaction in the file and, unless this is a dry
+    /// run, creates them. Nothing is created if any transaction fails
+    /// validation.
+    pub fn import(&self, data: &str) -> Result<CsvImportReport> {
+        let proposed = self.propose(data)?;
+
+        // Resolve and check everything before creating anything.
+        let mut resolved = Vec::with_capacity(proposed.len());
+        for txn in &proposed {
+            let accounts = txn
+                .splits
+                .iter()
+                .map(|split| self.resolve(&split.account, txn.line))
+                .collect::<Result<Vec<_>>>()?;
+            self.builder(txn, &accounts)
+                .check_balance()
+                .map_err(|e| row_error(txn.line, &e.to_string()))?;
+            resolved.push(accounts);
+        }
+
+        let mut report = CsvImportReport::default();
+        if !self.dry_run {
+            for (txn, accounts) in proposed.iter().zip(&resolved) {
+                let mut created = self.builder(txn, accounts).build()?;
+                created.mark_unowned();
+                report.created.push(created.guid());
+            }
+        }
+        report.proposed = proposed;
+        Ok(report)
+    }
+
+    fn builder<'b>(
+        &'b self,
+        txn: &ProposedTransaction,
+        accounts: &'b [Account],
+    ) -> TransactionBuilder<'b> {
+        let mut builder = TransactionBuilder::new(self.book)
+            .description(&txn.description)
+            .date(
+                txn.date.day() as i32,
+                txn.date.month() as i32,
+                txn.date.year(),
+            );
+        if let Some(num) = &txn.num {
+            builder = builder.num(num);
+        }
+        for (split, account) in txn.splits.iter().zip(accounts) {
+            builder = builder.split(account, split.amount, split.memo.as_deref());
+        }
+        builder
+    }
+
+    fn resolve(&self, full_name: &str, line: usize) -> Result<Account> {
+        self.root
+            .lookup_by_full_name(full_name)
+            .ok_or_else(|| row_error(line, &format!("unknown account '{}'", full_name)))
+    }
+
+    fn row_amount(&self, row: &[String], line: usize) -> Result<Numeric> {
+        let profile = &self.profile;
+        let parse = |column: usize| -> Result<Option<Numeric>> {
+            let text = row.get(column).map(|s| s.trim()).unwrap_or("");
+            if text.is_empty() {
+                return Ok(None);
+            }
+            parse_amount(text, profile.decimal_comma)
+                .map(Some)
+                .ok_or_else(|| row_error(line, &format!("invalid amount '{}'", text)))
+        };
+        let amount = match profile.amount {
+            AmountColumns::Single(column) => {
+                parse(column)?.ok_or_else(|| row_error(line, "missing amount"))?
+            }
+            AmountColumns::DebitCredit { debit, credit } => match (parse(debit)?, parse(credit)?) {
+                (None, None) => return Err(row_error(line, "missing debit and credit")),
+                (debit, credit) => {
+                    credit.unwrap_or_else(Numeric::zero).abs()
+                        - debit.unwrap_or_else(Numeric::zero).abs()
+                }
+            },
+        };
+        Ok(if profile.negate { amount.neg() } else { amount })
+    }
+}
+
+fn row_error(line: usize, message: &str) -> Error {
+    Error::Parse(format!("CSV line {}: {}", line, message))
+}
+
+/// Splits CSV text into records of fields, honouring double-quoted
+/// fields with embedded delimiters, newlines and `""` escapes. Each
+/// record is paired with the 1-based line it starts on.
+fn read_records(data: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(row_error(start, "unterminated quoted field"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+/// Parses a date with a `strftime`-style format (see [`CsvProfile::new`]).
+/// Numeric fields accept one or two digits, so `%d.%m.%Y` also reads
+/// `1.2.2024`.
+fn parse_date(text: &str, format: &str) -> Option<Date> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let (mut year, mut month, mut day) = (None, None, None);
+    let mut rest = text.trim();
+    let mut spec = format.chars();
+
+    fn number(rest: &mut &str, max_digits: usize) -> Option<i32> {
+        let len = rest
+            .char_indices()
+            .take(max_digits)
+            .take_while(|(_, c)| c.is_ascii_digit())
+            .count();
+        if len == 0 {
+            return None;
+        }
+        let value = rest[..len].parse().ok()?;
+        *rest = &rest[len..];
+        Some(value)
+    }
+
+    while let Some(c) = spec.next() {
+        if c != '%' {
+            rest = rest.strip_prefix(c)?;
+            continue;
+        }
+        match spec.next()? {
+            'Y' => year = Some(number(&mut rest, 4)?),
+            'y' => year = Some(2000 + number(&mut rest, 2)?),
+            'm' => month = Some(number(&mut rest, 2)? as u32),
+            'd' => day = Some(number(&mut rest, 2)? as u32),
+            'b' => {
+                let abbrev = rest.get(..3)?.to_ascii_lowercase();
+                month = Some(MONTHS.iter().position(|m| *m == abbrev)? as u32 + 1);
+                rest = &rest[3..];
+            }
+            '%' => rest = rest.strip_prefix('%')?,
+            _ => return None,
+        }
+    }
+    if !rest.trim().is_empty() {
+        return None;
+    }
+    Date::new(year?, month?, day?)
+}
+
+/// Parses an amount, accepting thousands separators, currency symbols,
+/// a leading or trailing minus, accounting-style parentheses and a `CR`
+/// or `DR` marker, which makes the amount positive or negative.
+fn parse_amount(text: &str, decimal_comma: bool) -> Option<Numeric> {
+    let (decimal, thousands) = if decimal_comma {
+        (',', '.')
+    } else {
+        ('.', ',')
+    };
+    let (text, debit) = strip_credit_debit(text.trim());
+    let mut negative = text.starts_with('(') && text.ends_with(')');
+    let mut digits = String::new();
+    for c in text.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            '-' => negative = !negative,
+            c if c == decimal => digits.push('.'),
+            c if c == thousands || c.is_whitespace() => {}
+            '+' | '(' | ')' | '$' | '€' | '£' | '¥' => {}
+            _ => return None,
+        }
+    }
+    let value = Numeric::parse_decimal(&digits)?;
+    Some(match debit {
+        Some(true) => value.abs().neg(),
+        Some(false) => value.abs(),
+        None if negative => value.neg(),
+        None => value,
+    })
+}
+
+/// Splits a `CR` or `DR` marker (any case) off either end of `text`,
+/// returning the rest and whether the marker denotes a debit.
+fn strip_credit_debit(text: &str) -> (&str, Option<bool>) {
+    for (marker, debit) in [("DR", true), ("CR", false)] {
+        let n = text.len();
+        if text
+            .get(n.saturating_sub(2)..)
+            .is_some_and(|m| m.eq_ignore_ascii_case(marker))
+        {
+            return (&text[..n - 2], Some(debit));
+        }
+        if text
+            .get(..2)
+            .is_some_and(|m| m.eq_ignore_ascii_case(marker))
+        {
+            return (&text[2..], Some(debit));
+        }
+    }
+    (text, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_records_quotes() {
+        let data = "Date,Payee,Amount\r\n2024-01-02,\"Smith, J\",\"1,000.00\"\n2024-01-03,\"Say \"\"hi\"\"\nthere\",-5\n";
+        let records = read_records(data, ',').unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].1, vec!["2024-01-02", "Smith, J", "1,000.00"]);
+        assert_eq!(records[2].0, 3);
+        assert_eq!(records[2].1[1], "Say \"hi\"\nthere");
+        assert!(read_records("a,\"b", ',').is_err());
+    }
+
+    #[test]
+    fn test_parse_date_formats() {
+        assert_eq!(parse_date("2024-01-31", "%Y-%m-%d"), Date::new(2024, 1, 31));
+        assert_eq!(parse_date("1.2.2024", "%d.%m.%Y"), Date::new(2024, 2, 1));
+        assert_eq!(parse_date("03/15/24", "%m/%d/%y"), Date::new(2024, 3, 15));
+        assert_eq!(parse_date("05-Mar-2024", "%d-%b-%Y"), Date::new(2024, 3, 5));
+        assert_eq!(parse_date("2024-01-31 extra", "%Y-%m-%d"), None);
+        assert_eq!(parse_date("2024-02-30", "%Y-%m-%d"), None);
+    }
+
+    #[test]
+    fn test_parse_amounts() {
+        assert_eq!(
+            parse_amount("1,234.50", false),
+            Some(Numeric::new(123450, 100))
+        );
+        assert_eq!(
+            parse_amount("-1.234,50 €", true),
+            Some(Numeric::new(-123450, 100))
+        );
+        assert_eq!(
+            parse_amount("(12.00)", false),
+            Some(Numeric::new(-1200, 100))
+        );
+        assert_eq!(parse_amount("$7.25-", false), Some(Numeric::new(-725, 100)));
+        assert_eq!(parse_amount("12#3", false), None);
+        assert_eq!(
+            parse_amount("12.50 DR", false),
+            Some(Numeric::new(-1250, 100))
+        );
+        assert_eq!(
+            parse_amount("cr 12.50", false),
+            Some(Numeric::new(1250, 100))
+        );
+        assert_eq!(parse_amount("1e5", false), None);
+        assert_eq!(parse_amount("12.50 USD", false), None);
+    }
+}
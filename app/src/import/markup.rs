@@ -0,0 +1,276 @@
+//! Minimal tag-tree reader shared by the OFX and ISO 20022 importers.
+//!
+//! Handles well-formed XML as well as OFX 1.x SGML, where leaf elements
+//! carry a value and are never closed (`<TRNAMT>-12.00<FITID>...`). An
+//! element that already holds text is closed implicitly when the next
+//! tag arrives. An empty one (`<MEMO><FITID>...`) cannot be told from an
+//! aggregate until its parent's closing tag shows it was never closed;
+//! the elements read after it are then moved up to be its siblings.
+//! Namespace prefixes are dropped from element names.
+
+use gnucash_sys::{Error, Result};
+
+/// A parsed element.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub text: String,
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the first direct child with the given name.
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// Returns all direct children with the given name.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Returns the value of an attribute.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns all descendants with the given name, in document order.
+    pub fn find_all<'a>(&'a self, name: &str) -> Vec<&'a Element> {
+        let mut out = Vec::new();
+        self.collect(name, &mut out);
+        out
+    }
+
+    fn collect<'a>(&'a self, name: &str, out: &mut Vec<&'a Element>) {
+        for child in &self.children {
+            if child.name == name {
+                out.push(child);
+            }
+            child.collect(name, out);
+        }
+    }
+
+    /// Follows a path of direct children.
+    pub fn path(&self, path: &[&str]) -> Option<&Element> {
+        path.iter().try_fold(self, |el, name| el.child(name))
+    }
+
+    /// Returns the trimmed text of the element, or `None` if empty.
+    pub fn value(&self) -> Option<&str> {
+        let text = self.text.trim();
+        if text.is_empty() { None } else { Some(text) }
+    }
+
+    /// Returns the trimmed text at a path of direct children.
+    pub fn path_value(&self, path: &[&str]) -> Option<&str> {
+        self.path(path).and_then(Element::value)
+    }
+}
+
+/// Parses a document into a synthetic `#document` element whose children
+/// are the top-level elements. Text outside any element (such as the
+/// OFX 1.x header block) is discarded.
+pub(crate) fn parse(input: &str) -> Result<Element> {
+    let mut stack = vec![Element::new("#document")];
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        if let Some(body) = rest.strip_prefix("<!--") {
+            rest = skip_past(body, "-->")?;
+        } else if let Some(body) = rest.strip_prefix("<![CDATA[") {
+            let end = body
+                .find("]]>")
+                .ok_or_else(|| Error::Parse("unterminated CDATA section".to_string()))?;
+            append_text(&mut stack, &body[..end]);
+            rest = &body[end + 3..];
+        } else if let Some(body) = rest.strip_prefix("<?") {
+            rest = skip_past(body, "?>")?;
+        } else if let Some(body) = rest.strip_prefix("<!") {
+            rest = skip_past(body, ">")?;
+        } else if let Some(body) = rest.strip_prefix("</") {
+            let end = body
+                .find('>')
+                .ok_or_else(|| Error::Parse("unterminated closing tag".to_string()))?;
+            close(&mut stack, local_name(body[..end].trim()));
+            rest = &body[end + 1..];
+        } else if let Some(body) = rest.strip_prefix('<') {
+            let end = tag_end(body)?;
+            let mut tag = body[..end].trim();
+            let self_closing = tag.ends_with('/');
+            if self_closing {
+                tag = tag[..tag.len() - 1].trim_end();
+            }
+            let (name, attrs) = parse_tag(tag)?;
+            close_open_leaf(&mut stack);
+            let mut element = Element::new(local_name(name));
+            element.attrs = attrs;
+            if self_closing {
+                attach(&mut stack, element);
+            } else {
+                stack.push(element);
+            }
+            rest = &body[end + 1..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = decode_entities(&rest[..end]);
+            append_text(&mut stack, &text);
+            rest = &rest[end..];
+        }
+    }
+
+    while stack.len() > 1 {
+        let element = stack.pop().unwrap();
+        attach(&mut stack, element);
+    }
+    Ok(stack.pop().unwrap())
+}
+
+fn skip_past<'a>(body: &'a str, terminator: &str) -> Result<&'a str> {
+    body.find(terminator)
+        .map(|i| &body[i + terminator.len()..])
+        .ok_or_else(|| Error::Parse(format!("missing '{}'", terminator)))
+}
+
+/// Finds the `>` ending a start tag, skipping over quoted attribute values.
+fn tag_end(body: &str) -> Result<usize> {
+    let mut quote = None;
+    for (i, c) in body.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Ok(i),
+            _ => {}
+        }
+    }
+    Err(Error::Parse("unterminated start tag".to_string()))
+}
+
+fn parse_tag(tag: &str) -> Result<(&str, Vec<(String, String)>)> {
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    if name.is_empty() {
+        return Err(Error::Parse("empty tag name".to_string()));
+    }
+    let mut attrs = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest
+            .find('=')
+            .ok_or_else(|| Error::Parse(format!("malformed attribute in <{}>", name)))?;
+        let key = local_name(rest[..eq].trim()).to_string();
+        let after = rest[eq + 1..].trim_start();
+        let quote = after
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| Error::Parse(format!("unquoted attribute in <{}>", name)))?;
+        let close = after[1..]
+            .find(quote)
+            .ok_or_else(|| Error::Parse(format!("unterminated attribute in <{}>", name)))?;
+        attrs.push((key, decode_entities(&after[1..1 + close])));
+        rest = after[close + 2..].trim_start();
+    }
+    Ok((name, attrs))
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn append_text(stack: &mut [Element], text: &str) {
+    // Text at document level (e.g. OFX headers) is ignored.
+    if stack.len() > 1 {
+        stack.last_mut().unwrap().text.push_str(text);
+    }
+}
+
+fn attach(stack: &mut [Element], element: Element) {
+    stack.last_mut().unwrap().children.push(element);
+}
+
+/// SGML leaf handling: an element holding text and no children is closed
+/// when the next tag opens.
+fn close_open_leaf(stack: &mut Vec<Element>) {
+    if stack.len() > 1 {
+        let top = stack.last().unwrap();
+        if top.children.is_empty() && !top.text.trim().is_empty() {
+            let element = stack.pop().unwrap();
+            attach(stack, element);
+        }
+    }
+}
+
+/// Closes the nearest open element with the given name, along with any
+/// unclosed elements above it, which are taken for empty leaves. Stray
+/// closing tags are ignored.
+fn close(stack: &mut Vec<Element>, name: &str) {
+    let Some(pos) = stack.iter().rposition(|e| e.name == name) else {
+        return;
+    };
+    if pos == 0 {
+        return;
+    }
+    while stack.len() > pos + 1 {
+        // Never closed, so an empty SGML leaf: what followed it belongs
+        // to its parent.
+        let mut element = stack.pop().unwrap();
+        let siblings = std::mem::take(&mut element.children);
+        let parent = stack.last_mut().unwrap();
+        parent.children.push(element);
+        parent.children.extend(siblings);
+    }
+    let element = stack.pop().unwrap();
+    attach(stack, element);
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
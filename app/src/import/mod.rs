@@ -0,0 +1,280 @@
+//! Statement import.
+//!
+//! Format parsers turn a bank file into [`StatementLine`]s; an
+//! [`Importer`] then posts those lines to a target account:
+//!
+//! - [`ofx`] - OFX 1.x (SGML) and 2.x (XML) bank and credit card statements
+//! - [`camt`] - ISO 20022 camt.053 statements and camt.054 notifications
+//!
+//! [`csv`] maps arbitrary bank CSV layouts through a column profile and
+//! has its own [`CsvImporter`](csv::CsvImporter) with a dry-run mode.
+//!
+//! [`bayes`] reads and trains the Bayes map GnuCash's own import matcher
+//! keeps on each account; [`Importer::auto_categorize`] uses it to pick
+//! counter accounts.
+//!
+//! QIF files describe whole registers, including categories, split
+//! transactions and investment actions, so [`qif`] comes with its own
+//! [`QifImporter`](qif::QifImporter) that creates accounts as needed.
+//!
+//! Each line carrying an online ID (the OFX `FITID`) is stored on the
+//! target split under the `online_id` slot, the same place the GnuCash
+//! GUI importer uses, so re-importing an overlapping statement skips
+//! lines that are already in the book. Lines that are not yet imported
+//! are first matched against existing splits by amount and date window;
+//! only unmatched lines create new transactions.
+//!
+//! ```ignore
+//! use gnucash_ext::import::{ofx, Importer};
+//!
+//! let statements = ofx::parse(&std::fs::read_to_string("bank.ofx")?)?;
+//! let report = Importer::new(&book, &checking, &imbalance)
+//!     .match_window_days(3)
+//!     .import(&statements[0].lines())?;
+//! println!("{} created, {} matched", report.created.len(), report.matched.len());
+//! ```
+
+use std::collections::HashSet;
+
+use gnucash_sys::{Account, Book, Editable, Error, GNCAccountType, Guid, Numeric, Result, Split};
+
+use crate::builder::TransactionBuilder;
+use crate::business::Commodity;
+use crate::date::Date;
+use crate::engine_ext::AccountEditorExt;
+use bayes::BayesMatcher;
+
+mod markup;
+
+pub mod bayes;
+pub mod camt;
+pub mod csv;
+pub mod ofx;
+pub mod qif;
+
+/// A single booked entry from a bank statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementLine {
+    /// Bank-assigned unique ID used for duplicate detection.
+    pub online_id: Option<String>,
+    /// Posting date.
+    pub date: Date,
+    /// Amount in the target account's commodity; positive for money in.
+    pub amount: Numeric,
+    /// Transaction description (payee).
+    pub description: String,
+    /// Memo stored on both splits.
+    pub memo: Option<String>,
+    /// Check or reference number.
+    pub num: Option<String>,
+}
+
+/// Outcome of an [`Importer::import`] run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    /// Transactions created for lines with no existing counterpart.
+    pub created: Vec<Guid>,
+    /// Existing transactions a line was matched to.
+    pub matched: Vec<Guid>,
+    /// Online IDs skipped because they were already imported.
+    pub duplicates: Vec<String>,
+}
+
+/// Posts statement lines to a target account, balancing each against a
+/// counter account.
+pub struct Importer<'a> {
+    book: &'a Book,
+    account: &'a Account,
+    counter_account: &'a Account,
+    match_window_days: i64,
+    min_confidence: Option<f64>,
+}
+
+impl<'a> Importer<'a> {
+    /// Creates an importer posting to `account` and balancing against
+    /// `counter_account` (typically an "Imbalance" or suspense account).
+    pub fn new(book: &'a Book, account: &'a Account, counter_account: &'a Account) -> Self {
+        Self {
+            book,
+            account,
+            counter_account,
+            match_window_days: 3,
+            min_confidence: None,
+        }
+    }
+
+    /// Sets how many days a statement date may differ from an existing
+    /// transaction's posted date and still match it. Defaults to 3.
+    pub fn match_window_days(mut self, days: i64) -> Self {
+        self.match_window_days = days;
+        self
+    }
+
+    /// Balances new transactions against the account suggested by the
+    /// target account's Bayes map when its confidence is at least
+    /// `min_confidence` (GnuCash uses [`bayes::GNUCASH_THRESHOLD`]),
+    /// falling back to the counter account.
+    pub fn auto_categorize(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    /// Imports the lines, skipping already-imported online IDs, matching
+    /// existing splits, and creating transactions for the rest.
+    pub fn import(&self, lines: &[StatementLine]) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut existing: Vec<Candidate> = self
+            .account
+            .splits()
+            .filter_map(|split| {
+                let date = Date::from_time64(split.transaction()?.date_posted());
+                Some(Candidate {
+                    online_id: split.online_id(),
+                    amount: split.amount(),
+                    date,
+                    split,
+                    used: false,
+                })
+            })
+            .collect();
+        let mut seen: HashSet<String> = existing
+            .iter()
+            .filter_map(|c| c.online_id.clone())
+            .collect();
+        let matcher = self.min_confidence.map(|_| BayesMatcher::new(self.account));
+
+        for line in lines {
+            if let Some(id) = &line.online_id
+                && seen.contains(id)
+            {
+                report.duplicates.push(id.clone());
+                continue;
+            }
+
+            if let Some(candidate) = self.best_match(&mut existing, line) {
+                candidate.used = true;
+                let txn = candidate.split.transaction();
+                if let (Some(id), Some(txn)) = (&line.online_id, &txn) {
+                    txn.edit().split(&candidate.split).set_online_id(id);
+                }
+                if let Some(txn) = txn {
+                    report.matched.push(txn.guid());
+                }
+            } else {
+                report.created.push(self.create(line, matcher.as_ref())?);
+            }
+
+            if let Some(id) = &line.online_id {
+                seen.insert(id.clone());
+            }
+        }
+        Ok(report)
+    }
+
+    /// Finds the unused split with the same amount and the closest date
+    /// inside the window. A split already tagged with a different online
+    /// ID belongs to another bank entry and is never matched.
+    fn best_match<'c>(
+        &self,
+        existing: &'c mut [Candidate],
+        line: &StatementLine,
+    ) -> Option<&'c mut Candidate> {
+        existing
+            .iter_mut()
+            .filter(|c| !c.used && c.amount.eq_value(&line.amount))
+            .filter(|c| match (&c.online_id, &line.online_id) {
+                (Some(have), Some(want)) => have == want,
+                _ => true,
+            })
+            .map(|c| (c.date.days_between(&line.date).abs(), c))
+            .filter(|(diff, _)| *diff <= self.match_window_days)
+            .min_by_key(|(diff, _)| *diff)
+            .map(|(_, c)| c)
+    }
+
+    fn create(&self, line: &StatementLine, matcher: Option<&BayesMatcher>) -> Result<Guid> {
+        let memo = line.memo.as_deref();
+        let suggested = matcher
+            .and_then(|m| m.suggest(&line.description, memo))
+            .filter(|s| Some(s.confidence) >= self.min_confidence)
+            .map(|s| s.account);
+        let counter = suggested.as_ref().unwrap_or(self.counter_account);
+        let mut builder = TransactionBuilder::new(self.book)
+            .description(&line.description)
+            .date(
+                line.date.day() as i32,
+                line.date.month() as i32,
+                line.date.year(),
+            )
+            .split(self.account, line.amount, memo)
+            .split(counter, line.amount.neg(), memo);
+        if let Some(num) = &line.num {
+            builder = builder.num(num);
+        }
+        let mut txn = builder.build()?;
+
+        if let Some(id) = &line.online_id {
+            let edit = txn.edit();
+            if let Some(split) = txn
+                .splits()
+                .find(|s| s.account().as_ref() == Some(self.account))
+            {
+                edit.split(&split).set_online_id(id);
+            }
+            edit.commit();
+        }
+        txn.mark_unowned();
+        Ok(txn.guid())
+    }
+}
+
+struct Candidate {
+    split: Split,
+    online_id: Option<String>,
+    amount: Numeric,
+    date: Date,
+    used: bool,
+}
+
+/// Looks up a colon-separated account path below `root`, creating any
+/// missing accounts with the given type and commodity. Full names of
+/// newly created accounts are appended to `created`.
+pub(crate) fn find_or_create_account(
+    book: &Book,
+    root: &Account,
+    path: &str,
+    account_type: GNCAccountType,
+    commodity: &Commodity,
+    created: &mut Vec<String>,
+) -> Result<Account> {
+    let mut current: Option<Account> = None;
+    for name in path.split(':') {
+        if name.is_empty() {
+            return Err(Error::InvalidOperation(format!(
+                "Invalid account path '{}'",
+                path
+            )));
+        }
+        let parent = current.as_ref().unwrap_or(root);
+        let existing = parent
+            .children()
+            .find(|c| c.name().as_deref() == Some(name));
+        let account = match existing {
+            Some(account) => account,
+            None => {
+                let mut account = Account::new(book);
+                let edit = account.edit();
+                edit.set_name(name);
+                edit.set_type(account_type);
+                edit.set_commodity(commodity);
+                edit.commit();
+                parent.edit().append_child(&account);
+                account.mark_unowned();
+                created.push(account.full_name().unwrap_or_else(|| name.to_string()));
+                account
+            }
+        };
+        current = Some(account);
+    }
+    current.ok_or_else(|| Error::InvalidOperation("Empty account path".to_string()))
+}
@@ -0,0 +1,256 @@
+//! OFX bank and credit card statement parser.
+//!
+//! Reads both OFX 1.x (SGML, with the `OFXHEADER:100` text header) and
+//! OFX 2.x (XML) documents. Every `STMTRS` / `CCSTMTRS` aggregate becomes
+//! one [`OfxStatement`]; investment statements are not supported.
+
+use gnucash_sys::{Error, Numeric, Result};
+
+use super::StatementLine;
+use super::markup::{self, Element};
+use crate::date::Date;
+
+/// One account statement from an OFX file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfxStatement {
+    /// Default currency (`CURDEF`), e.g. "USD".
+    pub currency: Option<String>,
+    /// Routing number (`BANKID`); absent on credit card statements.
+    pub bank_id: Option<String>,
+    /// Account number (`ACCTID`).
+    pub account_id: Option<String>,
+    /// Booked transactions in file order.
+    pub transactions: Vec<OfxTransaction>,
+    /// Ledger balance (`LEDGERBAL`) and the date it applies to.
+    pub ledger_balance: Option<(Numeric, Date)>,
+}
+
+/// A single `STMTTRN` record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfxTransaction {
+    /// Financial institution transaction ID (`FITID`).
+    pub fitid: String,
+    /// Transaction type (`TRNTYPE`), e.g. "DEBIT", "CHECK".
+    pub trn_type: Option<String>,
+    /// Posting date (`DTPOSTED`).
+    pub date_posted: Date,
+    /// Signed amount (`TRNAMT`); positive for money in.
+    pub amount: Numeric,
+    /// Payee (`NAME`, or `PAYEE/NAME`).
+    pub name: Option<String>,
+    /// Memo (`MEMO`).
+    pub memo: Option<String>,
+    /// Check number (`CHECKNUM`).
+    pub check_num: Option<String>,
+}
+
+impl OfxStatement {
+    /// Converts the transactions to importer lines. The payee becomes the
+    /// description, falling back to the memo when the bank sends no name.
+    pub fn lines(&self) -> Vec<StatementLine> {
+        self.transactions
+            .iter()
+            .map(|t| StatementLine {
+                online_id: Some(t.fitid.clone()),
+                date: t.date_posted,
+                amount: t.amount,
+                description: t
+                    .name
+                    .clone()
+                    .or_else(|| t.memo.clone())
+                    .unwrap_or_default(),
+                memo: t.memo.clone(),
+                num: t.check_num.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Parses every bank and credit card statement in an OFX document.
+pub fn parse(data: &str) -> Result<Vec<OfxStatement>> {
+    let doc = markup::parse(data)?;
+    let ofx = doc
+        .child("OFX")
+        .ok_or_else(|| Error::Parse("missing <OFX> root element".to_string()))?;
+
+    let mut statements = Vec::new();
+    for name in ["STMTRS", "CCSTMTRS"] {
+        for stmt in ofx.find_all(name) {
+            statements.push(parse_statement(stmt)?);
+        }
+    }
+    Ok(statements)
+}
+
+fn parse_statement(stmt: &Element) -> Result<OfxStatement> {
+    let account = stmt
+        .child("BANKACCTFROM")
+        .or_else(|| stmt.child("CCACCTFROM"));
+    let transactions = stmt
+        .find_all("STMTTRN")
+        .into_iter()
+        .map(parse_transaction)
+        .collect::<Result<Vec<_>>>()?;
+    let ledger_balance = match stmt.child("LEDGERBAL") {
+        Some(bal) => Some((
+            parse_amount(required(bal, "BALAMT")?)?,
+            parse_date(required(bal, "DTASOF")?)?,
+        )),
+        None => None,
+    };
+
+    Ok(OfxStatement {
+        currency: stmt.path_value(&["CURDEF"]).map(str::to_string),
+        bank_id: account
+            .and_then(|a| a.path_value(&["BANKID"]))
+            .map(str::to_string),
+        account_id: account
+            .and_then(|a| a.path_value(&["ACCTID"]))
+            .map(str::to_string),
+        transactions,
+        ledger_balance,
+    })
+}
+
+fn parse_transaction(trn: &Element) -> Result<OfxTransaction> {
+    let text = |name: &str| trn.path_value(&[name]).map(str::to_string);
+    Ok(OfxTransaction {
+        fitid: required(trn, "FITID")?.to_string(),
+        trn_type: text("TRNTYPE"),
+        date_posted: parse_date(required(trn, "DTPOSTED")?)?,
+        amount: parse_amount(required(trn, "TRNAMT")?)?,
+        name: text("NAME").or_else(|| trn.path_value(&["PAYEE", "NAME"]).map(str::to_string)),
+        memo: text("MEMO"),
+        check_num: text("CHECKNUM"),
+    })
+}
+
+fn required<'a>(el: &'a Element, name: &str) -> Result<&'a str> {
+    el.path_value(&[name])
+        .ok_or_else(|| Error::Parse(format!("<{}> is missing <{}>", el.name, name)))
+}
+
+/// Parses an OFX datetime (`YYYYMMDD[HHMMSS[.XXX]][[offset:TZ]]`). Only
+/// the calendar day is kept.
+fn parse_date(s: &str) -> Result<Date> {
+    let err = || Error::Parse(format!("invalid OFX date '{}'", s));
+    let digits = s.get(0..8).ok_or_else(err)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(err());
+    }
+    let year = digits[0..4].parse().map_err(|_| err())?;
+    let month = digits[4..6].parse().map_err(|_| err())?;
+    let day = digits[6..8].parse().map_err(|_| err())?;
+    Date::new(year, month, day).ok_or_else(err)
+}
+
+/// Parses an OFX amount. Some banks use a decimal comma.
+fn parse_amount(s: &str) -> Result<Numeric> {
+    let normalized = if s.contains('.') {
+        s.to_string()
+    } else {
+        s.replace(',', ".")
+    };
+    Numeric::parse_decimal(&normalized)
+        .ok_or_else(|| Error::Parse(format!("invalid OFX amount '{}'", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SGML: &str = "OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<SIGNONMSGSRSV1><SONRS><STATUS><CODE>0<SEVERITY>INFO</STATUS></SONRS></SIGNONMSGSRSV1>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<CURDEF>USD
+<BANKACCTFROM><BANKID>121000248<ACCTID>0012345<ACCTTYPE>CHECKING</BANKACCTFROM>
+<BANKTRANLIST><DTSTART>20240101<DTEND>20240131
+<STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20240115120000.000[-5:EST]<TRNAMT>-42.10<FITID>A1<NAME>GROCERY &amp; CO<MEMO>card 1234</STMTTRN>
+<STMTTRN><TRNTYPE>CHECK<DTPOSTED>20240120<TRNAMT>-100<FITID>A2<CHECKNUM>1001<MEMO>rent</STMTTRN>
+</BANKTRANLIST>
+<LEDGERBAL><BALAMT>857.90<DTASOF>20240131</LEDGERBAL>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?OFX OFXHEADER="200" VERSION="220"?>
+<OFX>
+  <CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS>
+    <CURDEF>EUR</CURDEF>
+    <CCACCTFROM><ACCTID>4111</ACCTID></CCACCTFROM>
+    <BANKTRANLIST>
+      <STMTTRN>
+        <TRNTYPE>CREDIT</TRNTYPE>
+        <DTPOSTED>20240203</DTPOSTED>
+        <TRNAMT>15,50</TRNAMT>
+        <FITID>X9</FITID>
+        <PAYEE><NAME>Refund</NAME></PAYEE>
+      </STMTTRN>
+    </BANKTRANLIST>
+  </CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1>
+</OFX>"#;
+
+    #[test]
+    fn test_parse_sgml() {
+        let stmts = parse(SGML).unwrap();
+        assert_eq!(stmts.len(), 1);
+        let stmt = &stmts[0];
+        assert_eq!(stmt.currency.as_deref(), Some("USD"));
+        assert_eq!(stmt.account_id.as_deref(), Some("0012345"));
+        assert_eq!(stmt.transactions.len(), 2);
+        let t = &stmt.transactions[0];
+        assert_eq!(t.fitid, "A1");
+        assert_eq!(t.amount, Numeric::new(-4210, 100));
+        assert_eq!(t.date_posted, Date::new(2024, 1, 15).unwrap());
+        assert_eq!(t.name.as_deref(), Some("GROCERY & CO"));
+        assert_eq!(stmt.transactions[1].check_num.as_deref(), Some("1001"));
+        assert_eq!(
+            stmt.ledger_balance,
+            Some((Numeric::new(85790, 100), Date::new(2024, 1, 31).unwrap()))
+        );
+        let lines = stmt.lines();
+        assert_eq!(lines[1].description, "rent");
+        assert_eq!(lines[1].online_id.as_deref(), Some("A2"));
+    }
+
+    #[test]
+    fn test_parse_sgml_empty_leaf() {
+        let doc = "<OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><CURDEF>USD
+<BANKACCTFROM><BANKID>121000248<ACCTID>0012345<ACCTTYPE>CHECKING</BANKACCTFROM>
+<BANKTRANLIST>
+<STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20240115<TRNAMT>-5.00<MEMO>
+<FITID>B1<NAME>KIOSK</STMTTRN>
+<STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20240116<TRNAMT>-6.00<FITID>B2<MEMO></STMTTRN>
+</BANKTRANLIST>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>";
+        let stmts = parse(doc).unwrap();
+        let transactions = &stmts[0].transactions;
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].fitid, "B1");
+        assert_eq!(transactions[0].name.as_deref(), Some("KIOSK"));
+        assert_eq!(transactions[0].memo, None);
+        assert_eq!(transactions[1].fitid, "B2");
+        assert_eq!(transactions[1].amount, Numeric::new(-600, 100));
+    }
+
+    #[test]
+    fn test_parse_xml_credit_card() {
+        let stmts = parse(XML).unwrap();
+        assert_eq!(stmts.len(), 1);
+        let t = &stmts[0].transactions[0];
+        assert_eq!(stmts[0].bank_id, None);
+        assert_eq!(t.amount, Numeric::new(1550, 100));
+        assert_eq!(t.name.as_deref(), Some("Refund"));
+    }
+
+    #[test]
+    fn test_missing_fitid_is_an_error() {
+        let doc = "<OFX><STMTRS><STMTTRN><DTPOSTED>20240101<TRNAMT>1</STMTTRN></STMTRS></OFX>";
+        assert!(matches!(parse(doc), Err(Error::Parse(_))));
+    }
+}
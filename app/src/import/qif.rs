@@ -0,0 +1,1316 @@
+//! QIF (Quicken Interchange Format) import.
+//!
+//! [`parse`] reads a QIF file into registers ([`QifAccount`]), the
+//! category list and the security list. [`QifImporter`] then posts the
+//! registers to a book:
+//!
+//! - `!Account` records name the register that follows; registers
+//!   without one are posted to the importer's default account.
+//! - Bank, cash, credit card and other asset/liability registers become
+//!   two-split transactions, or multi-split ones for `S`/`E`/`$` lines.
+//! - Categories become accounts under top-level "Income" and "Expenses"
+//!   accounts; `[Name]` targets are transfers to another account. A
+//!   transfer appears once in each of the two registers, so the second
+//!   copy is skipped.
+//! - Investment registers (`!Type:Invst`) support `Buy`, `Sell`, `Div`
+//!   and `ReinvDiv` (and their `X` variants, which move cash through
+//!   the `L` account). Each security gets a stock account below the
+//!   investment account, priced with `Split::set_share_price_and_amount`.
+//!
+//! ```ignore
+//! use gnucash_ext::import::qif::{self, QifDateOrder, QifImporter};
+//!
+//! let file = qif::parse(&std::fs::read_to_string("export.qif")?, QifDateOrder::MonthDayYear)?;
+//! let report = QifImporter::new(&book, &root, &usd)
+//!     .default_account(&checking)
+//!     .import(&file)?;
+//! println!("{} transactions, new accounts: {:?}", report.created.len(), report.accounts_created);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use gnucash_sys::{
+    Account, Book, Editable, Error, GNCAccountType, Guid, Numeric, ReconcileState, Result,
+};
+
+use super::find_or_create_account;
+use crate::builder::TransactionBuilder;
+use crate::business::Commodity;
+use crate::date::Date;
+use crate::engine_ext::BookExt;
+
+/// Register type from a `!Type:` header or an `!Account` `T` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QifAccountType {
+    Bank,
+    Cash,
+    CCard,
+    Invst,
+    OthA,
+    OthL,
+}
+
+impl QifAccountType {
+    /// Parses a QIF type name such as "Bank" or "Oth A", ignoring case.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bank" => Some(Self::Bank),
+            "cash" => Some(Self::Cash),
+            "ccard" => Some(Self::CCard),
+            "invst" | "port" => Some(Self::Invst),
+            "oth a" => Some(Self::OthA),
+            "oth l" => Some(Self::OthL),
+            _ => None,
+        }
+    }
+
+    /// Returns the QIF type name, as written after `!Type:`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bank => "Bank",
+            Self::Cash => "Cash",
+            Self::CCard => "CCard",
+            Self::Invst => "Invst",
+            Self::OthA => "Oth A",
+            Self::OthL => "Oth L",
+        }
+    }
+
+    /// Returns the GnuCash account type used when creating an account
+    /// for a register of this type.
+    pub fn account_type(&self) -> GNCAccountType {
+        match self {
+            Self::Bank => GNCAccountType::ACCT_TYPE_BANK,
+            Self::Cash => GNCAccountType::ACCT_TYPE_CASH,
+            Self::CCard => GNCAccountType::ACCT_TYPE_CREDIT,
+            Self::Invst | Self::OthA => GNCAccountType::ACCT_TYPE_ASSET,
+            Self::OthL => GNCAccountType::ACCT_TYPE_LIABILITY,
+        }
+    }
+
+    /// Returns the QIF register type for a GnuCash account type.
+    pub fn from_account_type(account_type: GNCAccountType) -> Self {
+        match account_type {
+            GNCAccountType::ACCT_TYPE_BANK
+            | GNCAccountType::ACCT_TYPE_SAVINGS
+            | GNCAccountType::ACCT_TYPE_MONEYMRKT => Self::Bank,
+            GNCAccountType::ACCT_TYPE_CASH => Self::Cash,
+            GNCAccountType::ACCT_TYPE_CREDIT | GNCAccountType::ACCT_TYPE_CREDITLINE => Self::CCard,
+            GNCAccountType::ACCT_TYPE_STOCK | GNCAccountType::ACCT_TYPE_MUTUAL => Self::Invst,
+            GNCAccountType::ACCT_TYPE_LIABILITY | GNCAccountType::ACCT_TYPE_PAYABLE => Self::OthL,
+            _ => Self::OthA,
+        }
+    }
+}
+
+/// Field order of QIF dates, which the format itself does not record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QifDateOrder {
+    /// `01/31/2024` (Quicken US).
+    #[default]
+    MonthDayYear,
+    /// `31/01/2024`.
+    DayMonthYear,
+    /// `2024/01/31`.
+    YearMonthDay,
+}
+
+/// A parsed QIF file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QifFile {
+    /// Registers, in file order. Accounts listed under `!Account`
+    /// without a register have no transactions.
+    pub accounts: Vec<QifAccount>,
+    /// Category list (`!Type:Cat`).
+    pub categories: Vec<QifCategory>,
+    /// Security list (`!Type:Security`).
+    pub securities: Vec<QifSecurity>,
+}
+
+/// One register.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QifAccount {
+    /// Account name from the preceding `!Account` record.
+    pub name: Option<String>,
+    /// Register type.
+    pub account_type: QifAccountType,
+    /// Account description (`D` field of the `!Account` record).
+    pub description: Option<String>,
+    /// Transactions of a non-investment register.
+    pub transactions: Vec<QifTransaction>,
+    /// Actions of an investment register.
+    pub investments: Vec<QifInvestment>,
+}
+
+/// A category from the `!Type:Cat` list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QifCategory {
+    /// Colon-separated category name, e.g. "Auto:Fuel".
+    pub name: String,
+    /// Description.
+    pub description: Option<String>,
+    /// True for income categories (`I`), false for expense ones.
+    pub income: bool,
+}
+
+/// A security from the `!Type:Security` list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QifSecurity {
+    /// Name used in the `Y` field of investment records.
+    pub name: String,
+    /// Ticker symbol.
+    pub symbol: Option<String>,
+    /// Security type, e.g. "Stock" or "Mutual Fund".
+    pub security_type: Option<String>,
+}
+
+/// Where the other side of a transaction or split goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QifTarget {
+    /// An income or expense category.
+    Category(String),
+    /// A transfer to another account (`[Name]`).
+    Transfer(String),
+}
+
+impl QifTarget {
+    /// Parses an `L` or `S` field. Class suffixes (`/Class`) are dropped.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(inner) = s.strip_prefix('[') {
+            let name = inner.split(']').next().unwrap_or(inner).trim();
+            return (!name.is_empty()).then(|| Self::Transfer(name.to_string()));
+        }
+        let name = s.split('/').next().unwrap_or(s).trim();
+        (!name.is_empty()).then(|| Self::Category(name.to_string()))
+    }
+}
+
+/// A non-investment register entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QifTransaction {
+    /// Date (`D`).
+    pub date: Date,
+    /// Amount (`T`); positive for money into the register account.
+    pub amount: Numeric,
+    /// Check or reference number (`N`).
+    pub num: Option<String>,
+    /// Payee (`P`).
+    pub payee: Option<String>,
+    /// Memo (`M`).
+    pub memo: Option<String>,
+    /// Reconcile state (`C`): `*` is cleared, `X` or `R` reconciled.
+    pub cleared: Option<ReconcileState>,
+    /// Category or transfer (`L`).
+    pub target: Option<QifTarget>,
+    /// Split lines (`S`/`E`/`$`); empty for a simple transaction.
+    pub splits: Vec<QifSplit>,
+}
+
+/// One split line of a split transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QifSplit {
+    /// Category or transfer (`S`).
+    pub target: Option<QifTarget>,
+    /// Memo (`E`).
+    pub memo: Option<String>,
+    /// Amount (`$`), with the same sign convention as the transaction.
+    pub amount: Numeric,
+}
+
+/// Investment action (`N` field of an investment record).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QifAction {
+    Buy,
+    Sell,
+    Div,
+    ReinvDiv,
+    /// Any other action, which the importer skips.
+    Other(String),
+}
+
+impl QifAction {
+    /// Returns the action name without the `X` suffix.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Buy => "Buy",
+            Self::Sell => "Sell",
+            Self::Div => "Div",
+            Self::ReinvDiv => "ReinvDiv",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+/// An investment register entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QifInvestment {
+    /// Date (`D`).
+    pub date: Date,
+    /// Action (`N`).
+    pub action: QifAction,
+    /// True for `X` variants (`BuyX`, `DivX`, ...), whose cash moves
+    /// through the `L` account instead of the investment account.
+    pub transfer: bool,
+    /// Security name (`Y`).
+    pub security: Option<String>,
+    /// Price per share (`I`).
+    pub price: Option<Numeric>,
+    /// Number of shares (`Q`).
+    pub quantity: Option<Numeric>,
+    /// Total amount (`T`).
+    pub amount: Option<Numeric>,
+    /// Commission (`O`).
+    pub commission: Option<Numeric>,
+    /// Payee or description (`P`).
+    pub payee: Option<String>,
+    /// Memo (`M`).
+    pub memo: Option<String>,
+    /// Reconcile state (`C`), as for [`QifTransaction::cleared`].
+    pub cleared: Option<ReconcileState>,
+    /// Cash account (`L`) for `X` actions, or income category.
+    pub target: Option<QifTarget>,
+}
+
+// ==================== Parsing ====================
+
+enum Section {
+    None,
+    AccountList,
+    Categories,
+    Securities,
+    Register(usize),
+    Skip,
+}
+
+type Field<'a> = (usize, char, &'a str);
+
+/// Parses a QIF file. Dates are read in the given field order; years
+/// written with an apostrophe (`1/15'04`) are in the 2000s.
+pub fn parse(data: &str, order: QifDateOrder) -> Result<QifFile> {
+    let mut file = QifFile::default();
+    let mut section = Section::None;
+    let mut current: Option<(String, Option<String>)> = None;
+    let mut fields: Vec<Field> = Vec::new();
+
+    for (index, raw) in data.lines().enumerate() {
+        let line = raw.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let lineno = index + 1;
+        if let Some(header) = line.strip_prefix('!') {
+            finish_record(&mut file, &section, &mut current, &mut fields, order)?;
+            section = parse_header(&mut file, &current, header.trim(), lineno)?;
+        } else if line.trim() == "^" {
+            finish_record(&mut file, &section, &mut current, &mut fields, order)?;
+        } else {
+            let mut chars = line.chars();
+            let code = chars.next().unwrap();
+            fields.push((lineno, code, chars.as_str().trim()));
+        }
+    }
+    finish_record(&mut file, &section, &mut current, &mut fields, order)?;
+    Ok(file)
+}
+
+fn parse_header(
+    file: &mut QifFile,
+    current: &Option<(String, Option<String>)>,
+    header: &str,
+    lineno: usize,
+) -> Result<Section> {
+    let lower = header.to_ascii_lowercase();
+    if lower == "account" {
+        return Ok(Section::AccountList);
+    }
+    if lower.starts_with("option:") || lower.starts_with("clear:") {
+        return Ok(Section::None);
+    }
+    let Some(type_name) = lower.strip_prefix("type:") else {
+        return Ok(Section::Skip);
+    };
+    match type_name.trim() {
+        "cat" => return Ok(Section::Categories),
+        "security" => return Ok(Section::Securities),
+        "class" | "memorized" | "prices" | "invitem" | "template" => return Ok(Section::Skip),
+        _ => {}
+    }
+    let account_type = QifAccountType::parse(type_name).ok_or_else(|| {
+        Error::Parse(format!(
+            "QIF line {}: unknown register type '{}'",
+            lineno, header
+        ))
+    })?;
+    let name = current.as_ref().map(|(name, _)| name.clone());
+    let existing = file
+        .accounts
+        .iter()
+        .position(|a| a.name == name && (name.is_some() || a.account_type == account_type));
+    let index = match existing {
+        Some(index) => {
+            file.accounts[index].account_type = account_type;
+            index
+        }
+        None => {
+            file.accounts.push(QifAccount {
+                name,
+                account_type,
+                description: current.as_ref().and_then(|(_, d)| d.clone()),
+                transactions: Vec::new(),
+                investments: Vec::new(),
+            });
+            file.accounts.len() - 1
+        }
+    };
+    Ok(Section::Register(index))
+}
+
+fn finish_record(
+    file: &mut QifFile,
+    section: &Section,
+    current: &mut Option<(String, Option<String>)>,
+    fields: &mut Vec<Field>,
+    order: QifDateOrder,
+) -> Result<()> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+    let record = std::mem::take(fields);
+    let text = |code: char| {
+        record
+            .iter()
+            .find(|(_, c, v)| *c == code && !v.is_empty())
+            .map(|(_, _, v)| v.to_string())
+    };
+    match section {
+        Section::AccountList => {
+            let Some(name) = text('N') else {
+                return Ok(());
+            };
+            let account_type = text('T').and_then(|t| QifAccountType::parse(&t));
+            let description = text('D');
+            if !file
+                .accounts
+                .iter()
+                .any(|a| a.name.as_deref() == Some(name.as_str()))
+            {
+                file.accounts.push(QifAccount {
+                    name: Some(name.clone()),
+                    account_type: account_type.unwrap_or(QifAccountType::Bank),
+                    description: description.clone(),
+                    transactions: Vec::new(),
+                    investments: Vec::new(),
+                });
+            }
+            *current = Some((name, description));
+        }
+        Section::Categories => {
+            if let Some(name) = text('N') {
+                file.categories.push(QifCategory {
+                    name,
+                    description: text('D'),
+                    income: record.iter().any(|(_, c, _)| *c == 'I'),
+                });
+            }
+        }
+        Section::Securities => {
+            if let Some(name) = text('N') {
+                file.securities.push(QifSecurity {
+                    name,
+                    symbol: text('S'),
+                    security_type: text('T'),
+                });
+            }
+        }
+        Section::Register(index) => {
+            let account = &mut file.accounts[*index];
+            if account.account_type == QifAccountType::Invst {
+                account.investments.push(parse_investment(&record, order)?);
+            } else {
+                account
+                    .transactions
+                    .push(parse_transaction(&record, order)?);
+            }
+        }
+        Section::None | Section::Skip => {}
+    }
+    Ok(())
+}
+
+fn parse_transaction(record: &[Field], order: QifDateOrder) -> Result<QifTransaction> {
+    let mut date = None;
+    let mut amount = None;
+    let mut txn = QifTransaction {
+        date: Date::from_days(0),
+        amount: Numeric::zero(),
+        num: None,
+        payee: None,
+        memo: None,
+        cleared: None,
+        target: None,
+        splits: Vec::new(),
+    };
+    for &(lineno, code, value) in record {
+        match code {
+            'D' => date = Some(parse_date(value, order, lineno)?),
+            'T' => amount = Some(parse_amount(value, lineno)?),
+            'U' if amount.is_none() => amount = Some(parse_amount(value, lineno)?),
+            'N' => txn.num = non_empty(value),
+            'P' => txn.payee = non_empty(value),
+            'M' => txn.memo = non_empty(value),
+            'C' => txn.cleared = parse_cleared(value),
+            'L' => txn.target = QifTarget::parse(value),
+            'S' => txn.splits.push(QifSplit {
+                target: QifTarget::parse(value),
+                memo: None,
+                amount: Numeric::zero(),
+            }),
+            'E' => {
+                if let Some(split) = txn.splits.last_mut() {
+                    split.memo = non_empty(value);
+                }
+            }
+            '$' => {
+                let value = parse_amount(value, lineno)?;
+                match txn.splits.last_mut() {
+                    Some(split) => split.amount = value,
+                    None => txn.splits.push(QifSplit {
+                        target: None,
+                        memo: None,
+                        amount: value,
+                    }),
+                }
+            }
+            _ => {}
+        }
+    }
+    let lineno = record[0].0;
+    txn.date = date.ok_or_else(|| missing(lineno, "date"))?;
+    txn.amount = match amount {
+        Some(amount) => amount,
+        None if !txn.splits.is_empty() => txn.splits.iter().map(|s| s.amount).sum(),
+        None => return Err(missing(lineno, "amount")),
+    };
+    Ok(txn)
+}
+
+fn parse_investment(record: &[Field], order: QifDateOrder) -> Result<QifInvestment> {
+    let mut date = None;
+    let mut action = None;
+    let mut inv = QifInvestment {
+        date: Date::from_days(0),
+        action: QifAction::Other(String::new()),
+        transfer: false,
+        security: None,
+        price: None,
+        quantity: None,
+        amount: None,
+        commission: None,
+        payee: None,
+        memo: None,
+        cleared: None,
+        target: None,
+    };
+    for &(lineno, code, value) in record {
+        match code {
+            'D' => date = Some(parse_date(value, order, lineno)?),
+            'N' => action = Some(value),
+            'Y' => inv.security = non_empty(value),
+            'I' => inv.price = parse_optional_amount(value, lineno)?,
+            'Q' => inv.quantity = parse_optional_amount(value, lineno)?,
+            'T' => inv.amount = parse_optional_amount(value, lineno)?,
+            'U' if inv.amount.is_none() => inv.amount = parse_optional_amount(value, lineno)?,
+            'O' => inv.commission = parse_optional_amount(value, lineno)?,
+            'P' => inv.payee = non_empty(value),
+            'M' => inv.memo = non_empty(value),
+            'C' => inv.cleared = parse_cleared(value),
+            'L' => inv.target = QifTarget::parse(value),
+            _ => {}
+        }
+    }
+    let lineno = record[0].0;
+    inv.date = date.ok_or_else(|| missing(lineno, "date"))?;
+    let action = action.ok_or_else(|| missing(lineno, "action"))?;
+    (inv.action, inv.transfer) = parse_action(action);
+    Ok(inv)
+}
+
+fn parse_action(s: &str) -> (QifAction, bool) {
+    let lower = s.to_ascii_lowercase();
+    let (base, transfer) = match lower.strip_suffix('x') {
+        Some(base) if matches!(base, "buy" | "sell" | "div") => (base, true),
+        _ => (lower.as_str(), false),
+    };
+    let action = match base {
+        "buy" => QifAction::Buy,
+        "sell" => QifAction::Sell,
+        "div" => QifAction::Div,
+        "reinvdiv" => QifAction::ReinvDiv,
+        _ => QifAction::Other(s.to_string()),
+    };
+    (action, transfer)
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn missing(lineno: usize, what: &str) -> Error {
+    Error::Parse(format!(
+        "QIF record ending at line {}: missing {}",
+        lineno, what
+    ))
+}
+
+fn parse_cleared(value: &str) -> Option<ReconcileState> {
+    match value {
+        "*" | "c" | "C" => Some(ReconcileState::Cleared),
+        "X" | "x" | "R" | "r" => Some(ReconcileState::Reconciled),
+        _ => None,
+    }
+}
+
+/// Parses a QIF date such as `1/15/2024`, `1/15'04`, ` 1/ 5/98` or
+/// `2024-01-15`.
+fn parse_date(s: &str, order: QifDateOrder, lineno: usize) -> Result<Date> {
+    let err = || Error::Parse(format!("QIF line {}: invalid date '{}'", lineno, s));
+    let parts: Vec<&str> = s
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if parts.len() != 3 {
+        return Err(err());
+    }
+    let order = if parts[0].len() == 4 {
+        QifDateOrder::YearMonthDay
+    } else {
+        order
+    };
+    let (y, m, d) = match order {
+        QifDateOrder::MonthDayYear => (parts[2], parts[0], parts[1]),
+        QifDateOrder::DayMonthYear => (parts[2], parts[1], parts[0]),
+        QifDateOrder::YearMonthDay => (parts[0], parts[1], parts[2]),
+    };
+    let mut year: i32 = y.parse().map_err(|_| err())?;
+    if y.len() <= 2 {
+        year += if s.contains('\'') || year < 70 {
+            2000
+        } else {
+            1900
+        };
+    }
+    let month = m.parse().map_err(|_| err())?;
+    let day = d.parse().map_err(|_| err())?;
+    Date::new(year, month, day).ok_or_else(err)
+}
+
+/// Parses an amount, ignoring thousands separators.
+fn parse_amount(s: &str, lineno: usize) -> Result<Numeric> {
+    let cleaned: String = s
+        .chars()
+        .filter(|c| *c != ',' && !c.is_whitespace())
+        .collect();
+    Numeric::parse_decimal(&cleaned)
+        .ok_or_else(|| Error::Parse(format!("QIF line {}: invalid amount '{}'", lineno, s)))
+}
+
+fn parse_optional_amount(s: &str, lineno: usize) -> Result<Option<Numeric>> {
+    if s.is_empty() {
+        Ok(None)
+    } else {
+        parse_amount(s, lineno).map(Some)
+    }
+}
+
+// ==================== Import ====================
+
+/// Outcome of a [`QifImporter::import`] run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QifImportReport {
+    /// Transactions created.
+    pub created: Vec<Guid>,
+    /// Full names of accounts created for registers, categories,
+    /// transfers and securities.
+    pub accounts_created: Vec<String>,
+    /// Transfers skipped because the other register already created them.
+    pub duplicate_transfers: usize,
+    /// Investment records that were not imported, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// Posts parsed QIF registers to a book.
+pub struct QifImporter<'a> {
+    book: &'a Book,
+    root: &'a Account,
+    currency: &'a Commodity,
+    default_account: Option<&'a Account>,
+    security_namespace: String,
+}
+
+/// Identifies a transfer independently of which register it came from.
+#[derive(PartialEq, Eq, Hash)]
+struct TransferKey {
+    date: Date,
+    accounts: (Guid, Guid),
+    amount: Numeric,
+}
+
+impl TransferKey {
+    /// `amount` is the change to `account`; the key stores the change to
+    /// the lower-ordered GUID so both registers produce the same key.
+    fn new(date: Date, account: Guid, other: Guid, amount: Numeric) -> Self {
+        let (accounts, amount) = if account.as_bytes() <= other.as_bytes() {
+            ((account, other), amount)
+        } else {
+            ((other, account), amount.neg())
+        };
+        Self {
+            date,
+            accounts,
+            amount: amount.reduce(),
+        }
+    }
+}
+
+struct ImportState<'f> {
+    report: QifImportReport,
+    register_types: HashMap<&'f str, QifAccountType>,
+    income_categories: HashSet<&'f str>,
+    expense_categories: HashSet<&'f str>,
+    symbols: HashMap<&'f str, &'f str>,
+    transfers: HashMap<TransferKey, usize>,
+}
+
+impl ImportState<'_> {
+    /// Returns true if the other register already imported this transfer,
+    /// otherwise remembers it for when the other register comes.
+    fn is_duplicate_transfer(&mut self, key: TransferKey) -> bool {
+        match self.transfers.get_mut(&key) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                self.report.duplicate_transfers += 1;
+                true
+            }
+            _ => {
+                *self.transfers.entry(key).or_insert(0) += 1;
+                false
+            }
+        }
+    }
+}
+
+impl<'a> QifImporter<'a> {
+    /// Creates an importer that resolves and creates accounts below
+    /// `root`. New accounts and all transactions use `currency`.
+    pub fn new(book: &'a Book, root: &'a Account, currency: &'a Commodity) -> Self {
+        Self {
+            book,
+            root,
+            currency,
+            default_account: None,
+            security_namespace: "FUND".to_string(),
+        }
+    }
+
+    /// Sets the account that receives registers with no `!Account` name.
+    pub fn default_account(mut self, account: &'a Account) -> Self {
+        self.default_account = Some(account);
+        self
+    }
+
+    /// Sets the commodity namespace for securities that are not yet in
+    /// the book's commodity table. Defaults to "FUND".
+    pub fn security_namespace(mut self, namespace: &str) -> Self {
+        self.security_namespace = namespace.to_string();
+        self
+    }
+
+    /// Imports every register in the file.
+    pub fn import(&self, file: &QifFile) -> Result<QifImportReport> {
+        let mut state = ImportState {
+            report: QifImportReport::default(),
+            register_types: file
+                .accounts
+                .iter()
+                .filter_map(|a| Some((a.name.as_deref()?, a.account_type)))
+                .collect(),
+            income_categories: file
+                .categories
+                .iter()
+                .filter(|c| c.income)
+                .map(|c| c.name.as_str())
+                .collect(),
+            expense_categories: file
+                .categories
+                .iter()
+                .filter(|c| !c.income)
+                .map(|c| c.name.as_str())
+                .collect(),
+            symbols: file
+                .securities
+                .iter()
+                .filter_map(|s| Some((s.name.as_str(), s.symbol.as_deref()?)))
+                .collect(),
+            transfers: HashMap::new(),
+        };
+
+        for register in &file.accounts {
+            if register.transactions.is_empty() && register.investments.is_empty() {
+                continue;
+            }
+            let named;
+            let account = match &register.name {
+                Some(name) => {
+                    named = self.register_account(name, register.account_type, &mut state)?;
+                    &named
+                }
+                None => self.default_account.ok_or_else(|| {
+                    Error::InvalidOperation(
+                        "QIF register has no !Account name and no default account is set"
+                            .to_string(),
+                    )
+                })?,
+            };
+            for txn in &register.transactions {
+                if let Some(guid) = self.import_transaction(account, txn, &mut state)? {
+                    state.report.created.push(guid);
+                }
+            }
+            for inv in &register.investments {
+                if let Some(guid) = self.import_investment(account, inv, &mut state)? {
+                    state.report.created.push(guid);
+                }
+            }
+        }
+        Ok(state.report)
+    }
+
+    fn import_transaction(
+        &self,
+        account: &Account,
+        txn: &QifTransaction,
+        state: &mut ImportState,
+    ) -> Result<Option<Guid>> {
+        let mut others = Vec::new();
+        // The change to `account` once transfers the other register
+        // already imported are left out.
+        let mut amount = txn.amount;
+        if txn.splits.is_empty() {
+            let other = self.target_account(txn.target.as_ref(), txn.amount, state)?;
+            if let Some(QifTarget::Transfer(_)) = &txn.target {
+                let key = TransferKey::new(txn.date, account.guid(), other.guid(), txn.amount);
+                if state.is_duplicate_transfer(key) {
+                    return Ok(None);
+                }
+            }
+            others.push((other, txn.amount.neg(), txn.memo.as_deref()));
+        } else {
+            for split in &txn.splits {
+                let other = self.target_account(split.target.as_ref(), split.amount, state)?;
+                if let Some(QifTarget::Transfer(_)) = &split.target {
+                    let key =
+                        TransferKey::new(txn.date, account.guid(), other.guid(), split.amount);
+                    if state.is_duplicate_transfer(key) {
+                        amount -= split.amount;
+                        continue;
+                    }
+                }
+                others.push((other, split.amount.neg(), split.memo.as_deref()));
+            }
+            if others.is_empty() {
+                return Ok(None);
+            }
+        }
+
+        let mut builder = self
+            .builder(txn.date, txn.payee.as_deref().unwrap_or(""))
+            .split(account, amount, txn.memo.as_deref());
+        for (other, amount, memo) in &others {
+            builder = builder.split(other, *amount, *memo);
+        }
+        if let Some(num) = &txn.num {
+            builder = builder.num(num);
+        }
+        self.finish(builder, account, txn.cleared).map(Some)
+    }
+
+    fn import_investment(
+        &self,
+        account: &Account,
+        inv: &QifInvestment,
+        state: &mut ImportState,
+    ) -> Result<Option<Guid>> {
+        if let QifAction::Other(action) = &inv.action {
+            state.report.skipped.push(format!(
+                "{} {}: unsupported action '{}'",
+                inv.date,
+                inv.security.as_deref().unwrap_or(""),
+                action
+            ));
+            return Ok(None);
+        }
+        let what = |field: &str| {
+            Error::Parse(format!(
+                "QIF {} on {} has no {}",
+                inv.action.name(),
+                inv.date,
+                field
+            ))
+        };
+        let commission = inv.commission.unwrap_or_else(Numeric::zero);
+        let amount = match (inv.amount, inv.quantity, inv.price) {
+            (Some(amount), _, _) => amount,
+            (None, Some(quantity), Some(price)) => {
+                let gross = quantity * price;
+                match inv.action {
+                    QifAction::Buy => gross + commission,
+                    QifAction::Sell => gross - commission,
+                    _ => gross,
+                }
+            }
+            _ => return Err(what("amount")),
+        };
+
+        let cash_owned;
+        let cash: &Account = match (&inv.target, inv.transfer) {
+            (Some(QifTarget::Transfer(name)), true) => {
+                cash_owned = self.register_account(name, QifAccountType::Bank, state)?;
+                &cash_owned
+            }
+            _ => account,
+        };
+        let stock = match &inv.security {
+            Some(security) => Some(self.security_account(account, security, state)?),
+            None => None,
+        };
+        let description = inv.payee.clone().unwrap_or_else(|| {
+            format!(
+                "{} {}",
+                inv.action.name(),
+                inv.security.as_deref().unwrap_or("")
+            )
+            .trim()
+            .to_string()
+        });
+        let memo = inv.memo.as_deref();
+        let builder = self.builder(inv.date, &description);
+
+        let guid = match inv.action {
+            QifAction::Buy | QifAction::Sell => {
+                let stock = stock.as_ref().ok_or_else(|| what("security"))?;
+                let shares = inv.quantity.ok_or_else(|| what("quantity"))?.abs();
+                let mut builder = if inv.action == QifAction::Buy {
+                    builder
+                        .stock_split(stock, shares, amount - commission, memo)
+                        .split(cash, amount.neg(), memo)
+                } else {
+                    builder
+                        .stock_split(stock, shares.neg(), (amount + commission).neg(), memo)
+                        .split(cash, amount, memo)
+                };
+                let commissions;
+                if !commission.is_zero() {
+                    commissions = self.category_account("Commissions", false, state)?;
+                    builder = builder.split(&commissions, commission, None);
+                }
+                self.finish(builder, cash, inv.cleared)?
+            }
+            QifAction::Div => {
+                let income = match (&inv.target, inv.transfer) {
+                    (Some(QifTarget::Category(name)), false) => {
+                        self.category_account(name, true, state)?
+                    }
+                    _ => self.category_account("Dividends", true, state)?,
+                };
+                let builder = builder
+                    .split(cash, amount, memo)
+                    .split(&income, amount.neg(), memo);
+                self.finish(builder, cash, inv.cleared)?
+            }
+            QifAction::ReinvDiv => {
+                let stock = stock.as_ref().ok_or_else(|| what("security"))?;
+                let shares = inv.quantity.ok_or_else(|| what("quantity"))?.abs();
+                let income = self.category_account("Dividends", true, state)?;
+                let builder = builder.stock_split(stock, shares, amount, memo).split(
+                    &income,
+                    amount.neg(),
+                    memo,
+                );
+                self.finish(builder, stock, inv.cleared)?
+            }
+            QifAction::Other(_) => unreachable!("unsupported actions are skipped above"),
+        };
+        Ok(Some(guid))
+    }
+
+    fn builder(&self, date: Date, description: &str) -> TransactionBuilder<'_> {
+        let builder = TransactionBuilder::new(self.book)
+            .description(description)
+            .date(date.day() as i32, date.month() as i32, date.year());
+        match self.currency.mnemonic() {
+            Some(mnemonic) => builder.currency(&mnemonic),
+            None => builder,
+        }
+    }
+
+    /// Builds the transaction and applies the register's reconcile flag
+    /// to the split in `account`.
+    fn finish(
+        &self,
+        builder: TransactionBuilder,
+        account: &Account,
+        cleared: Option<ReconcileState>,
+    ) -> Result<Guid> {
+        let mut txn = builder.build()?;
+        if let Some(state) = cleared {
+            let edit = txn.edit();
+            if let Some(split) = txn.splits().find(|s| s.account().as_ref() == Some(account)) {
+                edit.split(&split).set_reconcile_state(state);
+            }
+            edit.commit();
+        }
+        txn.mark_unowned();
+        Ok(txn.guid())
+    }
+
+    /// Resolves a register or transfer account by full name, then by
+    /// name anywhere in the tree, creating a top-level account if absent.
+    fn register_account(
+        &self,
+        name: &str,
+        fallback_type: QifAccountType,
+        state: &mut ImportState,
+    ) -> Result<Account> {
+        if let Some(account) = self
+            .root
+            .lookup_by_full_name(name)
+            .or_else(|| self.root.lookup_by_name(name))
+        {
+            return Ok(account);
+        }
+        let qif_type = state
+            .register_types
+            .get(name)
+            .copied()
+            .unwrap_or(fallback_type);
+        find_or_create_account(
+            self.book,
+            self.root,
+            name,
+            qif_type.account_type(),
+            self.currency,
+            &mut state.report.accounts_created,
+        )
+    }
+
+    /// Resolves the other side of a split. `amount` is the change to the
+    /// register account and decides income versus expense for
+    /// categories missing from the category list.
+    fn target_account(
+        &self,
+        target: Option<&QifTarget>,
+        amount: Numeric,
+        state: &mut ImportState,
+    ) -> Result<Account> {
+        match target {
+            Some(QifTarget::Transfer(name)) => {
+                self.register_account(name, QifAccountType::Bank, state)
+            }
+            Some(QifTarget::Category(name)) => {
+                let income = if state.income_categories.contains(name.as_str()) {
+                    true
+                } else if state.expense_categories.contains(name.as_str()) {
+                    false
+                } else {
+                    amount.is_positive()
+                };
+                self.category_account(name, income, state)
+            }
+            None => {
+                let mnemonic = self.currency.mnemonic().unwrap_or_default();
+                find_or_create_account(
+                    self.book,
+                    self.root,
+                    &format!("Imbalance-{}", mnemonic),
+                    GNCAccountType::ACCT_TYPE_BANK,
+                    self.currency,
+                    &mut state.report.accounts_created,
+                )
+            }
+        }
+    }
+
+    fn category_account(
+        &self,
+        name: &str,
+        income: bool,
+        state: &mut ImportState,
+    ) -> Result<Account> {
+        let (top, account_type) = if income {
+            ("Income", GNCAccountType::ACCT_TYPE_INCOME)
+        } else {
+            ("Expenses", GNCAccountType::ACCT_TYPE_EXPENSE)
+        };
+        find_or_create_account(
+            self.book,
+            self.root,
+            &format!("{}:{}", top, name),
+            account_type,
+            self.currency,
+            &mut state.report.accounts_created,
+        )
+    }
+
+    /// Returns the stock account for a security below the investment
+    /// account, registering the security commodity if needed.
+    fn security_account(
+        &self,
+        investment: &Account,
+        security: &str,
+        state: &mut ImportState,
+    ) -> Result<Account> {
+        let symbol = state.symbols.get(security).copied().unwrap_or(security);
+        let table = self
+            .book
+            .commodity_table()
+            .ok_or(Error::NullPointer("commodity table"))?;
+        let commodity = match table.lookup(&self.security_namespace, symbol) {
+            Some(commodity) => commodity,
+            None => {
+                let commodity = Commodity::new(
+                    self.book,
+                    security,
+                    &self.security_namespace,
+                    symbol,
+                    None,
+                    10000,
+                )
+                .ok_or_else(|| {
+                    Error::InvalidOperation(format!("Cannot create security '{}'", symbol))
+                })?;
+                table
+                    .insert(&commodity)
+                    .ok_or(Error::NullPointer("commodity table"))?
+            }
+        };
+        let parent = investment.full_name().unwrap_or_default();
+        find_or_create_account(
+            self.book,
+            self.root,
+            &format!("{}:{}", parent, security.replace(':', "-")),
+            GNCAccountType::ACCT_TYPE_STOCK,
+            &commodity,
+            &mut state.report.accounts_created,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BANK: &str = "!Type:Cat
+NSalary
+DPaycheck
+I
+^
+NGroceries
+E
+^
+!Account
+NChecking
+TBank
+^
+!Type:Bank
+D1/15/2024
+T-1,234.50
+N1001
+PRent Co
+CX
+LHousing
+^
+D01/16'24
+T-100.00
+PSupermarket
+SGroceries
+EFood
+$-80.00
+S[Savings]
+$-20.00
+^
+";
+
+    const INVST: &str = "!Account
+NBrokerage
+TInvst
+^
+!Type:Invst
+D2024-03-01
+NBuyX
+YACME
+I10.50
+Q10
+T110.00
+O5.00
+L[Checking]
+^
+D2024-03-15
+NReinvDiv
+YACME
+I11
+Q1
+T11
+^
+D2024-03-20
+NShrsIn
+YACME
+Q5
+^
+";
+
+    #[test]
+    fn test_parse_bank_register() {
+        let file = parse(BANK, QifDateOrder::MonthDayYear).unwrap();
+        assert_eq!(file.categories.len(), 2);
+        assert!(file.categories[0].income);
+        assert!(!file.categories[1].income);
+
+        assert_eq!(file.accounts.len(), 1);
+        let checking = &file.accounts[0];
+        assert_eq!(checking.name.as_deref(), Some("Checking"));
+        assert_eq!(checking.account_type, QifAccountType::Bank);
+        assert_eq!(checking.transactions.len(), 2);
+
+        let rent = &checking.transactions[0];
+        assert_eq!(rent.date, Date::new(2024, 1, 15).unwrap());
+        assert_eq!(rent.amount, Numeric::new(-123450, 100));
+        assert_eq!(rent.num.as_deref(), Some("1001"));
+        assert_eq!(rent.cleared, Some(ReconcileState::Reconciled));
+        assert_eq!(
+            rent.target,
+            Some(QifTarget::Category("Housing".to_string()))
+        );
+
+        let split = &checking.transactions[1];
+        assert_eq!(split.date, Date::new(2024, 1, 16).unwrap());
+        assert_eq!(split.splits.len(), 2);
+        assert_eq!(split.splits[0].memo.as_deref(), Some("Food"));
+        assert_eq!(split.splits[0].amount, Numeric::new(-8000, 100));
+        assert_eq!(
+            split.splits[1].target,
+            Some(QifTarget::Transfer("Savings".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_investment_register() {
+        let file = parse(INVST, QifDateOrder::MonthDayYear).unwrap();
+        let invst = &file.accounts[0];
+        assert_eq!(invst.account_type, QifAccountType::Invst);
+        assert_eq!(invst.investments.len(), 3);
+
+        let buy = &invst.investments[0];
+        assert_eq!(buy.date, Date::new(2024, 3, 1).unwrap());
+        assert_eq!(buy.action, QifAction::Buy);
+        assert!(buy.transfer);
+        assert_eq!(buy.quantity, Some(Numeric::new(10, 1)));
+        assert_eq!(buy.commission, Some(Numeric::new(500, 100)));
+        assert_eq!(
+            buy.target,
+            Some(QifTarget::Transfer("Checking".to_string()))
+        );
+
+        assert_eq!(invst.investments[1].action, QifAction::ReinvDiv);
+        assert!(!invst.investments[1].transfer);
+        assert_eq!(
+            invst.investments[2].action,
+            QifAction::Other("ShrsIn".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dates_and_targets() {
+        let d = |s, order| parse_date(s, order, 1).unwrap();
+        assert_eq!(
+            d(" 1/ 5/98", QifDateOrder::MonthDayYear),
+            Date::new(1998, 1, 5).unwrap()
+        );
+        assert_eq!(
+            d("31.01.2024", QifDateOrder::DayMonthYear),
+            Date::new(2024, 1, 31).unwrap()
+        );
+        assert_eq!(
+            d("1/15'04", QifDateOrder::MonthDayYear),
+            Date::new(2004, 1, 15).unwrap()
+        );
+        assert!(parse_date("13/40/2024", QifDateOrder::MonthDayYear, 1).is_err());
+
+        assert_eq!(
+            QifTarget::parse("Auto:Fuel/Business"),
+            Some(QifTarget::Category("Auto:Fuel".to_string()))
+        );
+        assert_eq!(
+            QifTarget::parse("[Credit Card]/Trip"),
+            Some(QifTarget::Transfer("Credit Card".to_string()))
+        );
+        assert_eq!(QifTarget::parse(""), None);
+    }
+
+    #[test]
+    fn test_missing_date_is_an_error() {
+        let data = "!Type:Bank\nT10.00\n^\n";
+        assert!(matches!(
+            parse(data, QifDateOrder::MonthDayYear),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_transfer_imported_once() {
+        let data = "!Account
+NSavings
+TBank
+^
+!Type:Bank
+D01/16'24
+T20.00
+PTransfer
+L[Checking]
+^
+!Account
+NChecking
+TBank
+^
+!Type:Bank
+D01/16'24
+T-100.00
+PSupermarket
+SGroceries
+$-80.00
+S[Savings]
+$-20.00
+^
+";
+        let book = Book::new();
+        let root = book.root_account().expect("books have a root account");
+        let table = book
+            .commodity_table()
+            .expect("books have a commodity table");
+        let usd = Commodity::new(&book, "US Dollar", "CURRENCY", "USD", None, 100)
+            .and_then(|c| table.insert(&c))
+            .expect("currency registers");
+
+        let file = parse(data, QifDateOrder::MonthDayYear).unwrap();
+        let report = QifImporter::new(&book, &root, &usd).import(&file).unwrap();
+        assert_eq!(report.duplicate_transfers, 1);
+        assert_eq!(report.created.len(), 2);
+
+        let savings = root.lookup_by_name("Savings").unwrap();
+        let checking = root.lookup_by_name("Checking").unwrap();
+        assert_eq!(savings.splits_size(), 1);
+        assert!(savings.balance().eq_value(&Numeric::new(2000, 100)));
+        assert_eq!(checking.splits_size(), 2);
+        assert!(checking.balance().eq_value(&Numeric::new(-10000, 100)));
+    }
+}
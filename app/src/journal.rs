@@ -0,0 +1,514 @@
+//! Change journal with undo.
+//!
+//! A [`Journal`] listens to the engine's change events for one book and
+//! records every committed change to its accounts, transactions, splits,
+//! prices and business objects: the fields before and after, when, and
+//! the user label set on the journal. Changes can be grouped under a
+//! name, and the last few changes or a whole group can be undone:
+//!
+//! ```ignore
+//! use gnucash_ext::journal::Journal;
+//!
+//! let journal = Journal::start(&book, "alice");
+//! journal.group("march statement", || {
+//!     reconcile_march(&book);
+//! });
+//! for entry in journal.history(transaction_guid) {
+//!     println!("{} {} {:?}", entry.time, entry.user, entry.change.fields);
+//! }
+//! let report = journal.undo_group("march statement");
+//! ```
+//!
+//! The journal only records while it exists, and only changes made on
+//! the thread that started it; nothing is stored in the book. Undo goes through [`merge`](crate::diff::merge), so a field
+//! changed again since the undone change is reported as a conflict and
+//! left alone, and a transaction is restored whole or not at all.
+
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gnucash_sys::events::{self, EntityType, Event, EventSubscription};
+use gnucash_sys::{Book, Guid, ffi};
+
+use crate::diff::{
+    BookDiff, ChangeKind, EntityChange, EntityKind, FieldChange, MergeReport, Snapshot,
+    change_between, merge, snapshot_book, snapshot_one,
+};
+
+/// One recorded change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// Position in the journal, counting from 1.
+    pub sequence: u64,
+    /// When the change was committed, as `time64`.
+    pub time: i64,
+    /// The journal's user label at the time.
+    pub user: String,
+    /// The group the change was made in, if any.
+    pub group: Option<String>,
+    pub change: EntityChange,
+}
+
+/// What the journal shares with its event handler.
+struct Shared {
+    state: RefCell<State>,
+    /// Set while an undo applies its changes, which are not recorded.
+    paused: Cell<bool>,
+    /// Events that arrived while the journal itself held `state`,
+    /// recorded at the next chance.
+    pending: RefCell<Vec<Event>>,
+}
+
+impl Shared {
+    fn record(&self, event: Event) {
+        if self.paused.get() {
+            return;
+        }
+        match self.state.try_borrow_mut() {
+            Ok(mut state) => {
+                for pending in self.pending.take() {
+                    state.record(pending);
+                }
+                state.record(event);
+            }
+            Err(_) => self.pending.borrow_mut().push(event),
+        }
+    }
+
+    /// Records the queued events, then borrows the state.
+    fn state(&self) -> RefMut<'_, State> {
+        let mut state = self.state.borrow_mut();
+        for pending in self.pending.take() {
+            state.record(pending);
+        }
+        state
+    }
+}
+
+struct State {
+    book: *mut ffi::QofBook,
+    user: String,
+    group: Option<String>,
+    sequence: u64,
+    /// The last recorded state of every entity of the book.
+    known: HashMap<Guid, Snapshot>,
+    entries: Vec<JournalEntry>,
+}
+
+impl State {
+    fn record(&mut self, event: Event) {
+        let Some(kind) = kind_of(event.entity()) else {
+            return;
+        };
+        let guid = event.guid();
+        let after = match event {
+            Event::Destroyed { .. } => None,
+            _ => {
+                let book =
+                    unsafe { Book::from_raw(self.book, false) }.expect("book pointer is non-null");
+                snapshot_one(&book, kind, guid)
+            }
+        };
+        let Some(change) = change_between(self.known.get(&guid), after.as_ref()) else {
+            return;
+        };
+        match after {
+            Some(after) => self.known.insert(guid, after),
+            None => self.known.remove(&guid),
+        };
+        self.sequence += 1;
+        self.entries.push(JournalEntry {
+            sequence: self.sequence,
+            time: unsafe { ffi::gnc_time(std::ptr::null_mut()) },
+            user: self.user.clone(),
+            group: self.group.clone(),
+            change,
+        });
+    }
+}
+
+fn kind_of(entity: &EntityType) -> Option<EntityKind> {
+    Some(match entity {
+        EntityType::Account => EntityKind::Account,
+        EntityType::Transaction => EntityKind::Transaction,
+        EntityType::Split => EntityKind::Split,
+        EntityType::Price => EntityKind::Price,
+        EntityType::Customer => EntityKind::Customer,
+        EntityType::Vendor => EntityKind::Vendor,
+        EntityType::Employee => EntityKind::Employee,
+        EntityType::Job => EntityKind::Job,
+        EntityType::Invoice => EntityKind::Invoice,
+        EntityType::Entry => EntityKind::Entry,
+        _ => return None,
+    })
+}
+
+/// A record of the changes made to a book while the journal exists.
+pub struct Journal<'a> {
+    book: &'a Book,
+    shared: Rc<Shared>,
+    _subscription: EventSubscription,
+}
+
+impl<'a> Journal<'a> {
+    /// Starts recording changes to `book`, labelled with `user`.
+    pub fn start(book: &'a Book, user: &str) -> Self {
+        let shared = Rc::new(Shared {
+            state: RefCell::new(State {
+                book: book.as_ptr(),
+                user: user.to_string(),
+                group: None,
+                sequence: 0,
+                known: snapshot_book(book)
+                    .into_iter()
+                    .map(|s| (s.guid, s))
+                    .collect(),
+                entries: Vec::new(),
+            }),
+            paused: Cell::new(false),
+            pending: RefCell::new(Vec::new()),
+        });
+        let recorder = Rc::clone(&shared);
+        let subscription = events::subscribe(move |event| recorder.record(event));
+        Self {
+            book,
+            shared,
+            _subscription: subscription,
+        }
+    }
+
+    /// Sets the user label recorded with later changes.
+    pub fn set_user(&self, user: &str) {
+        self.shared.state().user = user.to_string();
+    }
+
+    /// Records later changes under `name` until [`end_group`](Self::end_group).
+    pub fn begin_group(&self, name: &str) {
+        self.shared.state().group = Some(name.to_string());
+    }
+
+    /// Stops recording changes under the current group.
+    pub fn end_group(&self) {
+        self.shared.state().group = None;
+    }
+
+    /// Runs `f`, recording its changes under `name`. Groups nest: the
+    /// enclosing group resumes when `f` returns or panics.
+    pub fn group<R>(&self, name: &str, f: impl FnOnce() -> R) -> R {
+        let outer = self.shared.state().group.replace(name.to_string());
+        let _group = GroupGuard {
+            shared: &self.shared,
+            outer,
+        };
+        f()
+    }
+
+    /// Returns the number of recorded changes.
+    pub fn len(&self) -> usize {
+        self.shared.state().entries.len()
+    }
+
+    /// Returns true if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns every recorded change, oldest first.
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.shared.state().entries.clone()
+    }
+
+    /// Returns the recorded changes to one entity, oldest first.
+    pub fn history(&self, guid: Guid) -> Vec<JournalEntry> {
+        self.shared
+            .state()
+            .entries
+            .iter()
+            .filter(|e| e.change.guid == guid)
+            .cloned()
+            .collect()
+    }
+
+    /// Forgets every recorded change.
+    pub fn clear(&self) {
+        self.shared.state().entries.clear();
+    }
+
+    /// Undoes the last `n` recorded changes and removes them from the
+    /// journal.
+    pub fn undo_last(&self, n: usize) -> MergeReport {
+        let len = self.len();
+        self.undo(|i, _| i + n >= len)
+    }
+
+    /// Undoes every change recorded under `name` and removes them from
+    /// the journal.
+    pub fn undo_group(&self, name: &str) -> MergeReport {
+        self.undo(|_, entry| entry.group.as_deref() == Some(name))
+    }
+
+    fn undo(&self, select: impl Fn(usize, &JournalEntry) -> bool) -> MergeReport {
+        let diff = {
+            let mut state = self.shared.state();
+            let (undone, kept): (Vec<_>, Vec<_>) = state
+                .entries
+                .drain(..)
+                .enumerate()
+                .partition(|(i, entry)| select(*i, entry));
+            state.entries = kept.into_iter().map(|(_, e)| e).collect();
+            let undone: Vec<EntityChange> = undone.into_iter().map(|(_, e)| e.change).collect();
+            undo_diff(&undone)
+        };
+
+        let report = {
+            let _paused = PauseGuard::new(&self.shared);
+            merge(&diff, self.book)
+        };
+        let mut state = self.shared.state();
+        for change in &diff.changes {
+            match snapshot_one(self.book, change.kind, change.guid) {
+                Some(now) => state.known.insert(change.guid, now),
+                None => state.known.remove(&change.guid),
+            };
+        }
+        report
+    }
+}
+
+/// Restores the enclosing group when a [`Journal::group`] ends.
+struct GroupGuard<'s> {
+    shared: &'s Shared,
+    outer: Option<String>,
+}
+
+impl Drop for GroupGuard<'_> {
+    fn drop(&mut self) {
+        self.shared.state().group = self.outer.take();
+    }
+}
+
+/// Stops recording while an undo applies its changes, resuming when the
+/// undo returns or panics.
+struct PauseGuard<'s> {
+    shared: &'s Shared,
+}
+
+impl<'s> PauseGuard<'s> {
+    fn new(shared: &'s Shared) -> Self {
+        shared.paused.set(true);
+        Self { shared }
+    }
+}
+
+impl Drop for PauseGuard<'_> {
+    fn drop(&mut self) {
+        self.shared.paused.set(false);
+    }
+}
+
+/// Returns the diff that reverts `changes`, given oldest first.
+fn undo_diff(changes: &[EntityChange]) -> BookDiff {
+    let mut order: Vec<Guid> = Vec::new();
+    let mut by_entity: HashMap<Guid, Vec<&EntityChange>> = HashMap::new();
+    for change in changes {
+        by_entity
+            .entry(change.guid)
+            .or_insert_with(|| {
+                order.push(change.guid);
+                Vec::new()
+            })
+            .push(change);
+    }
+    let mut reverts: Vec<EntityChange> = order
+        .iter()
+        .filter_map(|guid| net_change(&by_entity[guid]))
+        .map(reverse)
+        .collect();
+    reverts.sort_by_key(|c| c.kind);
+    BookDiff { changes: reverts }
+}
+
+/// Folds successive changes to one entity into one: each field goes
+/// from its earliest old value to its latest new one.
+fn net_change(changes: &[&EntityChange]) -> Option<EntityChange> {
+    let (first, last) = (changes.first()?, changes.last()?);
+    let existed = first.change != ChangeKind::Added;
+    let exists = last.change != ChangeKind::Removed;
+
+    let mut fields: Vec<FieldChange> = Vec::new();
+    for change in changes {
+        for field in &change.fields {
+            match fields.iter_mut().find(|f| f.field == field.field) {
+                Some(net) => net.new = field.new.clone(),
+                None => fields.push(field.clone()),
+            }
+        }
+    }
+    let change = match (existed, exists) {
+        (false, false) => return None,
+        (false, true) => ChangeKind::Added,
+        (true, false) => ChangeKind::Removed,
+        (true, true) => {
+            fields.retain(|f| f.old != f.new);
+            if fields.is_empty() {
+                return None;
+            }
+            ChangeKind::Modified
+        }
+    };
+    for field in &mut fields {
+        if !existed {
+            field.old = None;
+        }
+        if !exists {
+            field.new = None;
+        }
+    }
+    Some(EntityChange {
+        kind: last.kind,
+        guid: last.guid,
+        label: last.label.clone(),
+        change,
+        fields,
+    })
+}
+
+fn reverse(change: EntityChange) -> EntityChange {
+    EntityChange {
+        change: match change.change {
+            ChangeKind::Added => ChangeKind::Removed,
+            ChangeKind::Removed => ChangeKind::Added,
+            ChangeKind::Modified => ChangeKind::Modified,
+        },
+        fields: change
+            .fields
+            .into_iter()
+            .map(|f| FieldChange {
+                field: f.field,
+                old: f.new,
+                new: f.old,
+            })
+            .collect(),
+        ..change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    use gnucash_sys::{Account, Editable};
+
+    use super::*;
+    use crate::diff::FieldValue;
+
+    fn change(
+        kind: ChangeKind,
+        fields: &[(&'static str, Option<&str>, Option<&str>)],
+    ) -> EntityChange {
+        let value = |v: &Option<&str>| v.map(|s| FieldValue::Text(s.to_string()));
+        EntityChange {
+            kind: EntityKind::Account,
+            guid: Guid::from_bytes([1; 16]),
+            label: String::new(),
+            change: kind,
+            fields: fields
+                .iter()
+                .map(|(field, old, new)| FieldChange {
+                    field,
+                    old: value(old),
+                    new: value(new),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_undo_diff() {
+        let renamed = change(
+            ChangeKind::Modified,
+            &[("name", Some("Bank"), Some("Checking"))],
+        );
+        let coded = change(ChangeKind::Modified, &[("code", Some(""), Some("1000"))]);
+        let renamed_back = change(
+            ChangeKind::Modified,
+            &[("name", Some("Checking"), Some("Bank"))],
+        );
+
+        let diff = undo_diff(&[renamed.clone(), coded, renamed_back]);
+        assert_eq!(diff.changes.len(), 1);
+        let revert = &diff.changes[0];
+        assert_eq!(revert.change, ChangeKind::Modified);
+        // The name ended where it started; only the code is reverted.
+        assert_eq!(revert.fields.len(), 1);
+        assert_eq!(
+            revert.new_value("code"),
+            Some(&FieldValue::Text(String::new()))
+        );
+
+        let created = change(ChangeKind::Added, &[("name", None, Some("Cash"))]);
+        let diff = undo_diff(&[created.clone(), renamed.clone()]);
+        assert_eq!(diff.changes[0].change, ChangeKind::Removed);
+        assert_eq!(
+            diff.changes[0].old_value("name"),
+            Some(&FieldValue::Text("Checking".to_string()))
+        );
+
+        let removed = change(ChangeKind::Removed, &[("name", Some("Checking"), None)]);
+        assert!(undo_diff(&[created, removed]).is_empty());
+    }
+
+    #[test]
+    fn test_undo_rename() {
+        let book = Book::new();
+        let root = book.root_account().expect("books have a root account");
+        let mut account = Account::new(&book);
+        account.edit().set_name("Bank");
+        root.edit().append_child(&account);
+        account.mark_unowned();
+
+        let journal = Journal::start(&book, "alice");
+        journal.group("rename", || account.edit().set_name("Checking"));
+        let history = journal.history(account.guid());
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].user, "alice");
+        assert_eq!(history[0].group.as_deref(), Some("rename"));
+
+        let report = journal.undo_group("rename");
+        assert!(report.is_clean());
+        assert_eq!(report.applied, 1);
+        assert_eq!(account.name().as_deref(), Some("Bank"));
+        assert!(journal.is_empty());
+
+        // A panic inside a group still ends it.
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            journal.group("broken", || panic!("interrupted"));
+        }));
+        assert!(result.is_err());
+        account.edit().set_name("Savings");
+        assert_eq!(journal.history(account.guid())[0].group, None);
+    }
+
+    #[test]
+    fn test_recording_survives_busy_state_and_panics() {
+        let book = Book::new();
+        let account = Account::new(&book);
+        let journal = Journal::start(&book, "alice");
+
+        // An event raised while the journal holds its state is queued.
+        let busy = journal.shared.state.borrow_mut();
+        account.edit().set_name("Checking");
+        drop(busy);
+        assert_eq!(journal.history(account.guid()).len(), 1);
+
+        // A panic while paused does not stop recording.
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _paused = PauseGuard::new(&journal.shared);
+            panic!("merge failed");
+        }));
+        assert!(result.is_err());
+        account.edit().set_name("Savings");
+        assert_eq!(journal.history(account.guid()).len(), 2);
+    }
+}
@@ -8,6 +8,14 @@
 //! - [`price`] - Price database and price entries
 //! - [`query`] - QOF query interface
 //! - [`builder`] - Builder patterns for entity creation
+//! - [`import`] - Bank statement import (OFX, QIF)
+//! - [`export`] - Account export (QIF)
+//! - [`reports`] - Financial statements
+//! - [`reconcile`] - Statement matching for reconciliation
+//! - [`audit`] - Book integrity checks
+//! - [`duplicates`] - Duplicate transaction detection
+//! - [`diff`] - Book comparison and merge
+//! - [`journal`] - Change journal with undo
 
 // Re-export gnucash-sys for convenience
 pub use gnucash_sys;
@@ -28,25 +36,57 @@ pub mod business;
 /// `gnucash_sys::Book` / `gnucash_sys::Account`.
 pub mod engine_ext;
 
-pub use engine_ext::{AccountExt, BookExt};
+pub use engine_ext::{AccountEditorExt, AccountExt, BookExt, TransactionEditorExt, TransactionExt};
+
+/// Calendar-date helpers for `time64` values.
+pub mod date;
+
+/// Bank statement import.
+pub mod import;
+
+/// Account export.
+pub mod export;
+
+/// Financial reports.
+pub mod reports;
+
+/// Statement matching for reconciliation.
+pub mod reconcile;
+
+/// Book integrity checks.
+pub mod audit;
+
+/// Duplicate transaction detection.
+pub mod duplicates;
+
+/// Book comparison and three-way merge.
+pub mod diff;
+
+/// Change journal with undo.
+pub mod journal;
+
+pub use date::Date;
 
 // Re-export commonly used types from gnucash-sys
 pub use gnucash_sys::{
-    init_engine, is_engine_initialized, Account, Book, Error, GNCAccountType, Guid, Numeric,
-    Result, Session, SessionOpenMode, Split, Transaction,
+    Account, AccountEditor, Book, BookEditor, EditGuard, Editable, Error, GNCAccountType, Guid,
+    Numeric, ReconcileState, Result, Session, SessionOpenMode, Split, SplitEditor, Transaction,
+    TransactionEditor, init_engine, is_engine_initialized,
 };
 
 // Re-export price types
-pub use price::{Price, PriceDB, PriceSource};
+pub use price::{Price, PriceDB, PriceDBEditor, PriceEditor, PriceSource};
 
 // Re-export query types
-pub use query::{obj_types, params, QofQueryOp, Query};
+pub use query::{QofCharMatch, QofQueryOp, Query, obj_types, params};
 
 // Re-export builders
 pub use builder::{InvoiceBuilder, TransactionBuilder};
 
 // Re-export business entities
 pub use business::{
-    Address, BillTerm, Commodity, CommodityTable, Customer, Employee, Entry, Invoice, Job, Owner,
-    OwnerType, TaxTable, TaxTableEntry, TypedOwner, Vendor,
+    Address, AddressEditor, BillTerm, BillTermEditor, Commodity, CommodityTable, Customer,
+    CustomerEditor, Employee, EmployeeEditor, Entry, EntryEditor, Invoice, InvoiceEditor, Job,
+    JobEditor, Owner, OwnerType, TaxTable, TaxTableEditor, TaxTableEntry, TypedOwner, Vendor,
+    VendorEditor,
 };
@@ -4,6 +4,10 @@ use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
 use gnucash_sys::{Book, Guid, Numeric};
+use gnucash_sys::edit::{Editable, Undo};
+use gnucash_sys::slots::{SlotEditor, Slots};
+
+use crate::business::Commodity;
 
 /// A price quote for a commodity.
 pub struct Price {
@@ -49,13 +53,19 @@ impl Price {
         }
     }
 
+    /// Returns the key-value slots attached to this price.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gnc_price_begin_edit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gnc_price_commit_edit(self.ptr.as_ptr()) }
     }
 
@@ -95,6 +105,16 @@ impl Price {
         }
     }
 
+    /// Returns the commodity being priced.
+    pub fn commodity(&self) -> Option<Commodity> {
+        unsafe { Commodity::from_raw(ffi::gnc_price_get_commodity(self.ptr.as_ptr()), false) }
+    }
+
+    /// Returns the currency the price is expressed in.
+    pub fn currency(&self) -> Option<Commodity> {
+        unsafe { Commodity::from_raw(ffi::gnc_price_get_currency(self.ptr.as_ptr()), false) }
+    }
+
     /// Returns the price value.
     pub fn value(&self) -> Numeric {
         unsafe { ffi::gnc_price_get_value(self.ptr.as_ptr()).into() }
@@ -102,30 +122,40 @@ impl Price {
 
     // ==================== Setters ====================
 
+    /// Sets the commodity being priced.
+    fn set_commodity(&self, commodity: &Commodity) {
+        unsafe { ffi::gnc_price_set_commodity(self.ptr.as_ptr(), commodity.as_ptr()) }
+    }
+
+    /// Sets the currency the price is expressed in.
+    fn set_currency(&self, currency: &Commodity) {
+        unsafe { ffi::gnc_price_set_currency(self.ptr.as_ptr(), currency.as_ptr()) }
+    }
+
     /// Sets the price time.
-    pub fn set_time(&self, time: i64) {
+    fn set_time(&self, time: i64) {
         unsafe { ffi::gnc_price_set_time64(self.ptr.as_ptr(), time) }
     }
 
     /// Sets the price source.
-    pub fn set_source(&self, source: PriceSource) {
+    fn set_source(&self, source: PriceSource) {
         unsafe { ffi::gnc_price_set_source(self.ptr.as_ptr(), source) }
     }
 
     /// Sets the price source as string.
-    pub fn set_source_string(&self, source: &str) {
+    fn set_source_string(&self, source: &str) {
         let c_source = std::ffi::CString::new(source).unwrap();
         unsafe { ffi::gnc_price_set_source_string(self.ptr.as_ptr(), c_source.as_ptr()) }
     }
 
     /// Sets the price type string.
-    pub fn set_type_string(&self, type_str: &str) {
+    fn set_type_string(&self, type_str: &str) {
         let c_type = std::ffi::CString::new(type_str).unwrap();
         unsafe { ffi::gnc_price_set_typestr(self.ptr.as_ptr(), c_type.as_ptr()) }
     }
 
     /// Sets the price value.
-    pub fn set_value(&self, value: Numeric) {
+    fn set_value(&self, value: Numeric) {
         unsafe { ffi::gnc_price_set_value(self.ptr.as_ptr(), value.into()) }
     }
 
@@ -166,6 +196,97 @@ impl Drop for Price {
     }
 }
 
+/// The setters of a [`Price`] inside an edit session.
+///
+/// Obtained from [`Editable::edit`]; records each value it replaces so a
+/// rolled-back edit can put it back.
+pub struct PriceEditor<'a> {
+    price: &'a Price,
+    undo: Undo<Price>,
+}
+
+impl PriceEditor<'_> {
+    /// Returns the price's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Price> {
+        SlotEditor::new(self.price.slots(), &self.undo)
+    }
+
+    /// Sets the commodity being priced.
+    pub fn set_commodity(&self, commodity: &Commodity) {
+        let old = unsafe { ffi::gnc_price_get_commodity(self.price.as_ptr()) };
+        self.undo
+            .record(move |p| unsafe { ffi::gnc_price_set_commodity(p.as_ptr(), old) });
+        self.price.set_commodity(commodity);
+    }
+
+    /// Sets the currency the price is expressed in.
+    pub fn set_currency(&self, currency: &Commodity) {
+        let old = unsafe { ffi::gnc_price_get_currency(self.price.as_ptr()) };
+        self.undo
+            .record(move |p| unsafe { ffi::gnc_price_set_currency(p.as_ptr(), old) });
+        self.price.set_currency(currency);
+    }
+
+    /// Sets the price time.
+    pub fn set_time(&self, time: i64) {
+        let old = self.price.time();
+        self.undo.record(move |e| e.set_time(old));
+        self.price.set_time(time);
+    }
+
+    /// Sets the price source.
+    pub fn set_source(&self, source: PriceSource) {
+        let old = self.price.source();
+        self.undo.record(move |e| e.set_source(old));
+        self.price.set_source(source);
+    }
+
+    /// Sets the price source as string.
+    pub fn set_source_string(&self, source: &str) {
+        let old = self.price.source_string().unwrap_or_default();
+        self.undo.record(move |e| e.set_source_string(&old));
+        self.price.set_source_string(source);
+    }
+
+    /// Sets the price type string.
+    pub fn set_type_string(&self, type_str: &str) {
+        let old = self.price.type_string().unwrap_or_default();
+        self.undo.record(move |e| e.set_type_string(&old));
+        self.price.set_type_string(type_str);
+    }
+
+    /// Sets the price value.
+    pub fn set_value(&self, value: Numeric) {
+        let old = self.price.value();
+        self.undo.record(move |e| e.set_value(old));
+        self.price.set_value(value);
+    }
+}
+
+impl std::ops::Deref for PriceEditor<'_> {
+    type Target = Price;
+
+    fn deref(&self) -> &Price {
+        self.price
+    }
+}
+
+impl Editable for Price {
+    type Editor<'e> = PriceEditor<'e>;
+
+    fn begin_edit(&self) {
+        Price::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Price::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> PriceEditor<'_> {
+        PriceEditor { price: self, undo }
+    }
+}
+
 impl std::fmt::Debug for Price {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Price")
@@ -210,24 +331,24 @@ impl PriceDB {
     }
 
     /// Begins an edit session.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gnc_pricedb_begin_edit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gnc_pricedb_commit_edit(self.ptr.as_ptr()) }
     }
 
     /// Adds a price to the database.
     /// Returns true if the price was added successfully.
-    pub fn add_price(&self, price: &Price) -> bool {
+    fn add_price(&self, price: &Price) -> bool {
         unsafe { ffi::gnc_pricedb_add_price(self.ptr.as_ptr(), price.as_ptr()) != 0 }
     }
 
     /// Removes a price from the database.
     /// Returns true if the price was removed successfully.
-    pub fn remove_price(&self, price: &Price) -> bool {
+    fn remove_price(&self, price: &Price) -> bool {
         unsafe { ffi::gnc_pricedb_remove_price(self.ptr.as_ptr(), price.as_ptr()) != 0 }
     }
 
@@ -240,6 +361,165 @@ impl PriceDB {
     pub fn has_prices(&self) -> bool {
         self.num_prices() > 0
     }
+
+    /// Returns every price in the database, in a stable order.
+    pub fn prices(&self) -> Vec<Price> {
+        unsafe extern "C" fn collect(
+            price: *mut ffi::GNCPrice,
+            data: ffi::gpointer,
+        ) -> ffi::gboolean {
+            unsafe {
+                let prices = &mut *(data as *mut Vec<Price>);
+                prices.extend(Price::from_raw(price, false));
+            }
+            1
+        }
+
+        let mut prices: Vec<Price> = Vec::new();
+        unsafe {
+            ffi::gnc_pricedb_foreach_price(
+                self.ptr.as_ptr(),
+                Some(collect),
+                &mut prices as *mut Vec<Price> as ffi::gpointer,
+                1,
+            );
+        }
+        prices
+    }
+
+    // ==================== Lookup ====================
+
+    /// Returns the most recent price of `commodity` in `currency`.
+    pub fn lookup_latest(&self, commodity: &Commodity, currency: &Commodity) -> Option<Price> {
+        unsafe {
+            let ptr = ffi::gnc_pricedb_lookup_latest(
+                self.ptr.as_ptr(),
+                commodity.as_ptr(),
+                currency.as_ptr(),
+            );
+            Price::from_raw(ptr, true)
+        }
+    }
+
+    /// Returns the price of `commodity` in `currency` closest to `time`.
+    pub fn lookup_nearest(
+        &self,
+        commodity: &Commodity,
+        currency: &Commodity,
+        time: i64,
+    ) -> Option<Price> {
+        unsafe {
+            let ptr = ffi::gnc_pricedb_lookup_nearest_in_time64(
+                self.ptr.as_ptr(),
+                commodity.as_ptr(),
+                currency.as_ptr(),
+                time,
+            );
+            Price::from_raw(ptr, true)
+        }
+    }
+
+    /// Converts `balance` from one commodity to another at the latest
+    /// price, trying inverse and intermediate-currency quotes as well.
+    /// Returns zero if no price is found.
+    pub fn convert_latest(&self, balance: Numeric, from: &Commodity, to: &Commodity) -> Numeric {
+        unsafe {
+            ffi::gnc_pricedb_convert_balance_latest_price(
+                self.ptr.as_ptr(),
+                balance.into(),
+                from.as_ptr(),
+                to.as_ptr(),
+            )
+            .into()
+        }
+    }
+
+    /// Converts `balance` from one commodity to another at the price
+    /// closest to `time`. Returns zero if no price is found.
+    pub fn convert_nearest(
+        &self,
+        balance: Numeric,
+        from: &Commodity,
+        to: &Commodity,
+        time: i64,
+    ) -> Numeric {
+        unsafe {
+            ffi::gnc_pricedb_convert_balance_nearest_price_t64(
+                self.ptr.as_ptr(),
+                balance.into(),
+                from.as_ptr(),
+                to.as_ptr(),
+                time,
+            )
+            .into()
+        }
+    }
+}
+
+/// The prices added to and removed from a [`PriceDB`] inside an edit
+/// session.
+///
+/// Obtained from [`Editable::edit`]; records each change so a rolled-back
+/// edit can undo it.
+pub struct PriceDBEditor<'a> {
+    db: &'a PriceDB,
+    undo: Undo<PriceDB>,
+}
+
+impl PriceDBEditor<'_> {
+    /// Adds a price to the database.
+    /// Returns true if the price was added successfully.
+    pub fn add_price(&self, price: &Price) -> bool {
+        let added = self.db.add_price(price);
+        if added {
+            let price = price.clone();
+            self.undo.record(move |db| {
+                db.remove_price(&price);
+            });
+        }
+        added
+    }
+
+    /// Removes a price from the database.
+    /// Returns true if the price was removed successfully.
+    pub fn remove_price(&self, price: &Price) -> bool {
+        // Held so that a rollback can add the price back after the
+        // database has released it.
+        let kept = price.clone();
+        let removed = self.db.remove_price(price);
+        if removed {
+            self.undo.record(move |db| {
+                db.add_price(&kept);
+            });
+        }
+        removed
+    }
+}
+
+impl std::ops::Deref for PriceDBEditor<'_> {
+    type Target = PriceDB;
+
+    fn deref(&self) -> &PriceDB {
+        self.db
+    }
+}
+
+/// A price database has no fields of its own to change; an edit session
+/// on it batches the prices added and removed meanwhile.
+impl Editable for PriceDB {
+    type Editor<'e> = PriceDBEditor<'e>;
+
+    fn begin_edit(&self) {
+        PriceDB::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        PriceDB::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> PriceDBEditor<'_> {
+        PriceDBEditor { db: self, undo }
+    }
 }
 
 impl std::fmt::Debug for PriceDB {
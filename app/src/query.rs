@@ -5,12 +5,12 @@ use std::ffi::CString;
 use std::ptr::NonNull;
 
 use gnucash_sys::ffi;
-use gnucash_sys::{Account, Book, Guid, Split, Transaction};
+use gnucash_sys::{Account, Book, Guid, ReconcileState, Split, Transaction};
 
 use crate::business::Invoice;
 
 /// Re-export query enums.
-pub use gnucash_sys::ffi::QofQueryOp;
+pub use gnucash_sys::ffi::{QofCharMatch, QofQueryOp};
 
 /// A query for searching GnuCash objects.
 ///
@@ -197,6 +197,25 @@ impl Query {
         // Note: qof_query takes ownership of the param_list
     }
 
+    /// Adds a predicate on a split's reconcile state: with
+    /// `QOF_CHAR_MATCH_ANY` the split must be in one of `states`, with
+    /// `QOF_CHAR_MATCH_NONE` in none of them.
+    pub fn add_reconcile_match(
+        &self,
+        states: &[ReconcileState],
+        how: QofCharMatch,
+        op: QofQueryOp,
+    ) {
+        let chars: String = states.iter().map(|s| s.as_char()).collect();
+        let c_chars = CString::new(chars).unwrap();
+        let c_params = make_gsl(&[params::SPLIT_RECONCILE]);
+        unsafe {
+            let pred = ffi::qof_query_char_predicate(how, c_chars.as_ptr());
+            ffi::qof_query_add_term(self.ptr.as_ptr(), c_params, pred, op);
+        }
+        // Note: qof_query takes ownership of the param_list and predicate
+    }
+
     /// Merges another query into this one.
     pub fn merge(&self, other: &Query, op: QofQueryOp) {
         unsafe { ffi::qof_query_merge_in_place(self.ptr.as_ptr(), other.ptr.as_ptr(), op) }
@@ -0,0 +1,349 @@
+//! Statement matching for reconciliation.
+//!
+//! A [`StatementMatcher`] pairs the lines of a bank statement with the
+//! unreconciled splits of an account. Each line first looks for a split
+//! with exactly the same amount posted within the date tolerance,
+//! preferring one whose number or online ID equals the line's reference
+//! and then the closest date. Lines still unmatched try a combination of
+//! up to [`max_combination`](StatementMatcher::max_combination) splits
+//! adding up to the line, which is how a single bank deposit of several
+//! checks shows up.
+//!
+//! The report can tick the matched splits in a [`Reconciliation`]:
+//!
+//! ```ignore
+//! use gnucash_ext::reconcile::{StatementEntry, StatementMatcher};
+//! use gnucash_sys::Reconciliation;
+//!
+//! let entries: Vec<StatementEntry> = statement.lines().iter().map(Into::into).collect();
+//! let report = StatementMatcher::new(&checking).date_tolerance_days(5).run(&entries);
+//! let mut rec = Reconciliation::new(&checking, statement_date, closing_balance);
+//! report.apply(&mut rec)?;
+//! for &line in &report.unmatched_lines {
+//!     println!("not in the book: {:?}", entries[line]);
+//! }
+//! ```
+
+use gnucash_sys::{Account, Guid, Numeric, Reconciliation, Result};
+
+use crate::date::Date;
+use crate::import::StatementLine;
+
+/// Splits considered for a combined match, closest dates first; bounds
+/// the subset search.
+const MAX_POOL: usize = 24;
+
+/// A line from a bank statement, from any source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementEntry {
+    pub date: Date,
+    /// Amount in the account's commodity; positive for money in.
+    pub amount: Numeric,
+    /// Check number, bank reference or online ID.
+    pub reference: Option<String>,
+}
+
+impl From<&StatementLine> for StatementEntry {
+    fn from(line: &StatementLine) -> Self {
+        Self {
+            date: line.date,
+            amount: line.amount,
+            reference: line.online_id.clone().or_else(|| line.num.clone()),
+        }
+    }
+}
+
+/// A statement line matched to one or more splits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementMatch {
+    /// Index of the line in the statement.
+    pub line: usize,
+    /// The matched splits; more than one for a combined match.
+    pub splits: Vec<Guid>,
+}
+
+impl StatementMatch {
+    /// Returns true if several splits add up to the line.
+    pub fn is_combined(&self) -> bool {
+        self.splits.len() > 1
+    }
+}
+
+/// Outcome of a [`StatementMatcher::run`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatchReport {
+    /// Matched lines, in statement order.
+    pub matched: Vec<StatementMatch>,
+    /// Indices of statement lines with no counterpart in the book.
+    pub unmatched_lines: Vec<usize>,
+    /// Unreconciled splits that no statement line accounts for.
+    pub unmatched_splits: Vec<Guid>,
+}
+
+impl MatchReport {
+    /// Returns the GUIDs of all matched splits.
+    pub fn matched_splits(&self) -> impl Iterator<Item = Guid> + '_ {
+        self.matched.iter().flat_map(|m| m.splits.iter().copied())
+    }
+
+    /// Ticks the matched splits as cleared in `reconciliation`. Fails if
+    /// a matched split is not one of its candidates, for example because
+    /// it was posted after the statement date.
+    pub fn apply(&self, reconciliation: &mut Reconciliation<'_>) -> Result<()> {
+        for guid in self.matched_splits() {
+            reconciliation.set_cleared(guid, true)?;
+        }
+        Ok(())
+    }
+}
+
+/// Matches statement lines against an account's unreconciled splits.
+pub struct StatementMatcher<'a> {
+    account: &'a Account,
+    date_tolerance_days: i64,
+    max_combination: usize,
+}
+
+impl<'a> StatementMatcher<'a> {
+    /// Creates a matcher for `account` with a 3 day tolerance and
+    /// combinations of up to 4 splits.
+    pub fn new(account: &'a Account) -> Self {
+        Self {
+            account,
+            date_tolerance_days: 3,
+            max_combination: 4,
+        }
+    }
+
+    /// Sets how many days a split's posted date may differ from the
+    /// statement date. Defaults to 3.
+    pub fn date_tolerance_days(mut self, days: i64) -> Self {
+        self.date_tolerance_days = days;
+        self
+    }
+
+    /// Sets the most splits a combined match may use; below 2 disables
+    /// combined matching. Defaults to 4.
+    pub fn max_combination(mut self, splits: usize) -> Self {
+        self.max_combination = splits;
+        self
+    }
+
+    /// Matches `lines` against the account's new and cleared splits.
+    pub fn run(&self, lines: &[StatementEntry]) -> MatchReport {
+        let open: Vec<OpenSplit> = self
+            .account
+            .splits()
+            .filter(|s| s.reconcile_state().is_open())
+            .filter_map(|split| {
+                let txn = split.transaction()?;
+                Some(OpenSplit {
+                    guid: split.guid(),
+                    date: Date::from_time64(txn.date_posted()),
+                    amount: split.amount(),
+                    references: [txn.num(), split.online_id()]
+                        .into_iter()
+                        .flatten()
+                        .filter(|r| !r.is_empty())
+                        .collect(),
+                })
+            })
+            .collect();
+        match_entries(lines, &open, self.date_tolerance_days, self.max_combination)
+    }
+}
+
+struct OpenSplit {
+    guid: Guid,
+    date: Date,
+    amount: Numeric,
+    references: Vec<String>,
+}
+
+impl OpenSplit {
+    fn has_reference(&self, reference: Option<&str>) -> bool {
+        reference.is_some_and(|r| self.references.iter().any(|s| s == r))
+    }
+}
+
+fn match_entries(
+    lines: &[StatementEntry],
+    open: &[OpenSplit],
+    tolerance: i64,
+    max_combination: usize,
+) -> MatchReport {
+    let mut matched: Vec<Option<Vec<usize>>> = vec![None; lines.len()];
+    let mut used = vec![false; open.len()];
+
+    // Exact amounts: take the best pairs first so an early line cannot
+    // steal a split that fits a later one better.
+    let mut pairs = Vec::new();
+    for (l, line) in lines.iter().enumerate() {
+        for (s, split) in open.iter().enumerate() {
+            let diff = split.date.days_between(&line.date).abs();
+            if diff <= tolerance && split.amount.eq_value(&line.amount) {
+                let reference = split.has_reference(line.reference.as_deref());
+                pairs.push((!reference, diff, l, s));
+            }
+        }
+    }
+    pairs.sort();
+    for (_, _, l, s) in pairs {
+        if matched[l].is_none() && !used[s] {
+            matched[l] = Some(vec![s]);
+            used[s] = true;
+        }
+    }
+
+    // Combined deposits and withdrawals: several splits of the same
+    // sign adding up to one line.
+    if max_combination >= 2 {
+        for (l, line) in lines.iter().enumerate() {
+            if matched[l].is_some() || line.amount.is_zero() {
+                continue;
+            }
+            let mut pool: Vec<(i64, usize)> = open
+                .iter()
+                .enumerate()
+                .filter(|(s, split)| {
+                    !used[*s]
+                        && split.amount.is_negative() == line.amount.is_negative()
+                        && !split.amount.is_zero()
+                })
+                .map(|(s, split)| (split.date.days_between(&line.date).abs(), s))
+                .filter(|(diff, _)| *diff <= tolerance)
+                .collect();
+            pool.sort();
+            pool.truncate(MAX_POOL);
+            let pool: Vec<usize> = pool.into_iter().map(|(_, s)| s).collect();
+
+            let mut chosen = Vec::new();
+            if find_combination(open, &pool, line.amount, max_combination, &mut chosen) {
+                for &s in &chosen {
+                    used[s] = true;
+                }
+                matched[l] = Some(chosen);
+            }
+        }
+    }
+
+    let mut report = MatchReport::default();
+    for (l, splits) in matched.into_iter().enumerate() {
+        match splits {
+            Some(splits) => report.matched.push(StatementMatch {
+                line: l,
+                splits: splits.into_iter().map(|s| open[s].guid).collect(),
+            }),
+            None => report.unmatched_lines.push(l),
+        }
+    }
+    report.unmatched_splits = open
+        .iter()
+        .zip(&used)
+        .filter(|(_, used)| !**used)
+        .map(|(split, _)| split.guid)
+        .collect();
+    report
+}
+
+/// Depth-first search for at least two splits from `pool` summing to
+/// `remaining`. All candidates share the target's sign, so a split
+/// larger than what is left is never part of the answer.
+fn find_combination(
+    open: &[OpenSplit],
+    pool: &[usize],
+    remaining: Numeric,
+    max: usize,
+    chosen: &mut Vec<usize>,
+) -> bool {
+    if remaining.is_zero() {
+        return chosen.len() >= 2;
+    }
+    if chosen.len() == max {
+        return false;
+    }
+    for (i, &s) in pool.iter().enumerate() {
+        let amount = open[s].amount;
+        if amount.abs().cmp_value(&remaining.abs()).is_gt() {
+            continue;
+        }
+        chosen.push(s);
+        if find_combination(open, &pool[i + 1..], remaining - amount, max, chosen) {
+            return true;
+        }
+        chosen.pop();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u32, cents: i64, reference: Option<&str>) -> StatementEntry {
+        StatementEntry {
+            date: Date::new(2024, 3, day).unwrap(),
+            amount: Numeric::new(cents, 100),
+            reference: reference.map(str::to_string),
+        }
+    }
+
+    fn open(id: u8, day: u32, cents: i64, reference: Option<&str>) -> OpenSplit {
+        OpenSplit {
+            guid: Guid::from_bytes([id; 16]),
+            date: Date::new(2024, 3, day).unwrap(),
+            amount: Numeric::new(cents, 100),
+            references: reference.map(str::to_string).into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn test_exact_matches() {
+        let lines = [entry(4, -5000, Some("102")), entry(10, -5000, None)];
+        let splits = [
+            open(1, 2, -5000, None),
+            open(2, 3, -5000, Some("102")),
+            open(3, 20, 900, None),
+        ];
+        let report = match_entries(&lines, &splits, 3, 4);
+
+        // The reference wins over the closer date; the other line takes
+        // the remaining split even though it is further away.
+        assert_eq!(report.matched[0].splits, vec![splits[1].guid]);
+        assert_eq!(report.unmatched_lines, vec![1]);
+        assert_eq!(
+            report.unmatched_splits,
+            vec![splits[0].guid, splits[2].guid]
+        );
+
+        let report = match_entries(&lines, &splits, 10, 4);
+        assert_eq!(report.matched.len(), 2);
+        assert_eq!(report.matched[1].splits, vec![splits[0].guid]);
+    }
+
+    #[test]
+    fn test_combined_deposit() {
+        let lines = [entry(15, 42500, None)];
+        let splits = [
+            open(1, 12, 10000, None),
+            open(2, 13, 30000, None),
+            open(3, 13, -2500, None),
+            open(4, 14, 2500, None),
+            open(5, 14, 20000, None),
+        ];
+        let report = match_entries(&lines, &splits, 3, 4);
+        let found = &report.matched[0].splits;
+        assert!(report.matched[0].is_combined());
+        let total: Numeric = splits
+            .iter()
+            .filter(|s| found.contains(&s.guid))
+            .map(|s| s.amount)
+            .sum();
+        assert!(total.eq_value(&Numeric::new(42500, 100)));
+        assert!(!found.contains(&splits[2].guid));
+
+        let report = match_entries(&lines, &splits, 3, 1);
+        assert_eq!(report.unmatched_lines, vec![0]);
+        assert_eq!(report.unmatched_splits.len(), 5);
+    }
+}
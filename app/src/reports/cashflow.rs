@@ -0,0 +1,572 @@
+//! Cash flow statement.
+//!
+//! Every transaction that moves money in or out of the cash accounts
+//! during the period is attributed to its counter accounts, which are
+//! grouped into operating, investing and financing activities by
+//! [`CashFlowRules`]. When a transaction has several counter splits the
+//! net cash movement is allocated among them in proportion to their
+//! values; transfers between cash accounts cancel out.
+//!
+//! ```ignore
+//! use gnucash_ext::reports::{self, CashFlowActivity, CashFlowOptions, CashFlowRules, Period};
+//!
+//! let rules = CashFlowRules::default().account(&mortgage, CashFlowActivity::Financing);
+//! let options = CashFlowOptions::new().rules(rules);
+//! let flow = reports::cash_flow(&book, Period::year(2024), &usd, &options)?;
+//! print!("{}", flow.to_text());
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use gnucash_sys::{Account, Book, Error, GNCAccountType, Guid, Numeric, Result, Transaction};
+
+use super::render::{TextTable, csv_record, json_string};
+use super::{CurrencyConverter, Period};
+use crate::business::Commodity;
+use crate::engine_ext::{AccountExt, TransactionExt};
+
+/// The activity a cash movement is reported under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CashFlowActivity {
+    Operating,
+    Investing,
+    Financing,
+}
+
+impl CashFlowActivity {
+    /// All activities in display order.
+    pub const ALL: [CashFlowActivity; 3] = [Self::Operating, Self::Investing, Self::Financing];
+
+    /// Returns the display name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Operating => "Operating Activities",
+            Self::Investing => "Investing Activities",
+            Self::Financing => "Financing Activities",
+        }
+    }
+}
+
+impl fmt::Display for CashFlowActivity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Maps counter accounts to activities.
+///
+/// Rules for specific accounts (and their subaccounts) take precedence
+/// over rules by account type; anything unmatched is operating. The
+/// defaults treat income, expense, receivable, payable and credit card
+/// accounts as operating; other assets, securities and non-cash bank
+/// accounts as investing; liabilities and equity as financing.
+#[derive(Debug, Clone)]
+pub struct CashFlowRules {
+    types: HashMap<GNCAccountType, CashFlowActivity>,
+    accounts: HashMap<Guid, CashFlowActivity>,
+}
+
+impl Default for CashFlowRules {
+    fn default() -> Self {
+        use CashFlowActivity::*;
+        use GNCAccountType::*;
+        let types = HashMap::from([
+            (ACCT_TYPE_INCOME, Operating),
+            (ACCT_TYPE_EXPENSE, Operating),
+            (ACCT_TYPE_RECEIVABLE, Operating),
+            (ACCT_TYPE_PAYABLE, Operating),
+            (ACCT_TYPE_CREDIT, Operating),
+            (ACCT_TYPE_BANK, Investing),
+            (ACCT_TYPE_CASH, Investing),
+            (ACCT_TYPE_SAVINGS, Investing),
+            (ACCT_TYPE_MONEYMRKT, Investing),
+            (ACCT_TYPE_ASSET, Investing),
+            (ACCT_TYPE_STOCK, Investing),
+            (ACCT_TYPE_MUTUAL, Investing),
+            (ACCT_TYPE_CURRENCY, Investing),
+            (ACCT_TYPE_LIABILITY, Financing),
+            (ACCT_TYPE_CREDITLINE, Financing),
+            (ACCT_TYPE_EQUITY, Financing),
+        ]);
+        Self {
+            types,
+            accounts: HashMap::new(),
+        }
+    }
+}
+
+impl CashFlowRules {
+    /// Reports counter accounts of `account_type` under `activity`.
+    pub fn account_type(
+        mut self,
+        account_type: GNCAccountType,
+        activity: CashFlowActivity,
+    ) -> Self {
+        self.types.insert(account_type, activity);
+        self
+    }
+
+    /// Reports `account` and its subaccounts under `activity`, whatever
+    /// their type.
+    pub fn account(mut self, account: &Account, activity: CashFlowActivity) -> Self {
+        self.accounts.insert(account.guid(), activity);
+        self
+    }
+
+    /// Returns the activity a counter account belongs to.
+    pub fn classify(&self, account: &Account) -> CashFlowActivity {
+        let mut current = unsafe { Account::from_raw(account.as_ptr(), false) };
+        while let Some(a) = current {
+            if let Some(activity) = self.accounts.get(&a.guid()) {
+                return *activity;
+            }
+            current = a.parent();
+        }
+        self.types
+            .get(&account.account_type())
+            .copied()
+            .unwrap_or(CashFlowActivity::Operating)
+    }
+}
+
+/// Options for [`cash_flow`].
+#[derive(Debug, Clone, Default)]
+pub struct CashFlowOptions {
+    cash_accounts: Vec<Guid>,
+    rules: CashFlowRules,
+}
+
+impl CashFlowOptions {
+    /// Creates options with the default cash accounts and rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `account` and its subaccounts to the cash accounts. Without
+    /// any, all bank and cash accounts are used.
+    pub fn cash_account(mut self, account: &Account) -> Self {
+        self.cash_accounts.push(account.guid());
+        self
+    }
+
+    /// Sets the classification rules.
+    pub fn rules(mut self, rules: CashFlowRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    fn is_cash(&self, account: &Account) -> bool {
+        if self.cash_accounts.is_empty() {
+            return matches!(
+                account.account_type(),
+                GNCAccountType::ACCT_TYPE_BANK | GNCAccountType::ACCT_TYPE_CASH
+            );
+        }
+        let mut current = unsafe { Account::from_raw(account.as_ptr(), false) };
+        while let Some(a) = current {
+            if self.cash_accounts.contains(&a.guid()) {
+                return true;
+            }
+            current = a.parent();
+        }
+        false
+    }
+}
+
+/// Cash attributed to one counter account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashFlowLine {
+    /// The counter account.
+    pub guid: Guid,
+    /// Colon-separated full account name.
+    pub full_name: String,
+    /// Account type.
+    pub account_type: GNCAccountType,
+    /// Money received from the account.
+    pub inflow: Numeric,
+    /// Money paid to the account, as a positive amount.
+    pub outflow: Numeric,
+}
+
+impl CashFlowLine {
+    /// Returns inflow minus outflow.
+    pub fn net(&self) -> Numeric {
+        self.inflow - self.outflow
+    }
+}
+
+/// The lines of one activity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashFlowSection {
+    /// The activity.
+    pub activity: CashFlowActivity,
+    /// Lines sorted by account name.
+    pub lines: Vec<CashFlowLine>,
+    /// Total inflow.
+    pub inflow: Numeric,
+    /// Total outflow, as a positive amount.
+    pub outflow: Numeric,
+}
+
+impl CashFlowSection {
+    /// Returns inflow minus outflow.
+    pub fn net(&self) -> Numeric {
+        self.inflow - self.outflow
+    }
+}
+
+/// A cash flow statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashFlow {
+    /// The period covered.
+    pub period: Period,
+    /// Report currency mnemonic.
+    pub currency: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// Full names of the cash accounts.
+    pub cash_accounts: Vec<String>,
+    /// Combined cash balance before the period.
+    pub opening_balance: Numeric,
+    /// Combined cash balance at the end of the period.
+    pub closing_balance: Numeric,
+    /// Operating, investing and financing sections.
+    pub sections: Vec<CashFlowSection>,
+    /// Commodities that could not be converted for lack of a price.
+    pub missing_prices: Vec<String>,
+}
+
+/// Builds the cash flow statement for `period`.
+pub fn cash_flow(
+    book: &Book,
+    period: Period,
+    currency: &Commodity,
+    options: &CashFlowOptions,
+) -> Result<CashFlow> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let opening_time = period.start_time64() - 1;
+    let closing_time = period.end_time64();
+
+    let cash: Vec<Account> = root.descendants().filter(|a| options.is_cash(a)).collect();
+    let cash_guids: HashSet<Guid> = cash.iter().map(|a| a.guid()).collect();
+
+    let mut opening_balance = Numeric::zero();
+    let mut closing_balance = Numeric::zero();
+    let mut seen = HashSet::new();
+    let mut lines: HashMap<Guid, (CashFlowActivity, CashFlowLine)> = HashMap::new();
+    for account in &cash {
+        let commodity = account.commodity();
+        opening_balance += converter.convert(
+            account.balance_as_of_date(opening_time),
+            commodity.as_ref(),
+            opening_time,
+        );
+        closing_balance += converter.convert(
+            account.balance_as_of_date(closing_time),
+            commodity.as_ref(),
+            closing_time,
+        );
+
+        for split in account.splits() {
+            let Some(txn) = split.transaction() else {
+                continue;
+            };
+            if !period.contains(txn.date_posted()) || !seen.insert(txn.guid()) {
+                continue;
+            }
+            for (counter, amount) in attribute(&txn, &cash_guids, &converter) {
+                let (_, line) = lines.entry(counter.guid()).or_insert_with(|| {
+                    (
+                        options.rules.classify(&counter),
+                        CashFlowLine {
+                            guid: counter.guid(),
+                            full_name: counter.full_name().unwrap_or_default(),
+                            account_type: counter.account_type(),
+                            inflow: Numeric::zero(),
+                            outflow: Numeric::zero(),
+                        },
+                    )
+                });
+                if amount.is_negative() {
+                    line.outflow -= amount;
+                } else {
+                    line.inflow += amount;
+                }
+            }
+        }
+    }
+
+    let sections = CashFlowActivity::ALL
+        .iter()
+        .map(|activity| {
+            let mut section_lines: Vec<CashFlowLine> = lines
+                .values()
+                .filter(|(a, _)| a == activity)
+                .map(|(_, line)| line.clone())
+                .collect();
+            section_lines.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+            CashFlowSection {
+                activity: *activity,
+                inflow: section_lines.iter().map(|l| l.inflow).sum(),
+                outflow: section_lines.iter().map(|l| l.outflow).sum(),
+                lines: section_lines,
+            }
+        })
+        .collect();
+
+    Ok(CashFlow {
+        period,
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        cash_accounts: cash
+            .iter()
+            .map(|a| a.full_name().unwrap_or_default())
+            .collect(),
+        opening_balance,
+        closing_balance,
+        sections,
+        missing_prices: converter.missing_prices(),
+    })
+}
+
+/// Returns the cash each counter account of `txn` contributed, in the
+/// report currency. Positive amounts are money received.
+fn attribute(
+    txn: &Transaction,
+    cash: &HashSet<Guid>,
+    converter: &CurrencyConverter,
+) -> Vec<(Account, Numeric)> {
+    let mut net_cash = Numeric::zero();
+    let mut counters = Vec::new();
+    for split in txn.splits() {
+        let Some(account) = split.account() else {
+            continue;
+        };
+        if cash.contains(&account.guid()) {
+            net_cash += split.value();
+        } else if account.account_type() != GNCAccountType::ACCT_TYPE_TRADING
+            && !split.value().is_zero()
+        {
+            counters.push((account, split.value().neg()));
+        }
+    }
+    if net_cash.is_zero() || counters.is_empty() {
+        return Vec::new();
+    }
+
+    let txn_currency = txn.currency();
+    let denom = txn_currency.as_ref().map_or(100, |c| c.fraction().max(1)) as i64;
+    let weights: Vec<Numeric> = counters.iter().map(|(_, w)| *w).collect();
+    let time = txn.date_posted();
+    counters
+        .into_iter()
+        .zip(allocate(net_cash, &weights, denom))
+        .map(|((account, _), share)| {
+            (
+                account,
+                converter.convert(share, txn_currency.as_ref(), time),
+            )
+        })
+        .collect()
+}
+
+/// Splits `total` over `weights` in proportion, rounded to `denom`. The
+/// rounding remainder goes to the largest weight so the shares add up to
+/// `total` exactly. Returns zeros if the weights sum to zero.
+fn allocate(total: Numeric, weights: &[Numeric], denom: i64) -> Vec<Numeric> {
+    let sum: Numeric = weights.iter().copied().sum();
+    if sum.is_zero() {
+        return vec![Numeric::zero(); weights.len()];
+    }
+    if sum.eq_value(&total) {
+        return weights.to_vec();
+    }
+    let mut shares: Vec<Numeric> = weights
+        .iter()
+        .map(|w| {
+            total
+                .checked_mul(w)
+                .and_then(|n| n.checked_div(&sum))
+                .map_or_else(Numeric::zero, |n| n.convert(denom))
+        })
+        .collect();
+    let remainder = total - shares.iter().copied().sum::<Numeric>();
+    if let Some((largest, _)) = weights
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().cmp_value(&b.1.abs()))
+    {
+        shares[largest] += remainder;
+    }
+    shares
+}
+
+impl CashFlow {
+    /// Returns the net change in cash from all activities.
+    pub fn net_change(&self) -> Numeric {
+        self.sections.iter().map(|s| s.net()).sum()
+    }
+
+    /// Returns the part of the change in cash balances not explained by
+    /// the activities, such as the effect of exchange rate changes on
+    /// foreign cash.
+    pub fn exchange_difference(&self) -> Numeric {
+        self.closing_balance - self.opening_balance - self.net_change()
+    }
+
+    /// Returns the section for `activity`.
+    pub fn section(&self, activity: CashFlowActivity) -> Option<&CashFlowSection> {
+        self.sections.iter().find(|s| s.activity == activity)
+    }
+
+    fn amount(&self, n: Numeric) -> String {
+        n.to_decimal_string(self.places)
+    }
+
+    /// Renders the statement as a plain-text table.
+    pub fn to_text(&self) -> String {
+        let mut table = TextTable::new();
+        table.heading("Cash Flow");
+        table.heading(format!("{} ({})", self.period, self.currency));
+        table.blank();
+        table.row(
+            0,
+            "Opening cash balance",
+            vec![self.amount(self.opening_balance)],
+        );
+        for section in &self.sections {
+            table.blank();
+            table.heading(section.activity.name());
+            for line in &section.lines {
+                table.row(1, &line.full_name, vec![self.amount(line.net())]);
+            }
+            table.row(
+                0,
+                &format!("Net cash from {}", section.activity.name().to_lowercase()),
+                vec![self.amount(section.net())],
+            );
+        }
+        table.blank();
+        table.rule();
+        table.row(
+            0,
+            "Net change in cash",
+            vec![self.amount(self.net_change())],
+        );
+        let difference = self.exchange_difference();
+        if !difference.is_zero() {
+            table.row(0, "Exchange rate effect", vec![self.amount(difference)]);
+        }
+        table.row(
+            0,
+            "Closing cash balance",
+            vec![self.amount(self.closing_balance)],
+        );
+        if !self.missing_prices.is_empty() {
+            table.blank();
+            table.heading(format!("No price for: {}", self.missing_prices.join(", ")));
+        }
+        table.render()
+    }
+
+    /// Renders the statement as CSV with one record per counter account.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(
+            &mut out,
+            &["activity", "account", "inflow", "outflow", "net"],
+        );
+        for section in &self.sections {
+            for line in &section.lines {
+                csv_record(
+                    &mut out,
+                    &[
+                        section.activity.name(),
+                        &line.full_name,
+                        &self.amount(line.inflow),
+                        &self.amount(line.outflow),
+                        &self.amount(line.net()),
+                    ],
+                );
+            }
+        }
+        out
+    }
+
+    /// Renders the statement as a JSON object.
+    pub fn to_json(&self) -> String {
+        let sections: Vec<String> = self
+            .sections
+            .iter()
+            .map(|section| {
+                let lines: Vec<String> = section
+                    .lines
+                    .iter()
+                    .map(|line| {
+                        format!(
+                            "{{\"guid\":{},\"full_name\":{},\"inflow\":{},\"outflow\":{},\"net\":{}}}",
+                            json_string(&line.guid.to_string()),
+                            json_string(&line.full_name),
+                            json_string(&self.amount(line.inflow)),
+                            json_string(&self.amount(line.outflow)),
+                            json_string(&self.amount(line.net())),
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"activity\":{},\"inflow\":{},\"outflow\":{},\"net\":{},\"lines\":[{}]}}",
+                    json_string(section.activity.name()),
+                    json_string(&self.amount(section.inflow)),
+                    json_string(&self.amount(section.outflow)),
+                    json_string(&self.amount(section.net())),
+                    lines.join(",")
+                )
+            })
+            .collect();
+        let cash: Vec<String> = self.cash_accounts.iter().map(|a| json_string(a)).collect();
+        format!(
+            "{{\"period\":{{\"start\":{},\"end\":{}}},\"currency\":{},\"cash_accounts\":[{}],\"opening_balance\":{},\"closing_balance\":{},\"net_change\":{},\"sections\":[{}]}}",
+            json_string(&self.period.start.to_string()),
+            json_string(&self.period.end.to_string()),
+            json_string(&self.currency),
+            cash.join(","),
+            json_string(&self.amount(self.opening_balance)),
+            json_string(&self.amount(self.closing_balance)),
+            json_string(&self.amount(self.net_change())),
+            sections.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(cents: i64) -> Numeric {
+        Numeric::new(cents, 100)
+    }
+
+    #[test]
+    fn test_allocate() {
+        // Balanced: each counter split keeps its own value.
+        assert_eq!(
+            allocate(n(80_000), &[n(100_000), n(-20_000)], 100),
+            vec![n(100_000), n(-20_000)]
+        );
+
+        // Proportional with the rounding remainder on the largest weight.
+        let shares = allocate(n(10_000), &[n(1), n(1), n(1)], 100);
+        assert_eq!(shares, vec![n(3_333), n(3_333), n(3_334)]);
+        assert!(shares.into_iter().sum::<Numeric>().eq_value(&n(10_000)));
+
+        assert!(
+            allocate(n(500), &[n(100), n(-100)], 100)
+                .iter()
+                .all(Numeric::is_zero)
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Balance history over time.
+//!
+//! [`balance_history`] samples account balances at regular steps between
+//! two dates. Each account's splits are read once and bucketed by step,
+//! rather than asking the engine for a balance at every date. Balances
+//! include subaccounts and are converted to the report currency at the
+//! price nearest each step, so a chart shows what the holdings were
+//! worth at the time.
+//!
+//! ```ignore
+//! use gnucash_ext::reports::{self, Interval};
+//!
+//! let history = reports::net_worth(&book, start, end, Interval::Monthly, &usd)?;
+//! for (date, value) in history.dates.iter().zip(history.series[2].balances.iter()) {
+//!     println!("{} {}", date, value.to_decimal_string(2));
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use gnucash_sys::{Account, Book, Error, Guid, Numeric, Result};
+
+use super::render::{csv_record, grid, json_string};
+use super::{AccountClass, CurrencyConverter};
+use crate::business::Commodity;
+use crate::date::{Date, days_in_month};
+use crate::engine_ext::AccountExt;
+
+/// Spacing of the sample dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interval {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+}
+
+impl Interval {
+    /// Returns the sample dates: `start`, every interval after it up to
+    /// `end`, and `end` itself. Monthly and quarterly steps keep the day
+    /// of the month of `start`, clamped to shorter months.
+    pub fn steps(&self, start: Date, end: Date) -> Vec<Date> {
+        let mut steps = Vec::new();
+        let mut n = 0;
+        loop {
+            let date = match self {
+                Self::Daily => start.add_days(n),
+                Self::Weekly => start.add_days(7 * n),
+                Self::Monthly => add_months(start, n),
+                Self::Quarterly => add_months(start, 3 * n),
+            };
+            if date > end {
+                break;
+            }
+            steps.push(date);
+            n += 1;
+        }
+        if steps.last() != Some(&end) && start <= end {
+            steps.push(end);
+        }
+        steps
+    }
+}
+
+/// Returns the date `n` months after `date`, clamping the day.
+fn add_months(date: Date, n: i64) -> Date {
+    let months = date.year() as i64 * 12 + date.month() as i64 - 1 + n;
+    let year = months.div_euclid(12) as i32;
+    let month = months.rem_euclid(12) as u32 + 1;
+    let day = date.day().min(days_in_month(year, month));
+    Date::new(year, month, day).expect("clamped day is valid")
+}
+
+/// Balances of one account (or computed line) at each step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceSeries {
+    /// The account, or `None` for computed series such as net worth.
+    pub guid: Option<Guid>,
+    /// Full account name or series label.
+    pub name: String,
+    /// One balance per entry of [`BalanceHistory::dates`].
+    pub balances: Vec<Numeric>,
+}
+
+/// Balances sampled at regular dates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceHistory {
+    /// The sample dates.
+    pub dates: Vec<Date>,
+    /// Report currency mnemonic.
+    pub currency: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// One series per requested account.
+    pub series: Vec<BalanceSeries>,
+    /// Commodities that could not be converted for lack of a price.
+    pub missing_prices: Vec<String>,
+}
+
+/// Returns the balance of each account, including its subaccounts, at
+/// every step from `start` to `end`. Balances keep the engine's sign, so
+/// liability and income balances are negative.
+pub fn balance_history(
+    accounts: &[Account],
+    start: Date,
+    end: Date,
+    interval: Interval,
+    currency: &Commodity,
+) -> Result<BalanceHistory> {
+    let book = accounts
+        .first()
+        .and_then(|a| a.book())
+        .ok_or_else(|| Error::InvalidOperation("no accounts given".to_string()))?;
+    let converter = CurrencyConverter::new(&book, currency);
+    let steps = interval.steps(start, end);
+    let mut sampler = Sampler::new(&steps, &converter);
+
+    let series = accounts
+        .iter()
+        .map(|account| BalanceSeries {
+            guid: Some(account.guid()),
+            name: account.full_name().unwrap_or_default(),
+            balances: sampler.rolled_up(account),
+        })
+        .collect();
+    Ok(BalanceHistory {
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        series,
+        missing_prices: converter.missing_prices(),
+        dates: steps,
+    })
+}
+
+/// Returns three series — assets, liabilities and net worth — over all
+/// asset and liability accounts of the book. Liabilities are shown as
+/// positive amounts owed; net worth is assets minus liabilities.
+pub fn net_worth(
+    book: &Book,
+    start: Date,
+    end: Date,
+    interval: Interval,
+    currency: &Commodity,
+) -> Result<BalanceHistory> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let steps = interval.steps(start, end);
+    let mut sampler = Sampler::new(&steps, &converter);
+
+    let mut assets = vec![Numeric::zero(); steps.len()];
+    let mut liabilities = vec![Numeric::zero(); steps.len()];
+    for account in root.descendants() {
+        let target = match AccountClass::of(account.account_type()) {
+            Some(AccountClass::Asset) => &mut assets,
+            Some(AccountClass::Liability) => &mut liabilities,
+            _ => continue,
+        };
+        for (total, balance) in target.iter_mut().zip(sampler.own(&account)) {
+            *total += balance;
+        }
+    }
+    let liabilities: Vec<Numeric> = liabilities.into_iter().map(|l| -l).collect();
+    let net: Vec<Numeric> = assets
+        .iter()
+        .zip(&liabilities)
+        .map(|(a, l)| *a - *l)
+        .collect();
+
+    let series = [
+        ("Assets", assets),
+        ("Liabilities", liabilities),
+        ("Net Worth", net),
+    ]
+    .into_iter()
+    .map(|(name, balances)| BalanceSeries {
+        guid: None,
+        name: name.to_string(),
+        balances,
+    })
+    .collect();
+    Ok(BalanceHistory {
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        series,
+        missing_prices: converter.missing_prices(),
+        dates: steps,
+    })
+}
+
+/// Samples converted account balances, reading each account's splits
+/// at most once.
+struct Sampler<'a> {
+    steps: &'a [Date],
+    converter: &'a CurrencyConverter,
+    cache: HashMap<Guid, Vec<Numeric>>,
+}
+
+impl<'a> Sampler<'a> {
+    fn new(steps: &'a [Date], converter: &'a CurrencyConverter) -> Self {
+        Self {
+            steps,
+            converter,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns the account's own balances, converted at each step.
+    fn own(&mut self, account: &Account) -> Vec<Numeric> {
+        if let Some(balances) = self.cache.get(&account.guid()) {
+            return balances.clone();
+        }
+        let postings = account.splits().filter_map(|split| {
+            let txn = split.transaction()?;
+            Some((Date::from_time64(txn.date_posted()), split.amount()))
+        });
+        let commodity = account.commodity();
+        let balances: Vec<Numeric> = step_balances(postings, self.steps)
+            .into_iter()
+            .zip(self.steps)
+            .map(|(balance, step)| {
+                self.converter
+                    .convert(balance, commodity.as_ref(), step.end_time64())
+            })
+            .collect();
+        self.cache.insert(account.guid(), balances.clone());
+        balances
+    }
+
+    /// Returns the balances of the account and all its subaccounts.
+    fn rolled_up(&mut self, account: &Account) -> Vec<Numeric> {
+        let mut totals = self.own(account);
+        for descendant in account.descendants() {
+            for (total, balance) in totals.iter_mut().zip(self.own(&descendant)) {
+                *total += balance;
+            }
+        }
+        totals
+    }
+}
+
+/// Returns the running balance at the end of each step from dated
+/// amounts in any order. Amounts after the last step are ignored.
+fn step_balances(
+    postings: impl IntoIterator<Item = (Date, Numeric)>,
+    steps: &[Date],
+) -> Vec<Numeric> {
+    let mut deltas = vec![Numeric::zero(); steps.len()];
+    for (date, amount) in postings {
+        let index = steps.partition_point(|step| *step < date);
+        if let Some(delta) = deltas.get_mut(index) {
+            *delta += amount;
+        }
+    }
+    let mut balance = Numeric::zero();
+    deltas
+        .into_iter()
+        .map(|delta| {
+            balance += delta;
+            balance
+        })
+        .collect()
+}
+
+impl BalanceHistory {
+    /// Returns the series with the given name.
+    pub fn series(&self, name: &str) -> Option<&BalanceSeries> {
+        self.series.iter().find(|s| s.name == name)
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.dates
+            .iter()
+            .enumerate()
+            .map(|(i, date)| {
+                let mut row = vec![date.to_string()];
+                row.extend(
+                    self.series
+                        .iter()
+                        .map(|s| s.balances[i].to_decimal_string(self.places)),
+                );
+                row
+            })
+            .collect()
+    }
+
+    fn header(&self) -> Vec<&str> {
+        let mut header = vec!["date"];
+        header.extend(self.series.iter().map(|s| s.name.as_str()));
+        header
+    }
+
+    /// Renders the history as a table with one row per date and one
+    /// column per series.
+    pub fn to_text(&self) -> String {
+        let mut right = vec![false];
+        right.extend(self.series.iter().map(|_| true));
+        format!(
+            "Balance History ({})\n\n{}",
+            self.currency,
+            grid(&self.header(), &self.rows(), &right)
+        )
+    }
+
+    /// Renders the history as CSV with one record per date.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(&mut out, &self.header());
+        for row in self.rows() {
+            let fields: Vec<&str> = row.iter().map(String::as_str).collect();
+            csv_record(&mut out, &fields);
+        }
+        out
+    }
+
+    /// Renders the history as a JSON object with parallel arrays of dates
+    /// and balances.
+    pub fn to_json(&self) -> String {
+        let dates: Vec<String> = self
+            .dates
+            .iter()
+            .map(|d| json_string(&d.to_string()))
+            .collect();
+        let series: Vec<String> = self
+            .series
+            .iter()
+            .map(|s| {
+                let balances: Vec<String> = s
+                    .balances
+                    .iter()
+                    .map(|b| json_string(&b.to_decimal_string(self.places)))
+                    .collect();
+                format!(
+                    "{{\"guid\":{},\"name\":{},\"balances\":[{}]}}",
+                    s.guid
+                        .map_or("null".to_string(), |g| json_string(&g.to_string())),
+                    json_string(&s.name),
+                    balances.join(",")
+                )
+            })
+            .collect();
+        format!(
+            "{{\"currency\":{},\"dates\":[{}],\"series\":[{}]}}",
+            json_string(&self.currency),
+            dates.join(","),
+            series.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(year: i32, month: u32, day: u32) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_steps() {
+        assert_eq!(
+            Interval::Monthly.steps(d(2024, 1, 31), d(2024, 4, 15)),
+            vec![
+                d(2024, 1, 31),
+                d(2024, 2, 29),
+                d(2024, 3, 31),
+                d(2024, 4, 15)
+            ]
+        );
+        assert_eq!(
+            Interval::Quarterly.steps(d(2023, 11, 30), d(2024, 5, 30)),
+            vec![d(2023, 11, 30), d(2024, 2, 29), d(2024, 5, 30)]
+        );
+        assert_eq!(
+            Interval::Weekly.steps(d(2024, 1, 1), d(2024, 1, 15)).len(),
+            3
+        );
+        assert!(
+            Interval::Daily
+                .steps(d(2024, 1, 2), d(2024, 1, 1))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_step_balances() {
+        let steps = [d(2024, 1, 31), d(2024, 2, 29), d(2024, 3, 31)];
+        let postings = [
+            (d(2024, 3, 1), Numeric::from(5)),
+            (d(2023, 12, 1), Numeric::from(100)),
+            (d(2024, 1, 31), Numeric::from(-20)),
+            (d(2024, 4, 1), Numeric::from(1_000)),
+        ];
+        assert_eq!(
+            step_balances(postings, &steps),
+            vec![Numeric::from(80), Numeric::from(80), Numeric::from(85)]
+        );
+    }
+}
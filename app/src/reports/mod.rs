@@ -0,0 +1,255 @@
+//! Financial reports.
+//!
+//! Reports are computed with exact [`Numeric`] arithmetic and converted
+//! to a single report currency through the book's [`PriceDB`], at the
+//! price nearest the report date. Each report is plain data — typed rows
+//! with their depth in the account tree, subtotals and totals — and can
+//! be rendered as text, CSV or JSON.
+//!
+//! - [`statements`] - balance sheet, income statement, trial balance
+//! - [`cashflow`] - cash flow statement
+//! - [`register`] - account register and general ledger
+//! - [`portfolio`] - investment holdings, gains and income
+//! - [`history`] - balances and net worth over time
+//! - [`spending`] - income and expenses by category and payee
+//!
+//! ```ignore
+//! use gnucash_ext::reports::{self, Period};
+//! use gnucash_ext::{BookExt, Date};
+//!
+//! let usd = book.commodity_table().unwrap().lookup("CURRENCY", "USD").unwrap();
+//! let sheet = reports::balance_sheet(&book, Date::new(2024, 12, 31).unwrap(), &usd)?;
+//! print!("{}", sheet.to_text());
+//!
+//! let income = reports::income_statement(&book, Period::year(2024), &usd)?;
+//! std::fs::write("income.json", income.to_json())?;
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+
+use gnucash_sys::{Book, GNCAccountType, Numeric};
+
+use crate::business::Commodity;
+use crate::date::{Date, days_in_month};
+use crate::price::PriceDB;
+
+pub mod cashflow;
+pub mod history;
+pub mod portfolio;
+pub mod register;
+pub(crate) mod render;
+pub mod spending;
+pub mod statements;
+
+pub use cashflow::{
+    CashFlow, CashFlowActivity, CashFlowLine, CashFlowOptions, CashFlowRules, CashFlowSection,
+    cash_flow,
+};
+pub use history::{BalanceHistory, BalanceSeries, Interval, balance_history, net_worth};
+pub use portfolio::{Holding, Portfolio, PortfolioOptions, portfolio};
+pub use register::{GeneralLedger, Register, RegisterRow, general_ledger, register};
+pub use spending::{
+    ComparisonRow, PayeeTotal, Spending, SpendingComparison, SpendingOptions, compare_month,
+    spending,
+};
+pub use statements::{
+    Statement, StatementRow, StatementSection, TrialBalance, TrialBalanceRow, balance_sheet,
+    income_statement, trial_balance,
+};
+
+/// An inclusive range of calendar dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Period {
+    /// First day of the period.
+    pub start: Date,
+    /// Last day of the period.
+    pub end: Date,
+}
+
+impl Period {
+    /// Creates a period from its first and last day.
+    pub fn new(start: Date, end: Date) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the calendar month, or `None` for an invalid month.
+    pub fn month(year: i32, month: u32) -> Option<Self> {
+        Some(Self {
+            start: Date::new(year, month, 1)?,
+            end: Date::new(year, month, days_in_month(year, month))?,
+        })
+    }
+
+    /// Returns the calendar year.
+    pub fn year(year: i32) -> Self {
+        Self {
+            start: Date::new(year, 1, 1).expect("January 1st exists"),
+            end: Date::new(year, 12, 31).expect("December 31st exists"),
+        }
+    }
+
+    /// Returns true if a `time64` falls on a day within the period.
+    pub fn contains(&self, time: i64) -> bool {
+        (self.start..=self.end).contains(&Date::from_time64(time))
+    }
+
+    /// Returns 00:00:00 UTC on the first day.
+    pub fn start_time64(&self) -> i64 {
+        self.start.start_time64()
+    }
+
+    /// Returns 23:59:59 UTC on the last day.
+    pub fn end_time64(&self) -> i64 {
+        self.end.end_time64()
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} to {}", self.start, self.end)
+    }
+}
+
+/// The broad class an account type belongs to in reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountClass {
+    Asset,
+    Liability,
+    Equity,
+    Income,
+    Expense,
+    Trading,
+}
+
+impl AccountClass {
+    /// Returns the class of an account type, or `None` for root and
+    /// invalid types.
+    pub fn of(account_type: GNCAccountType) -> Option<Self> {
+        use GNCAccountType::*;
+        match account_type {
+            ACCT_TYPE_BANK | ACCT_TYPE_CASH | ACCT_TYPE_ASSET | ACCT_TYPE_STOCK
+            | ACCT_TYPE_MUTUAL | ACCT_TYPE_CURRENCY | ACCT_TYPE_RECEIVABLE | ACCT_TYPE_SAVINGS
+            | ACCT_TYPE_MONEYMRKT => Some(Self::Asset),
+            ACCT_TYPE_CREDIT | ACCT_TYPE_LIABILITY | ACCT_TYPE_PAYABLE | ACCT_TYPE_CREDITLINE => {
+                Some(Self::Liability)
+            }
+            ACCT_TYPE_EQUITY => Some(Self::Equity),
+            ACCT_TYPE_INCOME => Some(Self::Income),
+            ACCT_TYPE_EXPENSE => Some(Self::Expense),
+            ACCT_TYPE_TRADING => Some(Self::Trading),
+            _ => None,
+        }
+    }
+
+    /// Returns true for classes whose balances are credits and are shown
+    /// sign-reversed in reports.
+    pub fn is_credit(&self) -> bool {
+        !matches!(self, Self::Asset | Self::Expense)
+    }
+}
+
+/// Converts balances into a report currency through the book's price
+/// database, remembering commodities for which no price was found.
+pub struct CurrencyConverter {
+    pricedb: Option<PriceDB>,
+    currency: Commodity,
+    missing: RefCell<Vec<String>>,
+}
+
+impl CurrencyConverter {
+    /// Creates a converter into `currency` using the prices in `book`.
+    pub fn new(book: &Book, currency: &Commodity) -> Self {
+        Self {
+            pricedb: PriceDB::get(book),
+            currency: unsafe { Commodity::from_raw(currency.as_ptr(), false) }
+                .expect("commodity pointer is non-null"),
+            missing: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the report currency.
+    pub fn currency(&self) -> &Commodity {
+        &self.currency
+    }
+
+    /// Returns the report currency's mnemonic.
+    pub fn mnemonic(&self) -> String {
+        self.currency.mnemonic().unwrap_or_default()
+    }
+
+    /// Returns the number of decimal places of the report currency.
+    pub fn places(&self) -> u32 {
+        decimal_places(self.currency.fraction())
+    }
+
+    /// Converts `amount`, denominated in `commodity`, at the price
+    /// nearest `time`. Amounts without a commodity are taken to be in the
+    /// report currency already; amounts without a price convert to zero.
+    pub fn convert(&self, amount: Numeric, commodity: Option<&Commodity>, time: i64) -> Numeric {
+        let Some(commodity) = commodity else {
+            return amount;
+        };
+        if amount.is_zero() || commodity.equiv(&self.currency) {
+            return amount;
+        }
+        let converted = self
+            .pricedb
+            .as_ref()
+            .map(|db| db.convert_nearest(amount, commodity, &self.currency, time))
+            .unwrap_or_else(Numeric::zero);
+        if converted.is_zero() {
+            let name = commodity.unique_name().unwrap_or_default();
+            let mut missing = self.missing.borrow_mut();
+            if !missing.contains(&name) {
+                missing.push(name);
+            }
+        }
+        converted
+    }
+
+    /// Returns the commodities (`NAMESPACE::MNEMONIC`) that had no price.
+    pub fn missing_prices(&self) -> Vec<String> {
+        self.missing.borrow().clone()
+    }
+}
+
+/// Returns the number of decimal places for a commodity fraction such
+/// as 100 or 10000.
+pub(crate) fn decimal_places(fraction: i32) -> u32 {
+    let mut fraction = fraction.max(1);
+    let mut places = 0;
+    while fraction > 1 && fraction % 10 == 0 {
+        fraction /= 10;
+        places += 1;
+    }
+    places
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period() {
+        let feb = Period::month(2024, 2).unwrap();
+        assert_eq!(feb.end, Date::new(2024, 2, 29).unwrap());
+        assert!(feb.contains(Date::new(2024, 2, 29).unwrap().to_time64()));
+        assert!(!feb.contains(Date::new(2024, 3, 1).unwrap().start_time64()));
+        assert_eq!(Period::year(2023).to_string(), "2023-01-01 to 2023-12-31");
+        assert!(Period::month(2024, 13).is_none());
+    }
+
+    #[test]
+    fn test_account_class() {
+        use GNCAccountType::*;
+        assert_eq!(AccountClass::of(ACCT_TYPE_BANK), Some(AccountClass::Asset));
+        assert_eq!(
+            AccountClass::of(ACCT_TYPE_CREDIT),
+            Some(AccountClass::Liability)
+        );
+        assert_eq!(AccountClass::of(ACCT_TYPE_ROOT), None);
+        assert!(AccountClass::Income.is_credit());
+        assert!(!AccountClass::Expense.is_credit());
+    }
+}
@@ -0,0 +1,552 @@
+//! Investment portfolio valuation.
+//!
+//! Each stock and mutual fund account is a holding. Shares and cost
+//! basis are taken from its splits in date order: purchases add their
+//! value to the cost of the lot they belong to (or of the account when
+//! lots are not used), and sales remove the lot's average cost per share,
+//! the difference to the proceeds being the realized gain. Holdings are
+//! valued with the book's price nearest the end of the period — the
+//! latest price for a current report — falling back to the last trade's
+//! [`Split::share_price`] when the price database has none.
+//!
+//! Dividends and other income are the income-account splits of the
+//! holding's transactions, plus everything posted to income accounts
+//! linked to the holding with [`PortfolioOptions::income_account`].
+//! Sales and the gain transactions lot scrubbing creates (a zero-share
+//! split carrying the gain) are left out: their income splits are the
+//! realized gain, which is computed from the sale itself.
+//!
+//! ```ignore
+//! let options = PortfolioOptions::new()
+//!     .accounts_under(&brokerage)
+//!     .income_account(&acme, &acme_dividends);
+//! let portfolio = reports::portfolio(&book, Period::year(2024), &usd, &options)?;
+//! print!("{}", portfolio.to_text());
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use gnucash_sys::{
+    Account, Book, Error, GNCAccountType, Guid, Numeric, Result, Split, Transaction, ffi,
+};
+
+use super::render::{csv_record, grid, json_string};
+use super::{CurrencyConverter, Period, decimal_places};
+use crate::business::Commodity;
+use crate::date::Date;
+use crate::engine_ext::{AccountExt, TransactionExt};
+
+/// Options for [`portfolio`].
+#[derive(Debug, Clone, Default)]
+pub struct PortfolioOptions {
+    parents: Vec<Guid>,
+    income_links: Vec<(Guid, Guid)>,
+}
+
+impl PortfolioOptions {
+    /// Creates options covering every stock and mutual fund account.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the report to holdings below `account`. May be given more
+    /// than once.
+    pub fn accounts_under(mut self, account: &Account) -> Self {
+        self.parents.push(account.guid());
+        self
+    }
+
+    /// Counts everything posted to `income` in the period as income of
+    /// `holding`, e.g. a per-security dividends account.
+    pub fn income_account(mut self, holding: &Account, income: &Account) -> Self {
+        self.income_links.push((holding.guid(), income.guid()));
+        self
+    }
+
+    fn includes(&self, account: &Account) -> bool {
+        if !matches!(
+            account.account_type(),
+            GNCAccountType::ACCT_TYPE_STOCK | GNCAccountType::ACCT_TYPE_MUTUAL
+        ) {
+            return false;
+        }
+        if self.parents.is_empty() {
+            return true;
+        }
+        let mut current = account.parent();
+        while let Some(a) = current {
+            if self.parents.contains(&a.guid()) {
+                return true;
+            }
+            current = a.parent();
+        }
+        false
+    }
+}
+
+/// One security account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    /// The account.
+    pub guid: Guid,
+    /// Colon-separated full account name.
+    pub full_name: String,
+    /// Commodity mnemonic (ticker).
+    pub symbol: String,
+    /// Shares held at the end of the period.
+    pub shares: Numeric,
+    /// Decimal places of the share quantity.
+    pub share_places: u32,
+    /// Price per share in the report currency.
+    pub price: Numeric,
+    /// Shares times price.
+    pub market_value: Numeric,
+    /// Cost of the shares held.
+    pub cost_basis: Numeric,
+    /// Market value minus cost basis.
+    pub unrealized_gain: Numeric,
+    /// Proceeds minus cost of shares sold during the period.
+    pub realized_gain: Numeric,
+    /// Cost of the shares sold during the period.
+    pub sold_cost: Numeric,
+    /// Dividends and distributions received during the period.
+    pub income: Numeric,
+}
+
+impl Holding {
+    /// Returns the total gain — unrealized, realized and income.
+    pub fn total_return(&self) -> Numeric {
+        self.unrealized_gain + self.realized_gain + self.income
+    }
+
+    /// Returns the total return as a fraction of the money invested in
+    /// the shares held and sold, or `None` if nothing was invested.
+    pub fn rate_of_return(&self) -> Option<f64> {
+        let invested = self.cost_basis + self.sold_cost;
+        if invested.is_zero() {
+            return None;
+        }
+        Some(self.total_return().to_f64() / invested.to_f64())
+    }
+}
+
+/// Holdings valued in one report currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portfolio {
+    /// The period covered.
+    pub period: Period,
+    /// Report currency mnemonic.
+    pub currency: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// Holdings sorted by account name.
+    pub holdings: Vec<Holding>,
+    /// Commodities that could not be converted for lack of a price.
+    pub missing_prices: Vec<String>,
+}
+
+/// Values the book's security holdings at the end of `period`, with the
+/// gains and income of the period.
+pub fn portfolio(
+    book: &Book,
+    period: Period,
+    currency: &Commodity,
+    options: &PortfolioOptions,
+) -> Result<Portfolio> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let denom = converter.currency().fraction().max(1) as i64;
+
+    let mut holdings = Vec::new();
+    for account in root.descendants().filter(|a| options.includes(a)) {
+        let links: Vec<Guid> = options
+            .income_links
+            .iter()
+            .filter(|(holding, _)| *holding == account.guid())
+            .map(|(_, income)| *income)
+            .collect();
+        let holding = value_holding(&root, &account, period, &converter, denom, &links);
+        if !holding.shares.is_zero()
+            || !holding.realized_gain.is_zero()
+            || !holding.income.is_zero()
+        {
+            holdings.push(holding);
+        }
+    }
+    holdings.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+
+    Ok(Portfolio {
+        period,
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        holdings,
+        missing_prices: converter.missing_prices(),
+    })
+}
+
+fn value_holding(
+    root: &Account,
+    account: &Account,
+    period: Period,
+    converter: &CurrencyConverter,
+    denom: i64,
+    links: &[Guid],
+) -> Holding {
+    let mut tracker = CostTracker::new(denom);
+    let mut income = Numeric::zero();
+    let mut counted = HashSet::new();
+    let mut last_trade: Option<(Numeric, Option<Commodity>, i64)> = None;
+
+    for split in account.splits() {
+        let Some(txn) = split.transaction() else {
+            continue;
+        };
+        let time = txn.date_posted();
+        let date = Date::from_time64(time);
+        if date > period.end {
+            continue;
+        }
+        let txn_currency = txn.currency();
+        let shares = split.amount();
+        if !shares.is_zero() {
+            let value = converter.convert(split.value(), txn_currency.as_ref(), time);
+            tracker.record(lot_guid(&split), shares, value, date >= period.start);
+            if !split.value().is_zero() {
+                last_trade = Some((split.share_price(), txn.currency(), time));
+            }
+        }
+        if date >= period.start
+            && counted.insert(txn.guid())
+            && is_income(&holding_legs(&txn, account))
+        {
+            for other in txn.splits() {
+                if other
+                    .account()
+                    .is_some_and(|a| a.account_type() == GNCAccountType::ACCT_TYPE_INCOME)
+                {
+                    income -= converter.convert(other.value(), txn_currency.as_ref(), time);
+                }
+            }
+        }
+    }
+
+    for income_account in root.descendants().filter(|a| links.contains(&a.guid())) {
+        for split in income_account.splits() {
+            let Some(txn) = split.transaction() else {
+                continue;
+            };
+            let time = txn.date_posted();
+            if period.contains(time)
+                && counted.insert(txn.guid())
+                && is_income(&holding_legs(&txn, account))
+            {
+                income -= converter.convert(split.value(), txn.currency().as_ref(), time);
+            }
+        }
+    }
+
+    let commodity = account.commodity();
+    let shares = tracker.shares();
+    let time = period.end_time64();
+    let mut market_value = converter.convert(shares, commodity.as_ref(), time);
+    if market_value.is_zero()
+        && !shares.is_zero()
+        && let Some((price, price_currency, trade_time)) = last_trade
+    {
+        let price = converter.convert(price, price_currency.as_ref(), trade_time);
+        market_value = (shares * price).convert(denom);
+    }
+    let price = if shares.is_zero() {
+        Numeric::zero()
+    } else {
+        (market_value / shares).convert(10_000)
+    };
+    let cost_basis = tracker.cost_basis();
+
+    Holding {
+        guid: account.guid(),
+        full_name: account.full_name().unwrap_or_default(),
+        symbol: commodity
+            .as_ref()
+            .and_then(|c| c.mnemonic())
+            .unwrap_or_default(),
+        shares,
+        share_places: decimal_places(account.commodity_scu()),
+        price,
+        market_value,
+        cost_basis,
+        unrealized_gain: market_value - cost_basis,
+        realized_gain: tracker.realized,
+        sold_cost: tracker.sold_cost,
+        income,
+    }
+}
+
+/// Returns the (shares, value) of each split of `txn` in `account`.
+fn holding_legs(txn: &Transaction, account: &Account) -> Vec<(Numeric, Numeric)> {
+    let guid = account.guid();
+    txn.splits()
+        .filter(|s| s.account().is_some_and(|a| a.guid() == guid))
+        .map(|s| (s.amount(), s.value()))
+        .collect()
+}
+
+/// Returns true if a transaction with these holding splits can carry
+/// dividend income: not a sale, and not a gain transaction (no shares
+/// but a value).
+fn is_income(legs: &[(Numeric, Numeric)]) -> bool {
+    !legs
+        .iter()
+        .any(|(shares, value)| shares.is_negative() || (shares.is_zero() && !value.is_zero()))
+}
+
+/// Returns the GUID of the lot a split is assigned to.
+fn lot_guid(split: &Split) -> Option<Guid> {
+    unsafe {
+        let lot = ffi::xaccSplitGetLot(split.as_ptr());
+        if lot.is_null() {
+            return None;
+        }
+        let guid = ffi::qof_instance_get_guid(lot as *const std::ffi::c_void);
+        (!guid.is_null()).then(|| Guid::from_bytes((*guid).reserved))
+    }
+}
+
+/// Tracks shares and cost per lot, with splits outside any lot sharing
+/// one average-cost pool.
+struct CostTracker {
+    pools: HashMap<Option<Guid>, (Numeric, Numeric)>,
+    denom: i64,
+    realized: Numeric,
+    sold_cost: Numeric,
+}
+
+impl CostTracker {
+    fn new(denom: i64) -> Self {
+        Self {
+            pools: HashMap::new(),
+            denom,
+            realized: Numeric::zero(),
+            sold_cost: Numeric::zero(),
+        }
+    }
+
+    /// Records a purchase (positive shares) or sale (negative shares)
+    /// with its value. Gains on sales count only `in_period`.
+    fn record(&mut self, lot: Option<Guid>, shares: Numeric, value: Numeric, in_period: bool) {
+        let (held, cost) = self
+            .pools
+            .entry(lot)
+            .or_insert((Numeric::zero(), Numeric::zero()));
+        if shares.is_positive() {
+            *held += shares;
+            *cost += value;
+            return;
+        }
+        let sold = shares.abs();
+        let removed = if !held.is_positive() || sold.cmp_value(held).is_ge() {
+            *cost
+        } else {
+            (*cost * sold / *held).convert(self.denom)
+        };
+        *held -= sold;
+        *cost -= removed;
+        if in_period {
+            self.realized += value.neg() - removed;
+            self.sold_cost += removed;
+        }
+    }
+
+    fn shares(&self) -> Numeric {
+        self.pools.values().map(|(shares, _)| *shares).sum()
+    }
+
+    fn cost_basis(&self) -> Numeric {
+        self.pools.values().map(|(_, cost)| *cost).sum()
+    }
+}
+
+impl Portfolio {
+    /// Returns the total market value.
+    pub fn market_value(&self) -> Numeric {
+        self.holdings.iter().map(|h| h.market_value).sum()
+    }
+
+    /// Returns the total cost basis.
+    pub fn cost_basis(&self) -> Numeric {
+        self.holdings.iter().map(|h| h.cost_basis).sum()
+    }
+
+    /// Returns the total unrealized gain.
+    pub fn unrealized_gain(&self) -> Numeric {
+        self.holdings.iter().map(|h| h.unrealized_gain).sum()
+    }
+
+    /// Returns the total realized gain.
+    pub fn realized_gain(&self) -> Numeric {
+        self.holdings.iter().map(|h| h.realized_gain).sum()
+    }
+
+    /// Returns the total income.
+    pub fn income(&self) -> Numeric {
+        self.holdings.iter().map(|h| h.income).sum()
+    }
+
+    fn amount(&self, n: Numeric) -> String {
+        n.to_decimal_string(self.places)
+    }
+
+    fn rate(rate: Option<f64>) -> String {
+        rate.map_or(String::new(), |r| format!("{:.2}%", r * 100.0))
+    }
+
+    fn cells(&self, h: &Holding) -> Vec<String> {
+        vec![
+            h.full_name.clone(),
+            h.symbol.clone(),
+            h.shares.to_decimal_string(h.share_places),
+            h.price.to_decimal_string(4),
+            self.amount(h.market_value),
+            self.amount(h.cost_basis),
+            self.amount(h.unrealized_gain),
+            self.amount(h.realized_gain),
+            self.amount(h.income),
+            Self::rate(h.rate_of_return()),
+        ]
+    }
+
+    /// Renders the portfolio as a plain-text table.
+    pub fn to_text(&self) -> String {
+        let mut rows: Vec<Vec<String>> = self.holdings.iter().map(|h| self.cells(h)).collect();
+        rows.push(vec![
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            self.amount(self.market_value()),
+            self.amount(self.cost_basis()),
+            self.amount(self.unrealized_gain()),
+            self.amount(self.realized_gain()),
+            self.amount(self.income()),
+            String::new(),
+        ]);
+        let mut out = format!("Portfolio\n{} ({})\n\n", self.period, self.currency);
+        out.push_str(&grid(
+            &HEADER,
+            &rows,
+            &[false, false, true, true, true, true, true, true, true, true],
+        ));
+        if !self.missing_prices.is_empty() {
+            out.push_str(&format!(
+                "\nNo price for: {}\n",
+                self.missing_prices.join(", ")
+            ));
+        }
+        out
+    }
+
+    /// Renders the portfolio as CSV with one record per holding.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(&mut out, &HEADER);
+        for holding in &self.holdings {
+            let cells = self.cells(holding);
+            let fields: Vec<&str> = cells.iter().map(String::as_str).collect();
+            csv_record(&mut out, &fields);
+        }
+        out
+    }
+
+    /// Renders the portfolio as a JSON object. The rate of return is a
+    /// fraction, or `null`.
+    pub fn to_json(&self) -> String {
+        let holdings: Vec<String> = self
+            .holdings
+            .iter()
+            .map(|h| {
+                format!(
+                    "{{\"guid\":{},\"full_name\":{},\"symbol\":{},\"shares\":{},\"price\":{},\"market_value\":{},\"cost_basis\":{},\"unrealized_gain\":{},\"realized_gain\":{},\"income\":{},\"rate_of_return\":{}}}",
+                    json_string(&h.guid.to_string()),
+                    json_string(&h.full_name),
+                    json_string(&h.symbol),
+                    json_string(&h.shares.to_decimal_string(h.share_places)),
+                    json_string(&h.price.to_decimal_string(4)),
+                    json_string(&self.amount(h.market_value)),
+                    json_string(&self.amount(h.cost_basis)),
+                    json_string(&self.amount(h.unrealized_gain)),
+                    json_string(&self.amount(h.realized_gain)),
+                    json_string(&self.amount(h.income)),
+                    h.rate_of_return()
+                        .filter(|r| r.is_finite())
+                        .map_or("null".to_string(), |r| format!("{:.6}", r)),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"period\":{{\"start\":{},\"end\":{}}},\"currency\":{},\"market_value\":{},\"cost_basis\":{},\"holdings\":[{}]}}",
+            json_string(&self.period.start.to_string()),
+            json_string(&self.period.end.to_string()),
+            json_string(&self.currency),
+            json_string(&self.amount(self.market_value())),
+            json_string(&self.amount(self.cost_basis())),
+            holdings.join(",")
+        )
+    }
+}
+
+const HEADER: [&str; 10] = [
+    "account",
+    "symbol",
+    "shares",
+    "price",
+    "market_value",
+    "cost_basis",
+    "unrealized_gain",
+    "realized_gain",
+    "income",
+    "rate_of_return",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(cents: i64) -> Numeric {
+        Numeric::new(cents, 100)
+    }
+
+    #[test]
+    fn test_cost_tracker() {
+        let lot_a = Some(Guid::from_bytes([1; 16]));
+        let lot_b = Some(Guid::from_bytes([2; 16]));
+        let mut tracker = CostTracker::new(100);
+        tracker.record(lot_a, 10.into(), n(100_000), false);
+        tracker.record(lot_b, 10.into(), n(150_000), false);
+        // A 2:1 stock split adds shares without changing the cost.
+        tracker.record(lot_b, 10.into(), Numeric::zero(), false);
+
+        // Selling half of lot B at 80.00 a share.
+        tracker.record(lot_b, Numeric::from(-10), n(-80_000), true);
+        assert!(tracker.sold_cost.eq_value(&n(75_000)));
+        assert!(tracker.realized.eq_value(&n(5_000)));
+        assert!(tracker.shares().eq_value(&20.into()));
+        assert!(tracker.cost_basis().eq_value(&n(175_000)));
+
+        // Sales outside the period move cost but no gain.
+        tracker.record(lot_a, Numeric::from(-10), n(-200_000), false);
+        assert!(tracker.realized.eq_value(&n(5_000)));
+        assert!(tracker.cost_basis().eq_value(&n(75_000)));
+    }
+
+    #[test]
+    fn test_is_income() {
+        // A dividend: the holding is not touched, or only by a reinvestment.
+        assert!(is_income(&[]));
+        assert!(is_income(&[(Numeric::from(2), n(5_000))]));
+        // A sale, and the gain transaction lot scrubbing books for it.
+        assert!(!is_income(&[(Numeric::from(-10), n(-80_000))]));
+        assert!(!is_income(&[(Numeric::zero(), n(5_000))]));
+    }
+}
@@ -0,0 +1,455 @@
+//! Account register and general ledger.
+//!
+//! A [`Register`] lists an account's splits in a period the way the
+//! GnuCash register shows them: amounts in the account's commodity,
+//! debits and credits in separate columns and a running balance that
+//! starts from the balance before the period.
+//!
+//! ```ignore
+//! let checking = root.lookup_by_full_name("Assets:Checking").unwrap();
+//! let register = reports::register(&checking, Period::month(2024, 3).unwrap());
+//! print!("{}", register.to_text());
+//!
+//! let ledger = reports::general_ledger(&book, Period::year(2024))?;
+//! std::fs::write("ledger.csv", ledger.to_csv())?;
+//! ```
+
+use gnucash_sys::{
+    Account, Book, Error, Guid, Numeric, ReconcileState, Result, Split, Transaction,
+};
+
+use super::render::{csv_record, grid, json_string};
+use super::{Period, decimal_places};
+use crate::date::Date;
+use crate::engine_ext::AccountExt;
+
+/// Transfer column text for splits with several counter splits.
+pub const SPLIT_TRANSACTION: &str = "-- Split Transaction --";
+
+/// One split of a [`Register`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterRow {
+    /// The split.
+    pub split: Guid,
+    /// The split's transaction.
+    pub transaction: Guid,
+    /// Posted date.
+    pub date: Date,
+    /// Check number, or the split action when the book uses it as num.
+    pub num: String,
+    /// Transaction description.
+    pub description: String,
+    /// Split memo.
+    pub memo: String,
+    /// Full name of the counter account, [`SPLIT_TRANSACTION`] for
+    /// several, or empty for none.
+    pub transfer: String,
+    /// Debit amount, or zero.
+    pub debit: Numeric,
+    /// Credit amount as a positive number, or zero.
+    pub credit: Numeric,
+    /// Running balance after this split.
+    pub balance: Numeric,
+    /// Reconcile state, written as its flag: `n`, `c`, `y`, `f` or `v`.
+    pub reconcile: ReconcileState,
+}
+
+impl RegisterRow {
+    /// Returns the split amount, debit minus credit.
+    pub fn amount(&self) -> Numeric {
+        self.debit - self.credit
+    }
+}
+
+/// The splits of one account in a period, with running balances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register {
+    /// The account.
+    pub account: Guid,
+    /// Colon-separated full account name.
+    pub full_name: String,
+    /// Mnemonic of the account's commodity.
+    pub commodity: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// The period covered.
+    pub period: Period,
+    /// Balance before the first day of the period.
+    pub opening_balance: Numeric,
+    /// Balance at the end of the period.
+    pub closing_balance: Numeric,
+    /// Splits in posted-date order.
+    pub rows: Vec<RegisterRow>,
+}
+
+/// Builds the register of `account` for `period`.
+pub fn register(account: &Account, period: Period) -> Register {
+    let split_action_for_num = account
+        .splits()
+        .next()
+        .and_then(|s| s.book())
+        .is_some_and(|b| b.use_split_action_for_num_field());
+
+    let mut opening_balance = Numeric::zero();
+    let mut balance = Numeric::zero();
+    let mut rows = Vec::new();
+    for split in account.splits() {
+        let Some(txn) = split.transaction() else {
+            continue;
+        };
+        let date = Date::from_time64(txn.date_posted());
+        if date > period.end {
+            continue;
+        }
+        let amount = split.amount();
+        balance += amount;
+        if date < period.start {
+            opening_balance += amount;
+            continue;
+        }
+        let (debit, credit) = if amount.is_negative() {
+            (Numeric::zero(), -amount)
+        } else {
+            (amount, Numeric::zero())
+        };
+        let num = if split_action_for_num {
+            split.action()
+        } else {
+            txn.num()
+        };
+        rows.push(RegisterRow {
+            split: split.guid(),
+            transaction: txn.guid(),
+            date,
+            num: num.unwrap_or_default(),
+            description: txn.description().unwrap_or_default(),
+            memo: split.memo().unwrap_or_default(),
+            transfer: transfer(&split, &txn),
+            debit,
+            credit,
+            balance,
+            reconcile: split.reconcile_state(),
+        });
+    }
+
+    let commodity = account.commodity();
+    Register {
+        account: account.guid(),
+        full_name: account.full_name().unwrap_or_default(),
+        commodity: commodity
+            .as_ref()
+            .and_then(|c| c.mnemonic())
+            .unwrap_or_default(),
+        places: decimal_places(account.commodity_scu()),
+        period,
+        opening_balance,
+        closing_balance: balance,
+        rows,
+    }
+}
+
+/// Returns the transfer column for a split.
+fn transfer(split: &Split, txn: &Transaction) -> String {
+    if let Some(other) = split.other_split() {
+        return other
+            .account()
+            .and_then(|a| a.full_name())
+            .unwrap_or_default();
+    }
+    let guid = split.guid();
+    let mut others = txn.splits().filter(|s| s.guid() != guid);
+    match (others.next(), others.next()) {
+        (None, _) => String::new(),
+        (Some(other), None) => other
+            .account()
+            .and_then(|a| a.full_name())
+            .unwrap_or_default(),
+        _ => SPLIT_TRANSACTION.to_string(),
+    }
+}
+
+impl Register {
+    /// Returns the sum of the debit column.
+    pub fn total_debit(&self) -> Numeric {
+        self.rows.iter().map(|r| r.debit).sum()
+    }
+
+    /// Returns the sum of the credit column.
+    pub fn total_credit(&self) -> Numeric {
+        self.rows.iter().map(|r| r.credit).sum()
+    }
+
+    fn amount(&self, n: Numeric) -> String {
+        n.to_decimal_string(self.places)
+    }
+
+    fn column(&self, n: Numeric) -> String {
+        if n.is_zero() {
+            String::new()
+        } else {
+            self.amount(n)
+        }
+    }
+
+    /// Renders the register as a plain-text table.
+    pub fn to_text(&self) -> String {
+        let mut rows = vec![vec![
+            String::new(),
+            String::new(),
+            "Opening Balance".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            self.amount(self.opening_balance),
+        ]];
+        for row in &self.rows {
+            rows.push(vec![
+                row.date.to_string(),
+                row.num.clone(),
+                row.description.clone(),
+                row.transfer.clone(),
+                row.reconcile.to_string(),
+                self.column(row.debit),
+                self.column(row.credit),
+                self.amount(row.balance),
+            ]);
+        }
+        rows.push(vec![
+            String::new(),
+            String::new(),
+            "Closing Balance".to_string(),
+            String::new(),
+            String::new(),
+            self.amount(self.total_debit()),
+            self.amount(self.total_credit()),
+            self.amount(self.closing_balance),
+        ]);
+        format!(
+            "{} ({})\n{}\n\n{}",
+            self.full_name,
+            self.commodity,
+            self.period,
+            grid(
+                &[
+                    "Date",
+                    "Num",
+                    "Description",
+                    "Transfer",
+                    "R",
+                    "Debit",
+                    "Credit",
+                    "Balance",
+                ],
+                &rows,
+                &[false, false, false, false, false, true, true, true],
+            )
+        )
+    }
+
+    fn write_csv(&self, out: &mut String) {
+        csv_record(
+            out,
+            &[
+                &self.full_name,
+                "",
+                "",
+                "Opening Balance",
+                "",
+                "",
+                "",
+                "",
+                "",
+                &self.amount(self.opening_balance),
+            ],
+        );
+        for row in &self.rows {
+            csv_record(
+                out,
+                &[
+                    &self.full_name,
+                    &row.date.to_string(),
+                    &row.num,
+                    &row.description,
+                    &row.memo,
+                    &row.transfer,
+                    &row.reconcile.to_string(),
+                    &self.column(row.debit),
+                    &self.column(row.credit),
+                    &self.amount(row.balance),
+                ],
+            );
+        }
+        csv_record(
+            out,
+            &[
+                &self.full_name,
+                "",
+                "",
+                "Closing Balance",
+                "",
+                "",
+                "",
+                &self.amount(self.total_debit()),
+                &self.amount(self.total_credit()),
+                &self.amount(self.closing_balance),
+            ],
+        );
+    }
+
+    /// Renders the register as CSV, with the opening and closing
+    /// balances as the first and last records.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(&mut out, &CSV_HEADER);
+        self.write_csv(&mut out);
+        out
+    }
+
+    /// Renders the register as a JSON object.
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                format!(
+                    "{{\"split\":{},\"transaction\":{},\"date\":{},\"num\":{},\"description\":{},\"memo\":{},\"transfer\":{},\"debit\":{},\"credit\":{},\"balance\":{},\"reconcile\":{}}}",
+                    json_string(&row.split.to_string()),
+                    json_string(&row.transaction.to_string()),
+                    json_string(&row.date.to_string()),
+                    json_string(&row.num),
+                    json_string(&row.description),
+                    json_string(&row.memo),
+                    json_string(&row.transfer),
+                    json_string(&self.amount(row.debit)),
+                    json_string(&self.amount(row.credit)),
+                    json_string(&self.amount(row.balance)),
+                    json_string(&row.reconcile.to_string()),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"account\":{},\"full_name\":{},\"commodity\":{},\"period\":{{\"start\":{},\"end\":{}}},\"opening_balance\":{},\"closing_balance\":{},\"rows\":[{}]}}",
+            json_string(&self.account.to_string()),
+            json_string(&self.full_name),
+            json_string(&self.commodity),
+            json_string(&self.period.start.to_string()),
+            json_string(&self.period.end.to_string()),
+            json_string(&self.amount(self.opening_balance)),
+            json_string(&self.amount(self.closing_balance)),
+            rows.join(",")
+        )
+    }
+}
+
+const CSV_HEADER: [&str; 10] = [
+    "account",
+    "date",
+    "num",
+    "description",
+    "memo",
+    "transfer",
+    "reconcile",
+    "debit",
+    "credit",
+    "balance",
+];
+
+/// The registers of every account in a period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneralLedger {
+    /// The period covered.
+    pub period: Period,
+    /// One register per account, in account tree order.
+    pub registers: Vec<Register>,
+}
+
+/// Builds the general ledger for `period`. Accounts without splits in
+/// the period and with a zero opening balance are left out.
+pub fn general_ledger(book: &Book, period: Period) -> Result<GeneralLedger> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let registers = root
+        .descendants()
+        .map(|account| register(&account, period))
+        .filter(|r| !r.rows.is_empty() || !r.opening_balance.is_zero())
+        .collect();
+    Ok(GeneralLedger { period, registers })
+}
+
+impl GeneralLedger {
+    /// Renders every register as text, separated by blank lines.
+    pub fn to_text(&self) -> String {
+        self.registers
+            .iter()
+            .map(Register::to_text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders every register into one CSV table.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(&mut out, &CSV_HEADER);
+        for register in &self.registers {
+            register.write_csv(&mut out);
+        }
+        out
+    }
+
+    /// Renders the ledger as a JSON object.
+    pub fn to_json(&self) -> String {
+        let registers: Vec<String> = self.registers.iter().map(Register::to_json).collect();
+        format!(
+            "{{\"period\":{{\"start\":{},\"end\":{}}},\"registers\":[{}]}}",
+            json_string(&self.period.start.to_string()),
+            json_string(&self.period.end.to_string()),
+            registers.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_rendering() {
+        let date = Date::new(2024, 3, 5).unwrap();
+        let register = Register {
+            account: Guid::from_bytes([1; 16]),
+            full_name: "Assets:Checking".into(),
+            commodity: "USD".into(),
+            places: 2,
+            period: Period::month(2024, 3).unwrap(),
+            opening_balance: Numeric::new(10_000, 100),
+            closing_balance: Numeric::new(7_500, 100),
+            rows: vec![RegisterRow {
+                split: Guid::from_bytes([2; 16]),
+                transaction: Guid::from_bytes([3; 16]),
+                date,
+                num: "101".into(),
+                description: "Groceries, weekly".into(),
+                memo: String::new(),
+                transfer: SPLIT_TRANSACTION.into(),
+                debit: Numeric::zero(),
+                credit: Numeric::new(2_500, 100),
+                balance: Numeric::new(7_500, 100),
+                reconcile: ReconcileState::Cleared,
+            }],
+        };
+        assert_eq!(
+            register.to_csv(),
+            concat!(
+                "account,date,num,description,memo,transfer,reconcile,debit,credit,balance\n",
+                "Assets:Checking,,,Opening Balance,,,,,,100.00\n",
+                "Assets:Checking,2024-03-05,101,\"Groceries, weekly\",,-- Split Transaction --,c,,25.00,75.00\n",
+                "Assets:Checking,,,Closing Balance,,,,0.00,25.00,75.00\n",
+            )
+        );
+        let text = register.to_text();
+        assert!(text.starts_with("Assets:Checking (USD)\n2024-03-01 to 2024-03-31\n\nDate"));
+        assert!(text.contains("2024-03-05  101  Groceries, weekly  -- Split Transaction --  c"));
+    }
+}
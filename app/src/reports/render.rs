@@ -0,0 +1,181 @@
+//! Text, CSV and JSON building blocks shared by the reports.
+
+/// One line of a [`TextTable`].
+enum Line {
+    Heading(String),
+    Row(String, Vec<String>),
+    Rule,
+    Blank,
+}
+
+/// A plain-text table of labels followed by right-aligned amount
+/// columns.
+#[derive(Default)]
+pub(crate) struct TextTable {
+    lines: Vec<Line>,
+}
+
+impl TextTable {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a line spanning the whole table.
+    pub(crate) fn heading(&mut self, text: impl Into<String>) {
+        self.lines.push(Line::Heading(text.into()));
+    }
+
+    /// Adds a label indented by `depth` levels, followed by columns.
+    pub(crate) fn row(&mut self, depth: usize, label: &str, columns: Vec<String>) {
+        self.lines.push(Line::Row(
+            format!("{}{}", "  ".repeat(depth), label),
+            columns,
+        ));
+    }
+
+    /// Adds a horizontal rule.
+    pub(crate) fn rule(&mut self) {
+        self.lines.push(Line::Rule);
+    }
+
+    /// Adds an empty line.
+    pub(crate) fn blank(&mut self) {
+        self.lines.push(Line::Blank);
+    }
+
+    /// Renders the table with every column as wide as its widest cell.
+    pub(crate) fn render(&self) -> String {
+        let mut label_width = 0;
+        let mut widths: Vec<usize> = Vec::new();
+        for line in &self.lines {
+            if let Line::Row(label, columns) = line {
+                label_width = label_width.max(label.chars().count());
+                for (i, column) in columns.iter().enumerate() {
+                    if widths.len() <= i {
+                        widths.push(0);
+                    }
+                    widths[i] = widths[i].max(column.chars().count());
+                }
+            }
+        }
+        let total_width = label_width + widths.iter().map(|w| w + 2).sum::<usize>();
+
+        let mut out = String::new();
+        for line in &self.lines {
+            match line {
+                Line::Heading(text) => out.push_str(text),
+                Line::Row(label, columns) => {
+                    out.push_str(label);
+                    let pad = label_width - label.chars().count();
+                    out.push_str(&" ".repeat(pad));
+                    for (column, width) in columns.iter().zip(&widths) {
+                        out.push_str(&format!("  {:>width$}", column, width = width));
+                    }
+                }
+                Line::Rule => out.push_str(&"-".repeat(total_width)),
+                Line::Blank => {}
+            }
+            let trimmed = out.trim_end_matches(' ').len();
+            out.truncate(trimmed);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Renders a header and rows as aligned columns, right-aligning the
+/// columns flagged in `right`.
+pub(crate) fn grid(header: &[&str], rows: &[Vec<String>], right: &[bool]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &mut dyn Iterator<Item = &str>| {
+        let mut out = String::new();
+        for (i, (cell, width)) in cells.zip(&widths).enumerate() {
+            if i > 0 {
+                out.push_str("  ");
+            }
+            if right.get(i).copied().unwrap_or(false) {
+                out.push_str(&format!("{:>width$}", cell, width = width));
+            } else {
+                out.push_str(&format!("{:<width$}", cell, width = width));
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    };
+
+    let mut out = line(&mut header.iter().copied());
+    out.push_str(&"-".repeat(widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1)));
+    out.push('\n');
+    for row in rows {
+        out.push_str(&line(&mut row.iter().map(String::as_str)));
+    }
+    out
+}
+
+/// Appends one CSV record, quoting fields as RFC 4180 requires.
+pub(crate) fn csv_record(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push('\n');
+}
+
+/// Returns `s` as a quoted JSON string.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escaping() {
+        let mut csv = String::new();
+        csv_record(&mut csv, &["Assets:Cash", "a,b", "say \"hi\""]);
+        assert_eq!(csv, "Assets:Cash,\"a,b\",\"say \"\"hi\"\"\"\n");
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+
+    #[test]
+    fn test_text_table() {
+        let mut table = TextTable::new();
+        table.heading("Title");
+        table.row(0, "Assets", vec!["10.00".into()]);
+        table.row(1, "Cash", vec!["5.00".into()]);
+        table.rule();
+        assert_eq!(
+            table.render(),
+            "Title\nAssets  10.00\n  Cash   5.00\n-------------\n"
+        );
+    }
+}
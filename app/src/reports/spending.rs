@@ -0,0 +1,595 @@
+//! Income and expense breakdown by category and payee.
+//!
+//! [`spending`] totals the expense (or income) accounts of a period,
+//! rolled up by account subtree like the income statement, and by payee.
+//! Payees are transaction descriptions normalized so that
+//! `"ACME SUPERMARKET #1234"` and `"Acme Supermarket 0087"` count
+//! together. [`compare_month`] sets a month against the previous month
+//! and the same month a year earlier.
+//!
+//! ```ignore
+//! let spending = reports::spending(&book, Period::year(2024), &usd, &SpendingOptions::new())?;
+//! for payee in spending.top_payees(10) {
+//!     println!("{:30} {}", payee.payee, payee.amount.to_decimal_string(2));
+//! }
+//! println!("per month: {}", spending.average_per_month(spending.total).to_decimal_string(2));
+//!
+//! let march = reports::compare_month(&book, 2024, 3, &usd, &SpendingOptions::new())?;
+//! print!("{}", march.to_text());
+//! ```
+
+use std::collections::HashMap;
+
+use gnucash_sys::{Book, Error, Guid, Numeric, Result};
+
+use super::render::{csv_record, grid, json_string};
+use super::statements::{StatementRow, section};
+use super::{AccountClass, CurrencyConverter, Period};
+use crate::business::Commodity;
+use crate::engine_ext::TransactionExt;
+
+/// Options for [`spending`] and [`compare_month`].
+#[derive(Debug, Clone)]
+pub struct SpendingOptions {
+    class: AccountClass,
+}
+
+impl Default for SpendingOptions {
+    fn default() -> Self {
+        Self {
+            class: AccountClass::Expense,
+        }
+    }
+}
+
+impl SpendingOptions {
+    /// Creates options reporting expenses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reports income accounts instead of expenses.
+    pub fn income(mut self) -> Self {
+        self.class = AccountClass::Income;
+        self
+    }
+}
+
+/// The total for one normalized payee.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayeeTotal {
+    /// Normalized description as first seen.
+    pub payee: String,
+    /// Total in the report currency.
+    pub amount: Numeric,
+    /// Number of splits.
+    pub count: usize,
+}
+
+/// Expense or income totals for a period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spending {
+    /// The period covered.
+    pub period: Period,
+    /// Report currency mnemonic.
+    pub currency: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// Accounts in tree order with their own amount and subtree total.
+    pub categories: Vec<StatementRow>,
+    /// Payees by descending amount.
+    pub payees: Vec<PayeeTotal>,
+    /// Total of all accounts.
+    pub total: Numeric,
+    /// Commodities that could not be converted for lack of a price.
+    pub missing_prices: Vec<String>,
+}
+
+/// Totals expenses (or income, see [`SpendingOptions::income`]) for
+/// `period` by account and payee. Closing entries are left out and
+/// income is shown as a positive amount.
+pub fn spending(
+    book: &Book,
+    period: Period,
+    currency: &Commodity,
+    options: &SpendingOptions,
+) -> Result<Spending> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let class = options.class;
+
+    let mut own: HashMap<Guid, Numeric> = HashMap::new();
+    let mut payees = PayeeTotals::default();
+    for account in root.descendants() {
+        if AccountClass::of(account.account_type()) != Some(class) {
+            continue;
+        }
+        for split in account.splits() {
+            let Some(txn) = split.transaction() else {
+                continue;
+            };
+            let time = txn.date_posted();
+            if !period.contains(time) || txn.is_closing() {
+                continue;
+            }
+            let value = converter.convert(split.value(), txn.currency().as_ref(), time);
+            *own.entry(account.guid()).or_insert_with(Numeric::zero) += value;
+            let shown = if class.is_credit() { -value } else { value };
+            payees.add(&txn.description().unwrap_or_default(), shown);
+        }
+    }
+
+    let amount = |account: &gnucash_sys::Account| {
+        own.get(&account.guid())
+            .copied()
+            .unwrap_or_else(Numeric::zero)
+    };
+    let tree = section(&root, class, "", &amount);
+    Ok(Spending {
+        period,
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        categories: tree.rows,
+        payees: payees.into_sorted(),
+        total: tree.total,
+        missing_prices: converter.missing_prices(),
+    })
+}
+
+/// Returns a description without words containing digits (store and
+/// reference numbers, dates) and without surrounding punctuation.
+pub fn normalize_payee(description: &str) -> String {
+    description
+        .split_whitespace()
+        .filter(|word| !word.chars().any(|c| c.is_ascii_digit()))
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '&'))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Accumulates payee totals keyed case-insensitively.
+#[derive(Default)]
+struct PayeeTotals {
+    totals: HashMap<String, PayeeTotal>,
+}
+
+impl PayeeTotals {
+    fn add(&mut self, description: &str, amount: Numeric) {
+        let mut payee = normalize_payee(description);
+        if payee.is_empty() {
+            payee = "(no description)".to_string();
+        }
+        let total = self
+            .totals
+            .entry(payee.to_lowercase())
+            .or_insert_with(|| PayeeTotal {
+                payee,
+                amount: Numeric::zero(),
+                count: 0,
+            });
+        total.amount += amount;
+        total.count += 1;
+    }
+
+    fn into_sorted(self) -> Vec<PayeeTotal> {
+        let mut totals: Vec<PayeeTotal> = self.totals.into_values().collect();
+        totals.sort_by(|a, b| b.amount.cmp_value(&a.amount).then(a.payee.cmp(&b.payee)));
+        totals
+    }
+}
+
+impl Spending {
+    /// Returns the number of calendar months the period touches.
+    pub fn months(&self) -> u32 {
+        let (start, end) = (self.period.start, self.period.end);
+        let months =
+            (end.year() - start.year()) * 12 + end.month() as i32 - start.month() as i32 + 1;
+        months.max(1) as u32
+    }
+
+    /// Returns `amount` divided by [`months`](Self::months), rounded to
+    /// the report currency.
+    pub fn average_per_month(&self, amount: Numeric) -> Numeric {
+        (amount / Numeric::from(self.months() as i64)).convert(10i64.pow(self.places))
+    }
+
+    /// Returns the `n` accounts with the largest own amounts.
+    pub fn top_categories(&self, n: usize) -> Vec<&StatementRow> {
+        let mut rows: Vec<&StatementRow> = self
+            .categories
+            .iter()
+            .filter(|r| !r.balance.is_zero())
+            .collect();
+        rows.sort_by(|a, b| b.balance.cmp_value(&a.balance));
+        rows.truncate(n);
+        rows
+    }
+
+    /// Returns the `n` payees with the largest totals.
+    pub fn top_payees(&self, n: usize) -> &[PayeeTotal] {
+        &self.payees[..n.min(self.payees.len())]
+    }
+
+    fn amount(&self, n: Numeric) -> String {
+        n.to_decimal_string(self.places)
+    }
+
+    /// Renders categories and payees as plain-text tables with a monthly
+    /// average column.
+    pub fn to_text(&self) -> String {
+        let mut categories: Vec<Vec<String>> = self
+            .categories
+            .iter()
+            .filter(|r| !r.subtotal.is_zero())
+            .map(|r| {
+                vec![
+                    format!("{}{}", "  ".repeat(r.depth), r.name),
+                    self.amount(r.subtotal),
+                    self.amount(self.average_per_month(r.subtotal)),
+                ]
+            })
+            .collect();
+        categories.push(vec![
+            "Total".to_string(),
+            self.amount(self.total),
+            self.amount(self.average_per_month(self.total)),
+        ]);
+        let payees: Vec<Vec<String>> = self
+            .payees
+            .iter()
+            .map(|p| {
+                vec![
+                    p.payee.clone(),
+                    p.count.to_string(),
+                    self.amount(p.amount),
+                    self.amount(self.average_per_month(p.amount)),
+                ]
+            })
+            .collect();
+        format!(
+            "Spending\n{} ({})\n\n{}\n{}",
+            self.period,
+            self.currency,
+            grid(
+                &["Account", "Total", "Per month"],
+                &categories,
+                &[false, true, true]
+            ),
+            grid(
+                &["Payee", "Count", "Total", "Per month"],
+                &payees,
+                &[false, true, true, true]
+            )
+        )
+    }
+
+    /// Renders categories and payees as one CSV table, distinguished by
+    /// the `kind` column.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(
+            &mut out,
+            &["kind", "name", "depth", "amount", "subtotal", "per_month"],
+        );
+        for row in &self.categories {
+            csv_record(
+                &mut out,
+                &[
+                    "account",
+                    &row.full_name,
+                    &row.depth.to_string(),
+                    &self.amount(row.balance),
+                    &self.amount(row.subtotal),
+                    &self.amount(self.average_per_month(row.subtotal)),
+                ],
+            );
+        }
+        for payee in &self.payees {
+            csv_record(
+                &mut out,
+                &[
+                    "payee",
+                    &payee.payee,
+                    "",
+                    &self.amount(payee.amount),
+                    &self.amount(payee.amount),
+                    &self.amount(self.average_per_month(payee.amount)),
+                ],
+            );
+        }
+        out
+    }
+
+    /// Renders the report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let categories: Vec<String> = self
+            .categories
+            .iter()
+            .map(|r| {
+                format!(
+                    "{{\"guid\":{},\"full_name\":{},\"depth\":{},\"amount\":{},\"subtotal\":{}}}",
+                    r.guid
+                        .map_or("null".to_string(), |g| json_string(&g.to_string())),
+                    json_string(&r.full_name),
+                    r.depth,
+                    json_string(&self.amount(r.balance)),
+                    json_string(&self.amount(r.subtotal)),
+                )
+            })
+            .collect();
+        let payees: Vec<String> = self
+            .payees
+            .iter()
+            .map(|p| {
+                format!(
+                    "{{\"payee\":{},\"count\":{},\"amount\":{}}}",
+                    json_string(&p.payee),
+                    p.count,
+                    json_string(&self.amount(p.amount)),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"period\":{{\"start\":{},\"end\":{}}},\"currency\":{},\"months\":{},\"total\":{},\"categories\":[{}],\"payees\":[{}]}}",
+            json_string(&self.period.start.to_string()),
+            json_string(&self.period.end.to_string()),
+            json_string(&self.currency),
+            self.months(),
+            json_string(&self.amount(self.total)),
+            categories.join(","),
+            payees.join(",")
+        )
+    }
+}
+
+/// One category or payee in a [`SpendingComparison`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonRow {
+    /// Full account name or payee.
+    pub name: String,
+    /// Amount in the month.
+    pub current: Numeric,
+    /// Amount in the previous month.
+    pub previous: Numeric,
+    /// Amount in the same month a year earlier.
+    pub last_year: Numeric,
+}
+
+impl ComparisonRow {
+    /// Returns the change from the previous month.
+    pub fn change_from_previous(&self) -> Numeric {
+        self.current - self.previous
+    }
+
+    /// Returns the change from the same month last year.
+    pub fn change_from_last_year(&self) -> Numeric {
+        self.current - self.last_year
+    }
+}
+
+/// A month's spending next to the previous month and the same month a
+/// year earlier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpendingComparison {
+    /// The month.
+    pub current: Spending,
+    /// The month before.
+    pub previous: Spending,
+    /// The same month a year earlier.
+    pub last_year: Spending,
+}
+
+/// Compares `month` of `year` with the month before and with the same
+/// month a year earlier.
+pub fn compare_month(
+    book: &Book,
+    year: i32,
+    month: u32,
+    currency: &Commodity,
+    options: &SpendingOptions,
+) -> Result<SpendingComparison> {
+    let current = Period::month(year, month)
+        .ok_or_else(|| Error::InvalidOperation(format!("invalid month {}", month)))?;
+    let (previous_year, previous_month) = if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    };
+    let previous = Period::month(previous_year, previous_month).expect("valid month");
+    let last_year = Period::month(year - 1, month).expect("valid month");
+    Ok(SpendingComparison {
+        current: spending(book, current, currency, options)?,
+        previous: spending(book, previous, currency, options)?,
+        last_year: spending(book, last_year, currency, options)?,
+    })
+}
+
+impl SpendingComparison {
+    /// Returns the accounts with an amount in any of the three months, by
+    /// account name.
+    pub fn categories(&self) -> Vec<ComparisonRow> {
+        let rows = |s: &Spending| -> Vec<(String, Numeric)> {
+            s.categories
+                .iter()
+                .filter(|r| !r.balance.is_zero())
+                .map(|r| (r.full_name.clone(), r.balance))
+                .collect()
+        };
+        let mut merged = merge(
+            rows(&self.current),
+            rows(&self.previous),
+            rows(&self.last_year),
+        );
+        merged.sort_by(|a, b| a.name.cmp(&b.name));
+        merged
+    }
+
+    /// Returns the payees with an amount in any of the three months, by
+    /// descending amount in the current month.
+    pub fn payees(&self) -> Vec<ComparisonRow> {
+        let rows = |s: &Spending| -> Vec<(String, Numeric)> {
+            s.payees
+                .iter()
+                .map(|p| (p.payee.clone(), p.amount))
+                .collect()
+        };
+        let mut merged = merge(
+            rows(&self.current),
+            rows(&self.previous),
+            rows(&self.last_year),
+        );
+        merged.sort_by(|a, b| b.current.cmp_value(&a.current).then(a.name.cmp(&b.name)));
+        merged
+    }
+
+    fn cells(&self, rows: &[ComparisonRow]) -> Vec<Vec<String>> {
+        let places = self.current.places;
+        rows.iter()
+            .map(|r| {
+                vec![
+                    r.name.clone(),
+                    r.current.to_decimal_string(places),
+                    r.previous.to_decimal_string(places),
+                    r.change_from_previous().to_decimal_string(places),
+                    r.last_year.to_decimal_string(places),
+                    r.change_from_last_year().to_decimal_string(places),
+                ]
+            })
+            .collect()
+    }
+
+    /// Renders categories and payees side by side with the changes.
+    pub fn to_text(&self) -> String {
+        let right = [false, true, true, true, true, true];
+        let current = self.current.period.start;
+        format!(
+            "Spending {:04}-{:02} ({})\n\n{}\n{}",
+            current.year(),
+            current.month(),
+            self.current.currency,
+            grid(&COMPARISON_HEADER, &self.cells(&self.categories()), &right),
+            grid(
+                &[
+                    "Payee",
+                    COMPARISON_HEADER[1],
+                    COMPARISON_HEADER[2],
+                    COMPARISON_HEADER[3],
+                    COMPARISON_HEADER[4],
+                    COMPARISON_HEADER[5]
+                ],
+                &self.cells(&self.payees()),
+                &right
+            )
+        )
+    }
+
+    /// Renders the comparison as CSV.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(
+            &mut out,
+            &[
+                "kind",
+                "name",
+                "current",
+                "previous",
+                "change",
+                "last_year",
+                "change_last_year",
+            ],
+        );
+        for (kind, rows) in [("account", self.categories()), ("payee", self.payees())] {
+            for cells in self.cells(&rows) {
+                let mut fields = vec![kind];
+                fields.extend(cells.iter().map(String::as_str));
+                csv_record(&mut out, &fields);
+            }
+        }
+        out
+    }
+}
+
+const COMPARISON_HEADER: [&str; 6] = [
+    "Account",
+    "Month",
+    "Previous",
+    "Change",
+    "Last year",
+    "Change",
+];
+
+/// Joins three lists of named amounts into comparison rows.
+fn merge(
+    current: Vec<(String, Numeric)>,
+    previous: Vec<(String, Numeric)>,
+    last_year: Vec<(String, Numeric)>,
+) -> Vec<ComparisonRow> {
+    let mut rows: Vec<ComparisonRow> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (column, list) in [current, previous, last_year].into_iter().enumerate() {
+        for (name, amount) in list {
+            let i = *index.entry(name.clone()).or_insert_with(|| {
+                rows.push(ComparisonRow {
+                    name,
+                    current: Numeric::zero(),
+                    previous: Numeric::zero(),
+                    last_year: Numeric::zero(),
+                });
+                rows.len() - 1
+            });
+            let row = &mut rows[i];
+            match column {
+                0 => row.current += amount,
+                1 => row.previous += amount,
+                _ => row.last_year += amount,
+            }
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payee_normalization() {
+        assert_eq!(
+            normalize_payee("ACME SUPERMARKET #1234"),
+            "ACME SUPERMARKET"
+        );
+        assert_eq!(normalize_payee("  Joe's  Cafe* 03/14 "), "Joe's Cafe");
+
+        let mut totals = PayeeTotals::default();
+        totals.add("ACME SUPERMARKET #1234", Numeric::new(2_500, 100));
+        totals.add("Acme Supermarket 0087", Numeric::new(1_000, 100));
+        totals.add("Fuel 4 Less", Numeric::new(4_000, 100));
+        totals.add("", Numeric::new(100, 100));
+        let sorted = totals.into_sorted();
+        assert_eq!(sorted[0].payee, "Fuel Less");
+        assert_eq!(sorted[1].payee, "ACME SUPERMARKET");
+        assert_eq!(sorted[1].count, 2);
+        assert!(sorted[1].amount.eq_value(&Numeric::new(35, 1)));
+        assert_eq!(sorted[2].payee, "(no description)");
+    }
+
+    #[test]
+    fn test_merge() {
+        let rows = merge(
+            vec![("Food".into(), Numeric::from(10))],
+            vec![
+                ("Rent".into(), Numeric::from(5)),
+                ("Food".into(), Numeric::from(4)),
+            ],
+            vec![("Food".into(), Numeric::from(12))],
+        );
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "Food");
+        assert!(rows[0].change_from_previous().eq_value(&Numeric::from(6)));
+        assert!(rows[0].change_from_last_year().eq_value(&Numeric::from(-2)));
+        assert!(rows[1].current.is_zero());
+    }
+}
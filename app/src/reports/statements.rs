@@ -0,0 +1,641 @@
+//! Balance sheet, income statement and trial balance.
+//!
+//! Statement sections follow the account tree: each row carries its
+//! depth below the top-level accounts, the account's own balance and a
+//! subtotal including its subaccounts. Accounts are placed by their
+//! [`AccountClass`]; credit classes (liabilities, equity, income) are
+//! shown sign-reversed, so a normal balance is positive everywhere.
+//!
+//! ```ignore
+//! let sheet = reports::balance_sheet(&book, as_of, &usd)?;
+//! assert!(sheet.is_balanced());
+//! std::fs::write("balance-sheet.csv", sheet.hide_zero().to_csv())?;
+//! ```
+
+use gnucash_sys::{Account, Book, Error, GNCAccountType, Guid, Numeric, Result};
+
+use super::render::{TextTable, csv_record, json_string};
+use super::{AccountClass, CurrencyConverter, Period};
+use crate::business::Commodity;
+use crate::date::Date;
+use crate::engine_ext::AccountExt;
+
+/// One account line of a [`Statement`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementRow {
+    /// The account, or `None` for computed lines such as retained
+    /// earnings.
+    pub guid: Option<Guid>,
+    /// Account name.
+    pub name: String,
+    /// Colon-separated full account name.
+    pub full_name: String,
+    /// Account type.
+    pub account_type: GNCAccountType,
+    /// Depth below the top-level accounts of the section.
+    pub depth: usize,
+    /// The account's own balance in the report currency.
+    pub balance: Numeric,
+    /// The balance including all subaccounts.
+    pub subtotal: Numeric,
+}
+
+/// A titled group of rows, such as "Assets".
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementSection {
+    /// Section title.
+    pub title: String,
+    /// Rows in account tree order.
+    pub rows: Vec<StatementRow>,
+    /// Sum of the top-level subtotals.
+    pub total: Numeric,
+}
+
+/// A balance sheet or income statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    /// Report title.
+    pub title: String,
+    /// The date or period covered, for display.
+    pub period: String,
+    /// Report currency mnemonic.
+    pub currency: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// Sections in display order.
+    pub sections: Vec<StatementSection>,
+    /// Label of the closing total ("Net Income", ...).
+    pub total_label: String,
+    /// The closing total.
+    pub total: Numeric,
+    /// Commodities that could not be converted for lack of a price.
+    pub missing_prices: Vec<String>,
+}
+
+/// Builds the balance sheet as of the end of `as_of`.
+///
+/// Income and expense balances not yet closed to equity appear as a
+/// "Retained Earnings" line, and any difference left by converting
+/// foreign balances at current prices as "Unrealized Gains", so that
+/// assets equal liabilities plus equity.
+pub fn balance_sheet(book: &Book, as_of: Date, currency: &Commodity) -> Result<Statement> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let time = as_of.end_time64();
+    let amount = |account: &Account| {
+        converter.convert(
+            account.balance_as_of_date(time),
+            account.commodity().as_ref(),
+            time,
+        )
+    };
+
+    let assets = section(&root, AccountClass::Asset, "Assets", &amount);
+    let liabilities = section(&root, AccountClass::Liability, "Liabilities", &amount);
+    let mut equity = section(&root, AccountClass::Equity, "Equity", &amount);
+    let trading = section(&root, AccountClass::Trading, "Trading", &amount);
+    let income = section(&root, AccountClass::Income, "Income", &amount);
+    let expenses = section(&root, AccountClass::Expense, "Expenses", &amount);
+
+    let retained = income.total - expenses.total;
+    push_computed(&mut equity, "Retained Earnings", retained);
+    let unrealized = assets.total - liabilities.total - equity.total - trading.total;
+    push_computed(&mut equity, "Unrealized Gains", unrealized);
+
+    let total = liabilities.total + equity.total + trading.total;
+    let mut sections = vec![assets, liabilities, equity];
+    if !trading.rows.is_empty() {
+        sections.push(trading);
+    }
+    Ok(Statement {
+        title: "Balance Sheet".to_string(),
+        period: format!("As of {}", as_of),
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        sections,
+        total_label: "Liabilities + Equity".to_string(),
+        total,
+        missing_prices: converter.missing_prices(),
+    })
+}
+
+/// Builds the income statement for `period`. Closing entries are left
+/// out, so closing the books does not zero the statement. Foreign
+/// amounts are converted at prices nearest the end of the period.
+pub fn income_statement(book: &Book, period: Period, currency: &Commodity) -> Result<Statement> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let time = period.end_time64();
+    let amount = |account: &Account| {
+        converter.convert(
+            period_amount(account, period),
+            account.commodity().as_ref(),
+            time,
+        )
+    };
+
+    let income = section(&root, AccountClass::Income, "Income", &amount);
+    let expenses = section(&root, AccountClass::Expense, "Expenses", &amount);
+    let total = income.total - expenses.total;
+    Ok(Statement {
+        title: "Income Statement".to_string(),
+        period: period.to_string(),
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        sections: vec![income, expenses],
+        total_label: "Net Income".to_string(),
+        total,
+        missing_prices: converter.missing_prices(),
+    })
+}
+
+/// Returns the sum of the account's split amounts posted in `period`,
+/// excluding closing transactions.
+fn period_amount(account: &Account, period: Period) -> Numeric {
+    account
+        .splits()
+        .filter(|split| {
+            split
+                .transaction()
+                .is_some_and(|txn| period.contains(txn.date_posted()) && !txn.is_closing())
+        })
+        .map(|split| split.amount())
+        .sum()
+}
+
+/// Collects the rows of one account class below `root`.
+pub(super) fn section(
+    root: &Account,
+    class: AccountClass,
+    title: &str,
+    amount: &dyn Fn(&Account) -> Numeric,
+) -> StatementSection {
+    let mut rows = Vec::new();
+    let total = collect(root, class, 0, amount, &mut rows);
+    StatementSection {
+        title: title.to_string(),
+        rows,
+        total,
+    }
+}
+
+/// Appends rows for the children of `parent` in `class` and returns the
+/// sum of their subtotals. Accounts of other classes are not shown, but
+/// their subaccounts are still searched.
+fn collect(
+    parent: &Account,
+    class: AccountClass,
+    depth: usize,
+    amount: &dyn Fn(&Account) -> Numeric,
+    rows: &mut Vec<StatementRow>,
+) -> Numeric {
+    let mut total = Numeric::zero();
+    for child in parent.children() {
+        if AccountClass::of(child.account_type()) != Some(class) {
+            total += collect(&child, class, depth, amount, rows);
+            continue;
+        }
+        let mut balance = amount(&child);
+        if class.is_credit() {
+            balance = -balance;
+        }
+        let index = rows.len();
+        rows.push(StatementRow {
+            guid: Some(child.guid()),
+            name: child.name().unwrap_or_default(),
+            full_name: child.full_name().unwrap_or_default(),
+            account_type: child.account_type(),
+            depth,
+            balance,
+            subtotal: balance,
+        });
+        let below = collect(&child, class, depth + 1, amount, rows);
+        rows[index].subtotal = balance + below;
+        total += balance + below;
+    }
+    total
+}
+
+/// Adds a computed top-level line to a section unless it is zero.
+fn push_computed(section: &mut StatementSection, name: &str, amount: Numeric) {
+    if amount.is_zero() {
+        return;
+    }
+    section.rows.push(StatementRow {
+        guid: None,
+        name: name.to_string(),
+        full_name: name.to_string(),
+        account_type: GNCAccountType::ACCT_TYPE_EQUITY,
+        depth: 0,
+        balance: amount,
+        subtotal: amount,
+    });
+    section.total += amount;
+}
+
+impl Statement {
+    /// Returns the section with the given title.
+    pub fn section(&self, title: &str) -> Option<&StatementSection> {
+        self.sections.iter().find(|s| s.title == title)
+    }
+
+    /// Returns true if the first section's total equals the closing
+    /// total, i.e. assets equal liabilities plus equity.
+    pub fn is_balanced(&self) -> bool {
+        self.sections
+            .first()
+            .is_some_and(|s| s.total.eq_value(&self.total))
+    }
+
+    /// Removes rows whose subtotal is zero.
+    pub fn hide_zero(mut self) -> Self {
+        for section in &mut self.sections {
+            section.rows.retain(|row| !row.subtotal.is_zero());
+        }
+        self
+    }
+
+    fn amount(&self, n: Numeric) -> String {
+        n.to_decimal_string(self.places)
+    }
+
+    /// Renders the statement as an indented plain-text table. Rows show
+    /// subtotals, so parent accounts include their subaccounts.
+    pub fn to_text(&self) -> String {
+        let mut table = TextTable::new();
+        table.heading(&self.title);
+        table.heading(format!("{} ({})", self.period, self.currency));
+        for section in &self.sections {
+            table.blank();
+            table.heading(&section.title);
+            for row in &section.rows {
+                table.row(row.depth + 1, &row.name, vec![self.amount(row.subtotal)]);
+            }
+            table.row(
+                0,
+                &format!("Total {}", section.title),
+                vec![self.amount(section.total)],
+            );
+        }
+        table.blank();
+        table.rule();
+        table.row(0, &self.total_label, vec![self.amount(self.total)]);
+        if !self.missing_prices.is_empty() {
+            table.blank();
+            table.heading(format!("No price for: {}", self.missing_prices.join(", ")));
+        }
+        table.render()
+    }
+
+    /// Renders the statement as CSV with one record per account and per
+    /// total.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(
+            &mut out,
+            &["section", "account", "depth", "balance", "subtotal"],
+        );
+        for section in &self.sections {
+            for row in &section.rows {
+                csv_record(
+                    &mut out,
+                    &[
+                        &section.title,
+                        &row.full_name,
+                        &row.depth.to_string(),
+                        &self.amount(row.balance),
+                        &self.amount(row.subtotal),
+                    ],
+                );
+            }
+            let label = format!("Total {}", section.title);
+            csv_record(
+                &mut out,
+                &[&section.title, &label, "", "", &self.amount(section.total)],
+            );
+        }
+        csv_record(
+            &mut out,
+            &["", &self.total_label, "", "", &self.amount(self.total)],
+        );
+        out
+    }
+
+    /// Renders the statement as a JSON object. Amounts are decimal
+    /// strings so no precision is lost.
+    pub fn to_json(&self) -> String {
+        let sections: Vec<String> = self
+            .sections
+            .iter()
+            .map(|section| {
+                let rows: Vec<String> = section
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        format!(
+                            "{{\"guid\":{},\"name\":{},\"full_name\":{},\"depth\":{},\"balance\":{},\"subtotal\":{}}}",
+                            row.guid
+                                .map_or("null".to_string(), |g| json_string(&g.to_string())),
+                            json_string(&row.name),
+                            json_string(&row.full_name),
+                            row.depth,
+                            json_string(&self.amount(row.balance)),
+                            json_string(&self.amount(row.subtotal)),
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"title\":{},\"total\":{},\"rows\":[{}]}}",
+                    json_string(&section.title),
+                    json_string(&self.amount(section.total)),
+                    rows.join(",")
+                )
+            })
+            .collect();
+        let missing: Vec<String> = self.missing_prices.iter().map(|m| json_string(m)).collect();
+        format!(
+            "{{\"title\":{},\"period\":{},\"currency\":{},\"sections\":[{}],\"total_label\":{},\"total\":{},\"missing_prices\":[{}]}}",
+            json_string(&self.title),
+            json_string(&self.period),
+            json_string(&self.currency),
+            sections.join(","),
+            json_string(&self.total_label),
+            json_string(&self.amount(self.total)),
+            missing.join(",")
+        )
+    }
+}
+
+/// One account line of a [`TrialBalance`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrialBalanceRow {
+    /// The account, or `None` for the unrealized gains line.
+    pub guid: Option<Guid>,
+    /// Colon-separated full account name.
+    pub full_name: String,
+    /// Account type.
+    pub account_type: GNCAccountType,
+    /// Debit balance in the report currency, or zero.
+    pub debit: Numeric,
+    /// Credit balance in the report currency, or zero.
+    pub credit: Numeric,
+}
+
+/// Debit and credit balances of every account with a balance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrialBalance {
+    /// The date the balances are taken at.
+    pub as_of: Date,
+    /// Report currency mnemonic.
+    pub currency: String,
+    /// Decimal places used when rendering amounts.
+    pub places: u32,
+    /// Rows in account tree order.
+    pub rows: Vec<TrialBalanceRow>,
+    /// Sum of the debit column.
+    pub total_debit: Numeric,
+    /// Sum of the credit column.
+    pub total_credit: Numeric,
+    /// Commodities that could not be converted for lack of a price.
+    pub missing_prices: Vec<String>,
+}
+
+/// Builds the trial balance as of the end of `as_of`. Each account's
+/// own balance (without subaccounts) goes in the debit or credit column;
+/// a difference left by converting foreign balances is shown as
+/// "Unrealized Gains".
+pub fn trial_balance(book: &Book, as_of: Date, currency: &Commodity) -> Result<TrialBalance> {
+    let root = book
+        .root_account()
+        .ok_or(Error::NullPointer("root account"))?;
+    let converter = CurrencyConverter::new(book, currency);
+    let time = as_of.end_time64();
+
+    let mut rows = Vec::new();
+    for account in root.descendants() {
+        let balance = converter.convert(
+            account.balance_as_of_date(time),
+            account.commodity().as_ref(),
+            time,
+        );
+        if balance.is_zero() {
+            continue;
+        }
+        rows.push(trial_row(
+            Some(account.guid()),
+            account.full_name().unwrap_or_default(),
+            account.account_type(),
+            balance,
+        ));
+    }
+    let total_debit: Numeric = rows.iter().map(|r| r.debit).sum();
+    let total_credit: Numeric = rows.iter().map(|r| r.credit).sum();
+    let difference = total_debit - total_credit;
+    if !difference.is_zero() {
+        rows.push(trial_row(
+            None,
+            "Unrealized Gains".to_string(),
+            GNCAccountType::ACCT_TYPE_EQUITY,
+            -difference,
+        ));
+    }
+
+    Ok(TrialBalance {
+        as_of,
+        currency: converter.mnemonic(),
+        places: converter.places(),
+        total_debit: rows.iter().map(|r| r.debit).sum(),
+        total_credit: rows.iter().map(|r| r.credit).sum(),
+        rows,
+        missing_prices: converter.missing_prices(),
+    })
+}
+
+fn trial_row(
+    guid: Option<Guid>,
+    full_name: String,
+    account_type: GNCAccountType,
+    balance: Numeric,
+) -> TrialBalanceRow {
+    let (debit, credit) = if balance.is_negative() {
+        (Numeric::zero(), -balance)
+    } else {
+        (balance, Numeric::zero())
+    };
+    TrialBalanceRow {
+        guid,
+        full_name,
+        account_type,
+        debit,
+        credit,
+    }
+}
+
+impl TrialBalance {
+    /// Returns true if total debits equal total credits.
+    pub fn is_balanced(&self) -> bool {
+        self.total_debit.eq_value(&self.total_credit)
+    }
+
+    fn amount(&self, n: Numeric) -> String {
+        if n.is_zero() {
+            String::new()
+        } else {
+            n.to_decimal_string(self.places)
+        }
+    }
+
+    /// Renders the trial balance as a plain-text table.
+    pub fn to_text(&self) -> String {
+        let mut table = TextTable::new();
+        table.heading("Trial Balance");
+        table.heading(format!("As of {} ({})", self.as_of, self.currency));
+        table.blank();
+        table.row(0, "Account", vec!["Debit".into(), "Credit".into()]);
+        table.rule();
+        for row in &self.rows {
+            table.row(
+                0,
+                &row.full_name,
+                vec![self.amount(row.debit), self.amount(row.credit)],
+            );
+        }
+        table.rule();
+        table.row(
+            0,
+            "Total",
+            vec![
+                self.amount(self.total_debit),
+                self.amount(self.total_credit),
+            ],
+        );
+        if !self.missing_prices.is_empty() {
+            table.blank();
+            table.heading(format!("No price for: {}", self.missing_prices.join(", ")));
+        }
+        table.render()
+    }
+
+    /// Renders the trial balance as CSV.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv_record(&mut out, &["account", "debit", "credit"]);
+        for row in &self.rows {
+            csv_record(
+                &mut out,
+                &[
+                    &row.full_name,
+                    &self.amount(row.debit),
+                    &self.amount(row.credit),
+                ],
+            );
+        }
+        csv_record(
+            &mut out,
+            &[
+                "Total",
+                &self.amount(self.total_debit),
+                &self.amount(self.total_credit),
+            ],
+        );
+        out
+    }
+
+    /// Renders the trial balance as a JSON object.
+    pub fn to_json(&self) -> String {
+        let places = self.places;
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                format!(
+                    "{{\"guid\":{},\"full_name\":{},\"debit\":{},\"credit\":{}}}",
+                    row.guid
+                        .map_or("null".to_string(), |g| json_string(&g.to_string())),
+                    json_string(&row.full_name),
+                    json_string(&row.debit.to_decimal_string(places)),
+                    json_string(&row.credit.to_decimal_string(places)),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"as_of\":{},\"currency\":{},\"rows\":[{}],\"total_debit\":{},\"total_credit\":{}}}",
+            json_string(&self.as_of.to_string()),
+            json_string(&self.currency),
+            rows.join(","),
+            json_string(&self.total_debit.to_decimal_string(places)),
+            json_string(&self.total_credit.to_decimal_string(places)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(name: &str, depth: usize, balance: i64, subtotal: i64) -> StatementRow {
+        StatementRow {
+            guid: None,
+            name: name.to_string(),
+            full_name: name.to_string(),
+            account_type: GNCAccountType::ACCT_TYPE_ASSET,
+            depth,
+            balance: Numeric::new(balance, 100),
+            subtotal: Numeric::new(subtotal, 100),
+        }
+    }
+
+    fn statement() -> Statement {
+        Statement {
+            title: "Balance Sheet".into(),
+            period: "As of 2024-12-31".into(),
+            currency: "USD".into(),
+            places: 2,
+            sections: vec![StatementSection {
+                title: "Assets".into(),
+                rows: vec![
+                    row("Current", 0, 0, 150_000),
+                    row("Checking", 1, 150_000, 150_000),
+                    row("Savings", 1, 0, 0),
+                ],
+                total: Numeric::new(150_000, 100),
+            }],
+            total_label: "Liabilities + Equity".into(),
+            total: Numeric::new(1_500, 1),
+            missing_prices: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_statement_rendering() {
+        let sheet = statement().hide_zero();
+        assert!(sheet.is_balanced());
+        assert_eq!(
+            sheet.to_text(),
+            concat!(
+                "Balance Sheet\n",
+                "As of 2024-12-31 (USD)\n",
+                "\n",
+                "Assets\n",
+                "  Current             1500.00\n",
+                "    Checking          1500.00\n",
+                "Total Assets          1500.00\n",
+                "\n",
+                "-----------------------------\n",
+                "Liabilities + Equity  1500.00\n",
+            )
+        );
+        assert!(
+            sheet
+                .to_csv()
+                .contains("Assets,Checking,1,1500.00,1500.00\nAssets,Total Assets,,,1500.00\n")
+        );
+        assert!(sheet.to_json().starts_with(
+            "{\"title\":\"Balance Sheet\",\"period\":\"As of 2024-12-31\",\"currency\":\"USD\",\"sections\":[{\"title\":\"Assets\",\"total\":\"1500.00\",\"rows\":[{\"guid\":null,\"name\":\"Current\""
+        ));
+    }
+}
@@ -13,6 +13,7 @@ fn main() {
     println!("cargo:rerun-if-changed=wrapper.h");
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=.env");
+    println!("cargo:rerun-if-env-changed=GNUCASH_UPDATE_BINDINGS");
 
     // Get paths from environment or use defaults
     let lib_path = PathBuf::from(
@@ -33,6 +34,12 @@ fn main() {
         .probe("glib-2.0")
         .expect("glib-2.0 not found via pkg-config");
 
+    // GValue accessors used for KVP slots live in gobject-2.0
+    pkg_config::Config::new()
+        .atleast_version("2.56")
+        .probe("gobject-2.0")
+        .expect("gobject-2.0 not found via pkg-config");
+
     // Library search path
     println!("cargo:rustc-link-search=native={}", lib_path.display());
 
@@ -93,6 +100,7 @@ fn main() {
         .allowlist_function("gnc_time64_to_iso8601_buff")
         .allowlist_function("time64_to_gdate")
         .allowlist_function("gdate_to_time64")
+        .allowlist_function("time64_get_type")
         // Entity functions
         .allowlist_function("xacc.*")
         .allowlist_function("gnc_.*")
@@ -169,6 +177,9 @@ fn main() {
         .allowlist_function("g_list_.*")
         .allowlist_function("g_slist_.*")
         .allowlist_function("g_date_.*")
+        .allowlist_function("g_value_.*")
+        .allowlist_function("g_hash_table_foreach")
+        .allowlist_function("g_hash_table_unref")
         .allowlist_type("GSList")
         .allowlist_type("_GSList")
         // Generate Rust enums for C enums
@@ -203,4 +214,11 @@ fn main() {
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
+
+    // Refresh the pre-generated copy used on docs.rs
+    if env::var("GNUCASH_UPDATE_BINDINGS").is_ok() {
+        bindings
+            .write_to_file(manifest_dir.join("src").join("bindings_generated.rs"))
+            .expect("Couldn't write src/bindings_generated.rs");
+    }
 }
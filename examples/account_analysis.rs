@@ -14,6 +14,9 @@ use std::env;
 
 use gnucash_sys::{init_engine, Account, Numeric, Session, SessionOpenMode};
 
+mod common;
+use common::format_date;
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -182,19 +185,6 @@ fn add_numeric(a: &Numeric, b: &Numeric) -> Numeric {
     Numeric::new(a_scaled + b_scaled, denom)
 }
 
-fn format_date(timestamp: i64) -> String {
-    if timestamp == 0 {
-        return "N/A".to_string();
-    }
-    // Simple date formatting - in production use chrono
-    let days_since_epoch = timestamp / 86400;
-    let years = days_since_epoch / 365 + 1970;
-    let remaining_days = days_since_epoch % 365;
-    let month = remaining_days / 30 + 1;
-    let day = remaining_days % 30 + 1;
-    format!("{:04}-{:02}-{:02}", years, month.min(12), day.min(28))
-}
-
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -3,6 +3,10 @@
 //! This example opens a GnuCash file and generates a simple
 //! balance sheet showing assets, liabilities, and equity.
 //!
+//! Balances are summed as floating point in each account's own commodity;
+//! see the `reports` module of gnucash-ext for exact, multi-currency
+//! statements.
+//!
 //! Usage: balance_sheet <gnucash_file>
 
 use std::env;
@@ -0,0 +1,22 @@
+//! Helpers shared by the examples.
+
+use std::ffi::CStr;
+
+use gnucash_sys::ffi;
+
+/// Formats a `time64` as `YYYY-MM-DD` with the engine's date printing, or
+/// `N/A` for zero.
+pub fn format_date(timestamp: i64) -> String {
+    if timestamp == 0 {
+        return "N/A".to_string();
+    }
+    unsafe {
+        let ptr = ffi::gnc_print_time64(timestamp, c"%Y-%m-%d".as_ptr());
+        if ptr.is_null() {
+            return "N/A".to_string();
+        }
+        let date = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+        ffi::g_free(ptr as *mut _);
+        date
+    }
+}
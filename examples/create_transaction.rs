@@ -4,7 +4,9 @@
 //! - A debit to an expense account
 //! - A credit from a bank account
 
-use gnucash_sys::{init_engine, Account, Book, GNCAccountType, Numeric, Split, Transaction};
+use gnucash_sys::{
+    Account, Book, Editable, GNCAccountType, Numeric, Split, Transaction, init_engine,
+};
 
 fn main() {
     // Initialize the GnuCash engine
@@ -35,38 +37,38 @@ fn main() {
     println!("Creating transaction: Grocery shopping ($50.00)");
 
     let txn = Transaction::new(&book);
-    txn.begin_edit();
+    let edit = txn.edit();
 
-    txn.set_description("Weekly grocery shopping");
-    txn.set_num("1001");
-    txn.set_notes("Bought food for the week");
+    edit.set_description("Weekly grocery shopping");
+    edit.set_num("1001");
+    edit.set_notes("Bought food for the week");
 
     // Set the date (using Unix timestamp - Jan 15, 2024)
     // In production, use proper date handling
-    txn.set_date(15, 1, 2024);
+    edit.set_date(15, 1, 2024);
 
     // Create the expense split (debit - positive in expense account)
     let expense_split = Split::new(&book);
-    expense_split.set_account(&groceries);
-    expense_split.set_transaction(&txn);
-    expense_split.set_memo("Groceries");
+    let split = edit.add_split(&expense_split);
+    split.set_account(&groceries);
+    split.set_memo("Groceries");
 
     // $50.00 = 5000 cents / 100
     let amount = Numeric::new(5000, 100);
-    expense_split.set_amount(amount);
-    expense_split.set_value(amount);
+    split.set_amount(amount);
+    split.set_value(amount);
 
     // Create the bank split (credit - negative from bank account)
     let bank_split = Split::new(&book);
-    bank_split.set_account(&bank);
-    bank_split.set_transaction(&txn);
-    bank_split.set_memo("Debit card");
+    let split = edit.add_split(&bank_split);
+    split.set_account(&bank);
+    split.set_memo("Debit card");
 
     let neg_amount = Numeric::new(-5000, 100);
-    bank_split.set_amount(neg_amount);
-    bank_split.set_value(neg_amount);
+    split.set_amount(neg_amount);
+    split.set_value(neg_amount);
 
-    txn.commit_edit();
+    edit.commit();
 
     println!("  Description: {}", txn.description().unwrap());
     println!("  Date: 2024-01-15");
@@ -122,11 +124,11 @@ fn create_account(
     account_type: GNCAccountType,
 ) -> Account {
     let mut account = Account::new(book);
-    account.begin_edit();
-    account.set_name(name);
-    account.set_type(account_type);
-    account.commit_edit();
-    parent.append_child(&account);
+    let edit = account.edit();
+    edit.set_name(name);
+    edit.set_type(account_type);
+    edit.commit();
+    parent.edit().append_child(&account);
     account.mark_unowned();
     account
 }
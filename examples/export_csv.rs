@@ -11,6 +11,9 @@ use std::io::{self, Write};
 
 use gnucash_sys::{init_engine, Account, Session, SessionOpenMode};
 
+mod common;
+use common::format_date;
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -137,19 +140,6 @@ fn export_to_csv(account: &Account, output_file: Option<&str>) -> io::Result<usi
     Ok(count)
 }
 
-fn format_date(timestamp: i64) -> String {
-    if timestamp == 0 {
-        return "N/A".to_string();
-    }
-    // Simple date formatting
-    let days = timestamp / 86400;
-    let years = 1970 + days / 365;
-    let day_of_year = days % 365;
-    let month = day_of_year / 30 + 1;
-    let day = day_of_year % 30 + 1;
-    format!("{:04}-{:02}-{:02}", years, month.min(12), day.min(28))
-}
-
 fn format_amount(n: &gnucash_sys::Numeric) -> String {
     if n.denom() == 0 {
         return String::new();
@@ -6,7 +6,7 @@
 //! Based on: gnucash/bindings/python/example_scripts/new_book_with_opening_balances.py
 
 use gnucash_sys::{
-    init_engine, Account, Book, GNCAccountType, Numeric, Split, Transaction,
+    Account, Book, Editable, GNCAccountType, Numeric, Split, Transaction, init_engine,
 };
 
 fn main() {
@@ -102,11 +102,11 @@ fn create_account(
     account_type: GNCAccountType,
 ) -> Account {
     let mut account = Account::new(book);
-    account.begin_edit();
-    account.set_name(name);
-    account.set_type(account_type);
-    account.commit_edit();
-    parent.append_child(&account);
+    let edit = account.edit();
+    edit.set_name(name);
+    edit.set_type(account_type);
+    edit.commit();
+    parent.edit().append_child(&account);
     account.mark_unowned();
     account
 }
@@ -118,29 +118,29 @@ fn create_opening_balance(
     amount_cents: i64,
 ) {
     let txn = Transaction::new(book);
-    txn.begin_edit();
+    let edit = txn.edit();
 
     let account_name = account.name().unwrap_or_default();
-    txn.set_description(&format!("Opening Balance - {}", account_name));
-    txn.set_date(1, 1, 2024); // January 1, 2024
+    edit.set_description(&format!("Opening Balance - {}", account_name));
+    edit.set_date(1, 1, 2024); // January 1, 2024
 
     // Create split for the account
     let account_split = Split::new(book);
-    account_split.set_account(account);
-    account_split.set_transaction(&txn);
+    let split = edit.add_split(&account_split);
+    split.set_account(account);
     let amount = Numeric::new(amount_cents, 100);
-    account_split.set_amount(amount);
-    account_split.set_value(amount);
+    split.set_amount(amount);
+    split.set_value(amount);
 
     // Create balancing split for equity
     let equity_split = Split::new(book);
-    equity_split.set_account(equity_account);
-    equity_split.set_transaction(&txn);
+    let split = edit.add_split(&equity_split);
+    split.set_account(equity_account);
     let neg_amount = Numeric::new(-amount_cents, 100);
-    equity_split.set_amount(neg_amount);
-    equity_split.set_value(neg_amount);
+    split.set_amount(neg_amount);
+    split.set_value(neg_amount);
 
-    txn.commit_edit();
+    edit.commit();
 
     std::mem::forget(account_split);
     std::mem::forget(equity_split);
@@ -7,7 +7,7 @@
 //!
 //! Based on: gnucash/bindings/python/example_scripts/price_database_example.py
 
-use gnucash_sys::{init_engine, Book, Numeric, Price, PriceDB, PriceSource};
+use gnucash_sys::{Book, Editable, Numeric, Price, PriceDB, PriceSource, init_engine};
 
 fn main() {
     init_engine();
@@ -28,40 +28,40 @@ fn main() {
 
     // Add price: 1 USD = 0.85 EUR on Jan 1, 2024
     let price1 = Price::new(&book);
-    price1.begin_edit();
-    price1.set_time(1704067200); // Jan 1, 2024 00:00:00 UTC
-    price1.set_source(PriceSource::PRICE_SOURCE_USER_PRICE);
-    price1.set_type_string("last");
-    price1.set_value(Numeric::new(85, 100)); // 0.85
-    price1.commit_edit();
-
-    if pricedb.add_price(&price1) {
+    let edit = price1.edit();
+    edit.set_time(1704067200); // Jan 1, 2024 00:00:00 UTC
+    edit.set_source(PriceSource::PRICE_SOURCE_USER_PRICE);
+    edit.set_type_string("last");
+    edit.set_value(Numeric::new(85, 100)); // 0.85
+    edit.commit();
+
+    if pricedb.edit().add_price(&price1) {
         println!("  Added: USD/EUR = 0.85 (Jan 1, 2024)");
     }
 
     // Add price: 1 USD = 0.84 EUR on Jan 15, 2024
     let price2 = Price::new(&book);
-    price2.begin_edit();
-    price2.set_time(1705276800); // Jan 15, 2024 00:00:00 UTC
-    price2.set_source(PriceSource::PRICE_SOURCE_USER_PRICE);
-    price2.set_type_string("last");
-    price2.set_value(Numeric::new(84, 100)); // 0.84
-    price2.commit_edit();
-
-    if pricedb.add_price(&price2) {
+    let edit = price2.edit();
+    edit.set_time(1705276800); // Jan 15, 2024 00:00:00 UTC
+    edit.set_source(PriceSource::PRICE_SOURCE_USER_PRICE);
+    edit.set_type_string("last");
+    edit.set_value(Numeric::new(84, 100)); // 0.84
+    edit.commit();
+
+    if pricedb.edit().add_price(&price2) {
         println!("  Added: USD/EUR = 0.84 (Jan 15, 2024)");
     }
 
     // Add price: 1 USD = 0.86 EUR on Feb 1, 2024
     let price3 = Price::new(&book);
-    price3.begin_edit();
-    price3.set_time(1706745600); // Feb 1, 2024 00:00:00 UTC
-    price3.set_source(PriceSource::PRICE_SOURCE_FQ);
-    price3.set_type_string("last");
-    price3.set_value(Numeric::new(86, 100)); // 0.86
-    price3.commit_edit();
-
-    if pricedb.add_price(&price3) {
+    let edit = price3.edit();
+    edit.set_time(1706745600); // Feb 1, 2024 00:00:00 UTC
+    edit.set_source(PriceSource::PRICE_SOURCE_FQ);
+    edit.set_type_string("last");
+    edit.set_value(Numeric::new(86, 100)); // 0.86
+    edit.commit();
+
+    if pricedb.edit().add_price(&price3) {
         println!("  Added: USD/EUR = 0.86 (Feb 1, 2024)");
     }
 
@@ -90,7 +90,7 @@ fn main() {
 
     // Remove a price
     println!("\n--- Removing Price ---");
-    if pricedb.remove_price(&price2) {
+    if pricedb.edit().remove_price(&price2) {
         println!("Removed price2 from database");
     }
 
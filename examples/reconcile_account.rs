@@ -4,18 +4,27 @@
 //! - View unreconciled transactions
 //! - Mark splits as cleared or reconciled
 //! - Calculate reconciliation balance
+//! - Preview a reconciliation against a statement ending balance
 //!
-//! Usage: reconcile_account <gnucash_file> <account_path>
+//! Usage: reconcile_account <gnucash_file> <account_path> [ending_balance]
 
 use std::env;
 
-use gnucash_sys::{init_engine, Account, Session, SessionOpenMode};
+use gnucash_sys::{
+    Account, Numeric, ReconcileState, Reconciliation, Session, SessionOpenMode, init_engine,
+};
+
+mod common;
+use common::format_date;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     if args.len() < 3 {
-        eprintln!("Usage: {} <gnucash_file> <account_path>", args[0]);
+        eprintln!(
+            "Usage: {} <gnucash_file> <account_path> [ending_balance]",
+            args[0]
+        );
         eprintln!();
         eprintln!("Example:");
         eprintln!("  {} myfile.gnucash \"Assets:Checking\"", args[0]);
@@ -24,6 +33,12 @@ fn main() {
 
     let file_path = &args[1];
     let account_path = &args[2];
+    let ending_balance = args.get(3).map(|s| {
+        Numeric::parse_decimal(s).unwrap_or_else(|| {
+            eprintln!("Invalid ending balance: {}", s);
+            std::process::exit(1);
+        })
+    });
 
     init_engine();
 
@@ -36,6 +51,9 @@ fn main() {
                     match find_account_by_path(&root, account_path) {
                         Some(account) => {
                             show_reconciliation_status(&account);
+                            if let Some(ending_balance) = ending_balance {
+                                preview_reconciliation(&account, ending_balance);
+                            }
                         }
                         None => {
                             eprintln!("Account not found: {}", account_path);
@@ -91,9 +109,9 @@ fn show_reconciliation_status(account: &Account) {
     for split in account.splits() {
         let state = split.reconcile_state();
         match state {
-            'n' => unreconciled.push(split),
-            'c' => cleared_splits.push(split),
-            'y' => reconciled_splits.push(split),
+            ReconcileState::New => unreconciled.push(split),
+            ReconcileState::Cleared => cleared_splits.push(split),
+            ReconcileState::Reconciled => reconciled_splits.push(split),
             _ => {} // frozen or void
         }
     }
@@ -179,16 +197,36 @@ fn show_reconciliation_status(account: &Account) {
              reconciled.to_f64() + cleared_total);
 }
 
-fn format_date(timestamp: i64) -> String {
-    if timestamp == 0 {
-        return "N/A".to_string();
-    }
-    let days = timestamp / 86400;
-    let years = 1970 + days / 365;
-    let day_of_year = days % 365;
-    let month = day_of_year / 30 + 1;
-    let day = day_of_year % 30 + 1;
-    format!("{:04}-{:02}-{:02}", years, month.min(12), day.min(28))
+/// Ticks every candidate up to today and shows how far the cleared
+/// balance is from the statement, then cancels so nothing is changed.
+fn preview_reconciliation(account: &Account, ending_balance: Numeric) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let mut rec = Reconciliation::new(account, now, ending_balance);
+    rec.clear_all();
+
+    println!();
+    println!(
+        "Reconciliation Preview (statement balance {}):",
+        ending_balance.to_decimal_string(2)
+    );
+    println!("{:-<60}", "");
+    println!(
+        "  Starting Balance:   {:>12}",
+        rec.starting_balance().to_decimal_string(2)
+    );
+    println!("  Candidates:         {:>12}", rec.candidates().len());
+    println!(
+        "  Cleared Balance:    {:>12}",
+        rec.cleared_balance().to_decimal_string(2)
+    );
+    println!(
+        "  Difference:         {:>12}",
+        rec.difference().to_decimal_string(2)
+    );
+    rec.cancel();
 }
 
 fn truncate(s: &str, max_len: usize) -> String {
@@ -9,6 +9,9 @@ use std::env;
 
 use gnucash_sys::{init_engine, Account, Session, SessionOpenMode, Split};
 
+mod common;
+use common::format_date;
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -197,18 +200,6 @@ fn split_matches_term(split: &Split, term: &str) -> bool {
     false
 }
 
-fn format_date(timestamp: i64) -> String {
-    if timestamp == 0 {
-        return "N/A".to_string();
-    }
-    let days = timestamp / 86400;
-    let years = 1970 + days / 365;
-    let day_of_year = days % 365;
-    let month = day_of_year / 30 + 1;
-    let day = day_of_year % 30 + 1;
-    format!("{:04}-{:02}-{:02}", years, month.min(12), day.min(28))
-}
-
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -13,7 +13,7 @@
 //!
 //! Based on: gnucash/bindings/python/example_scripts/simple_book.py
 
-use gnucash_sys::{init_engine, Account, Book, GNCAccountType};
+use gnucash_sys::{Account, Book, Editable, GNCAccountType, init_engine};
 
 fn main() {
     // Initialize the GnuCash engine
@@ -38,11 +38,11 @@ fn main() {
 
     for (name, account_type) in accounts {
         let mut account = Account::new(&book);
-        account.begin_edit();
-        account.set_name(name);
-        account.set_type(account_type);
-        account.commit_edit();
-        root.append_child(&account);
+        let edit = account.edit();
+        edit.set_name(name);
+        edit.set_type(account_type);
+        edit.commit();
+        root.edit().append_child(&account);
         account.mark_unowned(); // Book now owns it
         println!("Created account: {}", name);
     }
@@ -51,21 +51,21 @@ fn main() {
     if let Some(assets) = root.lookup_by_name("Assets") {
         // Create Current Assets
         let mut current_assets = Account::new(&book);
-        current_assets.begin_edit();
-        current_assets.set_name("Current Assets");
-        current_assets.set_type(GNCAccountType::ACCT_TYPE_ASSET);
-        current_assets.commit_edit();
-        assets.append_child(&current_assets);
+        let edit = current_assets.edit();
+        edit.set_name("Current Assets");
+        edit.set_type(GNCAccountType::ACCT_TYPE_ASSET);
+        edit.commit();
+        assets.edit().append_child(&current_assets);
         current_assets.mark_unowned();
 
         // Create Checking Account under Current Assets
         let mut checking = Account::new(&book);
-        checking.begin_edit();
-        checking.set_name("Checking Account");
-        checking.set_type(GNCAccountType::ACCT_TYPE_BANK);
-        checking.set_description("Primary checking account");
-        checking.commit_edit();
-        current_assets.append_child(&checking);
+        let edit = checking.edit();
+        edit.set_name("Checking Account");
+        edit.set_type(GNCAccountType::ACCT_TYPE_BANK);
+        edit.set_description("Primary checking account");
+        edit.commit();
+        current_assets.edit().append_child(&checking);
         checking.mark_unowned();
 
         println!("Created sub-accounts under Assets");
@@ -74,11 +74,11 @@ fn main() {
     // Find Equity and add Opening Balances
     if let Some(equity) = root.lookup_by_name("Equity") {
         let mut opening = Account::new(&book);
-        opening.begin_edit();
-        opening.set_name("Opening Balances");
-        opening.set_type(GNCAccountType::ACCT_TYPE_EQUITY);
-        opening.commit_edit();
-        equity.append_child(&opening);
+        let edit = opening.edit();
+        edit.set_name("Opening Balances");
+        edit.set_type(GNCAccountType::ACCT_TYPE_EQUITY);
+        edit.commit();
+        equity.edit().append_child(&opening);
         opening.mark_unowned();
 
         println!("Created Opening Balances under Equity");
@@ -16,7 +16,7 @@
 
 use std::env;
 
-use gnucash_sys::{init_engine, Session, SessionOpenMode};
+use gnucash_sys::{Editable, Session, SessionOpenMode, init_engine};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -62,9 +62,9 @@ fn create_new_file(uri: &str) {
 
                 // Get root account
                 if let Some(root) = book.root_account() {
-                    root.begin_edit();
-                    root.set_description("Created by Rust gnucash-sys");
-                    root.commit_edit();
+                    let edit = root.edit();
+                    edit.set_description("Created by Rust gnucash-sys");
+                    edit.commit();
                     println!("Set root account description");
                 }
 
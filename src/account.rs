@@ -1,11 +1,28 @@
 //! Safe wrapper for GnuCash Account.
 
 use std::ffi::{CStr, CString};
-use std::ptr::NonNull;
+use std::ptr::{self, NonNull};
 
+use crate::edit::{Editable, Undo};
 use crate::ffi;
 use crate::iter::{AccountChildren, AccountDescendants, AccountSplits};
-use crate::{Book, Guid, Numeric};
+use crate::scrub::{self, ScrubOptions, ScrubReport};
+use crate::slots::{SlotEditor, Slots};
+use crate::split::ReconcileState;
+use crate::{Book, Error, Guid, Numeric, Result, Split, Transaction};
+
+/// KVP path of the last reconciliation's ending balance, next to the
+/// `reconcile-info/last-date` slot GnuCash keeps.
+const RECONCILE_LAST_BALANCE: &str = "reconcile-info/last-balance";
+
+/// KVP paths of the reconciliation values GnuCash keeps itself.
+const RECONCILE_LAST_DATE: &str = "reconcile-info/last-date";
+const RECONCILE_POSTPONE_DATE: &str = "reconcile-info/postpone/date";
+const RECONCILE_POSTPONE_BALANCE: &str = "reconcile-info/postpone/balance";
+
+/// KVP paths of the US tax settings.
+const TAX_US_CODE: &str = "tax-US/code";
+const TAX_US_PAYER_NAME_SOURCE: &str = "tax-US/payer-name-source";
 
 /// Account type enumeration.
 pub use crate::ffi::GNCAccountType;
@@ -57,14 +74,20 @@ impl Account {
         }
     }
 
+    /// Returns the key-value slots attached to this account.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     /// Begins an edit session on this account.
     /// Must be called before making changes.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::xaccAccountBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes made during the edit session.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::xaccAccountCommitEdit(self.ptr.as_ptr()) }
     }
 
@@ -173,67 +196,47 @@ impl Account {
     // ==================== Setters ====================
 
     /// Sets the account's name.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `name` contains a null byte.
-    pub fn set_name(&self, name: &str) {
+    fn set_name(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
         unsafe { ffi::xaccAccountSetName(self.ptr.as_ptr(), c_name.as_ptr()) }
     }
 
     /// Sets the account's code.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `code` contains a null byte.
-    pub fn set_code(&self, code: &str) {
+    fn set_code(&self, code: &str) {
         let c_code = CString::new(code).unwrap();
         unsafe { ffi::xaccAccountSetCode(self.ptr.as_ptr(), c_code.as_ptr()) }
     }
 
     /// Sets the account's description.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `desc` contains a null byte.
-    pub fn set_description(&self, desc: &str) {
+    fn set_description(&self, desc: &str) {
         let c_desc = CString::new(desc).unwrap();
         unsafe { ffi::xaccAccountSetDescription(self.ptr.as_ptr(), c_desc.as_ptr()) }
     }
 
     /// Sets the account's notes.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `notes` contains a null byte.
-    pub fn set_notes(&self, notes: &str) {
+    fn set_notes(&self, notes: &str) {
         let c_notes = CString::new(notes).unwrap();
         unsafe { ffi::xaccAccountSetNotes(self.ptr.as_ptr(), c_notes.as_ptr()) }
     }
 
     /// Sets the account's color.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `color` contains a null byte.
-    pub fn set_color(&self, color: &str) {
+    fn set_color(&self, color: &str) {
         let c_color = CString::new(color).unwrap();
         unsafe { ffi::xaccAccountSetColor(self.ptr.as_ptr(), c_color.as_ptr()) }
     }
 
     /// Sets the account type.
-    pub fn set_type(&self, account_type: GNCAccountType) {
+    fn set_type(&self, account_type: GNCAccountType) {
         unsafe { ffi::xaccAccountSetType(self.ptr.as_ptr(), account_type) }
     }
 
     /// Sets the placeholder flag.
-    pub fn set_placeholder(&self, val: bool) {
+    fn set_placeholder(&self, val: bool) {
         unsafe { ffi::xaccAccountSetPlaceholder(self.ptr.as_ptr(), val as i32) }
     }
 
     /// Sets the hidden flag.
-    pub fn set_hidden(&self, val: bool) {
+    fn set_hidden(&self, val: bool) {
         unsafe { ffi::xaccAccountSetHidden(self.ptr.as_ptr(), val as i32) }
     }
 
@@ -255,6 +258,14 @@ impl Account {
         }
     }
 
+    /// Returns the book this account belongs to.
+    pub fn book(&self) -> Option<Book> {
+        unsafe {
+            let ptr = ffi::gnc_account_get_book(self.ptr.as_ptr());
+            Book::from_raw(ptr, false)
+        }
+    }
+
     /// Returns the number of immediate children.
     pub fn n_children(&self) -> i32 {
         unsafe { ffi::gnc_account_n_children(self.ptr.as_ptr()) }
@@ -280,7 +291,7 @@ impl Account {
 
     /// Appends a child account to this account.
     /// Note: After appending, the parent/book takes ownership of the child.
-    pub fn append_child(&self, child: &Account) {
+    fn append_child(&self, child: &Account) {
         unsafe { ffi::gnc_account_append_child(self.ptr.as_ptr(), child.ptr.as_ptr()) }
     }
 
@@ -291,7 +302,7 @@ impl Account {
     }
 
     /// Removes a child account from this account.
-    pub fn remove_child(&self, child: &Account) {
+    fn remove_child(&self, child: &Account) {
         unsafe { ffi::gnc_account_remove_child(self.ptr.as_ptr(), child.ptr.as_ptr()) }
     }
 
@@ -364,6 +375,196 @@ impl Account {
         unsafe { ffi::xaccAccountRecomputeBalance(self.ptr.as_ptr()) }
     }
 
+    // ==================== Reconciliation ====================
+
+    /// Returns the statement date of the last completed reconciliation.
+    pub fn reconcile_last_date(&self) -> Option<i64> {
+        let mut date: i64 = 0;
+        let found = unsafe { ffi::xaccAccountGetReconcileLastDate(self.ptr.as_ptr(), &mut date) };
+        if found != 0 { Some(date) } else { None }
+    }
+
+    /// Sets the statement date of the last completed reconciliation.
+    fn set_reconcile_last_date(&self, date: i64) {
+        unsafe { ffi::xaccAccountSetReconcileLastDate(self.ptr.as_ptr(), date) }
+    }
+
+    /// Returns the ending balance of the last completed reconciliation.
+    pub fn reconcile_last_balance(&self) -> Option<Numeric> {
+        self.slots().get_numeric(RECONCILE_LAST_BALANCE)
+    }
+
+    /// Sets the ending balance of the last completed reconciliation.
+    fn set_reconcile_last_balance(&self, balance: Numeric) -> Result<()> {
+        self.slots().set(RECONCILE_LAST_BALANCE, balance)
+    }
+
+    /// Returns the statement date of a postponed reconciliation.
+    pub fn reconcile_postpone_date(&self) -> Option<i64> {
+        let mut date: i64 = 0;
+        let found =
+            unsafe { ffi::xaccAccountGetReconcilePostponeDate(self.ptr.as_ptr(), &mut date) };
+        if found != 0 { Some(date) } else { None }
+    }
+
+    /// Sets the statement date of a postponed reconciliation. The GnuCash
+    /// reconcile dialog offers it as the default statement date.
+    fn set_reconcile_postpone_date(&self, date: i64) {
+        unsafe { ffi::xaccAccountSetReconcilePostponeDate(self.ptr.as_ptr(), date) }
+    }
+
+    /// Returns the ending balance of a postponed reconciliation.
+    pub fn reconcile_postpone_balance(&self) -> Option<Numeric> {
+        let mut balance = Numeric::zero().into();
+        let found =
+            unsafe { ffi::xaccAccountGetReconcilePostponeBalance(self.ptr.as_ptr(), &mut balance) };
+        if found != 0 {
+            Some(balance.into())
+        } else {
+            None
+        }
+    }
+
+    /// Sets the ending balance of a postponed reconciliation. The GnuCash
+    /// reconcile dialog offers it as the default ending balance.
+    fn set_reconcile_postpone_balance(&self, balance: Numeric) {
+        unsafe { ffi::xaccAccountSetReconcilePostponeBalance(self.ptr.as_ptr(), balance.into()) }
+    }
+
+    /// Clears the postponed reconciliation date and balance.
+    fn clear_reconcile_postpone(&self) {
+        unsafe { ffi::xaccAccountClearReconcilePostpone(self.ptr.as_ptr()) }
+    }
+
+    /// Marks every new split posted at or before `date` as cleared and
+    /// returns how many changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReadOnly`] without changing anything if one of
+    /// the affected transactions is read-only.
+    pub fn clear_all_before(&self, date: i64) -> Result<usize> {
+        self.update_reconcile_states(ReconcileState::Cleared, |state, posted| {
+            state == ReconcileState::New && posted <= date
+        })
+    }
+
+    /// Marks every cleared or reconciled split posted between `start`
+    /// and `end` (inclusive) as new again and returns how many changed.
+    /// Frozen splits belong to a closed period and are left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReadOnly`] without changing anything if one of
+    /// the affected transactions is read-only.
+    pub fn unreconcile_range(&self, start: i64, end: i64) -> Result<usize> {
+        self.update_reconcile_states(ReconcileState::New, |state, posted| {
+            matches!(state, ReconcileState::Cleared | ReconcileState::Reconciled)
+                && (start..=end).contains(&posted)
+        })
+    }
+
+    /// Sets `to` on the splits selected by `filter(state, date_posted)`.
+    /// Every affected transaction is checked and opened before the first
+    /// change and committed after the last.
+    fn update_reconcile_states(
+        &self,
+        to: ReconcileState,
+        filter: impl Fn(ReconcileState, i64) -> bool,
+    ) -> Result<usize> {
+        // Each split with the index of its transaction.
+        let mut targets: Vec<(usize, Split)> = Vec::new();
+        let mut transactions: Vec<Transaction> = Vec::new();
+        for split in self.splits() {
+            let Some(trans) = split.transaction() else {
+                continue;
+            };
+            if !filter(split.reconcile_state(), trans.date_posted()) {
+                continue;
+            }
+            if trans.read_only_reason().is_some() || trans.is_readonly_by_posted_date() {
+                return Err(Error::ReadOnly);
+            }
+            let index = match transactions
+                .iter()
+                .position(|t| t.as_ptr() == trans.as_ptr())
+            {
+                Some(index) => index,
+                None => {
+                    transactions.push(trans);
+                    transactions.len() - 1
+                }
+            };
+            targets.push((index, split));
+        }
+
+        let edits: Vec<_> = transactions.iter().map(Editable::edit).collect();
+        for (index, split) in &targets {
+            edits[*index].split(split).set_reconcile_state(to);
+        }
+        drop(edits);
+        Ok(targets.len())
+    }
+
+    // ==================== Tax ====================
+
+    /// Returns true if the account is flagged as tax-related.
+    pub fn is_tax_related(&self) -> bool {
+        unsafe { ffi::xaccAccountGetTaxRelated(self.ptr.as_ptr()) != 0 }
+    }
+
+    /// Sets the tax-related flag.
+    fn set_tax_related(&self, val: bool) {
+        unsafe { ffi::xaccAccountSetTaxRelated(self.ptr.as_ptr(), val as i32) }
+    }
+
+    /// Returns the TXF code assigned to the account (e.g. "N521").
+    pub fn tax_us_code(&self) -> Option<String> {
+        unsafe {
+            let ptr = ffi::xaccAccountGetTaxUSCode(self.ptr.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    /// Sets the TXF code.
+    fn set_tax_us_code(&self, code: &str) {
+        let c_code = CString::new(code).unwrap();
+        unsafe { ffi::xaccAccountSetTaxUSCode(self.ptr.as_ptr(), c_code.as_ptr()) }
+    }
+
+    /// Returns where the payer name comes from: "current" for this
+    /// account's name, "parent" for its parent's.
+    pub fn tax_us_payer_name_source(&self) -> Option<String> {
+        unsafe {
+            let ptr = ffi::xaccAccountGetTaxUSPayerNameSource(self.ptr.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    /// Sets the payer name source.
+    fn set_tax_us_payer_name_source(&self, source: &str) {
+        let c_source = CString::new(source).unwrap();
+        unsafe { ffi::xaccAccountSetTaxUSPayerNameSource(self.ptr.as_ptr(), c_source.as_ptr()) }
+    }
+
+    /// Returns the copy number of the tax form (1 if unset).
+    pub fn tax_us_copy_number(&self) -> i64 {
+        unsafe { ffi::xaccAccountGetTaxUSCopyNumber(self.ptr.as_ptr()) }
+    }
+
+    /// Sets the copy number of the tax form.
+    fn set_tax_us_copy_number(&self, copy_number: i64) {
+        unsafe { ffi::xaccAccountSetTaxUSCopyNumber(self.ptr.as_ptr(), copy_number) }
+    }
+
     // ==================== Splits ====================
 
     /// Returns the number of splits in this account.
@@ -392,6 +593,16 @@ impl Account {
     pub fn splits(&self) -> AccountSplits {
         AccountSplits::new(self)
     }
+
+    // ==================== Check & Repair ====================
+
+    /// Runs Check & Repair on this account, its descendants and every
+    /// transaction touching them, and reports each problem fixed, or
+    /// only found when `options` asks for a dry run. Imbalance and
+    /// orphan accounts are created under the tree's root.
+    pub fn scrub_subtree(&self, options: &ScrubOptions) -> ScrubReport {
+        scrub::scrub_tree(self, options)
+    }
 }
 
 impl Drop for Account {
@@ -405,6 +616,232 @@ impl Drop for Account {
     }
 }
 
+/// The setters of an [`Account`] in an open edit session, reached
+/// through [`Editable::edit`](crate::Editable::edit). Dereferences to the
+/// account for everything else.
+pub struct AccountEditor<'a> {
+    account: &'a Account,
+    undo: Undo<Account>,
+}
+
+impl<'a> AccountEditor<'a> {
+    pub(crate) fn new(account: &'a Account, undo: Undo<Account>) -> Self {
+        Self { account, undo }
+    }
+
+    /// Records how to put back a change made through the raw pointer,
+    /// should the edit be rolled back.
+    pub fn on_rollback(&self, restore: impl FnOnce(&Account) + 'static) {
+        self.undo.record(restore);
+    }
+
+    /// Returns the account's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Account> {
+        SlotEditor::new(self.account.slots(), &self.undo)
+    }
+
+    /// Records how to put `child` back under its current parent. Both are
+    /// looked up again on rollback, in case either was destroyed since.
+    fn keep_parent(&self, child: &Account) {
+        let guid = child.guid();
+        let old = child.parent().map(|p| p.guid());
+        self.undo.record(move |a| unsafe {
+            let book = ffi::gnc_account_get_book(a.as_ptr());
+            let child = ffi::xaccAccountLookup(guid.as_ffi(), book);
+            if child.is_null() {
+                return;
+            }
+            let old = old.map_or(ptr::null_mut(), |g| {
+                ffi::xaccAccountLookup(g.as_ffi(), book)
+            });
+            if !old.is_null() {
+                ffi::gnc_account_append_child(old, child);
+            } else {
+                let parent = ffi::gnc_account_get_parent(child);
+                if !parent.is_null() {
+                    ffi::gnc_account_remove_child(parent, child);
+                }
+            }
+        });
+    }
+
+    /// Appends a child account to this account, moving it from its
+    /// current parent.
+    /// Note: After appending, the parent/book takes ownership of the child.
+    pub fn append_child(&self, child: &Account) {
+        self.keep_parent(child);
+        self.account.append_child(child);
+    }
+
+    /// Removes a child account from this account.
+    pub fn remove_child(&self, child: &Account) {
+        self.keep_parent(child);
+        self.account.remove_child(child);
+    }
+
+    /// Records how to restore the slot at `path` as it is now.
+    fn keep_slot(&self, path: &'static str) {
+        let old = self.account.slots().get(path);
+        self.undo.record(move |a| {
+            let _ = match old {
+                Some(value) => a.slots().set(path, value),
+                None => a.slots().delete(path),
+            };
+        });
+    }
+
+    /// Sets the account's name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains a null byte.
+    pub fn set_name(&self, name: &str) {
+        let old = self.account.name().unwrap_or_default();
+        self.undo.record(move |a| a.set_name(&old));
+        self.account.set_name(name);
+    }
+
+    /// Sets the account's code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `code` contains a null byte.
+    pub fn set_code(&self, code: &str) {
+        let old = self.account.code().unwrap_or_default();
+        self.undo.record(move |a| a.set_code(&old));
+        self.account.set_code(code);
+    }
+
+    /// Sets the account's description.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `desc` contains a null byte.
+    pub fn set_description(&self, desc: &str) {
+        let old = self.account.description().unwrap_or_default();
+        self.undo.record(move |a| a.set_description(&old));
+        self.account.set_description(desc);
+    }
+
+    /// Sets the account's notes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `notes` contains a null byte.
+    pub fn set_notes(&self, notes: &str) {
+        let old = self.account.notes().unwrap_or_default();
+        self.undo.record(move |a| a.set_notes(&old));
+        self.account.set_notes(notes);
+    }
+
+    /// Sets the account's color.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `color` contains a null byte.
+    pub fn set_color(&self, color: &str) {
+        let old = self.account.color().unwrap_or_default();
+        self.undo.record(move |a| a.set_color(&old));
+        self.account.set_color(color);
+    }
+
+    /// Sets the account type.
+    pub fn set_type(&self, account_type: GNCAccountType) {
+        let old = self.account.account_type();
+        self.undo.record(move |a| a.set_type(old));
+        self.account.set_type(account_type);
+    }
+
+    /// Sets the placeholder flag.
+    pub fn set_placeholder(&self, val: bool) {
+        let old = self.account.is_placeholder();
+        self.undo.record(move |a| a.set_placeholder(old));
+        self.account.set_placeholder(val);
+    }
+
+    /// Sets the hidden flag.
+    pub fn set_hidden(&self, val: bool) {
+        let old = self.account.is_hidden();
+        self.undo.record(move |a| a.set_hidden(old));
+        self.account.set_hidden(val);
+    }
+
+    /// Sets the statement date of the last completed reconciliation.
+    pub fn set_reconcile_last_date(&self, date: i64) {
+        self.keep_slot(RECONCILE_LAST_DATE);
+        self.account.set_reconcile_last_date(date);
+    }
+
+    /// Sets the ending balance of the last completed reconciliation.
+    pub fn set_reconcile_last_balance(&self, balance: Numeric) -> Result<()> {
+        self.keep_slot(RECONCILE_LAST_BALANCE);
+        self.account.set_reconcile_last_balance(balance)
+    }
+
+    /// Sets the statement date of a postponed reconciliation. The GnuCash
+    /// reconcile dialog offers it as the default statement date.
+    pub fn set_reconcile_postpone_date(&self, date: i64) {
+        self.keep_slot(RECONCILE_POSTPONE_DATE);
+        self.account.set_reconcile_postpone_date(date);
+    }
+
+    /// Sets the ending balance of a postponed reconciliation. The GnuCash
+    /// reconcile dialog offers it as the default ending balance.
+    pub fn set_reconcile_postpone_balance(&self, balance: Numeric) {
+        self.keep_slot(RECONCILE_POSTPONE_BALANCE);
+        self.account.set_reconcile_postpone_balance(balance);
+    }
+
+    /// Clears the postponed reconciliation date and balance.
+    pub fn clear_reconcile_postpone(&self) {
+        self.keep_slot(RECONCILE_POSTPONE_DATE);
+        self.keep_slot(RECONCILE_POSTPONE_BALANCE);
+        self.account.clear_reconcile_postpone();
+    }
+
+    /// Sets the tax-related flag.
+    pub fn set_tax_related(&self, val: bool) {
+        let old = self.account.is_tax_related();
+        self.undo.record(move |a| a.set_tax_related(old));
+        self.account.set_tax_related(val);
+    }
+
+    /// Sets the TXF code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `code` contains a null byte.
+    pub fn set_tax_us_code(&self, code: &str) {
+        self.keep_slot(TAX_US_CODE);
+        self.account.set_tax_us_code(code);
+    }
+
+    /// Sets the payer name source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` contains a null byte.
+    pub fn set_tax_us_payer_name_source(&self, source: &str) {
+        self.keep_slot(TAX_US_PAYER_NAME_SOURCE);
+        self.account.set_tax_us_payer_name_source(source);
+    }
+
+    /// Sets the copy number of the tax form.
+    pub fn set_tax_us_copy_number(&self, copy_number: i64) {
+        let old = self.account.tax_us_copy_number();
+        self.undo.record(move |a| a.set_tax_us_copy_number(old));
+        self.account.set_tax_us_copy_number(copy_number);
+    }
+}
+
+impl std::ops::Deref for AccountEditor<'_> {
+    type Target = Account;
+
+    fn deref(&self) -> &Account {
+        self.account
+    }
+}
+
 impl std::fmt::Debug for Account {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Account")
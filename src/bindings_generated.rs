@@ -868,6 +868,36 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn g_free(mem: gpointer);
 }
+unsafe extern "C" {
+    pub fn g_value_init(value: *mut GValue, g_type: GType) -> *mut GValue;
+}
+unsafe extern "C" {
+    pub fn g_value_unset(value: *mut GValue);
+}
+unsafe extern "C" {
+    pub fn g_value_set_int64(value: *mut GValue, v_int64: gint64);
+}
+unsafe extern "C" {
+    pub fn g_value_get_int64(value: *const GValue) -> gint64;
+}
+unsafe extern "C" {
+    pub fn g_value_set_double(value: *mut GValue, v_double: gdouble);
+}
+unsafe extern "C" {
+    pub fn g_value_get_double(value: *const GValue) -> gdouble;
+}
+unsafe extern "C" {
+    pub fn g_value_set_string(value: *mut GValue, v_string: *const gchar);
+}
+unsafe extern "C" {
+    pub fn g_value_get_string(value: *const GValue) -> *const gchar;
+}
+unsafe extern "C" {
+    pub fn g_value_set_boxed(value: *mut GValue, v_boxed: gconstpointer);
+}
+unsafe extern "C" {
+    pub fn g_value_get_boxed(value: *const GValue) -> gpointer;
+}
 pub type GList = _GList;
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -1017,6 +1047,15 @@ pub struct _GHashTable {
     _unused: [u8; 0],
 }
 pub type GHashTable = _GHashTable;
+pub type GHFunc = ::std::option::Option<
+    unsafe extern "C" fn(key: gpointer, value: gpointer, user_data: gpointer),
+>;
+unsafe extern "C" {
+    pub fn g_hash_table_foreach(hash_table: *mut GHashTable, func: GHFunc, user_data: gpointer);
+}
+unsafe extern "C" {
+    pub fn g_hash_table_unref(hash_table: *mut GHashTable);
+}
 pub type GSList = _GSList;
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -1708,6 +1747,9 @@ unsafe extern "C" {
     #[doc = " @name GValue\n@{"]
     pub fn gnc_numeric_get_type() -> GType;
 }
+unsafe extern "C" {
+    pub fn time64_get_type() -> GType;
+}
 #[doc = " Most systems that are currently maintained, including Microsoft Windows,\n BSD-derived Unixes and Linux, support 64-bit time_t even on 32-bit\n architectures. See https://en.wikipedia.org/wiki/Year_2038_problem\n\n For practical reasons -- as not all have made the transition to 64-bit --\n we define our own 64-bit time type."]
 pub type time64 = gint64;
 #[repr(C)]
@@ -2182,6 +2224,9 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn qof_instance_get_guid(arg1: gconstpointer) -> *const GncGUID;
 }
+unsafe extern "C" {
+    pub fn qof_instance_set_guid(inst: gpointer, guid: *const GncGUID);
+}
 unsafe extern "C" {
     pub fn qof_entity_get_guid(arg1: gconstpointer) -> *const GncGUID;
 }
@@ -2260,6 +2305,28 @@ unsafe extern "C" {
         ref_: *const QofInstance,
     ) -> *mut GList;
 }
+unsafe extern "C" {
+    #[doc = " Set the dirty flag"]
+    pub fn qof_instance_set_dirty(inst: *mut QofInstance);
+}
+unsafe extern "C" {
+    #[doc = " Sets a KVP slot to a value from a GValue. The key can be a '/'-delimited\n path, and intermediate folders will be created if necessary."]
+    pub fn qof_instance_set_kvp(
+        inst: *mut QofInstance,
+        value: *const GValue,
+        count: ::std::os::raw::c_uint,
+        ...
+    );
+}
+unsafe extern "C" {
+    #[doc = " Retrieves the contents of a KVP slot into a provided GValue."]
+    pub fn qof_instance_get_kvp(
+        inst: *mut QofInstance,
+        value: *mut GValue,
+        count: ::std::os::raw::c_uint,
+        ...
+    );
+}
 unsafe extern "C" {
     #[doc = " create a new collection of entities of type"]
     pub fn qof_collection_new(type_: QofIdType) -> *mut QofCollection;
@@ -6174,6 +6241,29 @@ unsafe extern "C" {
     #[doc = " The xaccTransScrubGains() routine performs a number of cleanup\n  functions on the indicated transaction, with the end-goal of\n  setting up a consistent set of gains/losses for all the splits\n  in the transaction.  This includes making sure that the lot\n  assignments of all the splits are good, and that the lots\n  balance appropriately."]
     pub fn xaccTransScrubGains(trans: *mut Transaction, gain_acc: *mut Account);
 }
+unsafe extern "C" {
+    #[doc = " The xaccTransScrubOrphans() method scrubs only the splits in the\n  given transaction."]
+    pub fn xaccTransScrubOrphans(trans: *mut Transaction);
+}
+unsafe extern "C" {
+    pub fn xaccTransScrubSplits(trans: *mut Transaction);
+}
+unsafe extern "C" {
+    #[doc = " The xaccScrubImbalance() method searches for transactions that do\n    not balance to zero. If any such transactions are found, a split\n    is created to offset this amount and is added to an \"imbalance\"\n    account."]
+    pub fn xaccTransScrubImbalance(
+        trans: *mut Transaction,
+        root: *mut Account,
+        parent: *mut Account,
+    );
+}
+unsafe extern "C" {
+    #[doc = " The xaccTransScrubCurrency method fixes transactions without a\n common_currency by looking for the most commonly used currency\n among all the splits in the transaction."]
+    pub fn xaccTransScrubCurrency(trans: *mut Transaction);
+}
+unsafe extern "C" {
+    #[doc = " The xaccAccountScrubLots() routine makes sure that every split\n    in the account is assigned to a lot, and that then, every\n    lot is self-consistent (by calling xaccScrubLot() on each lot)."]
+    pub fn xaccAccountScrubLots(acc: *mut Account);
+}
 unsafe extern "C" {
     #[doc = " \\warning XXX FIXME\n gnc_book_count_transactions is a utility function,\n probably needs to be moved to a utility file somewhere."]
     pub fn gnc_book_count_transactions(book: *mut QofBook) -> guint;
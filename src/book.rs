@@ -1,9 +1,18 @@
 //! Safe wrapper for QofBook.
 
-use std::ptr::NonNull;
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::ptr::{self, NonNull};
 
+use crate::book_options::{self, BookOptions, FiscalYearEnd};
+use crate::edit::Undo;
+use crate::features::{self, Feature, Version};
 use crate::ffi;
-use crate::{Account, Guid};
+use crate::scrub::{self, ScrubOptions, ScrubReport};
+use crate::slots::{SlotEditor, Slots};
+use crate::{Account, Error, Guid, Result};
+
+/// KVP key of the fiscal year end behind the book's `fy-end` property.
+const FY_END: &str = "fy_end";
 
 /// A GnuCash Book - the top-level container for all financial data.
 ///
@@ -55,6 +64,22 @@ impl Book {
         }
     }
 
+    /// Returns the key-value slots attached to this book.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
+    /// Begins an edit session on this book.
+    pub(crate) fn begin_edit(&self) {
+        unsafe { ffi::qof_book_begin_edit(self.ptr.as_ptr()) }
+    }
+
+    /// Commits changes made during the edit session.
+    pub(crate) fn commit_edit(&self) {
+        unsafe { ffi::qof_book_commit_edit(self.ptr.as_ptr()) }
+    }
+
     /// Returns true if the book is read-only.
     pub fn is_readonly(&self) -> bool {
         unsafe { ffi::qof_book_is_readonly(self.ptr.as_ptr()) != 0 }
@@ -111,6 +136,179 @@ impl Book {
         unsafe { ffi::qof_book_uses_autoreadonly(self.ptr.as_ptr()) != 0 }
     }
 
+    // ==================== Options ====================
+
+    /// Returns the book options from File > Properties.
+    pub fn options(&self) -> BookOptions {
+        BookOptions::read(self)
+    }
+
+    /// Writes all book options.
+    fn set_options(&self, options: &BookOptions) -> Result<()> {
+        options.write(self)
+    }
+
+    /// Sets whether the book uses trading accounts.
+    fn set_use_trading_accounts(&self, val: bool) {
+        self.edit_options(|| book_options::set_flag(self, book_options::TRADING_ACCOUNTS, val));
+    }
+
+    /// Sets whether the split action field is used as the number.
+    /// Turning it on also records the "Number Field Source" feature, so
+    /// that GnuCash releases before 2.5 refuse the book.
+    fn set_use_split_action_for_num_field(&self, val: bool) -> Result<()> {
+        self.edit_options(|| book_options::set_flag(self, book_options::NUM_FIELD_SOURCE, val));
+        if val {
+            self.set_feature(features::NUM_FIELD_SOURCE)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the number of days after which transactions become
+    /// read-only; 0 disables the feature.
+    fn set_num_days_autoreadonly(&self, days: u32) {
+        self.edit_options(|| book_options::set_days(self, days));
+    }
+
+    fn edit_options(&self, f: impl FnOnce()) {
+        self.begin_edit();
+        f();
+        self.commit_edit();
+    }
+
+    /// Returns the last day of the fiscal year, if set.
+    pub fn fiscal_year_end(&self) -> Option<FiscalYearEnd> {
+        unsafe {
+            let mut date: *mut ffi::GDate = ptr::null_mut();
+            ffi::qof_instance_get(
+                self.ptr.as_ptr() as *const ffi::QofInstance,
+                c"fy-end".as_ptr(),
+                &mut date,
+                ptr::null::<c_char>(),
+            );
+            if date.is_null() {
+                return None;
+            }
+            let fy_end = (ffi::g_date_valid(date) != 0).then(|| FiscalYearEnd {
+                month: ffi::g_date_get_month(date),
+                day: ffi::g_date_get_day(date) as u32,
+            });
+            ffi::g_date_free(date);
+            fy_end
+        }
+    }
+
+    /// Sets the last day of the fiscal year. Returns an error if the
+    /// day does not exist in the month; February 29 is accepted.
+    pub(crate) fn set_fiscal_year_end(&self, fy_end: FiscalYearEnd) -> Result<()> {
+        if !fy_end.is_valid() {
+            return Err(Error::InvalidOperation(format!(
+                "invalid fiscal year end: month {}, day {}",
+                fy_end.month, fy_end.day
+            )));
+        }
+        unsafe {
+            // A leap year, so that February 29 is accepted.
+            let date = ffi::g_date_new_dmy(fy_end.day as u8, fy_end.month, 2000);
+            if date.is_null() {
+                return Err(Error::NullPointer("GDate"));
+            }
+            self.edit_options(|| {
+                ffi::qof_instance_set(
+                    self.ptr.as_ptr() as *mut ffi::QofInstance,
+                    c"fy-end".as_ptr(),
+                    date,
+                    ptr::null::<c_char>(),
+                )
+            });
+            ffi::g_date_free(date);
+        }
+        Ok(())
+    }
+
+    /// Removes the fiscal year end, as in a book that never had one set.
+    fn clear_fiscal_year_end(&self) {
+        self.edit_options(|| {
+            let _ = self.slots().delete(FY_END);
+        });
+    }
+
+    // ==================== Features ====================
+
+    /// Returns the features recorded in the book, sorted by name.
+    pub fn features(&self) -> Vec<Feature> {
+        unsafe extern "C" fn collect(
+            key: ffi::gpointer,
+            value: ffi::gpointer,
+            data: ffi::gpointer,
+        ) {
+            unsafe {
+                let features = &mut *(data as *mut Vec<Feature>);
+                let text = |p: ffi::gpointer| {
+                    if p.is_null() {
+                        String::new()
+                    } else {
+                        CStr::from_ptr(p as *const c_char)
+                            .to_string_lossy()
+                            .into_owned()
+                    }
+                };
+                features.push(Feature {
+                    name: text(key),
+                    description: text(value),
+                });
+            }
+        }
+
+        let mut features: Vec<Feature> = Vec::new();
+        unsafe {
+            let table = ffi::qof_book_get_features(self.ptr.as_ptr());
+            if !table.is_null() {
+                ffi::g_hash_table_foreach(
+                    table,
+                    Some(collect),
+                    &mut features as *mut Vec<Feature> as *mut c_void,
+                );
+                ffi::g_hash_table_unref(table);
+            }
+        }
+        features.sort_by(|a, b| a.name.cmp(&b.name));
+        features
+    }
+
+    /// Returns true if the book records the named feature.
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features().iter().any(|f| f.name == name)
+    }
+
+    /// Records a feature in the book. Features known to this crate get
+    /// GnuCash's description; others are described by their name.
+    pub(crate) fn set_feature(&self, name: &str) -> Result<()> {
+        let description = features::known(name).map_or(name, |(_, descr, _)| descr);
+        self.record_feature(name, description)
+    }
+
+    fn record_feature(&self, name: &str, description: &str) -> Result<()> {
+        let c_name = CString::new(name).map_err(|e| Error::StringConversion(e.to_string()))?;
+        let c_descr =
+            CString::new(description).map_err(|e| Error::StringConversion(e.to_string()))?;
+        unsafe { ffi::qof_book_set_feature(self.ptr.as_ptr(), c_name.as_ptr(), c_descr.as_ptr()) }
+        Ok(())
+    }
+
+    /// Removes a feature from the book.
+    fn unset_feature(&self, name: &str) -> Result<()> {
+        let c_name = CString::new(name).map_err(|e| Error::StringConversion(e.to_string()))?;
+        unsafe { ffi::qof_book_unset_feature(self.ptr.as_ptr(), c_name.as_ptr()) }
+        Ok(())
+    }
+
+    /// Returns the features that would stop GnuCash `max_version` from
+    /// opening the book; empty if it can.
+    pub fn check_compatible(&self, max_version: Version) -> Vec<Feature> {
+        features::blocking(&self.features(), max_version)
+    }
+
     /// Marks the book as closed for editing.
     pub fn mark_closed(&self) {
         unsafe { ffi::qof_book_mark_closed(self.ptr.as_ptr()) }
@@ -135,6 +333,129 @@ impl Book {
     pub fn transaction_count(&self) -> u32 {
         unsafe { ffi::gnc_book_count_transactions(self.ptr.as_ptr()) }
     }
+
+    // ==================== Check & Repair ====================
+
+    /// Runs Check & Repair over the whole account tree and reports each
+    /// problem fixed, or only found when `options` asks for a dry run.
+    pub fn scrub(&self, options: &ScrubOptions) -> Result<ScrubReport> {
+        let root = self
+            .root_account()
+            .ok_or(Error::NullPointer("root account"))?;
+        Ok(scrub::scrub_tree(&root, options))
+    }
+}
+
+/// The setters of a [`Book`] in an open edit session, reached through
+/// [`Editable::edit`](crate::Editable::edit). Dereferences to the book
+/// for everything else.
+pub struct BookEditor<'a> {
+    book: &'a Book,
+    undo: Undo<Book>,
+}
+
+impl<'a> BookEditor<'a> {
+    pub(crate) fn new(book: &'a Book, undo: Undo<Book>) -> Self {
+        Self { book, undo }
+    }
+
+    /// Returns the book's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Book> {
+        SlotEditor::new(self.book.slots(), &self.undo)
+    }
+
+    /// Records how to restore the named feature as it is now.
+    fn keep_feature(&self, name: &str) {
+        let name = name.to_string();
+        let old = self.book.features().into_iter().find(|f| f.name == name);
+        self.undo.record(move |b| {
+            let _ = match old {
+                Some(feature) => b.record_feature(&feature.name, &feature.description),
+                None => b.unset_feature(&name),
+            };
+        });
+    }
+
+    /// Records how to restore the fiscal year end as it is now.
+    fn keep_fiscal_year_end(&self) {
+        let old = self.book.fiscal_year_end();
+        self.undo.record(move |b| match old {
+            Some(fy_end) => {
+                let _ = b.set_fiscal_year_end(fy_end);
+            }
+            None => b.clear_fiscal_year_end(),
+        });
+    }
+
+    /// Writes all book options in one edit. Turning on the split action
+    /// as the number also records the "Number Field Source" feature.
+    pub fn set_options(&self, options: &BookOptions) -> Result<()> {
+        self.keep_feature(features::NUM_FIELD_SOURCE);
+        self.keep_fiscal_year_end();
+        let old = self.book.options();
+        self.undo.record(move |b| {
+            let _ = b.set_options(&BookOptions {
+                fiscal_year_end: None,
+                ..old
+            });
+        });
+        self.book.set_options(options)
+    }
+
+    /// Sets whether the book uses trading accounts.
+    pub fn set_use_trading_accounts(&self, val: bool) {
+        let old = self.book.use_trading_accounts();
+        self.undo.record(move |b| b.set_use_trading_accounts(old));
+        self.book.set_use_trading_accounts(val);
+    }
+
+    /// Sets whether the split action field is used as the number.
+    /// Turning it on also records the "Number Field Source" feature, so
+    /// that GnuCash releases before 2.5 refuse the book.
+    pub fn set_use_split_action_for_num_field(&self, val: bool) -> Result<()> {
+        self.keep_feature(features::NUM_FIELD_SOURCE);
+        let old = self.book.use_split_action_for_num_field();
+        self.undo.record(move |b| {
+            b.edit_options(|| book_options::set_flag(b, book_options::NUM_FIELD_SOURCE, old));
+        });
+        self.book.set_use_split_action_for_num_field(val)
+    }
+
+    /// Sets the number of days after which transactions become
+    /// read-only; 0 disables the feature.
+    pub fn set_num_days_autoreadonly(&self, days: u32) {
+        let old = self.book.num_days_autoreadonly().max(0) as u32;
+        self.undo.record(move |b| b.set_num_days_autoreadonly(old));
+        self.book.set_num_days_autoreadonly(days);
+    }
+
+    /// Sets the last day of the fiscal year. Returns an error if the
+    /// day does not exist in the month; February 29 is accepted.
+    pub fn set_fiscal_year_end(&self, fy_end: FiscalYearEnd) -> Result<()> {
+        self.keep_fiscal_year_end();
+        self.book.set_fiscal_year_end(fy_end)
+    }
+
+    /// Records a feature in the book. Features known to this crate get
+    /// GnuCash's description; others are described by their name.
+    pub fn set_feature(&self, name: &str) -> Result<()> {
+        self.keep_feature(name);
+        self.book.set_feature(name)
+    }
+
+    /// Removes a feature from the book.
+    pub fn unset_feature(&self, name: &str) -> Result<()> {
+        self.keep_feature(name);
+        self.book.unset_feature(name)
+    }
+}
+
+impl std::ops::Deref for BookEditor<'_> {
+    type Target = Book;
+
+    fn deref(&self) -> &Book {
+        self.book
+    }
 }
 
 impl Default for Book {
@@ -0,0 +1,231 @@
+//! Typed access to the book options stored in the book's KVP.
+//!
+//! GnuCash keeps the File > Properties settings in the `options` frame
+//! of the book's slots, one sub-frame per dialog page. [`BookOptions`]
+//! reads and writes the commonly used ones:
+//!
+//! ```ignore
+//! use gnucash_sys::{BookOptions, Editable};
+//!
+//! let mut options = book.options();
+//! options.company_name = Some("Acme Ltd".to_string());
+//! options.auto_readonly_days = 30;
+//! book.edit().set_options(&options)?;
+//! ```
+
+use std::ffi::{CStr, c_char};
+use std::ptr;
+
+use crate::features;
+use crate::ffi;
+use crate::slots::Slots;
+use crate::{Book, Error, Guid, Result};
+
+const COMPANY_NAME: &str = "options/Business/Company Name";
+const COMPANY_ADDRESS: &str = "options/Business/Company Address";
+const COMPANY_ID: &str = "options/Business/Company ID";
+const COMPANY_PHONE: &str = "options/Business/Company Phone Number";
+const COMPANY_EMAIL: &str = "options/Business/Company Email Address";
+const CUSTOMER_TAX_TABLE: &str = "options/Business/Default Customer TaxTable";
+const VENDOR_TAX_TABLE: &str = "options/Business/Default Vendor TaxTable";
+/// Read by GnuCash 3.x only; earlier and later versions ignore it and
+/// take the default currency from the user's preferences.
+const BOOK_CURRENCY: &str = "options/Accounts/Book Currency";
+// Options the book caches are set through its GObject properties, whose
+// change notifications make the engine drop the cached value.
+pub(crate) const TRADING_ACCOUNTS: &CStr = c"trading-accts";
+pub(crate) const NUM_FIELD_SOURCE: &CStr = c"split-action-num-field";
+const AUTO_READONLY_DAYS: &CStr = c"autoreadonly-days";
+
+/// The last day of the fiscal year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FiscalYearEnd {
+    /// Month, 1-12.
+    pub month: u32,
+    /// Day of the month, 1-31.
+    pub day: u32,
+}
+
+impl FiscalYearEnd {
+    /// Returns true if the day exists in the month, counting February 29.
+    pub fn is_valid(&self) -> bool {
+        let days = match self.month {
+            2 => 29,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return false,
+        };
+        (1..=days).contains(&self.day)
+    }
+}
+
+/// Book-level settings from File > Properties.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookOptions {
+    pub company_name: Option<String>,
+    pub company_address: Option<String>,
+    pub company_id: Option<String>,
+    pub company_phone: Option<String>,
+    pub company_email: Option<String>,
+    /// ISO 4217 code of the book currency. Only GnuCash 3.x reads this
+    /// option; 2.6 and earlier, and 4.0 and later, keep it in the file
+    /// but ignore it and use the currency from the user's preferences.
+    pub default_currency: Option<String>,
+    pub fiscal_year_end: Option<FiscalYearEnd>,
+    /// Show the split action field as the register's number.
+    pub use_split_action_for_num: bool,
+    pub use_trading_accounts: bool,
+    /// Transactions older than this many days are read-only; 0 disables.
+    pub auto_readonly_days: u32,
+    pub default_customer_tax_table: Option<Guid>,
+    pub default_vendor_tax_table: Option<Guid>,
+}
+
+impl BookOptions {
+    /// Reads the options of `book`. Missing options take their defaults.
+    pub fn read(book: &Book) -> Self {
+        let slots = book.slots();
+        Self {
+            company_name: slots.get_string(COMPANY_NAME),
+            company_address: slots.get_string(COMPANY_ADDRESS),
+            company_id: slots.get_string(COMPANY_ID),
+            company_phone: slots.get_string(COMPANY_PHONE),
+            company_email: slots.get_string(COMPANY_EMAIL),
+            default_currency: slots.get_string(BOOK_CURRENCY),
+            fiscal_year_end: book.fiscal_year_end(),
+            use_split_action_for_num: book.use_split_action_for_num_field(),
+            use_trading_accounts: book.use_trading_accounts(),
+            auto_readonly_days: book.num_days_autoreadonly().max(0) as u32,
+            default_customer_tax_table: slots.get_guid(CUSTOMER_TAX_TABLE),
+            default_vendor_tax_table: slots.get_guid(VENDOR_TAX_TABLE),
+        }
+    }
+
+    /// Writes the options to `book` in one edit. Empty strings, `None`
+    /// and `false` remove the option, as the GnuCash dialog does; a
+    /// `None` fiscal year end leaves the stored one in place.
+    pub(crate) fn write(&self, book: &Book) -> Result<()> {
+        if let Some(fy_end) = self.fiscal_year_end
+            && !fy_end.is_valid()
+        {
+            return Err(Error::InvalidOperation(format!(
+                "invalid fiscal year end: month {}, day {}",
+                fy_end.month, fy_end.day
+            )));
+        }
+        unsafe { ffi::qof_book_begin_edit(book.as_ptr()) };
+        let result = self.write_slots(&book.slots());
+        set_flag(book, TRADING_ACCOUNTS, self.use_trading_accounts);
+        set_flag(book, NUM_FIELD_SOURCE, self.use_split_action_for_num);
+        set_days(book, self.auto_readonly_days);
+        let fy_result = match self.fiscal_year_end {
+            Some(fy_end) => book.set_fiscal_year_end(fy_end),
+            None => Ok(()),
+        };
+        unsafe { ffi::qof_book_commit_edit(book.as_ptr()) };
+        result?;
+        fy_result?;
+        if self.use_split_action_for_num {
+            book.set_feature(features::NUM_FIELD_SOURCE)?;
+        }
+        Ok(())
+    }
+
+    fn write_slots(&self, slots: &Slots<'_>) -> Result<()> {
+        set_string(slots, COMPANY_NAME, &self.company_name)?;
+        set_string(slots, COMPANY_ADDRESS, &self.company_address)?;
+        set_string(slots, COMPANY_ID, &self.company_id)?;
+        set_string(slots, COMPANY_PHONE, &self.company_phone)?;
+        set_string(slots, COMPANY_EMAIL, &self.company_email)?;
+        set_string(slots, BOOK_CURRENCY, &self.default_currency)?;
+        set_guid(slots, CUSTOMER_TAX_TABLE, self.default_customer_tax_table)?;
+        set_guid(slots, VENDOR_TAX_TABLE, self.default_vendor_tax_table)
+    }
+}
+
+fn set_string(slots: &Slots<'_>, path: &str, value: &Option<String>) -> Result<()> {
+    match value.as_deref() {
+        Some(s) if !s.is_empty() => slots.set(path, s),
+        _ => slots.delete(path),
+    }
+}
+
+fn set_guid(slots: &Slots<'_>, path: &str, value: Option<Guid>) -> Result<()> {
+    match value {
+        Some(guid) => slots.set(path, guid),
+        None => slots.delete(path),
+    }
+}
+
+/// Sets a check-box option property: "t" when set, absent otherwise.
+pub(crate) fn set_flag(book: &Book, property: &CStr, value: bool) {
+    let value = if value { c"t".as_ptr() } else { ptr::null() };
+    unsafe {
+        ffi::qof_instance_set(
+            book.as_ptr() as *mut ffi::QofInstance,
+            property.as_ptr(),
+            value,
+            ptr::null::<c_char>(),
+        )
+    }
+}
+
+/// Sets the auto-readonly threshold, which GnuCash keeps as a double.
+pub(crate) fn set_days(book: &Book, days: u32) {
+    unsafe {
+        ffi::qof_instance_set(
+            book.as_ptr() as *mut ffi::QofInstance,
+            AUTO_READONLY_DAYS.as_ptr(),
+            days as f64,
+            ptr::null::<c_char>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Editable;
+
+    #[test]
+    fn test_book_options_round_trip() {
+        let book = Book::new();
+        let options = BookOptions {
+            company_name: Some("Acme Ltd".to_string()),
+            company_email: Some("books@acme.test".to_string()),
+            default_currency: Some("EUR".to_string()),
+            fiscal_year_end: Some(FiscalYearEnd { month: 6, day: 30 }),
+            use_split_action_for_num: true,
+            auto_readonly_days: 30,
+            default_customer_tax_table: Some(Guid::new()),
+            ..Default::default()
+        };
+        book.edit().set_options(&options).unwrap();
+        assert_eq!(book.options(), options);
+        assert!(book.use_split_action_for_num_field());
+        assert_eq!(book.num_days_autoreadonly(), 30);
+
+        // A rolled-back edit puts the options and the feature back.
+        let result = book.edit_with(|b| {
+            b.set_options(&BookOptions::default())?;
+            b.set_fiscal_year_end(FiscalYearEnd { month: 3, day: 31 })?;
+            b.unset_feature(features::NUM_FIELD_SOURCE)?;
+            Err::<(), _>(Error::InvalidOperation("no".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(book.options(), options);
+        assert!(book.has_feature(features::NUM_FIELD_SOURCE));
+
+        book.edit().set_options(&BookOptions::default()).unwrap();
+        let cleared = book.options();
+        assert_eq!(cleared.company_name, None);
+        assert!(!cleared.use_split_action_for_num);
+        assert_eq!(book.num_days_autoreadonly(), 0);
+
+        let bad = FiscalYearEnd { month: 2, day: 31 };
+        assert!(!bad.is_valid());
+        assert!(book.edit().set_fiscal_year_end(bad).is_err());
+        assert!(!FiscalYearEnd { month: 13, day: 1 }.is_valid());
+        assert!(FiscalYearEnd { month: 2, day: 29 }.is_valid());
+    }
+}
@@ -0,0 +1,338 @@
+//! Scoped edit sessions.
+//!
+//! Engine entities are changed between a `begin_edit` and a
+//! `commit_edit`. [`Editable::edit`] opens a session and returns an
+//! [`EditGuard`] that commits when it goes out of scope, and
+//! [`Editable::edit_with`] runs a closure inside one. Setters live on the
+//! guard, which dereferences to the entity's editor, so nothing can be
+//! changed outside a session:
+//!
+//! ```ignore
+//! use gnucash_sys::edit::Editable;
+//!
+//! account.edit_with(|a| -> gnucash_sys::Result<()> {
+//!     a.set_name("Checking");
+//!     a.set_code("1010");
+//!     Ok(())
+//! })?;
+//!
+//! let txn = transaction.edit();
+//! txn.set_description("Groceries");
+//! txn.commit();
+//! ```
+//!
+//! If the closure returns an error, or the thread panics while a guard is
+//! alive, the edit is rolled back. Transactions use the engine's own
+//! rollback when the session is the outermost one. The engine keeps no
+//! copy of other entities, and rolls back nothing in a nested session, so
+//! editors also record each value they replace; in those cases the guard
+//! puts the values back before closing the session.
+
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::ffi;
+use crate::{Account, Book, Price, PriceDB, Transaction};
+
+/// An entity changed inside a `begin_edit` / `commit_edit` session.
+pub trait Editable: Sized {
+    /// The view through which an open session changes the entity.
+    type Editor<'e>: Deref<Target = Self>
+    where
+        Self: 'e;
+
+    /// Opens an edit session. Sessions nest.
+    fn begin_edit(&self);
+
+    /// Closes an edit session and saves the changes.
+    fn commit_edit(&self);
+
+    /// Returns the editor for an open session. Only an [`EditGuard`] can
+    /// supply `undo`, so editors exist only inside a session.
+    fn editor(&self, undo: Undo<Self>) -> Self::Editor<'_>;
+
+    /// Closes an edit session and discards the changes, if the engine
+    /// keeps a copy of the entity to restore. Returns false, leaving the
+    /// session open, if it does not.
+    fn try_rollback_edit(&self) -> bool {
+        false
+    }
+
+    /// Opens an edit session that ends when the returned guard is dropped.
+    fn edit(&self) -> EditGuard<'_, Self> {
+        EditGuard::new(self)
+    }
+
+    /// Runs `f` inside an edit session, committing if it returns `Ok` and
+    /// rolling back if it returns `Err` or panics.
+    fn edit_with<R, E>(&self, f: impl FnOnce(&Self::Editor<'_>) -> Result<R, E>) -> Result<R, E> {
+        let guard = self.edit();
+        match f(&guard) {
+            Ok(value) => {
+                guard.commit();
+                Ok(value)
+            }
+            Err(err) => {
+                guard.rollback();
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Puts back one value an editor replaced.
+type Restore<T> = Box<dyn FnOnce(&T)>;
+
+/// The values an editor replaced, restored newest first when an edit the
+/// engine cannot roll back is rolled back.
+pub struct Undo<T> {
+    log: Rc<RefCell<Vec<Restore<T>>>>,
+}
+
+impl<T> Undo<T> {
+    fn new() -> Self {
+        Self {
+            log: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    fn share(&self) -> Self {
+        Self {
+            log: Rc::clone(&self.log),
+        }
+    }
+
+    /// Records how to put back a value that is about to change.
+    pub fn record(&self, restore: impl FnOnce(&T) + 'static) {
+        self.log.borrow_mut().push(Box::new(restore));
+    }
+
+    fn restore(&self, entity: &T) {
+        let log = std::mem::take(&mut *self.log.borrow_mut());
+        for restore in log.into_iter().rev() {
+            restore(entity);
+        }
+    }
+}
+
+/// An open edit session on an entity.
+///
+/// Dereferences to the entity's editor, which has the setters and
+/// dereferences to the entity in turn. Dropping the guard commits the
+/// edit, unless the thread is panicking, in which case it rolls back.
+#[must_use = "the edit is committed as soon as the guard is dropped"]
+pub struct EditGuard<'a, T: Editable + 'a> {
+    entity: &'a T,
+    editor: T::Editor<'a>,
+    undo: Undo<T>,
+    open: bool,
+}
+
+impl<'a, T: Editable> EditGuard<'a, T> {
+    fn new(entity: &'a T) -> Self {
+        entity.begin_edit();
+        let undo = Undo::new();
+        Self {
+            entity,
+            editor: entity.editor(undo.share()),
+            undo,
+            open: true,
+        }
+    }
+
+    /// Commits the edit now.
+    pub fn commit(mut self) {
+        self.open = false;
+        self.entity.commit_edit();
+    }
+
+    /// Discards the edit now.
+    pub fn rollback(mut self) {
+        self.discard();
+    }
+
+    fn discard(&mut self) {
+        self.open = false;
+        if !self.entity.try_rollback_edit() {
+            self.undo.restore(self.entity);
+            self.entity.commit_edit();
+        }
+    }
+}
+
+impl<'a, T: Editable> Deref for EditGuard<'a, T> {
+    type Target = T::Editor<'a>;
+
+    fn deref(&self) -> &T::Editor<'a> {
+        &self.editor
+    }
+}
+
+impl<T: Editable> Drop for EditGuard<'_, T> {
+    fn drop(&mut self) {
+        if !self.open {
+            return;
+        }
+        if std::thread::panicking() {
+            self.discard();
+        } else {
+            self.entity.commit_edit();
+        }
+    }
+}
+
+impl<T: Editable + std::fmt::Debug> std::fmt::Debug for EditGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EditGuard")
+            .field("entity", self.entity)
+            .finish()
+    }
+}
+
+// ==================== Implementations ====================
+
+impl Editable for Book {
+    type Editor<'e> = crate::book::BookEditor<'e>;
+
+    fn begin_edit(&self) {
+        Book::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Book::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> Self::Editor<'_> {
+        crate::book::BookEditor::new(self, undo)
+    }
+}
+
+impl Editable for Account {
+    type Editor<'e> = crate::account::AccountEditor<'e>;
+
+    fn begin_edit(&self) {
+        Account::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Account::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> Self::Editor<'_> {
+        crate::account::AccountEditor::new(self, undo)
+    }
+}
+
+impl Editable for Transaction {
+    type Editor<'e> = crate::transaction::TransactionEditor<'e>;
+
+    fn begin_edit(&self) {
+        Transaction::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Transaction::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> Self::Editor<'_> {
+        crate::transaction::TransactionEditor::new(self, undo)
+    }
+
+    /// Only the outermost session is rolled back by the engine; inside a
+    /// nested one it would just close the session and keep the changes.
+    fn try_rollback_edit(&self) -> bool {
+        let level = unsafe { ffi::qof_instance_get_editlevel(self.as_ptr() as ffi::gconstpointer) };
+        if level > 1 {
+            return false;
+        }
+        Transaction::rollback_edit(self);
+        true
+    }
+}
+
+impl Editable for Price {
+    type Editor<'e> = crate::price::PriceEditor<'e>;
+
+    fn begin_edit(&self) {
+        Price::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        Price::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> Self::Editor<'_> {
+        crate::price::PriceEditor::new(self, undo)
+    }
+}
+
+/// A price database has no fields of its own to change; an edit session
+/// on it batches the prices added and removed meanwhile.
+impl Editable for PriceDB {
+    type Editor<'e> = crate::price::PriceDBEditor<'e>;
+
+    fn begin_edit(&self) {
+        PriceDB::begin_edit(self)
+    }
+
+    fn commit_edit(&self) {
+        PriceDB::commit_edit(self)
+    }
+
+    fn editor(&self, undo: Undo<Self>) -> Self::Editor<'_> {
+        crate::price::PriceDBEditor::new(self, undo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Book;
+
+    #[test]
+    fn test_edit_guard() {
+        let book = Book::new();
+        let account = Account::new(&book);
+        {
+            let a = account.edit();
+            a.set_name("Checking");
+        }
+        assert_eq!(account.name().as_deref(), Some("Checking"));
+
+        let result: Result<(), &str> = account.edit_with(|a| {
+            a.set_name("Savings");
+            a.set_code("1010");
+            a.set_name("Deposits");
+            Err("no")
+        });
+        assert!(result.is_err());
+        // The editor recorded the replaced values and put them back.
+        assert_eq!(account.name().as_deref(), Some("Checking"));
+        assert_eq!(account.code().unwrap_or_default(), "");
+
+        let txn = Transaction::new(&book);
+        txn.edit().set_description("Rent");
+        let _ = txn.edit_with(|t| {
+            t.set_description("Groceries");
+            Err::<(), _>(())
+        });
+        assert_eq!(txn.description().as_deref(), Some("Rent"));
+        assert!(!txn.is_open());
+
+        // A nested session rolls back through the editor's record.
+        txn.edit_with(|outer| {
+            outer.set_num("1001");
+            let _ = txn.edit_with(|inner| {
+                inner.set_description("Groceries");
+                inner.set_num("1002");
+                Err::<(), _>(())
+            });
+            assert!(txn.is_open());
+            Ok::<(), ()>(())
+        })
+        .unwrap();
+        assert_eq!(txn.description().as_deref(), Some("Rent"));
+        assert_eq!(txn.num().as_deref(), Some("1001"));
+    }
+}
@@ -41,6 +41,8 @@ pub enum Error {
     InvalidOperation(String),
     /// A session error occurred.
     Session(String),
+    /// Input data (e.g. an imported statement file) could not be parsed.
+    Parse(String),
     /// Generic error with a message.
     Other(String),
 }
@@ -57,6 +59,7 @@ impl fmt::Display for Error {
             Error::Numeric(s) => write!(f, "numeric error: {}", s),
             Error::InvalidOperation(s) => write!(f, "invalid operation: {}", s),
             Error::Session(s) => write!(f, "session error: {}", s),
+            Error::Parse(s) => write!(f, "parse error: {}", s),
             Error::Other(s) => write!(f, "{}", s),
         }
     }
@@ -0,0 +1,467 @@
+//! Engine change events.
+//!
+//! The engine announces every entity it creates, modifies or destroys,
+//! and every entity added to or removed from a container (an account to
+//! its parent, a split to its account), through the QOF event system.
+//! [`subscribe`] turns those announcements into [`Event`]s for a
+//! callback, and [`channel`] queues them for polling:
+//!
+//! ```ignore
+//! use gnucash_sys::events::{self, EntityType, Event};
+//!
+//! let (_subscription, events) = events::channel();
+//! // ... edit the book ...
+//! for event in events.try_iter() {
+//!     if let Event::Modified { entity: EntityType::Transaction, guid } = event {
+//!         sync_transaction(guid);
+//!     }
+//! }
+//! ```
+//!
+//! The engine's handler list is process-wide, and events are raised on
+//! the thread that made the change, while the engine is in the middle of
+//! it. A handler is only called for changes made on the thread that
+//! subscribed it; changes made on other threads, such as to a book moved
+//! there, are not delivered to it. Handlers should record what changed
+//! and look at the entity later rather than edit the book themselves:
+//! events a handler causes are not delivered back to it. Nothing is
+//! delivered while events are [`suspend`]ed.
+
+use std::cell::{Cell, UnsafeCell};
+use std::ffi::{CStr, c_void};
+use std::fmt;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, ThreadId};
+
+use crate::Guid;
+use crate::ffi;
+
+const QOF_EVENT_CREATE: ffi::QofEventId = 1 << 0;
+const QOF_EVENT_MODIFY: ffi::QofEventId = 1 << 1;
+const QOF_EVENT_DESTROY: ffi::QofEventId = 1 << 2;
+const QOF_EVENT_ADD: ffi::QofEventId = 1 << 3;
+const QOF_EVENT_REMOVE: ffi::QofEventId = 1 << 4;
+// From gnc-event.h, which the bindings do not include. An account raises
+// these for the split inserted into or removed from it.
+const GNC_EVENT_ITEM_ADDED: ffi::QofEventId = 1 << 8;
+const GNC_EVENT_ITEM_REMOVED: ffi::QofEventId = 1 << 9;
+
+/// The type of entity an event is about.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EntityType {
+    Book,
+    Account,
+    Transaction,
+    Split,
+    Lot,
+    Price,
+    Commodity,
+    Customer,
+    Vendor,
+    Employee,
+    Job,
+    Invoice,
+    Entry,
+    /// Any other QOF type, by its type name.
+    Other(String),
+}
+
+impl EntityType {
+    /// Returns the type for a QOF type name such as `"Trans"`.
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "Book" => EntityType::Book,
+            "Account" => EntityType::Account,
+            "Trans" => EntityType::Transaction,
+            "Split" => EntityType::Split,
+            "Lot" => EntityType::Lot,
+            "Price" => EntityType::Price,
+            "Commodity" => EntityType::Commodity,
+            "gncCustomer" => EntityType::Customer,
+            "gncVendor" => EntityType::Vendor,
+            "gncEmployee" => EntityType::Employee,
+            "gncJob" => EntityType::Job,
+            "gncInvoice" => EntityType::Invoice,
+            "gncEntry" => EntityType::Entry,
+            other => EntityType::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for EntityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntityType::Other(id) => f.write_str(id),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// A change to one entity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// The entity was created and registered with its book.
+    Created { entity: EntityType, guid: Guid },
+    /// The entity was changed and the change committed.
+    Modified { entity: EntityType, guid: Guid },
+    /// The entity is about to be destroyed.
+    Destroyed { entity: EntityType, guid: Guid },
+    /// The entity was added to a container, such as an account to its
+    /// parent.
+    Added { entity: EntityType, guid: Guid },
+    /// The entity was removed from a container.
+    Removed { entity: EntityType, guid: Guid },
+}
+
+impl Event {
+    /// Returns the type of the entity concerned.
+    pub fn entity(&self) -> &EntityType {
+        match self {
+            Event::Created { entity, .. }
+            | Event::Modified { entity, .. }
+            | Event::Destroyed { entity, .. }
+            | Event::Added { entity, .. }
+            | Event::Removed { entity, .. } => entity,
+        }
+    }
+
+    /// Returns the GUID of the entity concerned.
+    pub fn guid(&self) -> Guid {
+        match self {
+            Event::Created { guid, .. }
+            | Event::Modified { guid, .. }
+            | Event::Destroyed { guid, .. }
+            | Event::Added { guid, .. }
+            | Event::Removed { guid, .. } => *guid,
+        }
+    }
+
+    /// Builds the event for a QOF event id, or `None` for ids other than
+    /// the five standard ones.
+    fn from_qof(event_type: ffi::QofEventId, entity: EntityType, guid: Guid) -> Option<Self> {
+        Some(match event_type {
+            QOF_EVENT_CREATE => Event::Created { entity, guid },
+            QOF_EVENT_MODIFY => Event::Modified { entity, guid },
+            QOF_EVENT_DESTROY => Event::Destroyed { entity, guid },
+            QOF_EVENT_ADD => Event::Added { entity, guid },
+            QOF_EVENT_REMOVE => Event::Removed { entity, guid },
+            _ => return None,
+        })
+    }
+}
+
+/// The handler the engine holds a pointer to, with what `dispatch` needs
+/// to call it safely.
+struct Registration {
+    /// Events raised on other threads are not delivered.
+    thread: ThreadId,
+    /// Set while the handler runs, so a change it makes is not delivered
+    /// back into it.
+    running: Cell<bool>,
+    /// Set when the subscription is dropped by its own handler; the
+    /// registration is then freed once the handler returns.
+    dropped: Cell<bool>,
+    handler: UnsafeCell<Box<dyn FnMut(Event)>>,
+}
+
+/// A registered event handler. Dropping it unregisters the handler.
+///
+/// A subscription is neither `Send` nor `Sync`: it is registered with,
+/// called by and unregistered from the engine on the thread that made
+/// it, and the handler is only ever called on that thread, which is why
+/// handlers need not be `Send` either.
+///
+/// ```compile_fail
+/// let subscription = gnucash_sys::events::subscribe(|_| {});
+/// std::thread::spawn(move || drop(subscription));
+/// ```
+pub struct EventSubscription {
+    id: i32,
+    /// Owns the handler the engine holds a pointer to. Being a raw
+    /// pointer, it also keeps the subscription on its thread.
+    registration: *mut Registration,
+}
+
+impl EventSubscription {
+    /// Returns the engine's id for the handler.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        unsafe {
+            // The engine tolerates unregistering a handler while events
+            // are being dispatched, but a handler dropping its own
+            // subscription is still running, so it is freed by `dispatch`.
+            ffi::qof_event_unregister_handler(self.id);
+            let registration = &*self.registration;
+            if registration.running.get() {
+                registration.dropped.set(true);
+            } else {
+                drop(Box::from_raw(self.registration));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for EventSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSubscription")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Returns the type and GUID of an instance, or `None` if it has none.
+///
+/// # Safety
+///
+/// `inst` must be null or point to a live instance.
+unsafe fn identify(inst: *const ffi::QofInstance) -> Option<(EntityType, Guid)> {
+    if inst.is_null() {
+        return None;
+    }
+    unsafe {
+        let type_ptr = (*inst).e_type;
+        let guid_ptr = ffi::qof_instance_get_guid(inst as *const c_void);
+        if type_ptr.is_null() || guid_ptr.is_null() {
+            return None;
+        }
+        let entity = EntityType::from_id(&CStr::from_ptr(type_ptr).to_string_lossy());
+        Some((entity, Guid::from_bytes((*guid_ptr).reserved)))
+    }
+}
+
+unsafe extern "C" fn dispatch(
+    ent: *mut ffi::QofInstance,
+    event_type: ffi::QofEventId,
+    handler_data: ffi::gpointer,
+    event_data: ffi::gpointer,
+) {
+    if ent.is_null() || handler_data.is_null() {
+        return;
+    }
+    let event = unsafe {
+        let Some((entity, guid)) = identify(ent) else {
+            return;
+        };
+        // An account reports a split added to or removed from it as an
+        // event of its own, naming the split in the event data.
+        let account = entity == EntityType::Account;
+        match event_type {
+            GNC_EVENT_ITEM_ADDED if account => identify(event_data as *const ffi::QofInstance)
+                .and_then(|(entity, guid)| Event::from_qof(QOF_EVENT_ADD, entity, guid)),
+            GNC_EVENT_ITEM_REMOVED if account && !event_data.is_null() => {
+                // A GncEventData, whose first field is the split.
+                let node = *(event_data as *const ffi::gpointer);
+                identify(node as *const ffi::QofInstance)
+                    .and_then(|(entity, guid)| Event::from_qof(QOF_EVENT_REMOVE, entity, guid))
+            }
+            _ => Event::from_qof(event_type, entity, guid),
+        }
+    };
+    let Some(event) = event else {
+        return;
+    };
+    let registration = handler_data as *mut Registration;
+    {
+        let registration = unsafe { &*registration };
+        if registration.thread != thread::current().id() || registration.running.get() {
+            return;
+        }
+        registration.running.set(true);
+        let handler = unsafe { &mut *registration.handler.get() };
+        // A panic must not unwind into the engine.
+        let _ = catch_unwind(AssertUnwindSafe(|| handler(event)));
+        registration.running.set(false);
+        if !registration.dropped.get() {
+            return;
+        }
+    }
+    drop(unsafe { Box::from_raw(registration) });
+}
+
+/// Calls `handler` with every event raised on the calling thread until
+/// the returned subscription is dropped. Events raised on other threads
+/// and events the handler itself causes are skipped.
+pub fn subscribe(handler: impl FnMut(Event) + 'static) -> EventSubscription {
+    let registration = Box::into_raw(Box::new(Registration {
+        thread: thread::current().id(),
+        running: Cell::new(false),
+        dropped: Cell::new(false),
+        handler: UnsafeCell::new(Box::new(handler)),
+    }));
+    let id =
+        unsafe { ffi::qof_event_register_handler(Some(dispatch), registration as ffi::gpointer) };
+    EventSubscription { id, registration }
+}
+
+/// Queues every event raised on the calling thread on a channel until
+/// the returned subscription is dropped.
+pub fn channel() -> (EventSubscription, Receiver<Event>) {
+    let (sender, receiver) = mpsc::channel();
+    let subscription = subscribe(move |event| {
+        let _ = sender.send(event);
+    });
+    (subscription, receiver)
+}
+
+/// Stops the engine from generating events until a matching
+/// [`resume`]. Calls nest.
+pub fn suspend() {
+    unsafe { ffi::qof_event_suspend() }
+}
+
+/// Undoes one [`suspend`].
+pub fn resume() {
+    unsafe { ffi::qof_event_resume() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::sync::mpsc::TryRecvError;
+    use std::sync::{Mutex, MutexGuard};
+
+    use super::*;
+    use crate::{Account, Book, Editable, Numeric, Split, Transaction};
+
+    /// The engine's handler list is shared by the whole process, so tests
+    /// that register handlers take turns.
+    fn serial() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn test_account_events() {
+        let _serial = serial();
+        let book = Book::new();
+        let (subscription, events) = channel();
+
+        let account = Account::new(&book);
+        account.edit().set_name("Checking");
+        let guid = account.guid();
+
+        let received: Vec<Event> = events.try_iter().filter(|e| e.guid() == guid).collect();
+        assert!(received.contains(&Event::Created {
+            entity: EntityType::Account,
+            guid
+        }));
+        assert!(received.contains(&Event::Modified {
+            entity: EntityType::Account,
+            guid
+        }));
+
+        // Unsubscribing frees the handler and with it the sender.
+        drop(subscription);
+        assert_eq!(events.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let _serial = serial();
+        let book = Book::new();
+        let account = Account::new(&book);
+        let guid = account.guid();
+        let count = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&count);
+        let subscription = subscribe(move |event| {
+            if event.guid() == guid {
+                counter.set(counter.get() + 1);
+            }
+        });
+
+        account.edit().set_name("Checking");
+        let seen = count.get();
+        assert!(seen > 0);
+
+        drop(subscription);
+        account.edit().set_name("Savings");
+        assert_eq!(count.get(), seen);
+    }
+
+    #[test]
+    fn test_handler_drops_own_subscription() {
+        let _serial = serial();
+        let book = Book::new();
+        let account = Account::new(&book);
+        let guid = account.guid();
+        let slot: Rc<RefCell<Option<EventSubscription>>> = Rc::new(RefCell::new(None));
+        let count = Rc::new(Cell::new(0));
+
+        let (inner_slot, counter) = (Rc::clone(&slot), Rc::clone(&count));
+        let subscription = subscribe(move |event| {
+            if event.guid() == guid {
+                counter.set(counter.get() + 1);
+                drop(inner_slot.borrow_mut().take());
+            }
+        });
+        *slot.borrow_mut() = Some(subscription);
+
+        account.edit().set_name("Checking");
+        assert_eq!(count.get(), 1);
+        assert!(slot.borrow().is_none());
+
+        account.edit().set_name("Savings");
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn test_other_threads_not_delivered() {
+        let _serial = serial();
+        let count = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&count);
+        let _subscription = subscribe(move |_| counter.set(counter.get() + 1));
+
+        std::thread::spawn(|| {
+            let book = Book::new();
+            Account::new(&book).edit().set_name("Checking");
+        })
+        .join()
+        .unwrap();
+        assert_eq!(count.get(), 0);
+    }
+
+    #[test]
+    fn test_split_added_and_removed() {
+        let _serial = serial();
+        let book = Book::new();
+        let checking = Account::new(&book);
+        let savings = Account::new(&book);
+        let (_subscription, events) = channel();
+
+        let txn = Transaction::new(&book);
+        let edit = txn.edit();
+        let mut split = Split::new(&book);
+        let s = edit.add_split(&split);
+        s.set_account(&checking);
+        s.set_amount(Numeric::new(100, 100));
+        s.set_value(Numeric::new(100, 100));
+        edit.commit();
+        split.mark_unowned();
+        let guid = split.guid();
+        let received: Vec<Event> = events.try_iter().filter(|e| e.guid() == guid).collect();
+        assert!(received.contains(&Event::Added {
+            entity: EntityType::Split,
+            guid
+        }));
+
+        let edit = txn.edit();
+        edit.split(&split).set_account(&savings);
+        edit.commit();
+        let received: Vec<Event> = events.try_iter().filter(|e| e.guid() == guid).collect();
+        assert!(received.contains(&Event::Removed {
+            entity: EntityType::Split,
+            guid
+        }));
+        assert!(received.contains(&Event::Added {
+            entity: EntityType::Split,
+            guid
+        }));
+    }
+}
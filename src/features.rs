@@ -0,0 +1,200 @@
+//! Book features.
+//!
+//! When a book starts using something older GnuCash releases cannot
+//! read, GnuCash records a named feature in the book's `features` frame
+//! and older releases refuse to open it. [`Book::features`] lists them
+//! and [`Book::check_compatible`] reports which would block a given
+//! release:
+//!
+//! ```ignore
+//! use gnucash_sys::Editable;
+//! use gnucash_sys::features::{self, Version};
+//!
+//! book.edit().set_feature(features::NUM_FIELD_SOURCE)?;
+//! for feature in book.check_compatible(Version::new(2, 4, 0)) {
+//!     println!("needs {:?}: {}", feature.required_version(), feature.name);
+//! }
+//! ```
+//!
+//! [`Book::features`]: crate::Book::features
+//! [`Book::check_compatible`]: crate::Book::check_compatible
+
+use std::fmt;
+
+pub const CREDIT_NOTES: &str = "Credit Notes";
+pub const NUM_FIELD_SOURCE: &str = "Number Field Source";
+pub const KVP_EXTRA_DATA: &str = "Extra data in addresses, jobs or invoice entries";
+pub const BOOK_CURRENCY: &str = "Use a Book-Currency";
+pub const GUID_BAYESIAN: &str = "Account GUID based Bayesian data";
+pub const GUID_FLAT_BAYESIAN: &str = "Account GUID based bayesian with flat KVP";
+pub const SQLITE3_ISO_DATES: &str = "ISO-8601 formatted date strings in SQLite3 databases.";
+pub const REG_SORT_FILTER: &str = "Register sort and filter settings stored in .gcm file";
+pub const BUDGET_UNREVERSED: &str = "Use natural signs in budget amounts";
+pub const BUDGET_SHOW_EXTRA_ACCOUNT_COLS: &str = "Show extra account columns in the Budget View";
+pub const EQUITY_TYPE_OPENING_BALANCE: &str = "Use Equity Type Opening Balance Account";
+
+/// A GnuCash release number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub micro: u32,
+}
+
+impl Version {
+    /// Creates a version.
+    pub const fn new(major: u32, minor: u32, micro: u32) -> Self {
+        Self {
+            major,
+            minor,
+            micro,
+        }
+    }
+
+    /// Parses "5", "4.14" or "2.6.19".
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().split('.').map(|p| p.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().transpose().ok()?.unwrap_or(0);
+        let micro = parts.next().transpose().ok()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self::new(major, minor, micro))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.micro)
+    }
+}
+
+/// Features known to this crate: name, description and the first
+/// GnuCash release that understands it.
+pub const KNOWN_FEATURES: &[(&str, &str, Version)] = &[
+    (
+        CREDIT_NOTES,
+        "Customer and vendor credit notes (requires at least GnuCash 2.5.0)",
+        Version::new(2, 5, 0),
+    ),
+    (
+        NUM_FIELD_SOURCE,
+        "User specifies source of 'num' field'; either transaction number or split action (requires at least GnuCash 2.5.0)",
+        Version::new(2, 5, 0),
+    ),
+    (
+        KVP_EXTRA_DATA,
+        "Extra data for addresses, jobs or invoice entries (requires at least GnuCash 2.6.4)",
+        Version::new(2, 6, 4),
+    ),
+    (
+        BOOK_CURRENCY,
+        "User specifies a 'book currency'; costs of other currencies/commodities tracked in terms of book currency (requires at least GnuCash 2.7.0)",
+        Version::new(2, 7, 0),
+    ),
+    (
+        GUID_BAYESIAN,
+        "Use account GUID as key for Bayesian data (requires at least GnuCash 2.6.12)",
+        Version::new(2, 6, 12),
+    ),
+    (
+        GUID_FLAT_BAYESIAN,
+        "Use account GUID as key for bayesian data and store KVP flat (requires at least Gnucash 2.6.19)",
+        Version::new(2, 6, 19),
+    ),
+    (
+        SQLITE3_ISO_DATES,
+        "Use ISO formatted date-time strings in SQLite3 databases (requires at least GnuCash 2.6.20)",
+        Version::new(2, 6, 20),
+    ),
+    (
+        REG_SORT_FILTER,
+        "Store the register sort and filter settings in .gcm metadata file (requires at least GnuCash 3.3)",
+        Version::new(3, 3, 0),
+    ),
+    (
+        BUDGET_UNREVERSED,
+        "Store budget amounts unreversed (i.e. natural) signs (requires at least Gnucash 3.8)",
+        Version::new(3, 8, 0),
+    ),
+    (
+        BUDGET_SHOW_EXTRA_ACCOUNT_COLS,
+        "Show extra account columns in the Budget View (requires at least Gnucash 3.8)",
+        Version::new(3, 8, 0),
+    ),
+    (
+        EQUITY_TYPE_OPENING_BALANCE,
+        "Show equity type opening balance account (requires at least Gnucash 4.3)",
+        Version::new(4, 3, 0),
+    ),
+];
+
+/// A feature recorded in a book.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Feature {
+    pub name: String,
+    pub description: String,
+}
+
+impl Feature {
+    /// Returns the first GnuCash release that understands the feature,
+    /// or `None` if this crate does not know it.
+    pub fn required_version(&self) -> Option<Version> {
+        known(&self.name).map(|(_, _, version)| version)
+    }
+}
+
+/// Returns the table entry for a feature name.
+pub(crate) fn known(name: &str) -> Option<(&'static str, &'static str, Version)> {
+    KNOWN_FEATURES.iter().copied().find(|(n, _, _)| *n == name)
+}
+
+/// Returns the features that GnuCash `max_version` cannot open: those
+/// requiring a later release, and those unknown to this crate, which
+/// are assumed to be newer than any release it knows of.
+pub fn blocking(features: &[Feature], max_version: Version) -> Vec<Feature> {
+    features
+        .iter()
+        .filter(|f| f.required_version().is_none_or(|v| v > max_version))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_parse() {
+        assert_eq!(Version::parse("2.6.19"), Some(Version::new(2, 6, 19)));
+        assert_eq!(Version::parse("4.14"), Some(Version::new(4, 14, 0)));
+        assert_eq!(Version::parse("5"), Some(Version::new(5, 0, 0)));
+        assert_eq!(Version::parse("5.x"), None);
+        assert_eq!(Version::parse("1.2.3.4"), None);
+        assert!(Version::new(3, 10, 0) > Version::new(3, 8, 0));
+    }
+
+    #[test]
+    fn test_blocking() {
+        let feature = |name: &str| Feature {
+            name: name.to_string(),
+            description: String::new(),
+        };
+        let features = [
+            feature(CREDIT_NOTES),
+            feature(BUDGET_UNREVERSED),
+            feature("Something from the future"),
+        ];
+        let names = |v: Vec<Feature>| v.into_iter().map(|f| f.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(blocking(&features, Version::new(3, 7, 0))),
+            vec![BUDGET_UNREVERSED, "Something from the future"]
+        );
+        assert_eq!(
+            names(blocking(&features, Version::new(5, 0, 0))),
+            vec!["Something from the future"]
+        );
+        assert_eq!(blocking(&features, Version::new(2, 4, 0)).len(), 3);
+    }
+}
@@ -15,42 +15,47 @@
 //! - [`Split`] - Single entry in a transaction
 //! - [`Guid`] - 128-bit unique identifier
 //! - [`Numeric`] - Rational number (numerator/denominator)
+//! - [`Slots`] - Key-value slots attached to any entity
+//! - [`Reconciliation`] - Reconciling an account against a statement
+//! - [`Event`] - Change notifications from the engine
 //!
 //! ## Example
 //!
 //! ```ignore
-//! use gnucash_sys::{Book, Account, Transaction, Split, Numeric, GNCAccountType};
+//! use gnucash_sys::{Book, Account, Editable, Transaction, Split, Numeric, GNCAccountType};
 //!
 //! // Create a new book
 //! let book = Book::new();
 //!
 //! // Create accounts
 //! let root = Account::new(&book);
-//! root.begin_edit();
-//! root.set_name("Root");
-//! root.set_type(GNCAccountType::ACCT_TYPE_ROOT);
-//! root.commit_edit();
+//! {
+//!     let root = root.edit();
+//!     root.set_name("Root");
+//!     root.set_type(GNCAccountType::ACCT_TYPE_ROOT);
+//! }
 //!
 //! let checking = Account::new(&book);
-//! checking.begin_edit();
-//! checking.set_name("Checking");
-//! checking.set_type(GNCAccountType::ACCT_TYPE_BANK);
-//! checking.commit_edit();
-//! root.append_child(&checking);
+//! {
+//!     let checking = checking.edit();
+//!     checking.set_name("Checking");
+//!     checking.set_type(GNCAccountType::ACCT_TYPE_BANK);
+//! }
+//! root.edit().append_child(&checking);
 //!
 //! // Create a transaction with splits
 //! let txn = Transaction::new(&book);
-//! txn.begin_edit();
-//! txn.set_description("Opening balance");
-//! txn.set_date(1, 1, 2024);
+//! let edit = txn.edit();
+//! edit.set_description("Opening balance");
+//! edit.set_date(1, 1, 2024);
 //!
 //! let split = Split::new(&book);
-//! split.set_account(&checking);
-//! split.set_transaction(&txn);
-//! split.set_amount(Numeric::new(10000, 100)); // $100.00
-//! split.set_value(Numeric::new(10000, 100));
+//! let s = edit.add_split(&split);
+//! s.set_account(&checking);
+//! s.set_amount(Numeric::new(10000, 100)); // $100.00
+//! s.set_value(Numeric::new(10000, 100));
 //!
-//! txn.commit_edit();
+//! edit.commit();
 //!
 //! // Iterate over account children
 //! for child in root.children() {
@@ -112,6 +117,12 @@ pub mod types;
 /// Safe wrapper for QofBook.
 pub mod book;
 
+/// Typed book options.
+pub mod book_options;
+
+/// Book features and GnuCash version compatibility.
+pub mod features;
+
 /// Safe wrapper for Account.
 pub mod account;
 
@@ -127,13 +138,34 @@ pub mod session;
 /// Safe wrappers for Price and PriceDB.
 pub mod price;
 
+/// Key-value slots on engine entities.
+pub mod slots;
+
+/// Account reconciliation sessions.
+pub mod reconciliation;
+
+/// Check & Repair.
+pub mod scrub;
+
+/// Engine change events.
+pub mod events;
+
+/// Scoped edit sessions.
+pub mod edit;
+
 // Re-export main types at crate root
-pub use account::{Account, GNCAccountType};
-pub use book::Book;
+pub use account::{Account, AccountEditor, GNCAccountType};
+pub use book::{Book, BookEditor};
+pub use book_options::{BookOptions, FiscalYearEnd};
+pub use edit::{EditGuard, Editable, Undo};
 pub use error::{Error, Result};
+pub use events::{EntityType, Event, EventSubscription};
 pub use iter::{AccountChildren, AccountDescendants, AccountSplits, TransactionSplits};
-pub use split::Split;
-pub use transaction::Transaction;
+pub use reconciliation::{Candidate, Reconciliation};
+pub use scrub::{CreatedAccount, ScrubFix, ScrubIssue, ScrubOptions, ScrubReport};
+pub use slots::{SlotEditor, SlotValue, Slots};
+pub use split::{ReconcileState, Split, SplitEditor};
+pub use transaction::{Transaction, TransactionEditor};
 pub use types::{Guid, Numeric, GUID_ENCODING_LENGTH};
 
 // Re-export session types
@@ -144,4 +176,4 @@ pub use split::reconcile;
 pub use transaction::txn_type;
 
 // Re-export price types
-pub use price::{Price, PriceDB, PriceSource};
+pub use price::{Price, PriceDB, PriceDBEditor, PriceEditor, PriceSource};
@@ -3,7 +3,9 @@
 use std::ffi::{CStr, CString};
 use std::ptr::NonNull;
 
+use crate::edit::Undo;
 use crate::ffi;
+use crate::slots::{SlotEditor, Slots};
 use crate::{Book, Guid, Numeric};
 
 /// Re-export PriceSource enum.
@@ -40,6 +42,12 @@ impl Price {
         self.ptr.as_ptr()
     }
 
+    /// Returns the key-value slots attached to this price.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
     /// Increments the reference count.
     pub fn ref_(&self) {
         unsafe { ffi::gnc_price_ref(self.ptr.as_ptr()) }
@@ -51,12 +59,12 @@ impl Price {
     }
 
     /// Begins an edit session on this price.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gnc_price_begin_edit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes made during the edit session.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gnc_price_commit_edit(self.ptr.as_ptr()) }
     }
 
@@ -120,23 +128,84 @@ impl Price {
     // ==================== Setters ====================
 
     /// Sets the time of this price quote.
-    pub fn set_time(&self, time: i64) {
+    fn set_time(&self, time: i64) {
         unsafe { ffi::gnc_price_set_time64(self.ptr.as_ptr(), time) }
     }
 
     /// Sets the price source.
-    pub fn set_source(&self, source: PriceSource) {
+    fn set_source(&self, source: PriceSource) {
         unsafe { ffi::gnc_price_set_source(self.ptr.as_ptr(), source) }
     }
 
+    /// Sets the price source from a string.
+    fn set_source_string(&self, source: &str) {
+        let c_source = CString::new(source).unwrap();
+        unsafe { ffi::gnc_price_set_source_string(self.ptr.as_ptr(), c_source.as_ptr()) }
+    }
+
+    /// Sets the price type string.
+    fn set_type_string(&self, type_str: &str) {
+        let c_type = CString::new(type_str).unwrap();
+        unsafe { ffi::gnc_price_set_typestr(self.ptr.as_ptr(), c_type.as_ptr()) }
+    }
+
+    /// Sets the price value.
+    fn set_value(&self, value: Numeric) {
+        unsafe { ffi::gnc_price_set_value(self.ptr.as_ptr(), value.into()) }
+    }
+
+    /// Takes a reference of the price's own, released when the returned
+    /// wrapper drops.
+    fn share(&self) -> Price {
+        self.ref_();
+        Price {
+            ptr: self.ptr,
+            owned: true,
+        }
+    }
+}
+
+/// The setters of a [`Price`] in an open edit session, reached through
+/// [`Editable::edit`](crate::Editable::edit). Dereferences to the price
+/// for everything else.
+pub struct PriceEditor<'a> {
+    price: &'a Price,
+    undo: Undo<Price>,
+}
+
+impl<'a> PriceEditor<'a> {
+    pub(crate) fn new(price: &'a Price, undo: Undo<Price>) -> Self {
+        Self { price, undo }
+    }
+
+    /// Returns the price's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Price> {
+        SlotEditor::new(self.price.slots(), &self.undo)
+    }
+
+    /// Sets the time of this price quote.
+    pub fn set_time(&self, time: i64) {
+        let old = self.price.time();
+        self.undo.record(move |p| p.set_time(old));
+        self.price.set_time(time);
+    }
+
+    /// Sets the price source.
+    pub fn set_source(&self, source: PriceSource) {
+        let old = self.price.source();
+        self.undo.record(move |p| p.set_source(old));
+        self.price.set_source(source);
+    }
+
     /// Sets the price source from a string.
     ///
     /// # Panics
     ///
     /// Panics if `source` contains a null byte.
     pub fn set_source_string(&self, source: &str) {
-        let c_source = CString::new(source).unwrap();
-        unsafe { ffi::gnc_price_set_source_string(self.ptr.as_ptr(), c_source.as_ptr()) }
+        let old = self.price.source_string().unwrap_or_default();
+        self.undo.record(move |p| p.set_source_string(&old));
+        self.price.set_source_string(source);
     }
 
     /// Sets the price type string.
@@ -145,13 +214,24 @@ impl Price {
     ///
     /// Panics if `type_str` contains a null byte.
     pub fn set_type_string(&self, type_str: &str) {
-        let c_type = CString::new(type_str).unwrap();
-        unsafe { ffi::gnc_price_set_typestr(self.ptr.as_ptr(), c_type.as_ptr()) }
+        let old = self.price.type_string().unwrap_or_default();
+        self.undo.record(move |p| p.set_type_string(&old));
+        self.price.set_type_string(type_str);
     }
 
     /// Sets the price value.
     pub fn set_value(&self, value: Numeric) {
-        unsafe { ffi::gnc_price_set_value(self.ptr.as_ptr(), value.into()) }
+        let old = self.price.value();
+        self.undo.record(move |p| p.set_value(old));
+        self.price.set_value(value);
+    }
+}
+
+impl std::ops::Deref for PriceEditor<'_> {
+    type Target = Price;
+
+    fn deref(&self) -> &Price {
+        self.price
     }
 }
 
@@ -221,27 +301,27 @@ impl PriceDB {
     }
 
     /// Begins an edit session on this price database.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::gnc_pricedb_begin_edit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes made during the edit session.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::gnc_pricedb_commit_edit(self.ptr.as_ptr()) }
     }
 
     /// Sets bulk update mode.
-    pub fn set_bulk_update(&self, bulk_update: bool) {
+    fn set_bulk_update(&self, bulk_update: bool) {
         unsafe { ffi::gnc_pricedb_set_bulk_update(self.ptr.as_ptr(), bulk_update as i32) }
     }
 
     /// Adds a price to the database.
-    pub fn add_price(&self, price: &Price) -> bool {
+    fn add_price(&self, price: &Price) -> bool {
         unsafe { ffi::gnc_pricedb_add_price(self.ptr.as_ptr(), price.as_ptr()) != 0 }
     }
 
     /// Removes a price from the database.
-    pub fn remove_price(&self, price: &Price) -> bool {
+    fn remove_price(&self, price: &Price) -> bool {
         unsafe { ffi::gnc_pricedb_remove_price(self.ptr.as_ptr(), price.as_ptr()) != 0 }
     }
 
@@ -254,6 +334,61 @@ impl PriceDB {
     }
 }
 
+/// The changes to a [`PriceDB`] in an open edit session, reached through
+/// [`Editable::edit`](crate::Editable::edit). Dereferences to the
+/// database for everything else.
+pub struct PriceDBEditor<'a> {
+    db: &'a PriceDB,
+    undo: Undo<PriceDB>,
+}
+
+impl<'a> PriceDBEditor<'a> {
+    pub(crate) fn new(db: &'a PriceDB, undo: Undo<PriceDB>) -> Self {
+        Self { db, undo }
+    }
+
+    /// Sets bulk update mode, which skips the duplicate checks while
+    /// loading. It is a mode rather than data, so a rolled-back edit
+    /// leaves it as set.
+    pub fn set_bulk_update(&self, bulk_update: bool) {
+        self.db.set_bulk_update(bulk_update);
+    }
+
+    /// Adds a price to the database. Returns true if it was added.
+    pub fn add_price(&self, price: &Price) -> bool {
+        let added = self.db.add_price(price);
+        if added {
+            let price = price.share();
+            self.undo.record(move |db| {
+                db.remove_price(&price);
+            });
+        }
+        added
+    }
+
+    /// Removes a price from the database. Returns true if it was removed.
+    pub fn remove_price(&self, price: &Price) -> bool {
+        // Held so that a rollback can add the price back after the
+        // database has released it.
+        let kept = price.share();
+        let removed = self.db.remove_price(price);
+        if removed {
+            self.undo.record(move |db| {
+                db.add_price(&kept);
+            });
+        }
+        removed
+    }
+}
+
+impl std::ops::Deref for PriceDBEditor<'_> {
+    type Target = PriceDB;
+
+    fn deref(&self) -> &PriceDB {
+        self.db
+    }
+}
+
 impl Drop for PriceDB {
     fn drop(&mut self) {
         if self.owned {
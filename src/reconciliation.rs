@@ -0,0 +1,327 @@
+//! Account reconciliation sessions.
+//!
+//! A [`Reconciliation`] works like the GnuCash reconcile window: it
+//! lists the account's unreconciled splits posted up to the statement
+//! date, lets the caller tick them off as cleared, and reports the
+//! difference between the cleared balance and the statement's ending
+//! balance. Once the difference is zero, [`Reconciliation::finish`]
+//! marks the cleared splits reconciled:
+//!
+//! ```ignore
+//! use gnucash_sys::Reconciliation;
+//!
+//! let mut rec = Reconciliation::new(&checking, statement_date, ending_balance);
+//! for guid in cleared_on_statement {
+//!     rec.set_cleared(guid, true)?;
+//! }
+//! if rec.difference().is_zero() {
+//!     rec.finish()?;
+//! } else {
+//!     rec.cancel();
+//! }
+//! ```
+
+use crate::ffi;
+use crate::{Account, Editable, Error, Guid, Numeric, ReconcileState, Result, Split};
+
+/// An unreconciled split offered for reconciliation.
+pub struct Candidate {
+    split: Split,
+    original: ReconcileState,
+    cleared: bool,
+}
+
+impl Candidate {
+    /// Returns the split.
+    pub fn split(&self) -> &Split {
+        &self.split
+    }
+
+    /// Returns the split's GUID.
+    pub fn guid(&self) -> Guid {
+        self.split.guid()
+    }
+
+    /// Returns the split's amount in the account's commodity.
+    pub fn amount(&self) -> Numeric {
+        self.split.amount()
+    }
+
+    /// Returns the posted date of the split's transaction.
+    pub fn date_posted(&self) -> i64 {
+        self.split
+            .transaction()
+            .map(|t| t.date_posted())
+            .unwrap_or(0)
+    }
+
+    /// Returns true if the split is ticked as cleared in this session.
+    pub fn is_cleared(&self) -> bool {
+        self.cleared
+    }
+}
+
+/// A reconciliation of one account against a bank statement.
+///
+/// Ticking a split sets its state to cleared right away, as the GnuCash
+/// reconcile window does. [`finish`](Self::finish) turns the cleared
+/// splits into reconciled ones and [`cancel`](Self::cancel) puts every
+/// split back the way it was. Dropping the session without either keeps
+/// the cleared marks, like postponing in GnuCash.
+pub struct Reconciliation<'a> {
+    account: &'a Account,
+    statement_date: i64,
+    ending_balance: Numeric,
+    starting_balance: Numeric,
+    candidates: Vec<Candidate>,
+}
+
+impl<'a> Reconciliation<'a> {
+    /// Starts reconciling `account` against a statement dated
+    /// `statement_date` with `ending_balance`.
+    ///
+    /// The candidates are the account's new and cleared splits posted at
+    /// or before the statement date, oldest first; splits that are
+    /// already cleared start out ticked.
+    pub fn new(account: &'a Account, statement_date: i64, ending_balance: Numeric) -> Self {
+        let mut starting_balance = Numeric::zero();
+        let mut candidates = Vec::new();
+        for split in account.splits() {
+            match split.reconcile_state() {
+                state if state.is_reconciled() => {
+                    starting_balance += split.amount();
+                }
+                state if state.is_open() => {
+                    let posted = split.transaction().map(|t| t.date_posted()).unwrap_or(0);
+                    if posted <= statement_date {
+                        candidates.push(Candidate {
+                            split,
+                            original: state,
+                            cleared: state == ReconcileState::Cleared,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        candidates.sort_by_key(|c| c.date_posted());
+
+        Self {
+            account,
+            statement_date,
+            ending_balance,
+            starting_balance,
+            candidates,
+        }
+    }
+
+    /// Returns the account being reconciled.
+    pub fn account(&self) -> &Account {
+        self.account
+    }
+
+    /// Returns the statement date.
+    pub fn statement_date(&self) -> i64 {
+        self.statement_date
+    }
+
+    /// Returns the statement's ending balance.
+    pub fn ending_balance(&self) -> Numeric {
+        self.ending_balance
+    }
+
+    /// Returns the balance of the splits reconciled before this session.
+    pub fn starting_balance(&self) -> Numeric {
+        self.starting_balance
+    }
+
+    /// Returns the splits that can be reconciled, oldest first.
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// Returns the starting balance plus the splits ticked as cleared.
+    pub fn cleared_balance(&self) -> Numeric {
+        self.starting_balance
+            + self
+                .candidates
+                .iter()
+                .filter(|c| c.cleared)
+                .map(|c| c.amount())
+                .sum::<Numeric>()
+    }
+
+    /// Returns the ending balance minus the cleared balance. The
+    /// reconciliation can be finished when this is zero.
+    pub fn difference(&self) -> Numeric {
+        self.ending_balance - self.cleared_balance()
+    }
+
+    /// Ticks or unticks a candidate split as cleared.
+    pub fn set_cleared(&mut self, split: Guid, cleared: bool) -> Result<()> {
+        let candidate = self
+            .candidates
+            .iter_mut()
+            .find(|c| c.guid() == split)
+            .ok_or_else(|| Error::InvalidOperation(format!("split {split} is not a candidate")))?;
+        if candidate.cleared != cleared {
+            candidate.cleared = cleared;
+            set_state(
+                &candidate.split,
+                if cleared {
+                    ReconcileState::Cleared
+                } else {
+                    ReconcileState::New
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Flips the cleared tick of a candidate split and returns the new
+    /// state.
+    pub fn toggle(&mut self, split: Guid) -> Result<bool> {
+        let cleared = self
+            .candidates
+            .iter()
+            .find(|c| c.guid() == split)
+            .map(|c| !c.cleared)
+            .ok_or_else(|| Error::InvalidOperation(format!("split {split} is not a candidate")))?;
+        self.set_cleared(split, cleared)?;
+        Ok(cleared)
+    }
+
+    /// Ticks every candidate as cleared.
+    pub fn clear_all(&mut self) {
+        for candidate in self.candidates.iter_mut().filter(|c| !c.cleared) {
+            candidate.cleared = true;
+            set_state(&candidate.split, ReconcileState::Cleared);
+        }
+    }
+
+    /// Marks the cleared splits reconciled as of now, then records the
+    /// statement date and ending balance as the account's last
+    /// reconciliation and drops any postponed one.
+    ///
+    /// Every affected transaction is opened before the first change and
+    /// committed after the last, so the splits change together.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unbalanced`] without changing anything if the
+    /// difference is not zero; the cleared ticks stay on the splits.
+    pub fn finish(self) -> Result<()> {
+        if !self.difference().is_zero() {
+            return Err(Error::Unbalanced);
+        }
+
+        let now = unsafe { ffi::gnc_time(std::ptr::null_mut()) };
+        let cleared: Vec<&Split> = self
+            .candidates
+            .iter()
+            .filter(|c| c.cleared)
+            .map(|c| &c.split)
+            .collect();
+        let transactions: Vec<(&Split, _)> = cleared
+            .iter()
+            .filter_map(|s| Some((*s, s.transaction()?)))
+            .collect();
+        let edits: Vec<_> = transactions
+            .iter()
+            .map(|(split, trans)| (*split, trans.edit()))
+            .collect();
+        for (split, edit) in &edits {
+            let split = edit.split(split);
+            split.set_reconcile_state(ReconcileState::Reconciled);
+            split.set_date_reconciled(now);
+        }
+        drop(edits);
+
+        let account = self.account.edit();
+        account.set_reconcile_last_date(self.statement_date);
+        let recorded = account.set_reconcile_last_balance(self.ending_balance);
+        account.clear_reconcile_postpone();
+        account.commit();
+        recorded
+    }
+
+    /// Abandons the session, putting every candidate back in the state
+    /// it had when the session started.
+    pub fn cancel(self) {
+        for candidate in &self.candidates {
+            if candidate.split.reconcile_state() != candidate.original {
+                set_state(&candidate.split, candidate.original);
+            }
+        }
+    }
+}
+
+/// Sets a split's reconcile state in an edit of its transaction.
+fn set_state(split: &Split, state: ReconcileState) {
+    if let Some(trans) = split.transaction() {
+        trans.edit().split(split).set_reconcile_state(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Book, GNCAccountType, Transaction};
+
+    fn deposit(book: &Book, account: &Account, other: &Account, day: i32, cents: i64) -> Guid {
+        let mut txn = Transaction::new(book);
+        let edit = txn.edit();
+        edit.set_date(day, 1, 2024);
+        let mut split = Split::new(book);
+        let s = edit.add_split(&split);
+        s.set_account(account);
+        s.set_amount(Numeric::new(cents, 100));
+        s.set_value(Numeric::new(cents, 100));
+        let mut counter = Split::new(book);
+        let c = edit.add_split(&counter);
+        c.set_account(other);
+        c.set_amount(Numeric::new(-cents, 100));
+        c.set_value(Numeric::new(-cents, 100));
+        edit.commit();
+        txn.mark_unowned();
+        split.mark_unowned();
+        counter.mark_unowned();
+        split.guid()
+    }
+
+    #[test]
+    fn test_reconciliation() {
+        let book = Book::new();
+        let checking = Account::new(&book);
+        checking.edit().set_type(GNCAccountType::ACCT_TYPE_BANK);
+        let income = Account::new(&book);
+
+        let first = deposit(&book, &checking, &income, 5, 10000);
+        let second = deposit(&book, &checking, &income, 20, 2500);
+        deposit(&book, &checking, &income, 28, 700);
+
+        let mut rec = Reconciliation::new(&checking, i64::MAX, Numeric::new(12500, 100));
+        assert_eq!(rec.candidates().len(), 3);
+        rec.set_cleared(first, true).unwrap();
+        assert!(rec.difference().eq_value(&Numeric::new(2500, 100)));
+        assert!(rec.toggle(second).unwrap());
+        assert!(rec.difference().is_zero());
+        rec.cancel();
+        assert!(checking.cleared_balance().is_zero());
+
+        let mut rec = Reconciliation::new(&checking, i64::MAX, Numeric::new(12500, 100));
+        rec.set_cleared(first, true).unwrap();
+        rec.set_cleared(second, true).unwrap();
+        assert!(rec.finish().is_ok());
+        assert!(
+            checking
+                .reconciled_balance()
+                .eq_value(&Numeric::new(12500, 100))
+        );
+        assert!(
+            checking
+                .reconcile_last_balance()
+                .is_some_and(|b| b.eq_value(&Numeric::new(12500, 100)))
+        );
+    }
+}
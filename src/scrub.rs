@@ -0,0 +1,412 @@
+//! Check & Repair.
+//!
+//! Runs the engine's `xaccScrub*` repairs over an account tree, the way
+//! GnuCash's Actions > Check & Repair does, and reports every problem it
+//! fixed. With [`ScrubOptions::dry_run`] the problems are only reported:
+//!
+//! ```ignore
+//! use gnucash_sys::ScrubOptions;
+//!
+//! let report = book.scrub(&ScrubOptions::new().dry_run(true))?;
+//! for fix in &report.fixes {
+//!     println!("{} {:?}", fix.transaction, fix.issue);
+//! }
+//! if !report.is_clean() {
+//!     book.scrub(&ScrubOptions::new())?;
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use crate::ffi;
+use crate::{Account, Guid, Numeric, Split, Transaction};
+
+/// Which repairs to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrubOptions {
+    dry_run: bool,
+    currency: bool,
+    orphans: bool,
+    splits: bool,
+    imbalance: bool,
+    lots: bool,
+}
+
+impl ScrubOptions {
+    /// Creates options running every repair.
+    pub fn new() -> Self {
+        Self {
+            dry_run: false,
+            currency: true,
+            orphans: true,
+            splits: true,
+            imbalance: true,
+            lots: true,
+        }
+    }
+
+    /// Only reports the problems, leaving the book untouched.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Gives transactions without a currency their splits' most common
+    /// one.
+    pub fn currency(mut self, enabled: bool) -> Self {
+        self.currency = enabled;
+        self
+    }
+
+    /// Moves splits without an account to an `Orphan-<currency>` account.
+    pub fn orphans(mut self, enabled: bool) -> Self {
+        self.orphans = enabled;
+        self
+    }
+
+    /// Makes amount equal value on splits whose account commodity is the
+    /// transaction currency.
+    pub fn splits(mut self, enabled: bool) -> Self {
+        self.splits = enabled;
+        self
+    }
+
+    /// Balances transactions against an `Imbalance-<currency>` account.
+    pub fn imbalance(mut self, enabled: bool) -> Self {
+        self.imbalance = enabled;
+        self
+    }
+
+    /// Assigns to lots the splits of accounts with trades: accounts with
+    /// a split whose amount is in another commodity than its
+    /// transaction's currency, such as stock and mutual fund accounts.
+    /// This is unrelated to the `Trading` account type.
+    pub fn lots(mut self, enabled: bool) -> Self {
+        self.lots = enabled;
+        self
+    }
+}
+
+impl Default for ScrubOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A problem found by a scrub.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScrubIssue {
+    /// The transaction has no currency.
+    MissingCurrency,
+    /// The split has no account.
+    OrphanSplit,
+    /// The split's amount and value differ although its account is in
+    /// the transaction currency.
+    AmountValueMismatch { amount: Numeric, value: Numeric },
+    /// The transaction's values do not sum to zero.
+    Imbalance { value: Numeric },
+    /// The split is in an account with trades but not in any lot.
+    SplitWithoutLot,
+}
+
+/// A problem and where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrubFix {
+    pub issue: ScrubIssue,
+    pub transaction: Guid,
+    /// The split concerned, for split-level issues.
+    pub split: Option<Guid>,
+}
+
+/// An account the engine created while repairing, such as
+/// `Imbalance-USD` or `Orphan-USD`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedAccount {
+    pub guid: Guid,
+    /// Colon-separated full account name.
+    pub full_name: String,
+}
+
+/// Outcome of a scrub.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScrubReport {
+    /// True if nothing was changed.
+    pub dry_run: bool,
+    /// Problems the engine fixed, checked after each repair, in the order
+    /// handled. In a dry run, every problem found.
+    pub fixes: Vec<ScrubFix>,
+    /// Problems still present after the engine's repair.
+    pub unresolved: Vec<ScrubFix>,
+    /// Accounts added to the tree by the repairs.
+    pub created_accounts: Vec<CreatedAccount>,
+    pub accounts_checked: usize,
+    pub transactions_checked: usize,
+}
+
+impl ScrubReport {
+    /// Returns true if no problems were found.
+    pub fn is_clean(&self) -> bool {
+        self.fixes.is_empty() && self.unresolved.is_empty()
+    }
+
+    /// Finds problems with `detect` and, unless this is a dry run, runs
+    /// `repair` and looks again to sort them into fixed and unresolved.
+    fn repair(&mut self, detect: impl Fn() -> Vec<ScrubFix>, repair: impl FnOnce()) {
+        let found = detect();
+        if found.is_empty() {
+            return;
+        }
+        if self.dry_run {
+            self.fixes.extend(found);
+            return;
+        }
+        repair();
+        let remaining = detect();
+        for fix in found {
+            if remaining.iter().any(|r| r.same_problem(&fix)) {
+                self.unresolved.push(fix);
+            } else {
+                self.fixes.push(fix);
+            }
+        }
+    }
+}
+
+impl ScrubFix {
+    fn same_problem(&self, other: &ScrubFix) -> bool {
+        self.transaction == other.transaction
+            && self.split == other.split
+            && std::mem::discriminant(&self.issue) == std::mem::discriminant(&other.issue)
+    }
+}
+
+/// Scrubs `top` and all its descendants.
+pub(crate) fn scrub_tree(top: &Account, options: &ScrubOptions) -> ScrubReport {
+    let descendants: Vec<Account> = top.descendants().collect();
+    let accounts: Vec<&Account> = std::iter::once(top).chain(&descendants).collect();
+
+    let mut seen = HashSet::new();
+    let mut transactions = Vec::new();
+    for account in &accounts {
+        for split in account.splits() {
+            if let Some(trans) = split.transaction()
+                && seen.insert(trans.guid())
+            {
+                transactions.push(trans);
+            }
+        }
+    }
+
+    let mut report = ScrubReport {
+        dry_run: options.dry_run,
+        accounts_checked: accounts.len(),
+        transactions_checked: transactions.len(),
+        ..Default::default()
+    };
+    // Repairs create their accounts under the root of the whole tree.
+    let tree_root = top.root();
+    let root = tree_root.as_ref().unwrap_or(top);
+    let existing: HashSet<Guid> = root.descendants().map(|a| a.guid()).collect();
+
+    for trans in &transactions {
+        scrub_transaction(trans, root.as_ptr(), options, &mut report);
+    }
+    if options.lots {
+        for account in &accounts {
+            scrub_lots(account, &mut report);
+        }
+    }
+
+    report.created_accounts = root
+        .descendants()
+        .filter(|a| !existing.contains(&a.guid()))
+        .map(|a| CreatedAccount {
+            guid: a.guid(),
+            full_name: a.full_name().unwrap_or_default(),
+        })
+        .collect();
+    report
+}
+
+fn scrub_transaction(
+    trans: &Transaction,
+    root: *mut ffi::Account,
+    options: &ScrubOptions,
+    report: &mut ScrubReport,
+) {
+    let t = trans.as_ptr();
+    if options.currency {
+        report.repair(
+            || missing_currency(trans),
+            || unsafe { ffi::xaccTransScrubCurrency(t) },
+        );
+    }
+    if options.orphans {
+        report.repair(
+            || orphan_splits(trans),
+            || unsafe { ffi::xaccTransScrubOrphans(t) },
+        );
+    }
+    if options.splits {
+        report.repair(
+            || amount_value_mismatches(trans),
+            || unsafe { ffi::xaccTransScrubSplits(t) },
+        );
+    }
+    if options.imbalance {
+        report.repair(
+            || imbalance(trans),
+            || unsafe { ffi::xaccTransScrubImbalance(t, root, std::ptr::null_mut()) },
+        );
+    }
+}
+
+fn fix(trans: &Transaction, issue: ScrubIssue, split: Option<&Split>) -> ScrubFix {
+    ScrubFix {
+        issue,
+        transaction: trans.guid(),
+        split: split.map(|s| s.guid()),
+    }
+}
+
+fn missing_currency(trans: &Transaction) -> Vec<ScrubFix> {
+    if unsafe { ffi::xaccTransGetCurrency(trans.as_ptr()) }.is_null() {
+        vec![fix(trans, ScrubIssue::MissingCurrency, None)]
+    } else {
+        Vec::new()
+    }
+}
+
+fn orphan_splits(trans: &Transaction) -> Vec<ScrubFix> {
+    trans
+        .splits()
+        .filter(|s| s.account().is_none())
+        .map(|s| fix(trans, ScrubIssue::OrphanSplit, Some(&s)))
+        .collect()
+}
+
+fn amount_value_mismatches(trans: &Transaction) -> Vec<ScrubFix> {
+    trans
+        .splits()
+        .filter(|split| {
+            in_transaction_currency(trans, split) == Some(true)
+                && !split.amount().eq_value(&split.value())
+        })
+        .map(|split| {
+            let issue = ScrubIssue::AmountValueMismatch {
+                amount: split.amount(),
+                value: split.value(),
+            };
+            fix(trans, issue, Some(&split))
+        })
+        .collect()
+}
+
+fn imbalance(trans: &Transaction) -> Vec<ScrubFix> {
+    if trans.is_balanced() {
+        Vec::new()
+    } else {
+        let value = trans.imbalance_value();
+        vec![fix(trans, ScrubIssue::Imbalance { value }, None)]
+    }
+}
+
+/// Reports the splits of an account with trades that are not in a lot
+/// and lets the engine assign them.
+fn scrub_lots(account: &Account, report: &mut ScrubReport) {
+    let has_trades = account.splits().any(|split| {
+        split
+            .transaction()
+            .is_some_and(|trans| in_transaction_currency(&trans, &split) == Some(false))
+    });
+    if !has_trades {
+        return;
+    }
+    report.repair(
+        || splits_without_lot(account),
+        || unsafe { ffi::xaccAccountScrubLots(account.as_ptr()) },
+    );
+}
+
+fn splits_without_lot(account: &Account) -> Vec<ScrubFix> {
+    account
+        .splits()
+        .filter(|split| {
+            unsafe { ffi::xaccSplitGetLot(split.as_ptr()) }.is_null() && !split.amount().is_zero()
+        })
+        .filter_map(|split| {
+            let trans = split.transaction()?;
+            Some(fix(&trans, ScrubIssue::SplitWithoutLot, Some(&split)))
+        })
+        .collect()
+}
+
+/// Returns whether the split's account is denominated in the
+/// transaction currency, or `None` if either is missing.
+fn in_transaction_currency(trans: &Transaction, split: &Split) -> Option<bool> {
+    let account = split.account()?;
+    unsafe {
+        let currency = ffi::xaccTransGetCurrency(trans.as_ptr());
+        let commodity = ffi::xaccAccountGetCommodity(account.as_ptr());
+        if currency.is_null() || commodity.is_null() {
+            None
+        } else {
+            Some(ffi::gnc_commodity_equiv(commodity, currency) != 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Book, Editable};
+
+    #[test]
+    fn test_scrub_dry_run() {
+        let book = Book::new();
+        let root = Account::new(&book);
+        book.set_root_account(&root);
+        let checking = Account::new(&book);
+        root.edit().append_child(&checking);
+
+        let txn = Transaction::new(&book);
+        let edit = txn.edit();
+        edit.set_date(1, 1, 2024);
+        let mut split = Split::new(&book);
+        let s = edit.add_split(&split);
+        s.set_account(&checking);
+        s.set_amount(Numeric::new(5000, 100));
+        s.set_value(Numeric::new(5000, 100));
+        edit.commit();
+        split.mark_unowned();
+
+        let report = scrub_tree(&root, &ScrubOptions::new().dry_run(true).lots(false));
+        assert!(report.dry_run);
+        assert_eq!(report.transactions_checked, 1);
+        assert!(
+            report
+                .fixes
+                .iter()
+                .any(|f| matches!(f.issue, ScrubIssue::Imbalance { .. }))
+        );
+        assert!(!txn.is_balanced());
+        assert_eq!(checking.splits().count(), 1);
+        assert!(report.created_accounts.is_empty());
+
+        let report = scrub_tree(&root, &ScrubOptions::new().lots(false));
+        assert!(txn.is_balanced());
+        assert!(report.unresolved.is_empty());
+        assert!(
+            report
+                .fixes
+                .iter()
+                .any(|f| matches!(f.issue, ScrubIssue::Imbalance { .. }))
+        );
+        assert_eq!(report.created_accounts.len(), 1);
+        assert!(
+            report.created_accounts[0]
+                .full_name
+                .starts_with("Imbalance-")
+        );
+    }
+}
@@ -0,0 +1,463 @@
+//! Key-value slots (KVP) attached to engine entities.
+//!
+//! Every QofInstance (book, account, transaction, split, price and the
+//! business objects) carries a tree of frames holding typed values.
+//! GnuCash keeps much of its metadata there: online IDs, reconcile
+//! info, import maps, book options, invoice links. [`Slots`] reads that
+//! tree by slash-separated path, and the [`SlotEditor`] that an entity's
+//! editor hands out writes it:
+//!
+//! ```ignore
+//! use gnucash_sys::Editable;
+//!
+//! let edit = account.edit();
+//! edit.slots().set("online_id", "1234-5678")?;
+//! edit.slots().set("reconcile-info/include-children", 1i64)?;
+//! edit.commit();
+//!
+//! let slots = account.slots();
+//! assert_eq!(slots.get_string("online_id").as_deref(), Some("1234-5678"));
+//! let reconcile = slots.frame("reconcile-info");
+//! assert_eq!(reconcile.get_i64("include-children"), Some(1));
+//! ```
+//!
+//! Frames are reached only by a path the caller already knows. The
+//! engine's one way of walking a frame, `qof_instance_foreach_slot`, is
+//! declared in the private `qofinstance-p.h`, so a frame's keys cannot
+//! be listed, and [`Slots::get`] gives `None` for a path that holds a
+//! frame rather than a value. Lists and other value types the engine
+//! stores are likewise not read.
+
+use std::ffi::{CStr, CString, c_char};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+use crate::edit::Undo;
+use crate::ffi;
+use crate::{Error, Guid, Numeric, Result};
+
+/// Deepest path accepted, counting the frame prefix.
+pub const MAX_DEPTH: usize = 8;
+
+// Fundamental GType ids (G_TYPE_MAKE_FUNDAMENTAL(n) == n << 2).
+const G_TYPE_INT64: ffi::GType = 10 << 2;
+const G_TYPE_DOUBLE: ffi::GType = 15 << 2;
+const G_TYPE_STRING: ffi::GType = 16 << 2;
+
+/// A typed value stored in a slot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlotValue {
+    Int64(i64),
+    Double(f64),
+    Numeric(Numeric),
+    String(String),
+    Guid(Guid),
+    /// Seconds since the epoch.
+    Time64(i64),
+}
+
+impl From<i64> for SlotValue {
+    fn from(v: i64) -> Self {
+        Self::Int64(v)
+    }
+}
+
+impl From<f64> for SlotValue {
+    fn from(v: f64) -> Self {
+        Self::Double(v)
+    }
+}
+
+impl From<Numeric> for SlotValue {
+    fn from(v: Numeric) -> Self {
+        Self::Numeric(v)
+    }
+}
+
+impl From<String> for SlotValue {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+impl From<&str> for SlotValue {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_string())
+    }
+}
+
+impl From<Guid> for SlotValue {
+    fn from(v: Guid) -> Self {
+        Self::Guid(v)
+    }
+}
+
+/// The slots of one entity, or of a frame nested in them.
+///
+/// Obtained from `slots()` on the entity wrappers; borrows the entity.
+pub struct Slots<'a> {
+    inst: NonNull<ffi::QofInstance>,
+    prefix: Vec<String>,
+    _entity: PhantomData<&'a ()>,
+}
+
+/// Calls a variadic `qof_instance_*_kvp` function with the path keys.
+macro_rules! kvp_call {
+    ($func:path, $inst:expr, $value:expr, $keys:expr) => {{
+        let k: Vec<*const c_char> = $keys.iter().map(|k| k.as_ptr()).collect();
+        match k.as_slice() {
+            [a] => $func($inst, $value, 1, *a),
+            [a, b] => $func($inst, $value, 2, *a, *b),
+            [a, b, c] => $func($inst, $value, 3, *a, *b, *c),
+            [a, b, c, d] => $func($inst, $value, 4, *a, *b, *c, *d),
+            [a, b, c, d, e] => $func($inst, $value, 5, *a, *b, *c, *d, *e),
+            [a, b, c, d, e, f] => $func($inst, $value, 6, *a, *b, *c, *d, *e, *f),
+            [a, b, c, d, e, f, g] => $func($inst, $value, 7, *a, *b, *c, *d, *e, *f, *g),
+            [a, b, c, d, e, f, g, h] => $func($inst, $value, 8, *a, *b, *c, *d, *e, *f, *g, *h),
+            _ => unreachable!("path depth is checked by full_path"),
+        }
+    }};
+}
+
+impl<'a> Slots<'a> {
+    /// Creates a slots view of a QofInstance.
+    ///
+    /// # Safety
+    /// `inst` must point to a valid QofInstance that outlives `'a`.
+    pub unsafe fn from_instance(inst: *mut ffi::QofInstance) -> Option<Self> {
+        NonNull::new(inst).map(|inst| Self {
+            inst,
+            prefix: Vec::new(),
+            _entity: PhantomData,
+        })
+    }
+
+    /// Returns a view of the frame at `path`. Paths given to the view are
+    /// relative to that frame.
+    pub fn frame(&self, path: &str) -> Slots<'a> {
+        let mut prefix = self.prefix.clone();
+        prefix.extend(split_path(path));
+        Slots {
+            inst: self.inst,
+            prefix,
+            _entity: PhantomData,
+        }
+    }
+
+    /// Returns the keys of `path` below the frame as C strings.
+    fn c_keys(&self, path: &str) -> Result<Vec<CString>> {
+        let keys = full_path(&self.prefix, path)?;
+        keys.into_iter()
+            .map(|k| CString::new(k).map_err(|e| Error::StringConversion(e.to_string())))
+            .collect()
+    }
+
+    /// Returns the value at `path`, or `None` if the slot is missing or
+    /// holds a frame or list.
+    pub fn get(&self, path: &str) -> Option<SlotValue> {
+        let keys = self.c_keys(path).ok()?;
+        let mut value = ffi::GValue::default();
+        unsafe {
+            kvp_call!(
+                ffi::qof_instance_get_kvp,
+                self.inst.as_ptr(),
+                &mut value,
+                keys
+            );
+            if value.g_type == 0 {
+                return None;
+            }
+            let result = from_gvalue(&value);
+            ffi::g_value_unset(&mut value);
+            result
+        }
+    }
+
+    /// Returns true if a value is stored at `path`.
+    pub fn contains(&self, path: &str) -> bool {
+        self.get(path).is_some()
+    }
+
+    /// Returns the integer at `path`.
+    pub fn get_i64(&self, path: &str) -> Option<i64> {
+        match self.get(path)? {
+            SlotValue::Int64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the double at `path`.
+    pub fn get_f64(&self, path: &str) -> Option<f64> {
+        match self.get(path)? {
+            SlotValue::Double(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric at `path`.
+    pub fn get_numeric(&self, path: &str) -> Option<Numeric> {
+        match self.get(path)? {
+            SlotValue::Numeric(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the string at `path`.
+    pub fn get_string(&self, path: &str) -> Option<String> {
+        match self.get(path)? {
+            SlotValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the GUID at `path`.
+    pub fn get_guid(&self, path: &str) -> Option<Guid> {
+        match self.get(path)? {
+            SlotValue::Guid(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the time at `path`.
+    pub fn get_time64(&self, path: &str) -> Option<i64> {
+        match self.get(path)? {
+            SlotValue::Time64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Stores `value` at `path`, creating intermediate frames and
+    /// replacing any existing value or frame, and marks the entity dirty.
+    pub(crate) fn set(&self, path: &str, value: impl Into<SlotValue>) -> Result<()> {
+        let keys = self.c_keys(path)?;
+        let value = value.into();
+        let c_string = match &value {
+            SlotValue::String(s) => {
+                Some(CString::new(s.as_str()).map_err(|e| Error::StringConversion(e.to_string()))?)
+            }
+            _ => None,
+        };
+        let mut gvalue = ffi::GValue::default();
+        unsafe {
+            match &value {
+                SlotValue::Int64(v) => {
+                    ffi::g_value_init(&mut gvalue, G_TYPE_INT64);
+                    ffi::g_value_set_int64(&mut gvalue, *v);
+                }
+                SlotValue::Double(v) => {
+                    ffi::g_value_init(&mut gvalue, G_TYPE_DOUBLE);
+                    ffi::g_value_set_double(&mut gvalue, *v);
+                }
+                SlotValue::Numeric(v) => {
+                    let n: ffi::gnc_numeric = (*v).into();
+                    ffi::g_value_init(&mut gvalue, ffi::gnc_numeric_get_type());
+                    ffi::g_value_set_boxed(&mut gvalue, &n as *const _ as *const _);
+                }
+                SlotValue::String(_) => {
+                    ffi::g_value_init(&mut gvalue, G_TYPE_STRING);
+                    let s = c_string.as_ref().expect("converted above");
+                    ffi::g_value_set_string(&mut gvalue, s.as_ptr());
+                }
+                SlotValue::Guid(v) => {
+                    ffi::g_value_init(&mut gvalue, ffi::gnc_guid_get_type());
+                    ffi::g_value_set_boxed(&mut gvalue, v.as_ffi() as *const _ as *const _);
+                }
+                SlotValue::Time64(v) => {
+                    let t = ffi::Time64 { t: *v };
+                    ffi::g_value_init(&mut gvalue, ffi::time64_get_type());
+                    ffi::g_value_set_boxed(&mut gvalue, &t as *const _ as *const _);
+                }
+            }
+            kvp_call!(ffi::qof_instance_set_kvp, self.inst.as_ptr(), &gvalue, keys);
+            ffi::g_value_unset(&mut gvalue);
+            ffi::qof_instance_set_dirty(self.inst.as_ptr());
+        }
+        Ok(())
+    }
+
+    /// Stores a time at `path`.
+    pub(crate) fn set_time64(&self, path: &str, time: i64) -> Result<()> {
+        self.set(path, SlotValue::Time64(time))
+    }
+
+    /// Removes the value or frame at `path` and marks the entity dirty.
+    pub(crate) fn delete(&self, path: &str) -> Result<()> {
+        let keys = self.c_keys(path)?;
+        unsafe {
+            kvp_call!(
+                ffi::qof_instance_set_kvp,
+                self.inst.as_ptr(),
+                std::ptr::null(),
+                keys
+            );
+            ffi::qof_instance_set_dirty(self.inst.as_ptr());
+        }
+        Ok(())
+    }
+}
+
+/// The slots of an entity in an open edit session, reached through the
+/// `slots()` of its editor. Dereferences to [`Slots`] for reading.
+///
+/// Each value replaced is recorded, so a rolled-back edit puts it back.
+/// A frame replaced or deleted as a whole is not restored.
+pub struct SlotEditor<'a, T> {
+    slots: Slots<'a>,
+    undo: &'a Undo<T>,
+}
+
+impl<'a, T> SlotEditor<'a, T> {
+    /// Creates the slot editor of an entity whose edit session `undo`
+    /// belongs to.
+    pub fn new(slots: Slots<'a>, undo: &'a Undo<T>) -> Self {
+        Self { slots, undo }
+    }
+
+    /// Returns an editor of the frame at `path`. Paths given to it are
+    /// relative to that frame.
+    pub fn frame(&self, path: &str) -> SlotEditor<'a, T> {
+        SlotEditor {
+            slots: self.slots.frame(path),
+            undo: self.undo,
+        }
+    }
+
+    /// Records how to restore the value at `path` as it is now.
+    fn keep(&self, path: &str) -> Result<()> {
+        let keys = full_path(&self.slots.prefix, path)?;
+        let old = self.slots.get(path);
+        let inst = self.slots.inst;
+        self.undo.record(move |_| {
+            let slots = Slots {
+                inst,
+                prefix: keys,
+                _entity: PhantomData,
+            };
+            let _ = match old {
+                Some(value) => slots.set("", value),
+                None => slots.delete(""),
+            };
+        });
+        Ok(())
+    }
+
+    /// Stores `value` at `path`, creating intermediate frames and
+    /// replacing any existing value or frame, and marks the entity dirty.
+    pub fn set(&self, path: &str, value: impl Into<SlotValue>) -> Result<()> {
+        self.keep(path)?;
+        self.slots.set(path, value)
+    }
+
+    /// Stores a time at `path`.
+    pub fn set_time64(&self, path: &str, time: i64) -> Result<()> {
+        self.set(path, SlotValue::Time64(time))
+    }
+
+    /// Removes the value or frame at `path` and marks the entity dirty.
+    pub fn delete(&self, path: &str) -> Result<()> {
+        self.keep(path)?;
+        self.slots.delete(path)
+    }
+}
+
+impl<'a, T> Deref for SlotEditor<'a, T> {
+    type Target = Slots<'a>;
+
+    fn deref(&self) -> &Slots<'a> {
+        &self.slots
+    }
+}
+
+/// Converts an initialized GValue holding a KVP value.
+unsafe fn from_gvalue(value: &ffi::GValue) -> Option<SlotValue> {
+    unsafe {
+        let g_type = value.g_type;
+        if g_type == G_TYPE_INT64 {
+            Some(SlotValue::Int64(ffi::g_value_get_int64(value)))
+        } else if g_type == G_TYPE_DOUBLE {
+            Some(SlotValue::Double(ffi::g_value_get_double(value)))
+        } else if g_type == G_TYPE_STRING {
+            let ptr = ffi::g_value_get_string(value);
+            (!ptr.is_null())
+                .then(|| SlotValue::String(CStr::from_ptr(ptr).to_string_lossy().into_owned()))
+        } else if g_type == ffi::gnc_numeric_get_type() {
+            let ptr = ffi::g_value_get_boxed(value) as *const ffi::gnc_numeric;
+            (!ptr.is_null()).then(|| SlotValue::Numeric((*ptr).into()))
+        } else if g_type == ffi::gnc_guid_get_type() {
+            let ptr = ffi::gnc_value_get_guid(value);
+            (!ptr.is_null()).then(|| SlotValue::Guid((*ptr).into()))
+        } else if g_type == ffi::time64_get_type() {
+            let ptr = ffi::g_value_get_boxed(value) as *const ffi::Time64;
+            (!ptr.is_null()).then(|| SlotValue::Time64((*ptr).t))
+        } else {
+            None
+        }
+    }
+}
+
+/// Splits a slash-separated path, ignoring empty segments.
+fn split_path(path: &str) -> impl Iterator<Item = String> + '_ {
+    path.split('/')
+        .filter(|k| !k.is_empty())
+        .map(str::to_string)
+}
+
+/// Returns the keys of `path` below `prefix`.
+fn full_path(prefix: &[String], path: &str) -> Result<Vec<String>> {
+    let mut keys = prefix.to_vec();
+    keys.extend(split_path(path));
+    if keys.is_empty() {
+        return Err(Error::InvalidOperation("empty slot path".to_string()));
+    }
+    if keys.len() > MAX_DEPTH {
+        return Err(Error::InvalidOperation(format!(
+            "slot path deeper than {} frames",
+            MAX_DEPTH
+        )));
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Book, Editable};
+
+    #[test]
+    fn test_full_path() {
+        let prefix = vec!["reconcile-info".to_string()];
+        assert_eq!(
+            full_path(&prefix, "/postpone//date").unwrap(),
+            vec!["reconcile-info", "postpone", "date"]
+        );
+        assert!(full_path(&[], "//").is_err());
+        assert!(full_path(&[], "a/b/c/d/e/f/g/h/i").is_err());
+    }
+
+    #[test]
+    fn test_book_slots() {
+        let book = Book::new();
+        let slots = book.slots();
+        slots.set("counter/count", 42i64).unwrap();
+        slots.set("name", "Test").unwrap();
+        assert_eq!(slots.get_i64("counter/count"), Some(42));
+        assert_eq!(slots.frame("counter").get_i64("count"), Some(42));
+        assert_eq!(slots.get_string("name").as_deref(), Some("Test"));
+        assert_eq!(slots.get_f64("name"), None);
+        slots.delete("counter").unwrap();
+        assert!(!slots.contains("counter/count"));
+
+        // A rolled-back edit puts replaced and deleted values back.
+        let result = book.edit_with(|b| {
+            let slots = b.slots();
+            slots.set("name", "Changed")?;
+            slots.frame("counter").set("count", 7i64)?;
+            slots.delete("name")?;
+            Err::<(), _>(Error::InvalidOperation("no".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(slots.get_string("name").as_deref(), Some("Test"));
+        assert!(!slots.contains("counter/count"));
+    }
+}
@@ -4,7 +4,9 @@ use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::ptr::NonNull;
 
+use crate::edit::Undo;
 use crate::ffi;
+use crate::slots::{SlotEditor, Slots};
 use crate::{Account, Book, Guid, Numeric, Transaction};
 
 /// Reconcile state constants.
@@ -21,6 +23,73 @@ pub mod reconcile {
     pub const VOIDED: char = 'v';
 }
 
+/// Reconcile state of a split, shown in the register's "R" column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReconcileState {
+    /// Not reconciled ('n').
+    #[default]
+    New,
+    /// Cleared with the bank but not yet reconciled ('c').
+    Cleared,
+    /// Reconciled against a statement ('y').
+    Reconciled,
+    /// Frozen into a closed accounting period ('f').
+    Frozen,
+    /// Part of a voided transaction ('v').
+    Void,
+}
+
+impl ReconcileState {
+    /// All states, in register order.
+    pub const ALL: [ReconcileState; 5] = [
+        ReconcileState::New,
+        ReconcileState::Cleared,
+        ReconcileState::Reconciled,
+        ReconcileState::Frozen,
+        ReconcileState::Void,
+    ];
+
+    /// Returns the flag character GnuCash stores for this state.
+    pub const fn as_char(self) -> char {
+        match self {
+            ReconcileState::New => reconcile::NOT_RECONCILED,
+            ReconcileState::Cleared => reconcile::CLEARED,
+            ReconcileState::Reconciled => reconcile::RECONCILED,
+            ReconcileState::Frozen => reconcile::FROZEN,
+            ReconcileState::Void => reconcile::VOIDED,
+        }
+    }
+
+    /// Returns the state for a stored flag character.
+    pub fn from_char(c: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|state| state.as_char() == c)
+    }
+
+    /// Returns true for states that count toward the reconciled
+    /// balance (reconciled and frozen).
+    pub fn is_reconciled(self) -> bool {
+        matches!(self, ReconcileState::Reconciled | ReconcileState::Frozen)
+    }
+
+    /// Returns true for states a reconciliation may still change (new
+    /// and cleared).
+    pub fn is_open(self) -> bool {
+        matches!(self, ReconcileState::New | ReconcileState::Cleared)
+    }
+}
+
+impl From<ReconcileState> for char {
+    fn from(state: ReconcileState) -> char {
+        state.as_char()
+    }
+}
+
+impl std::fmt::Display for ReconcileState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
 /// A GnuCash Split - a single entry in a transaction.
 ///
 /// A Split represents one side of a double-entry transaction, linking
@@ -69,9 +138,16 @@ impl Split {
         }
     }
 
-    /// Reinitializes the split to default values.
-    pub fn reinit(&self) {
-        unsafe { ffi::xaccSplitReinit(self.ptr.as_ptr()) }
+    /// Returns the key-value slots attached to this split.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
+    /// Marks this split as not owned by this wrapper.
+    /// Call this after attaching the split to a transaction.
+    pub fn mark_unowned(&mut self) {
+        self.owned = false;
     }
 
     // ==================== Account/Transaction Linkage ====================
@@ -85,7 +161,7 @@ impl Split {
     }
 
     /// Sets the account for this split.
-    pub fn set_account(&self, account: &Account) {
+    pub(crate) fn set_account(&self, account: &Account) {
         unsafe { ffi::xaccSplitSetAccount(self.ptr.as_ptr(), account.as_ptr()) }
     }
 
@@ -98,7 +174,7 @@ impl Split {
     }
 
     /// Sets the parent transaction for this split.
-    pub fn set_transaction(&self, trans: &Transaction) {
+    pub(crate) fn set_transaction(&self, trans: &Transaction) {
         unsafe { ffi::xaccSplitSetParent(self.ptr.as_ptr(), trans.as_ptr()) }
     }
 
@@ -129,7 +205,7 @@ impl Split {
     /// # Panics
     ///
     /// Panics if `memo` contains a null byte.
-    pub fn set_memo(&self, memo: &str) {
+    pub(crate) fn set_memo(&self, memo: &str) {
         let c_memo = CString::new(memo).unwrap();
         unsafe { ffi::xaccSplitSetMemo(self.ptr.as_ptr(), c_memo.as_ptr()) }
     }
@@ -151,7 +227,7 @@ impl Split {
     /// # Panics
     ///
     /// Panics if `action` contains a null byte.
-    pub fn set_action(&self, action: &str) {
+    pub(crate) fn set_action(&self, action: &str) {
         let c_action = CString::new(action).unwrap();
         unsafe { ffi::xaccSplitSetAction(self.ptr.as_ptr(), c_action.as_ptr()) }
     }
@@ -168,9 +244,42 @@ impl Split {
         }
     }
 
-    /// Marks this split as a stock split.
-    pub fn make_stock_split(&self) {
-        unsafe { ffi::xaccSplitMakeStockSplit(self.ptr.as_ptr()) }
+    /// Returns the online ID (e.g. an OFX `FITID`) recorded when this
+    /// split was imported from a bank statement.
+    pub fn online_id(&self) -> Option<String> {
+        unsafe {
+            let mut ptr: *mut c_char = std::ptr::null_mut();
+            ffi::qof_instance_get(
+                self.ptr.as_ptr() as *const ffi::QofInstance,
+                c"online-id".as_ptr(),
+                &mut ptr as *mut *mut c_char,
+                std::ptr::null::<c_char>(),
+            );
+            if ptr.is_null() {
+                None
+            } else {
+                let s = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+                ffi::g_free(ptr as *mut _);
+                Some(s)
+            }
+        }
+    }
+
+    /// Sets the online ID. The parent transaction must be open for editing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` contains a null byte.
+    pub(crate) fn set_online_id(&self, id: &str) {
+        let c_id = CString::new(id).unwrap();
+        unsafe {
+            ffi::qof_instance_set(
+                self.ptr.as_ptr() as *mut ffi::QofInstance,
+                c"online-id".as_ptr(),
+                c_id.as_ptr(),
+                std::ptr::null::<c_char>(),
+            )
+        }
     }
 
     // ==================== Amount/Value ====================
@@ -181,7 +290,7 @@ impl Split {
     }
 
     /// Sets the amount in the account's commodity.
-    pub fn set_amount(&self, amount: Numeric) {
+    pub(crate) fn set_amount(&self, amount: Numeric) {
         unsafe { ffi::xaccSplitSetAmount(self.ptr.as_ptr(), amount.into()) }
     }
 
@@ -191,7 +300,7 @@ impl Split {
     }
 
     /// Sets the value in the transaction's currency.
-    pub fn set_value(&self, value: Numeric) {
+    pub(crate) fn set_value(&self, value: Numeric) {
         unsafe { ffi::xaccSplitSetValue(self.ptr.as_ptr(), value.into()) }
     }
 
@@ -201,7 +310,7 @@ impl Split {
     }
 
     /// Sets both share price and amount simultaneously.
-    pub fn set_share_price_and_amount(&self, price: Numeric, amount: Numeric) {
+    pub(crate) fn set_share_price_and_amount(&self, price: Numeric, amount: Numeric) {
         unsafe {
             ffi::xaccSplitSetSharePriceAndAmount(self.ptr.as_ptr(), price.into(), amount.into())
         }
@@ -231,15 +340,17 @@ impl Split {
 
     // ==================== Reconciliation ====================
 
-    /// Returns the reconcile state ('n', 'c', 'y', 'f', 'v').
-    pub fn reconcile_state(&self) -> char {
-        unsafe { ffi::xaccSplitGetReconcile(self.ptr.as_ptr()) as u8 as char }
+    /// Returns the reconcile state. An unknown flag reads as
+    /// [`ReconcileState::New`], as it does in the GnuCash register.
+    pub fn reconcile_state(&self) -> ReconcileState {
+        let flag = unsafe { ffi::xaccSplitGetReconcile(self.ptr.as_ptr()) as u8 as char };
+        ReconcileState::from_char(flag).unwrap_or_default()
     }
 
     /// Sets the reconcile state.
-    pub fn set_reconcile_state(&self, state: char) {
+    pub(crate) fn set_reconcile_state(&self, state: ReconcileState) {
         // c_char is i8 on x86_64 / u8 on aarch64 — `as u8` would break on x86_64.
-        unsafe { ffi::xaccSplitSetReconcile(self.ptr.as_ptr(), state as c_char) }
+        unsafe { ffi::xaccSplitSetReconcile(self.ptr.as_ptr(), state.as_char() as c_char) }
     }
 
     /// Returns the date when this split was reconciled.
@@ -248,18 +359,18 @@ impl Split {
     }
 
     /// Sets the date when this split was reconciled.
-    pub fn set_date_reconciled(&self, time: i64) {
+    pub(crate) fn set_date_reconciled(&self, time: i64) {
         unsafe { ffi::xaccSplitSetDateReconciledSecs(self.ptr.as_ptr(), time) }
     }
 
     /// Returns true if this split is reconciled.
     pub fn is_reconciled(&self) -> bool {
-        self.reconcile_state() == reconcile::RECONCILED
+        self.reconcile_state() == ReconcileState::Reconciled
     }
 
     /// Returns true if this split is cleared.
     pub fn is_cleared(&self) -> bool {
-        self.reconcile_state() == reconcile::CLEARED
+        self.reconcile_state() == ReconcileState::Cleared
     }
 
     // ==================== Other Split ====================
@@ -346,6 +457,144 @@ impl Split {
     }
 }
 
+/// The setters of a [`Split`], reached through its transaction's editor
+/// with [`TransactionEditor::split`](crate::TransactionEditor::split) or
+/// [`add_split`](crate::TransactionEditor::add_split), so that splits only
+/// change while their transaction is open. Dereferences to the split for
+/// everything else.
+pub struct SplitEditor<'a> {
+    split: &'a Split,
+    undo: &'a Undo<Transaction>,
+}
+
+impl<'a> SplitEditor<'a> {
+    pub(crate) fn new(split: &'a Split, undo: &'a Undo<Transaction>) -> Self {
+        Self { split, undo }
+    }
+
+    /// Returns the split's slots for writing. Their changes roll back
+    /// with the transaction's edit.
+    pub fn slots(&self) -> SlotEditor<'a, Transaction> {
+        SlotEditor::new(self.split.slots(), self.undo)
+    }
+
+    /// Records how to put back a value of the split, should a nested
+    /// transaction edit be rolled back.
+    fn keep(&self, restore: impl FnOnce(&Split) + 'static) {
+        let ptr = self.split.as_ptr();
+        self.undo.record(move |_| {
+            if let Some(split) = unsafe { Split::from_raw(ptr, false) } {
+                restore(&split);
+            }
+        });
+    }
+
+    /// Sets the account for this split.
+    pub fn set_account(&self, account: &Account) {
+        let old = unsafe { ffi::xaccSplitGetAccount(self.split.as_ptr()) };
+        self.keep(move |s| unsafe { ffi::xaccSplitSetAccount(s.as_ptr(), old) });
+        self.split.set_account(account);
+    }
+
+    /// Sets the split memo.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `memo` contains a null byte.
+    pub fn set_memo(&self, memo: &str) {
+        let old = self.split.memo().unwrap_or_default();
+        self.keep(move |s| s.set_memo(&old));
+        self.split.set_memo(memo);
+    }
+
+    /// Sets the split action.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `action` contains a null byte.
+    pub fn set_action(&self, action: &str) {
+        let old = self.split.action().unwrap_or_default();
+        self.keep(move |s| s.set_action(&old));
+        self.split.set_action(action);
+    }
+
+    /// Sets the online ID (e.g. an OFX `FITID`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` contains a null byte.
+    pub fn set_online_id(&self, id: &str) {
+        let old = self.split.online_id();
+        self.keep(move |s| match old {
+            Some(old) => s.set_online_id(&old),
+            None => {
+                let _ = s.slots().delete("online_id");
+            }
+        });
+        self.split.set_online_id(id);
+    }
+
+    /// Records how to restore the amount and value as they are now.
+    fn keep_amounts(&self) {
+        let (amount, value) = (self.split.amount(), self.split.value());
+        self.keep(move |s| {
+            s.set_amount(amount);
+            s.set_value(value);
+        });
+    }
+
+    /// Sets the amount in the account's commodity.
+    pub fn set_amount(&self, amount: Numeric) {
+        self.keep_amounts();
+        self.split.set_amount(amount);
+    }
+
+    /// Sets the value in the transaction's currency.
+    pub fn set_value(&self, value: Numeric) {
+        self.keep_amounts();
+        self.split.set_value(value);
+    }
+
+    /// Sets both share price and amount simultaneously.
+    pub fn set_share_price_and_amount(&self, price: Numeric, amount: Numeric) {
+        self.keep_amounts();
+        self.split.set_share_price_and_amount(price, amount);
+    }
+
+    /// Marks this split as a stock split, which zeroes its value.
+    pub fn make_stock_split(&self) {
+        self.keep_amounts();
+        if self.split.split_type().as_deref() != Some("stock-split") {
+            self.keep(|s| {
+                let _ = s.slots().delete("split-type");
+            });
+        }
+        unsafe { ffi::xaccSplitMakeStockSplit(self.split.as_ptr()) }
+    }
+
+    /// Sets the reconcile state.
+    pub fn set_reconcile_state(&self, state: ReconcileState) {
+        let old = self.split.reconcile_state();
+        self.keep(move |s| s.set_reconcile_state(old));
+        self.split.set_reconcile_state(state);
+    }
+
+    /// Sets the date when this split was reconciled.
+    pub fn set_date_reconciled(&self, time: i64) {
+        let old = self.split.date_reconciled();
+        self.keep(move |s| s.set_date_reconciled(old));
+        self.split.set_date_reconciled(time);
+    }
+}
+
+impl std::ops::Deref for SplitEditor<'_> {
+    type Target = Split;
+
+    fn deref(&self) -> &Split {
+        self.split
+    }
+}
+
 impl Drop for Split {
     fn drop(&mut self) {
         if self.owned {
@@ -394,4 +643,15 @@ mod tests {
         assert_eq!(reconcile::NOT_RECONCILED, 'n');
         assert_eq!(reconcile::VOIDED, 'v');
     }
+
+    #[test]
+    fn test_reconcile_state_chars() {
+        for state in ReconcileState::ALL {
+            assert_eq!(ReconcileState::from_char(state.as_char()), Some(state));
+        }
+        assert_eq!(ReconcileState::Reconciled.as_char(), 'y');
+        assert_eq!(ReconcileState::from_char('x'), None);
+        assert!(ReconcileState::Frozen.is_reconciled());
+        assert!(ReconcileState::Cleared.is_open());
+    }
 }
@@ -1,11 +1,15 @@
 //! Safe wrapper for GnuCash Transaction.
 
+use std::cell::Cell;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::ptr::NonNull;
 
+use crate::edit::Undo;
 use crate::ffi;
 use crate::iter::TransactionSplits;
+use crate::slots::{SlotEditor, Slots};
+use crate::split::SplitEditor;
 use crate::{Account, Book, Guid, Numeric, Split};
 
 /// Transaction type constants.
@@ -22,7 +26,9 @@ pub mod txn_type {
 /// Each transaction has a date, description, and currency.
 pub struct Transaction {
     ptr: NonNull<ffi::Transaction>,
-    owned: bool,
+    /// Cleared by [`TransactionEditor::destroy`] as well as
+    /// [`Transaction::mark_unowned`], hence a `Cell`.
+    owned: Cell<bool>,
 }
 
 unsafe impl Send for Transaction {}
@@ -33,7 +39,7 @@ impl Transaction {
         let ptr = unsafe { ffi::xaccMallocTransaction(book.as_ptr()) };
         Self {
             ptr: NonNull::new(ptr).expect("xaccMallocTransaction returned null"),
-            owned: true,
+            owned: Cell::new(true),
         }
     }
 
@@ -42,7 +48,10 @@ impl Transaction {
     /// # Safety
     /// The pointer must be valid and point to a properly initialized Transaction.
     pub unsafe fn from_raw(ptr: *mut ffi::Transaction, owned: bool) -> Option<Self> {
-        NonNull::new(ptr).map(|ptr| Self { ptr, owned })
+        NonNull::new(ptr).map(|ptr| Self {
+            ptr,
+            owned: Cell::new(owned),
+        })
     }
 
     /// Returns the raw pointer to the underlying Transaction.
@@ -63,21 +72,33 @@ impl Transaction {
         }
     }
 
+    /// Returns the key-value slots attached to this transaction.
+    pub fn slots(&self) -> Slots<'_> {
+        unsafe { Slots::from_instance(self.ptr.as_ptr() as *mut ffi::QofInstance) }
+            .expect("entity pointer is non-null")
+    }
+
+    /// Marks this transaction as not owned by this wrapper.
+    /// Call this once the transaction should outlive the wrapper in the book.
+    pub fn mark_unowned(&mut self) {
+        self.owned.set(false);
+    }
+
     // ==================== Edit Cycle ====================
 
     /// Begins an edit session on this transaction.
     /// Must be called before making changes.
-    pub fn begin_edit(&self) {
+    pub(crate) fn begin_edit(&self) {
         unsafe { ffi::xaccTransBeginEdit(self.ptr.as_ptr()) }
     }
 
     /// Commits changes made during the edit session.
-    pub fn commit_edit(&self) {
+    pub(crate) fn commit_edit(&self) {
         unsafe { ffi::xaccTransCommitEdit(self.ptr.as_ptr()) }
     }
 
     /// Rolls back changes made during the edit session.
-    pub fn rollback_edit(&self) {
+    pub(crate) fn rollback_edit(&self) {
         unsafe { ffi::xaccTransRollbackEdit(self.ptr.as_ptr()) }
     }
 
@@ -180,74 +201,6 @@ impl Transaction {
         unsafe { ffi::xaccTransIsReadonlyByPostedDate(self.ptr.as_ptr()) != 0 }
     }
 
-    // ==================== Setters ====================
-
-    /// Sets the transaction description.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `desc` contains a null byte.
-    pub fn set_description(&self, desc: &str) {
-        let c_desc = CString::new(desc).unwrap();
-        unsafe { ffi::xaccTransSetDescription(self.ptr.as_ptr(), c_desc.as_ptr()) }
-    }
-
-    /// Sets the transaction number (ID).
-    ///
-    /// # Panics
-    ///
-    /// Panics if `num` contains a null byte.
-    pub fn set_num(&self, num: &str) {
-        let c_num = CString::new(num).unwrap();
-        unsafe { ffi::xaccTransSetNum(self.ptr.as_ptr(), c_num.as_ptr()) }
-    }
-
-    /// Sets the transaction notes.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `notes` contains a null byte.
-    pub fn set_notes(&self, notes: &str) {
-        let c_notes = CString::new(notes).unwrap();
-        unsafe { ffi::xaccTransSetNotes(self.ptr.as_ptr(), c_notes.as_ptr()) }
-    }
-
-    /// Sets the document link URL.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `link` contains a null byte.
-    pub fn set_doc_link(&self, link: &str) {
-        let c_link = CString::new(link).unwrap();
-        unsafe { ffi::xaccTransSetDocLink(self.ptr.as_ptr(), c_link.as_ptr()) }
-    }
-
-    /// Sets the transaction type.
-    pub fn set_txn_type(&self, txn_type: char) {
-        // c_char is i8 on x86_64 / u8 on aarch64 — `as u8` would break on x86_64.
-        unsafe { ffi::xaccTransSetTxnType(self.ptr.as_ptr(), txn_type as c_char) }
-    }
-
-    /// Sets whether this is a closing transaction.
-    pub fn set_is_closing(&self, is_closing: bool) {
-        unsafe { ffi::xaccTransSetIsClosingTxn(self.ptr.as_ptr(), is_closing as i32) }
-    }
-
-    /// Sets the read-only flag with a reason.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `reason` contains a null byte.
-    pub fn set_read_only(&self, reason: &str) {
-        let c_reason = CString::new(reason).unwrap();
-        unsafe { ffi::xaccTransSetReadOnly(self.ptr.as_ptr(), c_reason.as_ptr()) }
-    }
-
-    /// Clears the read-only flag.
-    pub fn clear_read_only(&self) {
-        unsafe { ffi::xaccTransClearReadOnly(self.ptr.as_ptr()) }
-    }
-
     // ==================== Dates ====================
 
     /// Returns the posted date as a Unix timestamp.
@@ -270,26 +223,6 @@ impl Transaction {
         unsafe { ffi::xaccTransGetVoidTime(self.ptr.as_ptr()) }
     }
 
-    /// Sets the posted date using day, month, year.
-    pub fn set_date(&self, day: i32, month: i32, year: i32) {
-        unsafe { ffi::xaccTransSetDate(self.ptr.as_ptr(), day, month, year) }
-    }
-
-    /// Sets the posted date as a Unix timestamp (normalized to date only).
-    pub fn set_date_posted(&self, time: i64) {
-        unsafe { ffi::xaccTransSetDatePostedSecsNormalized(self.ptr.as_ptr(), time) }
-    }
-
-    /// Sets the entered date as a Unix timestamp.
-    pub fn set_date_entered(&self, time: i64) {
-        unsafe { ffi::xaccTransSetDateEnteredSecs(self.ptr.as_ptr(), time) }
-    }
-
-    /// Sets the due date as a Unix timestamp.
-    pub fn set_date_due(&self, time: i64) {
-        unsafe { ffi::xaccTransSetDateDue(self.ptr.as_ptr(), time) }
-    }
-
     // ==================== Splits ====================
 
     /// Returns the number of splits in this transaction.
@@ -314,16 +247,6 @@ impl Transaction {
         unsafe { ffi::xaccTransGetSplitIndex(self.ptr.as_ptr(), split.as_ptr()) }
     }
 
-    /// Sorts the splits in the transaction (debits first, then credits).
-    pub fn sort_splits(&self) {
-        unsafe { ffi::xaccTransSortSplits(self.ptr.as_ptr()) }
-    }
-
-    /// Removes all splits from the transaction.
-    pub fn clear_splits(&self) {
-        unsafe { ffi::xaccTransClearSplits(self.ptr.as_ptr()) }
-    }
-
     // ==================== Balance ====================
 
     /// Returns the imbalance value of the transaction.
@@ -349,6 +272,134 @@ impl Transaction {
 
     // ==================== Voiding ====================
 
+    /// Returns the transaction that reversed this one, if any.
+    pub fn reversed_by(&self) -> Option<Transaction> {
+        unsafe {
+            let ptr = ffi::xaccTransGetReversedBy(self.ptr.as_ptr());
+            Self::from_raw(ptr, false)
+        }
+    }
+
+    // ==================== Misc ====================
+
+    /// Returns true if this transaction uses trading accounts.
+    pub fn use_trading_accounts(&self) -> bool {
+        unsafe { ffi::xaccTransUseTradingAccounts(self.ptr.as_ptr()) != 0 }
+    }
+
+    /// Returns true if the transaction has reconciled splits.
+    pub fn has_reconciled_splits(&self) -> bool {
+        unsafe { ffi::xaccTransHasReconciledSplits(self.ptr.as_ptr()) != 0 }
+    }
+
+    // ==================== Iterators ====================
+
+    /// Returns an iterator over the splits in this transaction.
+    pub fn splits(&self) -> TransactionSplits {
+        TransactionSplits::new(self)
+    }
+}
+
+/// The setters of a [`Transaction`] in an open edit session, reached
+/// through [`Editable::edit`](crate::Editable::edit). Dereferences to the
+/// transaction for everything else. Splits are changed through
+/// [`split`](Self::split) and [`add_split`](Self::add_split).
+///
+/// The engine rolls back only the outermost session on a transaction, so
+/// the editor also records each value it replaces, which the guard puts
+/// back when a nested session is rolled back.
+pub struct TransactionEditor<'a> {
+    transaction: &'a Transaction,
+    undo: Undo<Transaction>,
+}
+
+/// A transaction text setter from the engine.
+type TextSetter = unsafe extern "C" fn(*mut ffi::Transaction, *const c_char);
+
+/// A transaction date setter from the engine.
+type TimeSetter = unsafe extern "C" fn(*mut ffi::Transaction, ffi::time64);
+
+impl<'a> TransactionEditor<'a> {
+    pub(crate) fn new(transaction: &'a Transaction, undo: Undo<Transaction>) -> Self {
+        Self { transaction, undo }
+    }
+
+    /// Records how to put back a change made through the raw pointer,
+    /// should the edit be rolled back.
+    pub fn on_rollback(&self, restore: impl FnOnce(&Transaction) + 'static) {
+        self.undo.record(restore);
+    }
+
+    /// Returns the transaction's slots for writing.
+    pub fn slots(&self) -> SlotEditor<'_, Transaction> {
+        SlotEditor::new(self.transaction.slots(), &self.undo)
+    }
+
+    /// Records how to restore a text field as it is now.
+    fn keep_text(&self, old: Option<String>, set: TextSetter) {
+        let old = CString::new(old.unwrap_or_default()).unwrap_or_default();
+        self.undo
+            .record(move |t| unsafe { set(t.as_ptr(), old.as_ptr()) });
+    }
+
+    /// Records how to restore a date field as it is now.
+    fn keep_time(&self, old: i64, set: TimeSetter) {
+        self.undo.record(move |t| unsafe { set(t.as_ptr(), old) });
+    }
+
+    /// Returns the editor for one of this transaction's splits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `split` belongs to another transaction.
+    pub fn split<'s>(&'s self, split: &'s Split) -> SplitEditor<'s> {
+        let parent = unsafe { ffi::xaccSplitGetParent(split.as_ptr()) };
+        assert!(
+            parent == self.transaction.as_ptr(),
+            "split belongs to another transaction"
+        );
+        SplitEditor::new(split, &self.undo)
+    }
+
+    /// Moves `split` into this transaction, out of the one it was in if
+    /// any, and returns its editor.
+    pub fn add_split<'s>(&'s self, split: &'s Split) -> SplitEditor<'s> {
+        let split_ptr = split.as_ptr();
+        let old = unsafe { ffi::xaccSplitGetParent(split_ptr) };
+        self.undo
+            .record(move |_| unsafe { ffi::xaccSplitSetParent(split_ptr, old) });
+        split.set_transaction(self.transaction);
+        SplitEditor::new(split, &self.undo)
+    }
+
+    /// Destroys the transaction and its splits when the outermost edit
+    /// session commits. No wrapper owns it from then on, so dropping this
+    /// one does not destroy it again.
+    ///
+    /// Rolling back the outermost session keeps the transaction. Rolling
+    /// back a nested one does not: the engine has no way to take the
+    /// destruction back before the outer session commits.
+    pub fn destroy(&self) {
+        self.transaction.owned.set(false);
+        unsafe { ffi::xaccTransDestroy(self.transaction.as_ptr()) }
+    }
+
+    /// Sorts the splits in the transaction (debits first, then credits).
+    ///
+    /// Rolling back a nested session keeps the new order: the engine has
+    /// no way to put splits back in a given order.
+    pub fn sort_splits(&self) {
+        unsafe { ffi::xaccTransSortSplits(self.transaction.as_ptr()) }
+    }
+
+    /// Destroys all splits of the transaction.
+    ///
+    /// Like [`destroy`](Self::destroy), rolling back a nested session does
+    /// not bring the splits back.
+    pub fn clear_splits(&self) {
+        unsafe { ffi::xaccTransClearSplits(self.transaction.as_ptr()) }
+    }
+
     /// Voids the transaction with a reason.
     ///
     /// # Panics
@@ -356,53 +407,159 @@ impl Transaction {
     /// Panics if `reason` contains a null byte.
     pub fn void(&self, reason: &str) {
         let c_reason = CString::new(reason).unwrap();
-        unsafe { ffi::xaccTransVoid(self.ptr.as_ptr(), c_reason.as_ptr()) }
+        self.undo
+            .record(|t| unsafe { ffi::xaccTransUnvoid(t.as_ptr()) });
+        unsafe { ffi::xaccTransVoid(self.transaction.as_ptr(), c_reason.as_ptr()) }
     }
 
     /// Unvoids a voided transaction.
     pub fn unvoid(&self) {
-        unsafe { ffi::xaccTransUnvoid(self.ptr.as_ptr()) }
+        if let Some(reason) = self.void_reason().filter(|_| self.is_void()) {
+            let reason = CString::new(reason).unwrap_or_default();
+            self.undo
+                .record(move |t| unsafe { ffi::xaccTransVoid(t.as_ptr(), reason.as_ptr()) });
+        }
+        unsafe { ffi::xaccTransUnvoid(self.transaction.as_ptr()) }
     }
 
     /// Creates a reverse transaction that cancels this one.
+    ///
+    /// Rolling back a nested session unlinks this transaction from the
+    /// reversal; the reversal itself belongs to the returned wrapper.
     pub fn reverse(&self) -> Option<Transaction> {
+        self.undo.record(|t| {
+            let _ = t.slots().delete("reversed-by");
+        });
         unsafe {
-            let ptr = ffi::xaccTransReverse(self.ptr.as_ptr());
-            Self::from_raw(ptr, true)
+            let ptr = ffi::xaccTransReverse(self.transaction.as_ptr());
+            Transaction::from_raw(ptr, true)
         }
     }
 
-    /// Returns the transaction that reversed this one, if any.
-    pub fn reversed_by(&self) -> Option<Transaction> {
-        unsafe {
-            let ptr = ffi::xaccTransGetReversedBy(self.ptr.as_ptr());
-            Self::from_raw(ptr, false)
+    /// Sets the transaction description.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `desc` contains a null byte.
+    pub fn set_description(&self, desc: &str) {
+        let c_desc = CString::new(desc).unwrap();
+        self.keep_text(self.description(), ffi::xaccTransSetDescription);
+        unsafe { ffi::xaccTransSetDescription(self.transaction.as_ptr(), c_desc.as_ptr()) }
+    }
+
+    /// Sets the transaction number (ID).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` contains a null byte.
+    pub fn set_num(&self, num: &str) {
+        let c_num = CString::new(num).unwrap();
+        self.keep_text(self.num(), ffi::xaccTransSetNum);
+        unsafe { ffi::xaccTransSetNum(self.transaction.as_ptr(), c_num.as_ptr()) }
+    }
+
+    /// Sets the transaction notes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `notes` contains a null byte.
+    pub fn set_notes(&self, notes: &str) {
+        let c_notes = CString::new(notes).unwrap();
+        self.keep_text(self.notes(), ffi::xaccTransSetNotes);
+        unsafe { ffi::xaccTransSetNotes(self.transaction.as_ptr(), c_notes.as_ptr()) }
+    }
+
+    /// Sets the document link URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `link` contains a null byte.
+    pub fn set_doc_link(&self, link: &str) {
+        let c_link = CString::new(link).unwrap();
+        self.keep_text(self.doc_link(), ffi::xaccTransSetDocLink);
+        unsafe { ffi::xaccTransSetDocLink(self.transaction.as_ptr(), c_link.as_ptr()) }
+    }
+
+    /// Sets the transaction type.
+    pub fn set_txn_type(&self, txn_type: char) {
+        // c_char is i8 on x86_64 / u8 on aarch64 — `as u8` would break on x86_64.
+        let old = self.txn_type() as c_char;
+        self.undo
+            .record(move |t| unsafe { ffi::xaccTransSetTxnType(t.as_ptr(), old) });
+        unsafe { ffi::xaccTransSetTxnType(self.transaction.as_ptr(), txn_type as c_char) }
+    }
+
+    /// Sets whether this is a closing transaction.
+    pub fn set_is_closing(&self, is_closing: bool) {
+        let old = self.is_closing() as i32;
+        self.undo
+            .record(move |t| unsafe { ffi::xaccTransSetIsClosingTxn(t.as_ptr(), old) });
+        unsafe { ffi::xaccTransSetIsClosingTxn(self.transaction.as_ptr(), is_closing as i32) }
+    }
+
+    /// Records how to restore the read-only flag as it is now.
+    fn keep_read_only(&self) {
+        match self.read_only_reason() {
+            Some(old) => self.keep_text(Some(old), ffi::xaccTransSetReadOnly),
+            None => self
+                .undo
+                .record(|t| unsafe { ffi::xaccTransClearReadOnly(t.as_ptr()) }),
         }
     }
 
-    // ==================== Misc ====================
+    /// Sets the read-only flag with a reason.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reason` contains a null byte.
+    pub fn set_read_only(&self, reason: &str) {
+        let c_reason = CString::new(reason).unwrap();
+        self.keep_read_only();
+        unsafe { ffi::xaccTransSetReadOnly(self.transaction.as_ptr(), c_reason.as_ptr()) }
+    }
 
-    /// Returns true if this transaction uses trading accounts.
-    pub fn use_trading_accounts(&self) -> bool {
-        unsafe { ffi::xaccTransUseTradingAccounts(self.ptr.as_ptr()) != 0 }
+    /// Clears the read-only flag.
+    pub fn clear_read_only(&self) {
+        self.keep_read_only();
+        unsafe { ffi::xaccTransClearReadOnly(self.transaction.as_ptr()) }
     }
 
-    /// Returns true if the transaction has reconciled splits.
-    pub fn has_reconciled_splits(&self) -> bool {
-        unsafe { ffi::xaccTransHasReconciledSplits(self.ptr.as_ptr()) != 0 }
+    /// Sets the posted date using day, month, year.
+    pub fn set_date(&self, day: i32, month: i32, year: i32) {
+        self.keep_time(self.date_posted(), ffi::xaccTransSetDatePostedSecs);
+        unsafe { ffi::xaccTransSetDate(self.transaction.as_ptr(), day, month, year) }
     }
 
-    // ==================== Iterators ====================
+    /// Sets the posted date as a Unix timestamp (normalized to date only).
+    pub fn set_date_posted(&self, time: i64) {
+        self.keep_time(self.date_posted(), ffi::xaccTransSetDatePostedSecs);
+        unsafe { ffi::xaccTransSetDatePostedSecsNormalized(self.transaction.as_ptr(), time) }
+    }
 
-    /// Returns an iterator over the splits in this transaction.
-    pub fn splits(&self) -> TransactionSplits {
-        TransactionSplits::new(self)
+    /// Sets the entered date as a Unix timestamp.
+    pub fn set_date_entered(&self, time: i64) {
+        self.keep_time(self.date_entered(), ffi::xaccTransSetDateEnteredSecs);
+        unsafe { ffi::xaccTransSetDateEnteredSecs(self.transaction.as_ptr(), time) }
+    }
+
+    /// Sets the due date as a Unix timestamp.
+    pub fn set_date_due(&self, time: i64) {
+        self.keep_time(self.date_due(), ffi::xaccTransSetDateDue);
+        unsafe { ffi::xaccTransSetDateDue(self.transaction.as_ptr(), time) }
+    }
+}
+
+impl std::ops::Deref for TransactionEditor<'_> {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Transaction {
+        self.transaction
     }
 }
 
 impl Drop for Transaction {
     fn drop(&mut self) {
-        if self.owned {
+        if self.owned.get() {
             unsafe {
                 // Only destroy if not already open
                 if ffi::xaccTransIsOpen(self.ptr.as_ptr()) == 0 {
@@ -452,4 +609,24 @@ mod tests {
         assert_eq!(txn_type::PAYMENT, 'P');
         assert_eq!(txn_type::LINK, 'L');
     }
+
+    #[test]
+    fn test_destroy() {
+        use crate::Editable;
+
+        let book = Book::new();
+        let txn = Transaction::new(&book);
+        let edit = txn.edit();
+        let mut split = Split::new(&book);
+        edit.add_split(&split);
+        edit.commit();
+        split.mark_unowned();
+        let guid = txn.guid();
+
+        // The wrapper stops owning the transaction, so dropping it after
+        // the commit does not destroy it twice.
+        txn.edit().destroy();
+        let found = unsafe { ffi::xaccTransLookup(guid.as_ffi(), book.as_ptr()) };
+        assert!(found.is_null());
+    }
 }
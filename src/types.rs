@@ -166,6 +166,26 @@ impl Numeric {
         self.0.num == 0 && self.0.denom != 0
     }
 
+    /// Returns the engine's error value for `code`: the code over a zero
+    /// denominator.
+    pub fn error(code: ffi::GNCNumericErrorCode) -> Self {
+        Self::new(code as i64, 0)
+    }
+
+    /// Checks if this is an error value. Error values have a zero
+    /// denominator, as returned by the engine when a calculation fails.
+    ///
+    /// ```
+    /// use gnucash_sys::Numeric;
+    ///
+    /// let sum = Numeric::new(1, 0) + Numeric::new(2, 1);
+    /// assert!(sum.is_error());
+    /// assert!(!(Numeric::new(1, 2) + Numeric::new(1, 3)).is_error());
+    /// ```
+    pub fn is_error(&self) -> bool {
+        self.0.denom == 0
+    }
+
     /// Checks if this value is negative.
     pub fn is_negative(&self) -> bool {
         (self.0.num < 0) != (self.0.denom < 0)
@@ -200,6 +220,286 @@ impl Numeric {
     pub fn abs(&self) -> Self {
         Self::new(self.0.num.abs(), self.0.denom.abs())
     }
+
+    // ==================== Arithmetic ====================
+
+    /// Exact addition. Values sharing a denominator keep it; otherwise the
+    /// result uses the least common denominator. Returns `None` on overflow
+    /// or if either operand has a zero denominator.
+    ///
+    /// ```
+    /// use gnucash_sys::Numeric;
+    ///
+    /// let sum = Numeric::new(150, 100).checked_add(&Numeric::new(5, 10)).unwrap();
+    /// assert_eq!(sum, Numeric::new(200, 100));
+    ///
+    /// let huge = Numeric::new(i64::MAX, -i64::MAX);
+    /// assert_eq!(huge.checked_add(&Numeric::new(1, i64::MAX - 1)), None);
+    /// ```
+    pub fn checked_add(&self, other: &Numeric) -> Option<Numeric> {
+        let (an, ad) = self.parts()?;
+        let (bn, bd) = other.parts()?;
+        if ad == bd {
+            return Self::from_i128(an.checked_add(bn)?, ad);
+        }
+        let l = lcm_i128(ad, bd)?;
+        let an = an.checked_mul(l / ad)?;
+        let bn = bn.checked_mul(l / bd)?;
+        Self::from_i128(an.checked_add(bn)?, l)
+    }
+
+    /// Exact subtraction; see [`checked_add`](Self::checked_add).
+    ///
+    /// ```
+    /// use gnucash_sys::Numeric;
+    ///
+    /// let diff = Numeric::new(150, 100).checked_sub(&Numeric::new(5, 10)).unwrap();
+    /// assert_eq!(diff, Numeric::new(100, 100));
+    /// assert_eq!(Numeric::zero().checked_sub(&Numeric::new(i64::MIN, 1)), None);
+    /// ```
+    pub fn checked_sub(&self, other: &Numeric) -> Option<Numeric> {
+        let (an, ad) = self.parts()?;
+        let (bn, bd) = other.parts()?;
+        if ad == bd {
+            return Self::from_i128(an.checked_sub(bn)?, ad);
+        }
+        let l = lcm_i128(ad, bd)?;
+        let an = an.checked_mul(l / ad)?;
+        let bn = bn.checked_mul(l / bd)?;
+        Self::from_i128(an.checked_sub(bn)?, l)
+    }
+
+    /// Exact multiplication. Returns `None` on overflow.
+    pub fn checked_mul(&self, other: &Numeric) -> Option<Numeric> {
+        let (an, ad) = self.parts()?;
+        let (bn, bd) = other.parts()?;
+        Self::from_i128(an.checked_mul(bn)?, ad.checked_mul(bd)?)
+    }
+
+    /// Exact division. Returns `None` when dividing by zero or on overflow.
+    pub fn checked_div(&self, other: &Numeric) -> Option<Numeric> {
+        let (an, ad) = self.parts()?;
+        let (bn, bd) = other.parts()?;
+        if bn == 0 {
+            return None;
+        }
+        Self::from_i128(an.checked_mul(bd)?, ad.checked_mul(bn)?)
+    }
+
+    /// Compares two values numerically, so `1/2` equals `50/100`.
+    /// Values with a zero denominator sort as zero.
+    pub fn cmp_value(&self, other: &Numeric) -> std::cmp::Ordering {
+        let (an, ad) = self.parts().unwrap_or((0, 1));
+        let (bn, bd) = other.parts().unwrap_or((0, 1));
+        match (an.checked_mul(bd), bn.checked_mul(ad)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => cmp_fraction(an, ad, bn, bd),
+        }
+    }
+
+    /// Returns true if both values are numerically equal.
+    pub fn eq_value(&self, other: &Numeric) -> bool {
+        self.cmp_value(other) == std::cmp::Ordering::Equal
+    }
+
+    /// Reduces the fraction to lowest terms with a positive denominator.
+    pub fn reduce(&self) -> Numeric {
+        match self.parts() {
+            Some((n, d)) => {
+                let g = gcd_i128(n, d).max(1);
+                Self::from_i128(n / g, d / g).unwrap_or(*self)
+            }
+            None => *self,
+        }
+    }
+
+    /// Converts to the given denominator, rounding half away from zero.
+    /// Returns an overflow error value if the result does not fit.
+    ///
+    /// ```
+    /// use gnucash_sys::Numeric;
+    ///
+    /// assert_eq!(Numeric::new(1, 3).convert(100), Numeric::new(33, 100));
+    /// assert_eq!(Numeric::new(-5, 1000).convert(100), Numeric::new(-1, 100));
+    /// ```
+    pub fn convert(&self, denom: i64) -> Numeric {
+        let Some((n, d)) = self.parts() else {
+            return *self;
+        };
+        if denom <= 0 {
+            return *self;
+        }
+        let overflow = Numeric::error(ffi::GNCNumericErrorCode::GNC_ERROR_OVERFLOW);
+        let Some(scaled) = n.checked_mul(denom as i128) else {
+            return overflow;
+        };
+        let mut q = scaled / d;
+        let r = scaled % d;
+        if r.abs() * 2 >= d {
+            q += scaled.signum();
+        }
+        Self::from_i128(q, denom as i128).unwrap_or(overflow)
+    }
+
+    /// Parses a plain decimal string such as `"-1234.56"` or `"+7"`.
+    /// The denominator is `10^(digits after the point)`.
+    ///
+    /// ```
+    /// use gnucash_sys::Numeric;
+    ///
+    /// assert_eq!(Numeric::parse_decimal("-12.30"), Some(Numeric::new(-1230, 100)));
+    /// assert_eq!(Numeric::parse_decimal("abc"), None);
+    /// ```
+    pub fn parse_decimal(s: &str) -> Option<Numeric> {
+        let s = s.trim();
+        let (negative, digits) = match s.as_bytes().first()? {
+            b'-' => (true, &s[1..]),
+            b'+' => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (int_part, frac_part) = match digits.split_once('.') {
+            Some((i, f)) => (i, f),
+            None => (digits, ""),
+        };
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        if !int_part.bytes().all(|b| b.is_ascii_digit())
+            || !frac_part.bytes().all(|b| b.is_ascii_digit())
+            || frac_part.len() > 18
+        {
+            return None;
+        }
+        let denom = 10i64.checked_pow(frac_part.len() as u32)?;
+        let mut num: i64 = 0;
+        for b in int_part.bytes().chain(frac_part.bytes()) {
+            num = num.checked_mul(10)?.checked_add((b - b'0') as i64)?;
+        }
+        Some(Self::new(if negative { -num } else { num }, denom))
+    }
+
+    /// Formats the value as a decimal string with exactly `places` digits
+    /// after the point, rounding half away from zero. Returns `"NaN"` for
+    /// an error value and for one that cannot be written with that many
+    /// places.
+    ///
+    /// ```
+    /// use gnucash_sys::Numeric;
+    ///
+    /// assert_eq!(Numeric::new(-12345, 1000).to_decimal_string(2), "-12.35");
+    /// assert_eq!(Numeric::new(7, 1).to_decimal_string(0), "7");
+    /// assert_eq!(Numeric::new(1, 3).to_decimal_string(20), "0.33333333333333333333");
+    /// assert_eq!(Numeric::new(i64::MAX, 1).to_decimal_string(40), "NaN");
+    /// ```
+    pub fn to_decimal_string(&self, places: u32) -> String {
+        let Some((n, d)) = self.parts() else {
+            return "NaN".to_string();
+        };
+        let Some((scale, scaled)) = 10i128
+            .checked_pow(places)
+            .and_then(|scale| Some((scale, n.checked_mul(scale)?)))
+        else {
+            return "NaN".to_string();
+        };
+        let mut q = scaled / d;
+        if (scaled % d).abs() * 2 >= d {
+            q += scaled.signum();
+        }
+        let sign = if q < 0 { "-" } else { "" };
+        let q = q.unsigned_abs();
+        if places == 0 {
+            format!("{}{}", sign, q)
+        } else {
+            let scale = scale as u128;
+            format!(
+                "{}{}.{:0width$}",
+                sign,
+                q / scale,
+                q % scale,
+                width = places as usize
+            )
+        }
+    }
+
+    /// Picks the result of an operator: `result` if it succeeded, else the
+    /// first operand that was already an error, else the engine's error
+    /// for the failure.
+    fn propagate(a: &Numeric, b: &Numeric, result: Option<Numeric>) -> Numeric {
+        if let Some(n) = result {
+            n
+        } else if a.is_error() {
+            *a
+        } else if b.is_error() {
+            *b
+        } else if b.is_zero() {
+            Numeric::error(ffi::GNCNumericErrorCode::GNC_ERROR_ARG)
+        } else {
+            Numeric::error(ffi::GNCNumericErrorCode::GNC_ERROR_OVERFLOW)
+        }
+    }
+
+    /// Returns (num, denom) widened to i128 with a positive denominator.
+    /// GnuCash encodes a negative denominator as "multiply by |denom|".
+    fn parts(&self) -> Option<(i128, i128)> {
+        let (n, d) = (self.0.num as i128, self.0.denom as i128);
+        match d {
+            0 => None,
+            d if d < 0 => Some((n.checked_mul(-d)?, 1)),
+            d => Some((n, d)),
+        }
+    }
+
+    /// Builds a Numeric from i128 parts, reducing only when needed to fit.
+    fn from_i128(num: i128, denom: i128) -> Option<Numeric> {
+        let (mut num, mut denom) = if denom < 0 {
+            (-num, -denom)
+        } else {
+            (num, denom)
+        };
+        if i64::try_from(num).is_err() || i64::try_from(denom).is_err() {
+            let g = gcd_i128(num, denom).max(1);
+            num /= g;
+            denom /= g;
+        }
+        Some(Self::new(
+            i64::try_from(num).ok()?,
+            i64::try_from(denom).ok()?,
+        ))
+    }
+}
+
+fn gcd_i128(mut a: i128, mut b: i128) -> i128 {
+    a = a.abs();
+    b = b.abs();
+    while b != 0 {
+        let t = b;
+        b = a % b;
+        a = t;
+    }
+    a
+}
+
+fn lcm_i128(a: i128, b: i128) -> Option<i128> {
+    (a / gcd_i128(a, b)).checked_mul(b)
+}
+
+/// Compares `an/ad` with `bn/bd` (positive denominators) without
+/// multiplying, by comparing whole parts and then the inverted remainders.
+fn cmp_fraction(an: i128, ad: i128, bn: i128, bd: i128) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let (qa, ra) = (an.div_euclid(ad), an.rem_euclid(ad));
+    let (qb, rb) = (bn.div_euclid(bd), bn.rem_euclid(bd));
+    if qa != qb {
+        return qa.cmp(&qb);
+    }
+    match (ra == 0, rb == 0) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        // ra/ad < rb/bd exactly when bd/rb < ad/ra.
+        (false, false) => cmp_fraction(bd, rb, ad, ra),
+    }
 }
 
 impl Default for Numeric {
@@ -251,6 +551,59 @@ impl std::ops::Neg for Numeric {
     }
 }
 
+/// Operator forms of the `checked_*` methods.
+///
+/// Like the engine's `gnc_numeric_add` and friends, these never panic:
+/// an operand that is already an error value, or an overflow or division
+/// by zero, yields an error value (see [`Numeric::is_error`]), which then
+/// carries through any further arithmetic. Use the `checked_*` methods to
+/// detect the failure where it happens.
+impl std::ops::Add for Numeric {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Numeric::propagate(&self, &rhs, self.checked_add(&rhs))
+    }
+}
+
+impl std::ops::Sub for Numeric {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Numeric::propagate(&self, &rhs, self.checked_sub(&rhs))
+    }
+}
+
+impl std::ops::Mul for Numeric {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Numeric::propagate(&self, &rhs, self.checked_mul(&rhs))
+    }
+}
+
+impl std::ops::Div for Numeric {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        Numeric::propagate(&self, &rhs, self.checked_div(&rhs))
+    }
+}
+
+impl std::ops::AddAssign for Numeric {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::SubAssign for Numeric {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::iter::Sum for Numeric {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Numeric::zero(), |acc, n| acc + n)
+    }
+}
+
 // ==================== Serde Support ====================
 
 #[cfg(feature = "serde")]
@@ -8,6 +8,7 @@
 /* Engine and session */
 #include "gnc-engine.h"
 #include "qofsession.h"
+#include "qofinstance.h"
 
 /* Entity types */
 #include "qofbook.h"
@@ -15,6 +16,10 @@
 #include "Transaction.h"
 #include "Split.h"
 
+/* Check & Repair */
+#include "Scrub.h"
+#include "Scrub3.h"
+
 /* Price database */
 #include "gnc-pricedb.h"
 